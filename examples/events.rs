@@ -102,7 +102,7 @@ fn main() {
     // let seed_buffer = Buffer::with_vec_scrambled((0u32, 500u32), &dims, &ocl_pq.queue());
     let seed_vec = util::scrambled_vec((0u32, 500u32), ocl_pq.dims().to_len());
     let seed_buffer = Buffer::new(ocl_pq.queue().clone(), Some(core::MEM_READ_WRITE |
-        core::MEM_COPY_HOST_PTR), ocl_pq.dims().clone(), Some(&seed_vec)).unwrap();
+        core::MEM_COPY_HOST_PTR), ocl_pq.dims(), Some(&seed_vec)).unwrap();
 
     // let mut result_buffer = Buffer::with_vec(&dims, &ocl_pq.queue());
     let mut result_vec = vec![0; dims[0]];
@@ -14,7 +14,7 @@ extern crate time;
 // use std::time::Instant;
 
 
-use ocl::{util, core, ProQue, Buffer, EventList};
+use ocl::{util, ProQue, EventList};
 
 const DATASET_SIZE: usize = 2 << 12;
 
@@ -47,16 +47,14 @@ fn main() {
 
     // Create init and result buffers and vectors:
     let vec_init = util::scrambled_vec(INIT_VAL_RANGE, ocl_pq.dims().to_len());
-    let buffer_init = Buffer::new(ocl_pq.queue().clone(), Some(core::MEM_READ_WRITE |
-        core::MEM_COPY_HOST_PTR), ocl_pq.dims().clone(), Some(&vec_init)).unwrap();
+    let buffer_init = ocl_pq.create_buffer_with_data(None, &vec_init).unwrap();
 
     let mut vec_result = vec![0.0f32; DATASET_SIZE];
-    let buffer_result = Buffer::<f32>::new(ocl_pq.queue().clone(), None,
-        ocl_pq.dims(), None).unwrap();
+    let buffer_result = ocl_pq.create_buffer::<f32>().unwrap();
 
-    // Create a kernel with arguments matching those in the kernel:
+    // Create a kernel with arguments matching those in the kernel (its
+    // global work size defaults to the dims of `ocl_pq`):
     let mut kern = ocl_pq.create_kernel("add").unwrap()
-        .gws(ocl_pq.dims().clone())
         .arg_buf_named("source", Some(&buffer_init))
         .arg_scl(SCALAR)
         .arg_buf(&buffer_result);
@@ -139,8 +137,10 @@ fn main() {
 
     let kern_buf_start = time::get_time();
 
-    let mut kern_events = EventList::new();
-    let mut buf_events = EventList::new();
+    // Drop completed events as the lists grow rather than holding on to
+    // every event enqueued in the loops below:
+    let mut kern_events = EventList::with_pruning(256);
+    let mut buf_events = EventList::with_pruning(256);
 
     for _ in 0..KERNEL_AND_BUFFER_ITERS {
         kern.cmd().ewait(&buf_events).enew(&mut kern_events).enq().unwrap();
@@ -22,6 +22,22 @@
 //! [`ocl-core`] and [`cl-sys`] crates.
 //!
 //!
+//! ## Thread Safety
+//!
+//! `Platform`, `Device`, `Context`, `Program`, `Queue`, `Buffer`, `Image`,
+//! `Sampler`, `Event`, and `ProQue` are `Send` and `Sync`. Each wraps
+//! reference counted `OpenCL` objects, so cloning one for each thread is
+//! cheap and equivalent to sharing it.
+//!
+//! `Kernel` is `Send` but not `Sync` because setting kernel arguments is not
+//! thread-safe. Use `SyncKernel` to share one kernel between threads or
+//! create a kernel per thread from a shared `Program`.
+//!
+//! Commands may be enqueued on one queue from several threads, though using
+//! a queue per thread avoids contention. Use events to order commands across
+//! queues.
+//!
+//!
 //! ## Help Wanted
 //!
 //! Please help complete any functionality you may need by filing an
@@ -44,18 +60,33 @@
 
 #[macro_use] extern crate enum_primitive;
 #[macro_use] extern crate bitflags;
+extern crate log;
 extern crate libc;
 extern crate num;
 #[cfg(test)] extern crate rand;
+#[cfg(feature = "ndarray")] #[macro_use] extern crate ndarray;
+#[cfg(feature = "futures")] extern crate futures;
+#[cfg(feature = "image")] extern crate image;
 pub extern crate ocl_core as core;
 
+#[macro_use] mod macros;
 #[cfg(test)] mod tests;
 mod standard;
+pub mod ops;
+pub mod profiling;
 
 pub use core::ffi;
 pub use standard::{Platform, Device, Context, Program, Queue, Kernel, Buffer, Image, Event,
-    EventList, Sampler, SpatialDims, ProQue};
+    EventList, Sampler, SpatialDims, ProQue, CommandTimeline, CommandRecord, ArgInfo, ArgInfos,
+    HeatMap, HeatMapReport, Indices, StencilPlan, LocalMemBudget, SplitLaunch, PrintfCapture,
+    WriteTracker, RawQueueHandle, RawMemHandle, ImageMap, BufferMap, CallbackTracker, Tiles,
+    StructField, EventListIter, EventListIntoIter, PinnedVec, ProgramHandle, SyncKernel,
+    TaskGraph, StreamingBuffer, Extensions, SubDevice};
+#[cfg(feature = "opencl_version_2_1")] pub use standard::TimerSync;
+#[cfg(feature = "futures")] pub use standard::{FutureEvent, FutureRead};
+#[cfg(feature = "image")] pub use standard::ClPixel;
 pub use core::error::{Error, Result};
+pub use standard::{ErrorHints, status_hints};
 pub use core::util;
 
 pub mod aliases {
@@ -80,22 +111,45 @@ pub mod aliases {
         ClFloat2, ClFloat3, ClFloat4, ClFloat8, ClFloat16,
         ClDouble2, ClDouble3, ClDouble4, ClDouble8, ClDouble16};
 
+    // Shorter names matching the `OpenCL` C types (`float4` -> `Float4`).
+    pub use core::{ClChar2 as Char2, ClChar3 as Char3, ClChar4 as Char4,
+            ClChar8 as Char8, ClChar16 as Char16,
+        ClUchar2 as Uchar2, ClUchar3 as Uchar3, ClUchar4 as Uchar4,
+            ClUchar8 as Uchar8, ClUchar16 as Uchar16,
+        ClShort2 as Short2, ClShort3 as Short3, ClShort4 as Short4,
+            ClShort8 as Short8, ClShort16 as Short16,
+        ClUshort2 as Ushort2, ClUshort3 as Ushort3, ClUshort4 as Ushort4,
+            ClUshort8 as Ushort8, ClUshort16 as Ushort16,
+        ClInt2 as Int2, ClInt3 as Int3, ClInt4 as Int4,
+            ClInt8 as Int8, ClInt16 as Int16,
+        ClUint2 as Uint2, ClUint3 as Uint3, ClUint4 as Uint4,
+            ClUint8 as Uint8, ClUint16 as Uint16,
+        ClLong2 as Long2, ClLong3 as Long3, ClLong4 as Long4,
+            ClLong8 as Long8, ClLong16 as Long16,
+        ClUlong2 as Ulong2, ClUlong3 as Ulong3, ClUlong4 as Ulong4,
+            ClUlong8 as Ulong8, ClUlong16 as Ulong16,
+        ClFloat2 as Float2, ClFloat3 as Float3, ClFloat4 as Float4,
+            ClFloat8 as Float8, ClFloat16 as Float16,
+        ClDouble2 as Double2, ClDouble3 as Double3, ClDouble4 as Double4,
+            ClDouble8 as Double8, ClDouble16 as Double16};
+
     pub use ffi::{ cl_GLuint, cl_GLint, cl_GLenum };
 }
 
 pub mod traits {
     //! Commonly used traits.
 
-    pub use standard::{WorkDims, MemLen};
+    pub use standard::{WorkDims, MemLen, ClTypeName, OclStruct};
     pub use core::{OclPrm, OclScl, OclVec};
 }
 
 pub mod builders {
     //! Builders and associated settings-related types.
 
-    pub use standard::{ContextBuilder, BuildOpt, ProgramBuilder, ImageBuilder, ProQueBuilder,
-        DeviceSpecifier, BufferCmd, BufferCmdKind, BufferCmdDataShape,
-        ImageCmd, ImageCmdKind, KernelCmd};
+    pub use standard::{ContextBuilder, BuildOpt, ClStd, OptFlag, BuildProfile, ProgramBuilder,
+        ImageBuilder,
+        ProQueBuilder, DeviceSpecifier, BufferCmd, BufferCmdKind, BufferCmdDataShape,
+        ImageCmd, ImageCmdKind, KernelCmd, SamplerBuilder, QueueBuilder, MemFlagsBuilder, Task};
     pub use core::{ImageFormat, ImageDescriptor, ContextProperties};
     // #[cfg(not(release))] pub use standard::BufferTest;
 }
@@ -138,7 +192,8 @@ pub mod flags {
 pub mod enums {
     //! Enumerators for settings and information requests.
 
-    pub use standard::{DeviceSpecifier, BufferCmdKind, BufferCmdDataShape};
+    pub use standard::{DeviceSpecifier, BufferCmdKind, BufferCmdDataShape, BorderMode,
+        WriteConflict, FillColor, PartitionProperty};
 
     // API enums.
     pub use core::{ImageChannelOrder, ImageChannelDataType, Cbool, Polling, PlatformInfo,
@@ -158,3 +213,11 @@ pub mod enums {
     // Error status.
     pub use core::Status;
 }
+
+pub mod ext {
+    //! Function pointer types of extension functions, loaded with
+    //! `Platform::extension_fn` or one of its pre-typed loaders.
+
+    pub use standard::{CreateProgramWithIlKhrFn, TerminateContextKhrFn,
+        GetKernelSubGroupInfoKhrFn};
+}
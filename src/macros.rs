@@ -0,0 +1,64 @@
+//! Macros.
+
+/// Declares a `#[repr(C)]` struct usable as a buffer element and kernel
+/// argument, implementing `OclPrm`, `ClTypeName`, and `OclStruct` for it.
+///
+/// Every field must be public and have a type implementing `ClTypeName`
+/// (scalars and vector types). The `OpenCL` C declaration of the struct,
+/// with the same name, is available from `OclStruct::cl_decl`.
+///
+/// ## Example
+///
+/// ```text
+/// ocl_struct! {
+///     pub struct Particle {
+///         pub pos: ClFloat4,
+///         pub vel: ClFloat4,
+///         pub mass: f32,
+///     }
+/// }
+///
+/// Particle::check_layout().unwrap();
+/// let src = format!("{}\n{}", Particle::cl_decl(), kernel_src);
+/// let particles = Buffer::<Particle>::new(queue, None, [4096], None).unwrap();
+/// ```
+///
+#[macro_export]
+macro_rules! ocl_struct {
+    ($(#[$attr:meta])* pub struct $name:ident { $( pub $field:ident: $ty:ty ),+ $(,)* }) => {
+        $(#[$attr])*
+        #[repr(C)]
+        #[derive(Debug, Clone, Copy, Default, PartialEq)]
+        pub struct $name {
+            $( pub $field: $ty ),+
+        }
+
+        unsafe impl $crate::traits::OclPrm for $name {}
+
+        impl $crate::traits::ClTypeName for $name {
+            fn cl_type_name() -> &'static str { stringify!($name) }
+
+            fn cl_align() -> usize {
+                let mut align = 1;
+                $( align = ::std::cmp::max(align,
+                    <$ty as $crate::traits::ClTypeName>::cl_align()); )+
+                align
+            }
+        }
+
+        impl $crate::traits::OclStruct for $name {
+            fn fields() -> Vec<$crate::StructField> {
+                let base = <$name as ::std::default::Default>::default();
+                let base_addr = &base as *const $name as usize;
+
+                vec![ $( $crate::StructField::new(
+                    stringify!($field),
+                    <$ty as $crate::traits::ClTypeName>::cl_type_name(),
+                    &base.$field as *const $ty as usize - base_addr,
+                    ::std::mem::size_of::<$ty>(),
+                    <$ty as $crate::traits::ClTypeName>::cl_align(),
+                ) ),+ ]
+            }
+        }
+    };
+}
@@ -0,0 +1,224 @@
+//! Kernels generated from an argument list and a per-element statement.
+
+use core::OclPrm;
+use core::error::{Error as OclError, Result as OclResult};
+use standard::{Buffer, Kernel, Queue, Program, ClTypeName};
+
+
+/// A declared argument of an `ElementwiseKernel`.
+#[derive(Debug, Clone)]
+struct Arg {
+    name: String,
+    type_name: String,
+    is_buf: bool,
+}
+
+/// Parses one comma separated argument declaration such as
+/// `__global float const* x` or `uint n`.
+fn parse_arg(decl: &str) -> OclResult<Arg> {
+    let is_buf = decl.contains('*');
+    let spaced = decl.replace('*', " ");
+    let tokens: Vec<&str> = spaced.split_whitespace()
+        .filter(|t| !["__global", "global", "const", "restrict", "__restrict"].contains(t))
+        .collect();
+
+    match tokens.len() {
+        2 => Ok(Arg { name: tokens[1].to_owned(), type_name: tokens[0].to_owned(),
+            is_buf: is_buf }),
+        _ => OclError::err(format!("ocl::ops::ElementwiseKernel::new(): Unable to parse the \
+            argument declaration: '{}'. Use the form '[__global] type [const] [*] name'.",
+            decl.trim())),
+    }
+}
+
+
+/// A kernel applying a statement to every index of its buffer arguments,
+/// generated from an argument list and the statement alone.
+///
+/// `arguments` is a comma separated list of `OpenCL` C parameter
+/// declarations. Pointer arguments are buffers and are placed in global
+/// memory. `operation` is a statement, without the trailing semicolon, in
+/// which the current index is named `i`. The program is built once per
+/// queue (see `Queue::cached_program`).
+///
+/// Arguments are passed in order to the call returned by `::call`. Their
+/// element types are checked against the declarations.
+///
+/// ## Example
+///
+/// ```text
+/// let axpy = ElementwiseKernel::new(&queue, "axpy",
+///     "float a, float const* x, float* y", "y[i] = a * x[i] + y[i]").unwrap();
+///
+/// axpy.call().scl(2.0f32).buf(&x).buf(&y).enq().unwrap();
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct ElementwiseKernel {
+    name: String,
+    args: Vec<Arg>,
+    program: Program,
+    queue: Queue,
+}
+
+impl ElementwiseKernel {
+    /// Generates and builds a kernel named `name`.
+    pub fn new(queue: &Queue, name: &str, arguments: &str, operation: &str)
+            -> OclResult<ElementwiseKernel>
+    {
+        let args: Vec<Arg> = try!(arguments.split(',').map(parse_arg).collect());
+
+        if !args.iter().any(|arg| arg.is_buf) {
+            return OclError::err("ocl::ops::ElementwiseKernel::new(): At least one buffer \
+                argument is required.");
+        }
+
+        let params: Vec<String> = args.iter().map(|arg| {
+            if arg.is_buf {
+                format!("__global {}* const {}", arg.type_name, arg.name)
+            } else {
+                format!("{} const {}", arg.type_name, arg.name)
+            }
+        }).collect();
+
+        let kern_src = format!(r#"
+            __kernel void {name}({params}, uint const ocl_elementwise_len) {{
+                size_t const i = get_global_id(0);
+                if (i < ocl_elementwise_len) {{
+                    {operation};
+                }}
+            }}
+        "#, name = name, params = params.join(", "), operation = operation);
+
+        Ok(ElementwiseKernel {
+            name: name.to_owned(),
+            args: args,
+            program: try!(queue.cached_program(&kern_src)),
+            queue: queue.clone(),
+        })
+    }
+
+    /// Returns a new call to which the arguments are passed in order.
+    ///
+    /// Call `.enq()` to enqueue the kernel.
+    pub fn call(&self) -> ElementwiseCall {
+        ElementwiseCall {
+            ek: self,
+            kernel: Kernel::new(self.name.clone(), &self.program, &self.queue),
+            arg_count: 0,
+            len: None,
+            min_buf_len: None,
+            err: None,
+        }
+    }
+
+    /// Returns the name of the kernel.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+
+/// A call to an `ElementwiseKernel`, created by `ElementwiseKernel::call`.
+///
+/// Argument errors are returned by `::enq`.
+///
+#[must_use = "commands do nothing unless enqueued"]
+pub struct ElementwiseCall<'e> {
+    ek: &'e ElementwiseKernel,
+    kernel: OclResult<Kernel>,
+    arg_count: usize,
+    len: Option<usize>,
+    min_buf_len: Option<usize>,
+    err: Option<OclError>,
+}
+
+impl<'e> ElementwiseCall<'e> {
+    /// Checks the next argument against its declaration.
+    fn next_arg(&mut self, type_name: &str, is_buf: bool) -> bool {
+        if self.err.is_some() { return false; }
+
+        let idx = self.arg_count;
+        self.arg_count += 1;
+
+        let err = match self.ek.args.get(idx) {
+            None => format!("too many arguments (expected {})", self.ek.args.len()),
+            Some(arg) if arg.is_buf != is_buf || arg.type_name != type_name => {
+                format!("argument '{}' is declared as {} '{}' but was passed {} '{}'", arg.name,
+                    if arg.is_buf { "a buffer of" } else { "a scalar" }, arg.type_name,
+                    if is_buf { "a buffer of" } else { "a scalar" }, type_name)
+            },
+            Some(_) => return true,
+        };
+
+        self.err = Some(OclError::new(format!("ocl::ops::ElementwiseCall: Kernel '{}': {}.",
+            self.ek.name, err)));
+        false
+    }
+
+    /// Passes the next argument, a buffer.
+    ///
+    /// The shortest buffer passed determines the number of work items unless
+    /// set with `::len`.
+    pub fn buf<T: OclPrm + ClTypeName>(mut self, buffer: &Buffer<T>) -> ElementwiseCall<'e> {
+        if self.next_arg(T::cl_type_name(), true) {
+            self.min_buf_len = Some(match self.min_buf_len {
+                Some(min) => ::std::cmp::min(min, buffer.len()),
+                None => buffer.len(),
+            });
+            self.kernel = self.kernel.map(|k| k.arg_buf(buffer));
+        }
+        self
+    }
+
+    /// Passes the next argument, a scalar or vector value.
+    pub fn scl<T: OclPrm + ClTypeName>(mut self, scalar: T) -> ElementwiseCall<'e> {
+        if self.next_arg(T::cl_type_name(), false) {
+            self.kernel = self.kernel.map(|k| k.arg_scl(scalar));
+        }
+        self
+    }
+
+    /// Sets the number of elements to process, which may not exceed the
+    /// length of any buffer passed.
+    pub fn len(mut self, len: usize) -> ElementwiseCall<'e> {
+        self.len = Some(len);
+        self
+    }
+
+    /// Enqueues the kernel.
+    ///
+    /// ## Errors
+    ///
+    /// Every declared argument must have been passed, with matching types.
+    /// The length set with `::len` may not exceed that of the shortest
+    /// buffer passed.
+    ///
+    pub fn enq(self) -> OclResult<()> {
+        if let Some(err) = self.err { return Err(err); }
+
+        if self.arg_count != self.ek.args.len() {
+            return OclError::err(format!("ocl::ops::ElementwiseCall::enq(): Kernel '{}': \
+                {} of {} arguments passed.", self.ek.name, self.arg_count, self.ek.args.len()));
+        }
+
+        let min_buf_len = self.min_buf_len.unwrap_or(0);
+
+        let len = match self.len {
+            Some(len) if len > min_buf_len => {
+                return OclError::err(format!("ocl::ops::ElementwiseCall::enq(): Kernel '{}': \
+                    The length set with '::len' ({}) exceeds the length of the shortest buffer \
+                    passed ({}).", self.ek.name, len, min_buf_len));
+            },
+            Some(len) => len,
+            None => min_buf_len,
+        };
+
+        if len == 0 { return Ok(()); }
+
+        try!(self.kernel)
+            .gws([len])
+            .arg_scl(len as u32)
+            .enq()
+    }
+}
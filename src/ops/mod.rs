@@ -0,0 +1,1193 @@
+//! Ready-made operations on buffers, implemented by generating, caching, and
+//! launching small kernels.
+//!
+//! Generated programs are built once per queue (see
+//! `Queue::cached_program`) and reused on subsequent calls with the same
+//! types and expression.
+//!
+//! [UNSTABLE]: Function signatures may still change.
+
+use std::{cmp, mem};
+use core::OclPrm;
+use core::error::{Error as OclError, Result as OclResult};
+use standard::{Buffer, Kernel, Queue, Device, ClTypeName};
+
+mod elementwise;
+
+pub use self::elementwise::{ElementwiseKernel, ElementwiseCall};
+
+
+/// Returns an error if `dst_len` is less than `src_len`.
+fn check_lens(fn_name: &str, src_len: usize, dst_len: usize) -> OclResult<()> {
+    if dst_len < src_len {
+        OclError::err(format!("ocl::ops::{}(): The destination buffer (len: {}) is smaller \
+            than the source (len: {}).", fn_name, dst_len, src_len))
+    } else {
+        Ok(())
+    }
+}
+
+/// Applies `expr` to every element of `src`, storing the results in `dst`.
+///
+/// `expr` is an `OpenCL` C expression in which the current source element is
+/// named `x`. The expression must evaluate to the element type of `dst`.
+///
+/// ## Example
+///
+/// ```text
+/// ocl::ops::map(&src, &mut dst, "x * x + 1.0f").unwrap();
+/// ```
+///
+pub fn map<S, D>(src: &Buffer<S>, dst: &mut Buffer<D>, expr: &str) -> OclResult<()>
+        where S: OclPrm + ClTypeName, D: OclPrm + ClTypeName
+{
+    try!(check_lens("map", src.len(), dst.len()));
+
+    let kern_src = format!(r#"
+        __kernel void ocl_ops_map(__global {s} const* const src, __global {d}* const dst) {{
+            size_t idx = get_global_id(0);
+            {s} x = src[idx];
+            dst[idx] = {expr};
+        }}
+    "#, s = S::cl_type_name(), d = D::cl_type_name(), expr = expr);
+
+    let queue = src.default_queue();
+    let program = try!(queue.cached_program(&kern_src));
+
+    try!(Kernel::new("ocl_ops_map", &program, queue))
+        .gws([src.len()])
+        .arg_buf(src)
+        .arg_buf(dst)
+        .enq()
+}
+
+/// Combines each pair of elements from `a` and `b` using `expr`, storing the
+/// results in `dst`.
+///
+/// `expr` is an `OpenCL` C expression in which the current elements are
+/// named `a` and `b`. The expression must evaluate to the element type of
+/// `dst`.
+///
+/// ## Example
+///
+/// ```text
+/// ocl::ops::zip(&a, &b, &mut dst, "a + b").unwrap();
+/// ```
+///
+pub fn zip<A, B, D>(a: &Buffer<A>, b: &Buffer<B>, dst: &mut Buffer<D>, expr: &str)
+        -> OclResult<()>
+        where A: OclPrm + ClTypeName, B: OclPrm + ClTypeName, D: OclPrm + ClTypeName
+{
+    if a.len() != b.len() {
+        return OclError::err(format!("ocl::ops::zip(): Source buffer lengths differ \
+            (a: {}, b: {}).", a.len(), b.len()));
+    }
+    try!(check_lens("zip", a.len(), dst.len()));
+
+    let kern_src = format!(r#"
+        __kernel void ocl_ops_zip(__global {a} const* const src_a,
+                __global {b} const* const src_b, __global {d}* const dst)
+        {{
+            size_t idx = get_global_id(0);
+            {a} a = src_a[idx];
+            {b} b = src_b[idx];
+            dst[idx] = {expr};
+        }}
+    "#, a = A::cl_type_name(), b = B::cl_type_name(), d = D::cl_type_name(), expr = expr);
+
+    let queue = a.default_queue();
+    let program = try!(queue.cached_program(&kern_src));
+
+    try!(Kernel::new("ocl_ops_zip", &program, queue))
+        .gws([a.len()])
+        .arg_buf(a)
+        .arg_buf(b)
+        .arg_buf(dst)
+        .enq()
+}
+
+/// Copies each element of `src` whose corresponding element in `flags` is
+/// non-zero into `dst`, preserving order, and returns the number of elements
+/// copied.
+///
+/// Elements of `dst` past the returned count are left untouched. The count is
+/// obtained with a small blocking read.
+///
+/// ## Example
+///
+/// ```text
+/// let active_count = ocl::ops::compact(&rays, &active, &mut active_rays).unwrap();
+/// ```
+///
+pub fn compact<T, F>(src: &Buffer<T>, flags: &Buffer<F>, dst: &mut Buffer<T>) -> OclResult<usize>
+        where T: OclPrm + ClTypeName, F: OclPrm + ClTypeName
+{
+    if src.len() != flags.len() {
+        return OclError::err(format!("ocl::ops::compact(): Source and flag buffer lengths differ \
+            (src: {}, flags: {}).", src.len(), flags.len()));
+    }
+    try!(check_lens("compact", src.len(), dst.len()));
+
+    if src.len() == 0 { return Ok(0); }
+
+    let kern_src = format!(r#"
+        __kernel void ocl_ops_flags_to_uint(__global {f} const* const flags,
+                __global uint* const counts)
+        {{
+            size_t idx = get_global_id(0);
+            counts[idx] = flags[idx] != 0 ? 1 : 0;
+        }}
+
+        __kernel void ocl_ops_compact(__global {t} const* const src,
+                __global {f} const* const flags, __global uint const* const offsets,
+                __global {t}* const dst)
+        {{
+            size_t idx = get_global_id(0);
+
+            if (flags[idx] != 0) {{
+                dst[offsets[idx] - 1] = src[idx];
+            }}
+        }}
+    "#, t = T::cl_type_name(), f = F::cl_type_name());
+
+    let queue = src.default_queue();
+    let program = try!(queue.cached_program(&kern_src));
+
+    let counts = try!(Buffer::<u32>::new(queue.clone(), None, [src.len()], None));
+
+    try!(try!(Kernel::new("ocl_ops_flags_to_uint", &program, queue))
+        .gws([src.len()])
+        .arg_buf(flags)
+        .arg_buf(&counts)
+        .enq());
+
+    let mut offsets = try!(Buffer::<u32>::new(queue.clone(), None, [src.len()], None));
+    try!(scan_inclusive(&counts, &mut offsets));
+
+    try!(try!(Kernel::new("ocl_ops_compact", &program, queue))
+        .gws([src.len()])
+        .arg_buf(src)
+        .arg_buf(flags)
+        .arg_buf(&offsets)
+        .arg_buf(dst)
+        .enq());
+
+    let mut count = [0u32];
+    try!(offsets.read(&mut count).offset(src.len() - 1).enq());
+    Ok(count[0] as usize)
+}
+
+/// The tile width and height used by `transpose`.
+const TRANSPOSE_TILE: usize = 16;
+
+/// Transposes the `rows` x `cols` row-major matrix in `src`, storing the
+/// `cols` x `rows` result in `dst`.
+///
+/// Each work group stages a 16 x 16 tile in local memory so that both reads
+/// and writes to global memory are coalesced. Requires a device supporting
+/// work groups of at least 256 items.
+///
+pub fn transpose<T>(src: &Buffer<T>, dst: &mut Buffer<T>, rows: usize, cols: usize)
+        -> OclResult<()>
+        where T: OclPrm + ClTypeName
+{
+    if src.len() < rows * cols {
+        return OclError::err(format!("ocl::ops::transpose(): The source buffer (len: {}) is \
+            too small for a {} x {} matrix.", src.len(), rows, cols));
+    }
+    try!(check_lens("transpose", rows * cols, dst.len()));
+
+    if rows == 0 || cols == 0 { return Ok(()); }
+
+    let kern_src = format!(r#"
+        #define TILE {tile}
+
+        __kernel void ocl_ops_transpose(__global {t} const* const src, __global {t}* const dst,
+                uint const rows, uint const cols)
+        {{
+            __local {t} tile[TILE][TILE + 1];
+
+            size_t const tile_x = get_group_id(0) * TILE;
+            size_t const tile_y = get_group_id(1) * TILE;
+            size_t const lx = get_local_id(0);
+            size_t const ly = get_local_id(1);
+
+            size_t x = tile_x + lx;
+            size_t y = tile_y + ly;
+
+            if (x < cols && y < rows) {{
+                tile[ly][lx] = src[y * cols + x];
+            }}
+
+            barrier(CLK_LOCAL_MEM_FENCE);
+
+            x = tile_y + lx;
+            y = tile_x + ly;
+
+            if (x < rows && y < cols) {{
+                dst[y * rows + x] = tile[lx][ly];
+            }}
+        }}
+    "#, tile = TRANSPOSE_TILE, t = T::cl_type_name());
+
+    let queue = src.default_queue();
+    let program = try!(queue.cached_program(&kern_src));
+    let round_up = |len: usize| ((len + TRANSPOSE_TILE - 1) / TRANSPOSE_TILE) * TRANSPOSE_TILE;
+
+    try!(Kernel::new("ocl_ops_transpose", &program, queue))
+        .gws([round_up(cols), round_up(rows)])
+        .lws([TRANSPOSE_TILE, TRANSPOSE_TILE])
+        .arg_buf(src)
+        .arg_buf(dst)
+        .arg_scl(rows as u32)
+        .arg_scl(cols as u32)
+        .enq()
+}
+
+/// Copies a `rows` x `cols` block of elements from `src` to `dst`, where
+/// consecutive rows begin every `src_pitch` and `dst_pitch` elements
+/// respectively.
+///
+/// Useful for packing or unpacking padded (pitched) 2D data.
+///
+pub fn copy_pitched<T>(src: &Buffer<T>, src_pitch: usize, dst: &mut Buffer<T>, dst_pitch: usize,
+            rows: usize, cols: usize) -> OclResult<()>
+        where T: OclPrm
+{
+    if src_pitch < cols || dst_pitch < cols {
+        return OclError::err(format!("ocl::ops::copy_pitched(): Pitches (src: {}, dst: {}) \
+            must be at least as large as the number of columns ({}).", src_pitch, dst_pitch, cols));
+    }
+
+    if rows == 0 || cols == 0 { return Ok(()); }
+
+    let pitched_len = |pitch: usize| (rows - 1) * pitch + cols;
+
+    if src.len() < pitched_len(src_pitch) || dst.len() < pitched_len(dst_pitch) {
+        return OclError::err(format!("ocl::ops::copy_pitched(): Buffers (src len: {}, dst len: \
+            {}) are too small for {} rows of {} columns.", src.len(), dst.len(), rows, cols));
+    }
+
+    let ele_size = mem::size_of::<T>();
+    let region = [cols * ele_size, rows, 1];
+    let src_row_pitch = src_pitch * ele_size;
+    let dst_row_pitch = dst_pitch * ele_size;
+
+    src.cmd()
+        .copy(dst, 0, 0)
+        .rect([0, 0, 0], [0, 0, 0], region, src_row_pitch, src_row_pitch * rows,
+            dst_row_pitch, dst_row_pitch * rows)
+        .enq()
+}
+
+/// Converts each element of `src` to the element type of `dst` using the
+/// `OpenCL` C `convert_{type}` built-ins.
+///
+/// Conversions use the default rounding mode and are not saturated. Vector
+/// types are converted component-wise and must have matching widths.
+///
+/// ## Example
+///
+/// ```text
+/// ocl::ops::convert::<i32, f32>(&src, &mut dst).unwrap();
+/// ```
+///
+pub fn convert<S, D>(src: &Buffer<S>, dst: &mut Buffer<D>) -> OclResult<()>
+        where S: OclPrm + ClTypeName, D: OclPrm + ClTypeName
+{
+    map(src, dst, &format!("convert_{}(x)", D::cl_type_name()))
+}
+
+/// Converts normalized 8-bit unsigned values to floats in `[0.0, 1.0]`.
+pub fn unorm8_to_float(src: &Buffer<u8>, dst: &mut Buffer<f32>) -> OclResult<()> {
+    map(src, dst, "convert_float(x) / 255.0f")
+}
+
+/// Converts floats in `[0.0, 1.0]` to normalized 8-bit unsigned values,
+/// rounding to nearest and saturating values outside that range.
+pub fn float_to_unorm8(src: &Buffer<f32>, dst: &mut Buffer<u8>) -> OclResult<()> {
+    map(src, dst, "convert_uchar_sat_rte(x * 255.0f)")
+}
+
+/// Converts half-precision floats, stored as their raw bits in `u16`s, to
+/// single-precision floats.
+///
+/// Does not require the `cl_khr_fp16` extension.
+pub fn half_to_float(src: &Buffer<u16>, dst: &mut Buffer<f32>) -> OclResult<()> {
+    try!(check_lens("half_to_float", src.len(), dst.len()));
+
+    let kern_src = r#"
+        __kernel void ocl_ops_half_to_float(__global ushort const* const src,
+                __global float* const dst)
+        {
+            size_t idx = get_global_id(0);
+            dst[idx] = vload_half(idx, (__global half const*)src);
+        }
+    "#;
+
+    let queue = src.default_queue();
+    let program = try!(queue.cached_program(kern_src));
+
+    try!(Kernel::new("ocl_ops_half_to_float", &program, queue))
+        .gws([src.len()])
+        .arg_buf(src)
+        .arg_buf(dst)
+        .enq()
+}
+
+/// Converts single-precision floats to half-precision floats, stored as
+/// their raw bits in `u16`s, rounding to nearest even.
+///
+/// Does not require the `cl_khr_fp16` extension.
+pub fn float_to_half(src: &Buffer<f32>, dst: &mut Buffer<u16>) -> OclResult<()> {
+    try!(check_lens("float_to_half", src.len(), dst.len()));
+
+    let kern_src = r#"
+        __kernel void ocl_ops_float_to_half(__global float const* const src,
+                __global ushort* const dst)
+        {
+            size_t idx = get_global_id(0);
+            vstore_half_rte(src[idx], idx, (__global half*)dst);
+        }
+    "#;
+
+    let queue = src.default_queue();
+    let program = try!(queue.cached_program(kern_src));
+
+    try!(Kernel::new("ocl_ops_float_to_half", &program, queue))
+        .gws([src.len()])
+        .arg_buf(src)
+        .arg_buf(dst)
+        .enq()
+}
+
+/// Returns the `OpenCL` C statement converting the 64-bit random value `r`
+/// into an element in `[lo, hi)`, or `None` if `type_name` is unsupported.
+fn random_ele_src(type_name: &str) -> Option<&'static str> {
+    match type_name {
+        // Rounding may otherwise produce `hi`:
+        "float" => Some("fmin(lo + (hi - lo) * ((float)(r >> 40) * 0x1.0p-24f), \
+            nextafter(hi, lo))"),
+        "double" => Some("fmin(lo + (hi - lo) * ((double)(r >> 11) * 0x1.0p-53), \
+            nextafter(hi, lo))"),
+        "char" | "uchar" | "short" | "ushort" | "int" | "uint" | "long" | "ulong" =>
+            Some("lo + r % ((ulong)hi - (ulong)lo)"),
+        _ => None,
+    }
+}
+
+/// Fills `buffer` with pseudo-random values in `[range.0, range.1)`,
+/// generated on the device.
+///
+/// Much faster than generating and uploading a vector on the host (e.g.
+/// with `util::scrambled_vec`) for large buffers. Each element is derived
+/// from `seed` and its index with a SplitMix64 hash, so integer results are
+/// reproducible for a given seed and buffer length on any device. Floating
+/// point results are reproducible on a given device but may differ in the
+/// last bits between devices. Not suitable for cryptographic use.
+///
+/// Supported element types are the scalar integer types, `f32`, and,
+/// on devices supporting `cl_khr_fp64`, `f64`.
+///
+/// ## Errors
+///
+/// `range.0` must be less than `range.1`.
+///
+/// ## Example
+///
+/// ```text
+/// ocl::ops::fill_random(&mut buffer, (0.0f32, 20.0), 0xDEADBEEF).unwrap();
+/// ```
+///
+pub fn fill_random<T>(buffer: &mut Buffer<T>, range: (T, T), seed: u64) -> OclResult<()>
+        where T: OclPrm + ClTypeName + PartialOrd
+{
+    let ele_src = match random_ele_src(T::cl_type_name()) {
+        Some(src) => src,
+        None => return OclError::err(format!("ocl::ops::fill_random(): Element type '{}' is \
+            not supported. Use a scalar integer type, 'float', or 'double'.",
+            T::cl_type_name())),
+    };
+
+    if !(range.0 < range.1) {
+        return OclError::err(format!("ocl::ops::fill_random(): The range ({:?}) is empty or \
+            reversed.", range));
+    }
+
+    if buffer.len() == 0 { return Ok(()); }
+
+    let pragma = match T::cl_type_name() {
+        "double" => "#pragma OPENCL EXTENSION cl_khr_fp64 : enable",
+        _ => "",
+    };
+
+    let kern_src = format!(r#"
+        {pragma}
+
+        __kernel void ocl_ops_fill_random(__global {t}* const dst, {t} const lo, {t} const hi,
+                ulong const seed)
+        {{
+            size_t idx = get_global_id(0);
+
+            ulong r = seed + (idx + 1) * 0x9E3779B97F4A7C15UL;
+            r = (r ^ (r >> 30)) * 0xBF58476D1CE4E5B9UL;
+            r = (r ^ (r >> 27)) * 0x94D049BB133111EBUL;
+            r ^= r >> 31;
+
+            dst[idx] = ({t})({ele});
+        }}
+    "#, pragma = pragma, t = T::cl_type_name(), ele = ele_src);
+
+    let queue = buffer.default_queue();
+    let program = try!(queue.cached_program(&kern_src));
+
+    try!(Kernel::new("ocl_ops_fill_random", &program, queue))
+        .gws([buffer.len()])
+        .arg_buf(buffer)
+        .arg_scl(range.0)
+        .arg_scl(range.1)
+        .arg_scl(seed)
+        .enq()
+}
+
+/// The largest work group size used by reductions and scans.
+const MAX_WG_SIZE: usize = 256;
+
+/// Returns the largest power of two work group size, up to `MAX_WG_SIZE`,
+/// supported by both `device` and `kernel`.
+fn pow2_wg_size(kernel: &Kernel, device: &Device) -> OclResult<usize> {
+    let max_wg_size = cmp::min(cmp::min(MAX_WG_SIZE, try!(device.max_work_group_size())),
+        try!(kernel.wg_size(device)));
+    let mut wg_size = 1;
+    while wg_size * 2 <= max_wg_size { wg_size *= 2; }
+    Ok(wg_size)
+}
+
+/// Reduces the elements loaded by `load` (an expression of the `a` and `b`
+/// source pointers and the index `i`) using `op` (an expression of the
+/// accumulated value `acc` and the next value `x`), starting from `init`
+/// (which may refer to `a[0]`), and returns the result.
+///
+/// The first pass reduces the sources to one partial result per work group
+/// and the second reduces the partials using a single work group. The work
+/// group size is the largest power of two supported by both the device and
+/// the kernel, up to 256, and the number of groups is a small multiple of
+/// the number of compute units.
+fn reduce<T>(fn_name: &str, a: &Buffer<T>, b: &Buffer<T>, load: &str, op: &str, init: &str)
+        -> OclResult<T>
+        where T: OclPrm + ClTypeName
+{
+    let len = a.len();
+
+    if len == 0 {
+        return OclError::err(format!("ocl::ops::{}(): The buffer is empty.", fn_name));
+    }
+
+    let pragma = if T::cl_type_name().starts_with("double") {
+        "#pragma OPENCL EXTENSION cl_khr_fp64 : enable"
+    } else {
+        ""
+    };
+
+    let kern_src = format!(r#"
+        {pragma}
+
+        __kernel void ocl_ops_reduce(__global {t} const* const a, __global {t} const* const b,
+                __global {t}* const partials, uint const len, uint const first,
+                __local {t}* const scratch)
+        {{
+            size_t const lid = get_local_id(0);
+            {t} acc = {init};
+
+            for (size_t i = get_global_id(0); i < len; i += get_global_size(0)) {{
+                {t} x = first ? ({load}) : a[i];
+                acc = {op};
+            }}
+
+            scratch[lid] = acc;
+            barrier(CLK_LOCAL_MEM_FENCE);
+
+            for (size_t s = get_local_size(0) / 2; s > 0; s >>= 1) {{
+                if (lid < s) {{
+                    acc = scratch[lid];
+                    {t} x = scratch[lid + s];
+                    scratch[lid] = {op};
+                }}
+                barrier(CLK_LOCAL_MEM_FENCE);
+            }}
+
+            if (lid == 0) {{
+                partials[get_group_id(0)] = scratch[0];
+            }}
+        }}
+    "#, pragma = pragma, t = T::cl_type_name(), load = load, op = op, init = init);
+
+    let queue = a.default_queue();
+    let device = queue.device();
+    let program = try!(queue.cached_program(&kern_src));
+    let mut kernel = try!(Kernel::new("ocl_ops_reduce", &program, queue));
+
+    let wg_size = try!(pow2_wg_size(&kernel, device));
+
+    let max_groups = try!(device.max_compute_units()) as usize * 4;
+    let groups = cmp::max(1, cmp::min((len + wg_size - 1) / wg_size, max_groups));
+    let partials = try!(Buffer::<T>::new(queue.clone(), None, [groups], None));
+
+    kernel = kernel
+        .gws([groups * wg_size])
+        .lws([wg_size])
+        .arg_buf(a)
+        .arg_buf(b)
+        .arg_buf(&partials)
+        .arg_scl(len as u32)
+        .arg_scl(1u32)
+        .arg_loc::<T>(wg_size);
+    try!(kernel.enq());
+
+    let mut result = [T::default()];
+
+    if groups > 1 {
+        let total = try!(Buffer::<T>::new(queue.clone(), None, [1], None));
+        try!(kernel.set_arg_buf(0, &partials));
+        try!(kernel.set_arg_buf(2, &total));
+        try!(kernel.set_arg(3, groups as u32));
+        try!(kernel.set_arg(4, 0u32));
+        try!(kernel.cmd().gws([wg_size]).enq());
+        try!(total.read(&mut result[..]).enq());
+    } else {
+        try!(partials.read(&mut result[..]).enq());
+    }
+
+    Ok(result[0])
+}
+
+/// Returns the sum of the elements of `src`.
+///
+/// Elements are summed in their own type and in no particular order, so
+/// integer sums may overflow and floating point sums may differ slightly
+/// from a sequential sum.
+///
+pub fn sum<T>(src: &Buffer<T>) -> OclResult<T> where T: OclPrm + ClTypeName {
+    let zero = format!("({})0", T::cl_type_name());
+    reduce("sum", src, src, "a[i]", "acc + x", &zero)
+}
+
+/// Returns the smallest element of `src`, which must not be empty.
+pub fn min<T>(src: &Buffer<T>) -> OclResult<T> where T: OclPrm + ClTypeName {
+    reduce("min", src, src, "a[i]", "min(acc, x)", "a[0]")
+}
+
+/// Returns the largest element of `src`, which must not be empty.
+pub fn max<T>(src: &Buffer<T>) -> OclResult<T> where T: OclPrm + ClTypeName {
+    reduce("max", src, src, "a[i]", "max(acc, x)", "a[0]")
+}
+
+/// Returns the dot product of `a` and `b`, the sum of the products of their
+/// corresponding elements.
+///
+/// See `::sum` regarding precision.
+///
+pub fn dot<T>(a: &Buffer<T>, b: &Buffer<T>) -> OclResult<T> where T: OclPrm + ClTypeName {
+    if a.len() != b.len() {
+        return OclError::err(format!("ocl::ops::dot(): Buffer lengths differ (a: {}, b: {}).",
+            a.len(), b.len()));
+    }
+    let zero = format!("({})0", T::cl_type_name());
+    reduce("dot", a, b, "a[i] * b[i]", "acc + x", &zero)
+}
+
+/// Returns the source of the scan kernels for elements of type `T`.
+fn scan_src<T: ClTypeName>() -> String {
+    let pragma = if T::cl_type_name().starts_with("double") {
+        "#pragma OPENCL EXTENSION cl_khr_fp64 : enable"
+    } else {
+        ""
+    };
+
+    format!(r#"
+        {pragma}
+
+        // Scans blocks of two elements per work item in local memory
+        // (Blelloch), storing the exclusive scan of each block in `dst` and
+        // its total in `sums`.
+        __kernel void ocl_ops_scan_blocks(__global {t} const* const src,
+                __global {t}* const dst, __global {t}* const sums, uint const len,
+                __local {t}* const tmp)
+        {{
+            size_t const lid = get_local_id(0);
+            size_t const n = get_local_size(0) * 2;
+            size_t const base = get_group_id(0) * n;
+            size_t const ai = lid;
+            size_t const bi = lid + n / 2;
+            size_t offset = 1;
+
+            tmp[ai] = base + ai < len ? src[base + ai] : ({t})0;
+            tmp[bi] = base + bi < len ? src[base + bi] : ({t})0;
+
+            for (size_t d = n >> 1; d > 0; d >>= 1) {{
+                barrier(CLK_LOCAL_MEM_FENCE);
+                if (lid < d) {{
+                    tmp[offset * (2 * lid + 2) - 1] += tmp[offset * (2 * lid + 1) - 1];
+                }}
+                offset <<= 1;
+            }}
+
+            if (lid == 0) {{
+                sums[get_group_id(0)] = tmp[n - 1];
+                tmp[n - 1] = ({t})0;
+            }}
+
+            for (size_t d = 1; d < n; d <<= 1) {{
+                offset >>= 1;
+                barrier(CLK_LOCAL_MEM_FENCE);
+                if (lid < d) {{
+                    size_t const i = offset * (2 * lid + 1) - 1;
+                    size_t const j = offset * (2 * lid + 2) - 1;
+                    {t} const left = tmp[i];
+                    tmp[i] = tmp[j];
+                    tmp[j] += left;
+                }}
+            }}
+
+            barrier(CLK_LOCAL_MEM_FENCE);
+            if (base + ai < len) {{ dst[base + ai] = tmp[ai]; }}
+            if (base + bi < len) {{ dst[base + bi] = tmp[bi]; }}
+        }}
+
+        // Adds the scanned block totals to each element of their block.
+        __kernel void ocl_ops_scan_add_carry(__global {t}* const dst,
+                __global {t} const* const carries, uint const block_len)
+        {{
+            size_t const idx = get_global_id(0);
+            dst[idx] += carries[idx / block_len];
+        }}
+
+        // Converts an exclusive scan into an inclusive one.
+        __kernel void ocl_ops_scan_inclusive(__global {t} const* const src,
+                __global {t}* const dst)
+        {{
+            size_t const idx = get_global_id(0);
+            dst[idx] += src[idx];
+        }}
+    "#, pragma = pragma, t = T::cl_type_name())
+}
+
+/// An exclusive scan of the first `len` elements of one buffer into another,
+/// with its kernels and intermediate buffers created up front so that it
+/// may be enqueued repeatedly.
+struct ExclusiveScan<T: OclPrm> {
+    blocks: Kernel,
+    // The scan of the block totals and the kernel adding them back, when
+    // there is more than one block:
+    carry: Option<(Box<ExclusiveScan<T>>, Kernel, Buffer<T>)>,
+    _sums: Buffer<T>,
+}
+
+impl<T> ExclusiveScan<T> where T: OclPrm + ClTypeName {
+    /// Returns a new scan of the first `len` elements of `src` into `dst`,
+    /// scanning the block totals recursively when there is more than one.
+    fn new(queue: &Queue, kern_src: &str, src: &Buffer<T>, dst: &Buffer<T>, len: usize)
+            -> OclResult<ExclusiveScan<T>>
+    {
+        let program = try!(queue.cached_program(kern_src));
+        let kernel = try!(Kernel::new("ocl_ops_scan_blocks", &program, queue));
+        let wg_size = try!(pow2_wg_size(&kernel, queue.device()));
+        let block_len = wg_size * 2;
+        let blocks = (len + block_len - 1) / block_len;
+
+        let sums = try!(Buffer::<T>::new(queue.clone(), None, [blocks], None));
+
+        let kernel = kernel
+            .gws([blocks * wg_size])
+            .lws([wg_size])
+            .arg_buf(src)
+            .arg_buf(dst)
+            .arg_buf(&sums)
+            .arg_scl(len as u32)
+            .arg_loc::<T>(block_len);
+
+        let carry = if blocks > 1 {
+            let carries = try!(Buffer::<T>::new(queue.clone(), None, [blocks], None));
+            let scan = try!(ExclusiveScan::new(queue, kern_src, &sums, &carries, blocks));
+
+            let add_carry = try!(Kernel::new("ocl_ops_scan_add_carry", &program, queue))
+                .gws([len])
+                .arg_buf(dst)
+                .arg_buf(&carries)
+                .arg_scl(block_len as u32);
+
+            Some((Box::new(scan), add_carry, carries))
+        } else {
+            None
+        };
+
+        Ok(ExclusiveScan { blocks: kernel, carry: carry, _sums: sums })
+    }
+
+    /// Enqueues the scan.
+    fn enq(&self) -> OclResult<()> {
+        try!(self.blocks.enq());
+
+        if let Some((ref scan, ref add_carry, _)) = self.carry {
+            try!(scan.enq());
+            try!(add_carry.enq());
+        }
+
+        Ok(())
+    }
+}
+
+/// Stores the exclusive scan of the first `len` elements of `src` in `dst`.
+fn scan_exclusive_len<T>(queue: &Queue, kern_src: &str, src: &Buffer<T>, dst: &Buffer<T>,
+            len: usize) -> OclResult<()>
+        where T: OclPrm + ClTypeName
+{
+    try!(ExclusiveScan::new(queue, kern_src, src, dst, len)).enq()
+}
+
+/// Stores the inclusive prefix sum of `src` in `dst`: element `i` of `dst`
+/// becomes the sum of elements `0` through `i` of `src`.
+///
+/// Each work group scans a block in local memory (Blelloch) and the block
+/// totals are scanned recursively and added back, so any length is
+/// supported. Elements are summed in their own type.
+///
+/// ## Example
+///
+/// ```text
+/// ocl::ops::scan_inclusive(&counts, &mut offsets).unwrap();
+/// ```
+///
+pub fn scan_inclusive<T>(src: &Buffer<T>, dst: &mut Buffer<T>) -> OclResult<()>
+        where T: OclPrm + ClTypeName
+{
+    try!(check_lens("scan_inclusive", src.len(), dst.len()));
+    if src.len() == 0 { return Ok(()); }
+
+    let queue = src.default_queue();
+    let kern_src = scan_src::<T>();
+    try!(scan_exclusive_len(queue, &kern_src, src, dst, src.len()));
+
+    let program = try!(queue.cached_program(&kern_src));
+
+    try!(Kernel::new("ocl_ops_scan_inclusive", &program, queue))
+        .gws([src.len()])
+        .arg_buf(src)
+        .arg_buf(dst)
+        .enq()
+}
+
+/// Stores the exclusive prefix sum of `src` in `dst`: element `i` of `dst`
+/// becomes the sum of elements `0` through `i - 1` of `src`, the first
+/// being zero.
+///
+/// See `::scan_inclusive`.
+///
+pub fn scan_exclusive<T>(src: &Buffer<T>, dst: &mut Buffer<T>) -> OclResult<()>
+        where T: OclPrm + ClTypeName
+{
+    try!(check_lens("scan_exclusive", src.len(), dst.len()));
+    if src.len() == 0 { return Ok(()); }
+
+    scan_exclusive_len(src.default_queue(), &scan_src::<T>(), src, dst, src.len())
+}
+
+/// The number of key bits sorted by each radix sort pass.
+const RADIX_BITS: u32 = 4;
+
+/// The number of keys counted and scattered by each radix sort work item.
+const RADIX_BLOCK_LEN: usize = 64;
+
+/// Returns the unsigned type, bit width, and signedness used to radix sort
+/// keys of type `type_name`, or `None` if they are not integers.
+fn radix_key_info(type_name: &str) -> Option<(&'static str, u32, bool)> {
+    match type_name {
+        "uchar" => Some(("uchar", 8, false)),
+        "char" => Some(("uchar", 8, true)),
+        "ushort" => Some(("ushort", 16, false)),
+        "short" => Some(("ushort", 16, true)),
+        "uint" => Some(("uint", 32, false)),
+        "int" => Some(("uint", 32, true)),
+        "ulong" => Some(("ulong", 64, false)),
+        "long" => Some(("ulong", 64, true)),
+        _ => None,
+    }
+}
+
+/// Sorts `keys`, and `vals` alongside them if any.
+fn sort_impl<K, V>(fn_name: &str, keys: &Buffer<K>, vals: Option<&Buffer<V>>) -> OclResult<()>
+        where K: OclPrm + ClTypeName, V: OclPrm + ClTypeName
+{
+    let (k, v) = (K::cl_type_name(), V::cl_type_name());
+
+    let pragma = if k.starts_with("double") || (vals.is_some() && v.starts_with("double")) {
+        "#pragma OPENCL EXTENSION cl_khr_fp64 : enable"
+    } else {
+        ""
+    };
+
+    // Value parameters and statements, empty when sorting keys only:
+    let (val_params, val_scatter, val_param_in_place, val_swap) = match vals {
+        Some(_) => (
+            format!(", __global {v} const* const vals, __global {v}* const vals_dst", v = v),
+            "vals_dst[dst] = vals[i];".to_owned(),
+            format!(", __global {v}* const vals_dst", v = v),
+            format!("{v} const val = vals_dst[i]; vals_dst[i] = vals_dst[l]; \
+                vals_dst[l] = val;", v = v),
+        ),
+        None => (String::new(), String::new(), String::new(), String::new()),
+    };
+
+    if keys.len() <= 1 { return Ok(()); }
+    let len = keys.len();
+    let queue = keys.default_queue();
+
+    match radix_key_info(k) {
+        // Least significant digit radix sort, `RADIX_BITS` per pass. Each
+        // work item counts the digits of a block of keys, an exclusive scan
+        // of the digit-major counts gives the destination of the first key
+        // of each digit in each block, and each work item then stably
+        // scatters its block.
+        Some((u, bits, signed)) => {
+            let sign = if signed { format!("(({u})1 << {})", bits - 1, u = u) }
+                else { "0".to_owned() };
+
+            let kern_src = format!(r#"
+                {pragma}
+
+                #define DIGIT(key) (((({u})(key) ^ {sign}) >> shift) & {mask})
+
+                __kernel void ocl_ops_radix_count(__global {k} const* const keys,
+                        __global uint* const counts, uint const shift, uint const len)
+                {{
+                    size_t const block = get_global_id(0);
+                    size_t const blocks = get_global_size(0);
+                    size_t const end = min((block + 1) * {block_len}, (size_t)len);
+                    uint digit_counts[{radix}];
+
+                    for (uint d = 0; d < {radix}; d++) {{ digit_counts[d] = 0; }}
+
+                    for (size_t i = block * {block_len}; i < end; i++) {{
+                        digit_counts[DIGIT(keys[i])] += 1;
+                    }}
+
+                    for (uint d = 0; d < {radix}; d++) {{
+                        counts[d * blocks + block] = digit_counts[d];
+                    }}
+                }}
+
+                __kernel void ocl_ops_radix_scatter(__global {k} const* const keys,
+                        __global {k}* const keys_dst {val_params},
+                        __global uint const* const offsets, uint const shift, uint const len)
+                {{
+                    size_t const block = get_global_id(0);
+                    size_t const blocks = get_global_size(0);
+                    size_t const end = min((block + 1) * {block_len}, (size_t)len);
+                    uint dsts[{radix}];
+
+                    for (uint d = 0; d < {radix}; d++) {{
+                        dsts[d] = offsets[d * blocks + block];
+                    }}
+
+                    for (size_t i = block * {block_len}; i < end; i++) {{
+                        uint const dst = dsts[DIGIT(keys[i])]++;
+                        keys_dst[dst] = keys[i];
+                        {val_scatter}
+                    }}
+                }}
+            "#, pragma = pragma, k = k, u = u, sign = sign, mask = (1 << RADIX_BITS) - 1,
+                radix = 1 << RADIX_BITS, block_len = RADIX_BLOCK_LEN, val_params = val_params,
+                val_scatter = val_scatter);
+
+            let program = try!(queue.cached_program(&kern_src));
+            let blocks = (len + RADIX_BLOCK_LEN - 1) / RADIX_BLOCK_LEN;
+            let counts = try!(Buffer::<u32>::new(queue.clone(), None, [blocks << RADIX_BITS],
+                None));
+            let offsets = try!(Buffer::<u32>::new(queue.clone(), None, [blocks << RADIX_BITS],
+                None));
+            let scan = try!(ExclusiveScan::new(queue, &scan_src::<u32>(), &counts, &offsets,
+                counts.len()));
+            let keys_tmp = try!(Buffer::<K>::new(queue.clone(), None, [len], None));
+            let vals_tmp = match vals {
+                Some(_) => Some(try!(Buffer::<V>::new(queue.clone(), None, [len], None))),
+                None => None,
+            };
+
+            // The counting and scattering kernels of passes from `keys` into
+            // the temporary buffers, and of those back:
+            let mut passes = Vec::with_capacity(2);
+
+            for &(keys_src, keys_dst, vals_src, vals_dst) in &[
+                (keys, &keys_tmp, vals, vals_tmp.as_ref()),
+                (&keys_tmp, keys, vals_tmp.as_ref(), vals),
+            ] {
+                let count = try!(Kernel::new("ocl_ops_radix_count", &program, queue))
+                    .gws([blocks])
+                    .arg_buf(keys_src)
+                    .arg_buf(&counts)
+                    .arg_scl_named::<u32>("shift", None)
+                    .arg_scl(len as u32);
+
+                let mut scatter = try!(Kernel::new("ocl_ops_radix_scatter", &program, queue))
+                    .gws([blocks])
+                    .arg_buf(keys_src)
+                    .arg_buf(keys_dst);
+
+                if let (Some(vs), Some(vd)) = (vals_src, vals_dst) {
+                    scatter = scatter.arg_buf(vs).arg_buf(vd);
+                }
+
+                let scatter = scatter
+                    .arg_buf(&offsets)
+                    .arg_scl_named::<u32>("shift", None)
+                    .arg_scl(len as u32);
+
+                passes.push((count, scatter));
+            }
+
+            // The number of passes is even so the result ends up in `keys`:
+            for pass in 0..(bits / RADIX_BITS) {
+                let (ref mut count, ref mut scatter) = passes[pass as usize % 2];
+                let shift = pass * RADIX_BITS;
+
+                try!(try!(count.set_arg_scl_named("shift", shift)).enq());
+                try!(scan.enq());
+                try!(try!(scatter.set_arg_scl_named("shift", shift)).enq());
+            }
+
+            Ok(())
+        },
+        // Bitonic sort, with every comparator placing the smaller key first
+        // so that the keys may be treated as padded to a power of two with
+        // keys larger than any other.
+        None => {
+            match k {
+                "float" | "double" => (),
+                _ => return OclError::err(format!("ocl::ops::{}(): Key type '{}' is not \
+                    supported. Use a scalar integer type, 'float', or 'double'.", fn_name, k)),
+            }
+
+            let kern_src = format!(r#"
+                {pragma}
+
+                __kernel void ocl_ops_bitonic_step(__global {k}* const keys_dst
+                        {val_param}, uint const len, uint const partner_mask)
+                {{
+                    size_t const i = get_global_id(0);
+                    size_t const l = i ^ partner_mask;
+
+                    if (l > i && l < len && keys_dst[l] < keys_dst[i]) {{
+                        {k} const key = keys_dst[i];
+                        keys_dst[i] = keys_dst[l];
+                        keys_dst[l] = key;
+                        {val_swap}
+                    }}
+                }}
+            "#, pragma = pragma, k = k, val_param = val_param_in_place, val_swap = val_swap);
+
+            let program = try!(queue.cached_program(&kern_src));
+            let padded_len = len.next_power_of_two();
+            let mut kernel = try!(Kernel::new("ocl_ops_bitonic_step", &program, queue))
+                .gws([padded_len])
+                .arg_buf(keys);
+
+            if let Some(vals) = vals { kernel = kernel.arg_buf(vals); }
+
+            let mut kernel = kernel
+                .arg_scl(len as u32)
+                .arg_scl(0u32);
+            let mask_idx = if vals.is_some() { 3 } else { 2 };
+
+            let mut size = 2;
+            while size <= padded_len {
+                try!(kernel.set_arg(mask_idx, (size - 1) as u32));
+                try!(kernel.enq());
+
+                let mut dist = size / 4;
+                while dist > 0 {
+                    try!(kernel.set_arg(mask_idx, dist as u32));
+                    try!(kernel.enq());
+                    dist /= 2;
+                }
+                size *= 2;
+            }
+
+            Ok(())
+        },
+    }
+}
+
+/// Sorts `keys` into ascending order.
+///
+/// Integer keys are sorted with a stable radix sort built on
+/// `::scan_exclusive`, one pass per four bits. Floating point keys are sorted
+/// with a bitonic sort, which is not stable and does not order NaNs.
+///
+/// ## Example
+///
+/// ```text
+/// ocl::ops::sort(&mut cell_ids).unwrap();
+/// ```
+///
+pub fn sort<K>(keys: &mut Buffer<K>) -> OclResult<()> where K: OclPrm + ClTypeName {
+    sort_impl::<K, K>("sort", keys, None)
+}
+
+/// Sorts `keys` into ascending order, reordering `values` alongside them.
+///
+/// See `::sort`.
+///
+/// ## Example
+///
+/// ```text
+/// ocl::ops::sort_by_key(&mut cell_ids, &mut particle_ids).unwrap();
+/// ```
+///
+pub fn sort_by_key<K, V>(keys: &mut Buffer<K>, values: &mut Buffer<V>) -> OclResult<()>
+        where K: OclPrm + ClTypeName, V: OclPrm + ClTypeName
+{
+    if keys.len() != values.len() {
+        return OclError::err(format!("ocl::ops::sort_by_key(): Key and value buffer lengths \
+            differ (keys: {}, values: {}).", keys.len(), values.len()));
+    }
+    sort_impl("sort_by_key", keys, Some(&*values))
+}
+
+/// Returns the statement atomically adding `partials[idx]` to
+/// `totals[idx % len]`, or `None` if `T` has no atomic add.
+fn atomic_add_src(type_name: &str) -> Option<String> {
+    match type_name {
+        "int" | "uint" => Some("atomic_add(&totals[idx % len], partials[idx]);".to_owned()),
+        "long" | "ulong" => Some("atom_add(&totals[idx % len], partials[idx]);".to_owned()),
+        "float" => Some(r#"
+            __global volatile uint* const dst = (__global volatile uint*)&totals[idx % len];
+            float const val = partials[idx];
+            uint old = *dst;
+            uint prev;
+
+            do {
+                prev = old;
+                old = atomic_cmpxchg(dst, prev, as_uint(as_float(prev) + val));
+            } while (old != prev);
+        "#.to_owned()),
+        _ => None,
+    }
+}
+
+/// A device-side buffer of running totals, gathered from many kernel
+/// launches without reading results back after each one.
+///
+/// Kernels may add to `::buffer` directly (using atomics where work items
+/// share an element) or write their results to a separate buffer of
+/// partials which `::merge` then adds atomically, element `i` of the
+/// partials going to total `i % len`. `::read_and_reset` reads the totals
+/// and zeroes them for the next round.
+///
+/// Supported element types are `i32`, `u32`, `f32`, and, on devices
+/// supporting `cl_khr_int64_base_atomics`, `i64` and `u64`. Floating point
+/// totals are merged with a compare-and-swap loop and are not
+/// deterministic.
+///
+/// ## Example
+///
+/// ```text
+/// let hits = Accumulator::<u32>::new(&queue, 16).unwrap();
+///
+/// for _ in 0..iters {
+///     kern.enq().unwrap();
+///     hits.merge(&per_item_hits).unwrap();
+/// }
+///
+/// let totals = hits.read_and_reset().unwrap();
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Accumulator<T: OclPrm> {
+    totals: Buffer<T>,
+    kern_src: String,
+}
+
+impl<T: OclPrm + ClTypeName> Accumulator<T> {
+    /// Returns a new accumulator of `len` zeroed totals using `queue`.
+    pub fn new(queue: &Queue, len: usize) -> OclResult<Accumulator<T>> {
+        let add_src = match atomic_add_src(T::cl_type_name()) {
+            Some(src) => src,
+            None => return OclError::err(format!("ocl::ops::Accumulator::new(): Element type \
+                '{}' is not supported. Use 'int', 'uint', 'long', 'ulong', or 'float'.",
+                T::cl_type_name())),
+        };
+
+        if len == 0 {
+            return OclError::err("ocl::ops::Accumulator::new(): Length must be non-zero.");
+        }
+
+        let pragma = match T::cl_type_name() {
+            "long" | "ulong" => "#pragma OPENCL EXTENSION cl_khr_int64_base_atomics : enable",
+            _ => "",
+        };
+
+        let kern_src = format!(r#"
+            {pragma}
+
+            __kernel void ocl_ops_accumulate(__global {t} const* const partials,
+                    __global {t}* const totals, uint const len)
+            {{
+                size_t idx = get_global_id(0);
+                {add}
+            }}
+        "#, pragma = pragma, t = T::cl_type_name(), add = add_src);
+
+        let totals = try!(Buffer::<T>::new(queue.clone(), None, [len], None));
+        try!(totals.cmd().fill(T::default(), None).enq());
+
+        Ok(Accumulator { totals: totals, kern_src: kern_src })
+    }
+
+    /// Atomically adds each element `i` of `partials` to total `i % len`.
+    ///
+    /// The length of `partials` must be a multiple of the number of totals.
+    pub fn merge(&self, partials: &Buffer<T>) -> OclResult<()> {
+        if partials.len() % self.totals.len() != 0 {
+            return OclError::err(format!("ocl::ops::Accumulator::merge(): The length of the \
+                partials buffer ({}) must be a multiple of the number of totals ({}).",
+                partials.len(), self.totals.len()));
+        }
+
+        if partials.len() == 0 { return Ok(()); }
+
+        let queue = self.totals.default_queue();
+        let program = try!(queue.cached_program(&self.kern_src));
+
+        try!(Kernel::new("ocl_ops_accumulate", &program, queue))
+            .gws([partials.len()])
+            .arg_buf(partials)
+            .arg_buf(&self.totals)
+            .arg_scl(self.totals.len() as u32)
+            .enq()
+    }
+
+    /// Reads the totals, blocking until all previously enqueued merges have
+    /// completed, then zeroes them.
+    pub fn read_and_reset(&self) -> OclResult<Vec<T>> {
+        let mut totals = vec![T::default(); self.totals.len()];
+        try!(self.totals.read(&mut totals).enq());
+        try!(self.reset());
+        Ok(totals)
+    }
+
+    /// Zeroes the totals.
+    pub fn reset(&self) -> OclResult<()> {
+        self.totals.cmd().fill(T::default(), None).enq()
+    }
+
+    /// Returns the buffer holding the totals, for kernels accumulating into
+    /// it directly.
+    pub fn buffer(&self) -> &Buffer<T> {
+        &self.totals
+    }
+
+    /// Returns the number of totals.
+    pub fn len(&self) -> usize {
+        self.totals.len()
+    }
+}
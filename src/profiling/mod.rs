@@ -0,0 +1,550 @@
+//! Opt-in device-side command profiling.
+//!
+//! A `Profiler` attached to a queue created with profiling enabled (see
+//! `Queue::with_properties`) records the event of every buffer, image, and
+//! kernel command enqueued on that queue and aggregates their execution
+//! times per command label (`"Kernel::add"`, `"Buffer::read"`, etc.).
+//!
+//! ```text
+//! let queue = Queue::with_properties(&context, device, QUEUE_PROFILING_ENABLE)?;
+//! let profiler = Profiler::new();
+//! queue.set_profiler(Some(profiler.clone()))?;
+//!
+//! // ... enqueue commands ...
+//!
+//! profiler.collect()?;
+//! println!("{}", profiler);
+//! ```
+//!
+//! Commands enqueued with a destination event (`::enew`) are recorded as
+//! well: an internal event is always created while a profiler is attached
+//! and the destination receives a copy of it, so requesting an event does
+//! not hide a command from the profile.
+//!
+//! Commands can also be grouped by pipeline stage using scoped regions:
+//!
+//! ```text
+//! {
+//!     let _fft = profiler.region("fft");
+//!     // ... commands attributed to "fft" ...
+//!     {
+//!         let _twiddle = profiler.region("twiddle");
+//!         // ... commands attributed to both "fft" and "fft/twiddle" ...
+//!     }
+//! }
+//! ```
+//!
+//! With tracing enabled (`Profiler::set_tracing`), the start and end time of
+//! every collected command are also retained and can be exported as a
+//! `chrome://tracing` / Perfetto timeline with `Profiler::write_chrome_trace`.
+//!
+//! A `Sweep` runs a kernel across combinations of local work sizes and
+//! preprocessor definitions and ranks them by execution time.
+//!
+
+use std;
+use std::io::Write;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+use core::{EventInfo, EventInfoResult, CommandExecutionStatus, ProfilingInfo};
+use core::error::{Error as OclError, Result as OclResult};
+use standard::Event;
+
+mod sweep;
+
+pub use self::sweep::{Sweep, SweepPoint, SweepResult, SweepReport};
+
+/// The number of pending events retained before completed ones are folded
+/// into the statistics automatically.
+const AUTO_COLLECT_THRESHOLD: usize = 4096;
+
+
+/// Aggregated execution times for every recorded command sharing a label.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommandStats {
+    count: u64,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+impl CommandStats {
+    fn new() -> CommandStats {
+        CommandStats {
+            count: 0,
+            total: Duration::new(0, 0),
+            min: Duration::new(0, 0),
+            max: Duration::new(0, 0),
+        }
+    }
+
+    fn add(&mut self, duration: Duration) {
+        if self.count == 0 || duration < self.min { self.min = duration; }
+        if duration > self.max { self.max = duration; }
+        self.total += duration;
+        self.count += 1;
+    }
+
+    /// Returns the number of commands recorded.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the sum of all execution times.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// Returns the shortest execution time.
+    pub fn min(&self) -> Duration {
+        self.min
+    }
+
+    /// Returns the longest execution time.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns the mean execution time.
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 { return Duration::new(0, 0); }
+        let ns = duration_nanos(self.total) / self.count;
+        Duration::new(ns / 1_000_000_000, (ns % 1_000_000_000) as u32)
+    }
+}
+
+impl std::fmt::Display for CommandStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "count: {}, total: {:.3}ms, min: {:.3}ms, max: {:.3}ms, mean: {:.3}ms",
+            self.count, millis(self.total), millis(self.min), millis(self.max),
+            millis(self.mean()))
+    }
+}
+
+
+/// The device-side start and end time of a single collected command.
+#[derive(Clone, Debug)]
+pub struct TraceRecord {
+    label: String,
+    queue_idx: usize,
+    start: u64,
+    end: u64,
+}
+
+impl TraceRecord {
+    /// Returns the command label.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the index of the queue the command ran on, in the order in
+    /// which queues were first seen by the profiler.
+    pub fn queue_idx(&self) -> usize {
+        self.queue_idx
+    }
+
+    /// Returns the device timestamp, in nanoseconds, at which the command
+    /// started executing.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the device timestamp, in nanoseconds, at which the command
+    /// finished executing.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the trace category of the command: `"kernel"`, `"buffer"`,
+    /// `"image"`, or `"other"`.
+    pub fn category(&self) -> &'static str {
+        if self.label.starts_with("Kernel::") {
+            "kernel"
+        } else if self.label.starts_with("Buffer::") {
+            "buffer"
+        } else if self.label.starts_with("Image::") {
+            "image"
+        } else {
+            "other"
+        }
+    }
+}
+
+
+/// A recorded command awaiting collection.
+#[derive(Debug)]
+struct Pending {
+    label: String,
+    region: Option<String>,
+    event: Event,
+}
+
+
+#[derive(Debug)]
+struct Inner {
+    pending: Vec<Pending>,
+    stats: BTreeMap<String, CommandStats>,
+    region_stacks: HashMap<ThreadId, Vec<String>>,
+    region_stats: BTreeMap<String, CommandStats>,
+    trace: Option<Vec<TraceRecord>>,
+    queue_ptrs: Vec<usize>,
+}
+
+impl Inner {
+    /// Returns the path of the innermost region entered on the calling
+    /// thread, if any.
+    fn current_region(&self) -> Option<String> {
+        self.region_stacks.get(&thread::current().id()).and_then(|stack| stack.last().cloned())
+    }
+
+    /// Folds completed pending events into the statistics, leaving
+    /// incomplete events pending.
+    fn collect(&mut self) -> OclResult<()> {
+        let pending = std::mem::replace(&mut self.pending, Vec::new());
+        let mut result = Ok(());
+
+        for pending in pending {
+            if result.is_err() || !is_complete(&pending.event) {
+                self.pending.push(pending);
+                continue;
+            }
+
+            if self.trace.is_some() {
+                if let Err(err) = self.trace_event(&pending.label, &pending.event) {
+                    result = Err(err);
+                    continue;
+                }
+            }
+
+            let duration = match pending.event.duration() {
+                Ok(d) => d,
+                Err(err) => {
+                    result = Err(err);
+                    continue;
+                },
+            };
+
+            // Roll the duration up into the region and each of its parents:
+            if let Some(ref region) = pending.region {
+                for (idx, _) in region.match_indices('/').chain(Some((region.len(), ""))) {
+                    self.region_stats.entry(region[..idx].to_owned())
+                        .or_insert_with(CommandStats::new).add(duration);
+                }
+            }
+
+            self.stats.entry(pending.label).or_insert_with(CommandStats::new).add(duration);
+        }
+
+        result
+    }
+
+    /// Adds a trace record for a completed event.
+    fn trace_event(&mut self, label: &str, event: &Event) -> OclResult<()> {
+        let start = try!(event.profiling_nanos(ProfilingInfo::Start));
+        let end = try!(event.profiling_nanos(ProfilingInfo::End));
+
+        let queue_ptr = match event.info(EventInfo::CommandQueue) {
+            EventInfoResult::CommandQueue(queue) => (unsafe { queue.as_ptr() }) as usize,
+            EventInfoResult::Error(err) => return Err(*err),
+            _ => 0,
+        };
+
+        let queue_idx = match self.queue_ptrs.iter().position(|&ptr| ptr == queue_ptr) {
+            Some(idx) => idx,
+            None => {
+                self.queue_ptrs.push(queue_ptr);
+                self.queue_ptrs.len() - 1
+            },
+        };
+
+        if let Some(ref mut trace) = self.trace {
+            trace.push(TraceRecord {
+                label: label.to_owned(),
+                queue_idx: queue_idx,
+                start: start,
+                end: end,
+            });
+        }
+        Ok(())
+    }
+}
+
+
+/// Records the profiling events of commands enqueued on a queue and
+/// aggregates their execution times per command label.
+///
+/// Cloning a `Profiler` yields a handle to the same records.
+///
+/// Events are resolved lazily: call `::collect` (after `Queue::finish`, for
+/// example once per frame) to fold them into the statistics. Completed
+/// events are also folded in automatically once enough are pending.
+///
+#[derive(Clone, Debug)]
+pub struct Profiler {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Profiler {
+    /// Returns a new, empty profiler.
+    pub fn new() -> Profiler {
+        Profiler {
+            inner: Arc::new(Mutex::new(Inner {
+                pending: Vec::new(),
+                stats: BTreeMap::new(),
+                region_stacks: HashMap::new(),
+                region_stats: BTreeMap::new(),
+                trace: None,
+                queue_ptrs: Vec::new(),
+            })),
+        }
+    }
+
+    /// Records the event of a command under `label`, attributing it to the
+    /// current region, if any.
+    ///
+    /// Called automatically for commands enqueued on a queue this profiler
+    /// is attached to.
+    pub fn record<S: Into<String>>(&self, label: S, event: Event) {
+        let mut inner = self.inner.lock().expect("ocl::Profiler::record()");
+        let region = inner.current_region();
+        inner.pending.push(Pending { label: label.into(), region: region, event: event });
+
+        if inner.pending.len() >= AUTO_COLLECT_THRESHOLD {
+            // Errors are reported by the next call to `::collect`.
+            inner.collect().ok();
+        }
+    }
+
+    /// Waits for all pending events to complete and folds their execution
+    /// times into the statistics.
+    ///
+    /// Events are waited on without holding the lock, so commands may
+    /// continue to be enqueued (and recorded) in the meantime. Those are
+    /// folded in only if already complete.
+    pub fn collect(&self) -> OclResult<()> {
+        let events: Vec<Event> = self.inner.lock().expect("ocl::Profiler::collect()")
+            .pending.iter().map(|pending| pending.event.clone()).collect();
+
+        let waited = events.iter().map(|event| event.wait()).collect::<OclResult<Vec<_>>>();
+        let collected = self.inner.lock().expect("ocl::Profiler::collect()").collect();
+        waited.and(collected)
+    }
+
+    /// Returns the number of recorded events not yet collected.
+    pub fn pending_len(&self) -> usize {
+        self.inner.lock().expect("ocl::Profiler::pending_len()").pending.len()
+    }
+
+    /// Returns the collected statistics for commands labeled `label`.
+    pub fn get(&self, label: &str) -> Option<CommandStats> {
+        self.inner.lock().expect("ocl::Profiler::get()").stats.get(label).cloned()
+    }
+
+    /// Returns the collected statistics for every label, sorted by label.
+    pub fn stats(&self) -> Vec<(String, CommandStats)> {
+        self.inner.lock().expect("ocl::Profiler::stats()").stats.iter()
+            .map(|(label, stats)| (label.clone(), *stats))
+            .collect()
+    }
+
+    /// Enters a region named `name`, nested within the current region if
+    /// any. Commands recorded until the returned guard is dropped are
+    /// attributed to the region and to each of its parents.
+    ///
+    /// Nested regions are named by their path, for example `"fft/twiddle"`.
+    ///
+    /// Regions are tracked per thread: commands are attributed to the
+    /// regions entered on the thread enqueuing them, regardless of which
+    /// clone of this profiler was used to enter them.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `name` contains a `'/'`.
+    pub fn region<S: Into<String>>(&self, name: S) -> ProfilerRegion {
+        let name = name.into();
+        assert!(!name.contains('/'), "ocl::Profiler::region(): Region names may not contain \
+            '/' (name: '{}').", name);
+
+        let thread_id = thread::current().id();
+        let mut inner = self.inner.lock().expect("ocl::Profiler::region()");
+        let stack = inner.region_stacks.entry(thread_id).or_insert_with(Vec::new);
+        let depth = stack.len();
+        let path = match stack.last() {
+            Some(parent) => format!("{}/{}", parent, name),
+            None => name,
+        };
+        stack.push(path.clone());
+
+        ProfilerRegion { profiler: self.clone(), thread_id: thread_id, depth: depth, path: path }
+    }
+
+    /// Returns the path of the current region of the calling thread, if any.
+    pub fn current_region(&self) -> Option<String> {
+        self.inner.lock().expect("ocl::Profiler::current_region()").current_region()
+    }
+
+    /// Returns the collected statistics of all commands attributed to the
+    /// region with path `path`, including those of nested regions.
+    pub fn get_region(&self, path: &str) -> Option<CommandStats> {
+        self.inner.lock().expect("ocl::Profiler::get_region()").region_stats.get(path).cloned()
+    }
+
+    /// Returns the collected statistics for every region, sorted by path.
+    pub fn region_stats(&self) -> Vec<(String, CommandStats)> {
+        self.inner.lock().expect("ocl::Profiler::region_stats()").region_stats.iter()
+            .map(|(path, stats)| (path.clone(), *stats))
+            .collect()
+    }
+
+    /// Discards all collected statistics, trace records, and pending events.
+    ///
+    /// Open regions are unaffected.
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock().expect("ocl::Profiler::reset()");
+        inner.pending.clear();
+        inner.stats.clear();
+        inner.region_stats.clear();
+        if let Some(ref mut trace) = inner.trace { trace.clear(); }
+    }
+
+    /// Enables or disables the retention of a trace record for every
+    /// collected command (default: disabled). Disabling discards any
+    /// existing records.
+    ///
+    /// Trace records grow without bound while enabled, use `::reset` or
+    /// `::take_trace` to discard them periodically.
+    pub fn set_tracing(&self, enabled: bool) {
+        let mut inner = self.inner.lock().expect("ocl::Profiler::set_tracing()");
+        match (enabled, inner.trace.is_some()) {
+            (true, false) => inner.trace = Some(Vec::new()),
+            (false, true) => inner.trace = None,
+            _ => (),
+        }
+    }
+
+    /// Returns true if trace records are being retained.
+    pub fn is_tracing(&self) -> bool {
+        self.inner.lock().expect("ocl::Profiler::is_tracing()").trace.is_some()
+    }
+
+    /// Removes and returns the trace records collected so far, in order of
+    /// collection.
+    pub fn take_trace(&self) -> Vec<TraceRecord> {
+        let mut inner = self.inner.lock().expect("ocl::Profiler::take_trace()");
+        match inner.trace {
+            Some(ref mut trace) => std::mem::replace(trace, Vec::new()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Writes the trace records collected so far as a JSON timeline readable
+    /// by `chrome://tracing` and Perfetto, with each queue shown as a
+    /// separate thread.
+    ///
+    /// Timestamps are taken from the device clock, in microseconds.
+    pub fn write_chrome_trace<W: Write>(&self, writer: &mut W) -> OclResult<()> {
+        let inner = self.inner.lock().expect("ocl::Profiler::write_chrome_trace()");
+
+        let trace = match inner.trace {
+            Some(ref trace) => trace,
+            None => return OclError::err("ocl::Profiler::write_chrome_trace: Tracing is not \
+                enabled. Use 'Profiler::set_tracing' before enqueuing commands."),
+        };
+
+        let mut events: Vec<String> = (0..inner.queue_ptrs.len()).map(|idx| {
+            format!("{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":0,\"tid\":{},\
+                \"args\":{{\"name\":\"Queue {}\"}}}}", idx, idx)
+        }).collect();
+
+        for rec in trace.iter() {
+            events.push(format!("{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"X\",\
+                \"pid\":0,\"tid\":{},\"ts\":{:.3},\"dur\":{:.3}}}",
+                json_escape(&rec.label), rec.category(), rec.queue_idx,
+                rec.start as f64 / 1000.0, rec.end.saturating_sub(rec.start) as f64 / 1000.0));
+        }
+
+        let json = format!("{{\"traceEvents\":[\n{}\n],\"displayTimeUnit\":\"ns\"}}\n",
+            events.join(",\n"));
+        try!(writer.write_all(json.as_bytes()));
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Profiler {
+    /// Formats the collected statistics one label per line, followed by
+    /// those of each region. Pending events are not included.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (label, stats) in self.stats() {
+            try!(writeln!(f, "{}: {}", label, stats));
+        }
+        for (path, stats) in self.region_stats() {
+            try!(writeln!(f, "[{}]: {}", path, stats));
+        }
+        Ok(())
+    }
+}
+
+
+/// A guard returned by `Profiler::region` which leaves the region when
+/// dropped.
+#[derive(Debug)]
+pub struct ProfilerRegion {
+    profiler: Profiler,
+    thread_id: ThreadId,
+    depth: usize,
+    path: String,
+}
+
+impl ProfilerRegion {
+    /// Returns the path of this region.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Drop for ProfilerRegion {
+    /// Leaves this region along with any nested regions still open.
+    fn drop(&mut self) {
+        if let Ok(mut inner) = self.profiler.inner.lock() {
+            let empty = match inner.region_stacks.get_mut(&self.thread_id) {
+                Some(stack) => { stack.truncate(self.depth); stack.is_empty() },
+                None => false,
+            };
+
+            if empty {
+                inner.region_stacks.remove(&self.thread_id);
+            }
+        }
+    }
+}
+
+
+fn is_complete(event: &Event) -> bool {
+    match event.info(EventInfo::CommandExecutionStatus) {
+        EventInfoResult::CommandExecutionStatus(CommandExecutionStatus::Complete) => true,
+        _ => false,
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn duration_nanos(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000_000 + duration.subsec_nanos() as u64
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration_nanos(duration) as f64 / 1_000_000.0
+}
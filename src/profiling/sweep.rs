@@ -0,0 +1,287 @@
+//! Benchmarking a kernel across a sweep of build and launch parameters.
+
+use std;
+use core::{CommandQueueInfo, CommandQueueInfoResult, QUEUE_PROFILING_ENABLE};
+use core::error::{Error as OclError, Result as OclResult};
+use standard::{Context, Queue, Kernel, Event, Program, ProgramBuilder, SpatialDims};
+use super::{CommandStats, millis};
+
+
+/// A single combination of sweep parameters.
+#[derive(Clone, Debug)]
+pub struct SweepPoint {
+    lws: SpatialDims,
+    defines: Vec<(String, String)>,
+}
+
+impl SweepPoint {
+    /// Returns the local work size (unspecified if no candidates were
+    /// given).
+    pub fn lws(&self) -> SpatialDims {
+        self.lws
+    }
+
+    /// Returns the name and value of each swept definition.
+    pub fn defines(&self) -> &[(String, String)] {
+        &self.defines
+    }
+
+    /// Returns the value of the swept definition named `name`.
+    pub fn define(&self, name: &str) -> Option<&str> {
+        self.defines.iter().find(|&&(ref n, _)| n == name).map(|&(_, ref v)| v.as_str())
+    }
+}
+
+impl std::fmt::Display for SweepPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        try!(write!(f, "lws: {:?}", self.lws.to_lens().ok()));
+        for &(ref name, ref val) in &self.defines {
+            try!(write!(f, ", {}: {}", name, val));
+        }
+        Ok(())
+    }
+}
+
+
+/// The outcome of running a kernel with one combination of parameters.
+#[derive(Clone, Debug)]
+pub struct SweepResult {
+    point: SweepPoint,
+    outcome: Result<CommandStats, String>,
+}
+
+impl SweepResult {
+    /// Returns the parameters used.
+    pub fn point(&self) -> &SweepPoint {
+        &self.point
+    }
+
+    /// Returns the execution time statistics or, if building, setting up, or
+    /// enqueuing the kernel failed, the error message.
+    pub fn outcome(&self) -> Result<&CommandStats, &str> {
+        match self.outcome {
+            Ok(ref stats) => Ok(stats),
+            Err(ref msg) => Err(msg),
+        }
+    }
+}
+
+
+/// The results of a `Sweep`, ranked from fastest to slowest mean execution
+/// time with failed combinations last.
+#[derive(Clone, Debug)]
+pub struct SweepReport {
+    results: Vec<SweepResult>,
+}
+
+impl SweepReport {
+    /// Returns all results, fastest first.
+    pub fn results(&self) -> &[SweepResult] {
+        &self.results
+    }
+
+    /// Returns the fastest successful result.
+    pub fn best(&self) -> Option<&SweepResult> {
+        self.results.first().and_then(|r| if r.outcome.is_ok() { Some(r) } else { None })
+    }
+}
+
+impl std::fmt::Display for SweepReport {
+    /// Formats the results as a ranked table, one combination per line.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (rank, result) in self.results.iter().enumerate() {
+            match result.outcome {
+                Ok(ref stats) => try!(writeln!(f, "{:>3}. {:>10.3}ms (min: {:.3}ms) | {}",
+                    rank + 1, millis(stats.mean()), millis(stats.min()), result.point)),
+                Err(ref msg) => try!(writeln!(f, "  -. {:>12} | {} | {}", "failed",
+                    result.point, msg)),
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// Runs a kernel across the cartesian product of user-specified parameters
+/// (local work size candidates and values of preprocessor definitions, such
+/// as a vector width) and ranks the combinations by device execution time.
+///
+/// The program is built once per combination of definitions. Each
+/// combination is run `::warmup` times untimed, then `::iters` times timed
+/// using event profiling, which the queue must have enabled. Combinations
+/// which fail to build or run (for example due to an unsupported local work
+/// size) are reported as failed rather than aborting the sweep.
+///
+/// ## Example
+///
+/// ```text
+/// let report = Sweep::new("reduce", Program::builder().src(src), [1 << 20])
+///     .lws(vec![[64], [128], [256]])
+///     .define("VEC_WIDTH", vec![1, 2, 4])
+///     .run(&context, &queue, |kernel, _point| {
+///         Ok(kernel.arg_buf(&input).arg_buf(&output))
+///     }).unwrap();
+///
+/// println!("{}", report);
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct Sweep {
+    kernel_name: String,
+    prog_bldr: ProgramBuilder,
+    gws: SpatialDims,
+    lws: Vec<SpatialDims>,
+    defines: Vec<(String, Vec<String>)>,
+    warmup: usize,
+    iters: usize,
+}
+
+impl Sweep {
+    /// Returns a new sweep of the kernel named `kernel_name` within the
+    /// program built by `prog_bldr`, launched with a global work size of
+    /// `gws`.
+    pub fn new<S, D>(kernel_name: S, prog_bldr: ProgramBuilder, gws: D) -> Sweep
+            where S: Into<String>, D: Into<SpatialDims>
+    {
+        Sweep {
+            kernel_name: kernel_name.into(),
+            prog_bldr: prog_bldr,
+            gws: gws.into(),
+            lws: Vec::new(),
+            defines: Vec::new(),
+            warmup: 1,
+            iters: 10,
+        }
+    }
+
+    /// Sets the local work size candidates.
+    pub fn lws<D: Into<SpatialDims>>(mut self, candidates: Vec<D>) -> Sweep {
+        self.lws = candidates.into_iter().map(|d| d.into()).collect();
+        self
+    }
+
+    /// Adds a preprocessor definition, `-D {name}={val}`, to sweep over.
+    pub fn define<S: Into<String>, V: ToString>(mut self, name: S, values: Vec<V>) -> Sweep {
+        self.defines.push((name.into(), values.iter().map(|v| v.to_string()).collect()));
+        self
+    }
+
+    /// Sets the number of untimed runs per combination (default: 1).
+    pub fn warmup(mut self, warmup: usize) -> Sweep {
+        self.warmup = warmup;
+        self
+    }
+
+    /// Sets the number of timed runs per combination (default: 10).
+    pub fn iters(mut self, iters: usize) -> Sweep {
+        self.iters = iters;
+        self
+    }
+
+    /// Returns every combination of parameters, in the order run.
+    pub fn points(&self) -> Vec<SweepPoint> {
+        let mut define_sets: Vec<Vec<(String, String)>> = vec![Vec::new()];
+
+        for &(ref name, ref values) in &self.defines {
+            define_sets = define_sets.iter().flat_map(|set| values.iter().map(move |val| {
+                let mut set = set.clone();
+                set.push((name.clone(), val.clone()));
+                set
+            })).collect();
+        }
+
+        let lws = if self.lws.is_empty() { vec![SpatialDims::Unspecified] } else { self.lws.clone() };
+
+        define_sets.iter().flat_map(|defines| lws.iter().map(move |&lws| SweepPoint {
+            lws: lws,
+            defines: defines.clone(),
+        })).collect()
+    }
+
+    /// Runs every combination on `queue` and returns the ranked results.
+    ///
+    /// `set_args` is called with each newly created kernel, its global and
+    /// local work sizes already set, and must set its arguments.
+    pub fn run<F>(&self, context: &Context, queue: &Queue, mut set_args: F) -> OclResult<SweepReport>
+            where F: FnMut(Kernel, &SweepPoint) -> OclResult<Kernel>
+    {
+        let profiling = match queue.info(CommandQueueInfo::Properties) {
+            CommandQueueInfoResult::Properties(props) => props.contains(QUEUE_PROFILING_ENABLE),
+            _ => false,
+        };
+
+        if !profiling {
+            return OclError::err("ocl::Sweep::run(): The queue must be created with profiling \
+                enabled ('QUEUE_PROFILING_ENABLE').");
+        }
+
+        if self.iters == 0 {
+            return OclError::err("ocl::Sweep::run(): At least one timed iteration is required.");
+        }
+
+        let mut results = Vec::new();
+        let mut program = None;
+
+        for point in self.points() {
+            // Points sharing definitions are consecutive:
+            let rebuild = match results.last() {
+                Some(&SweepResult { point: ref prev, .. }) => prev.defines != point.defines,
+                None => true,
+            };
+
+            if rebuild {
+                let mut bldr = self.prog_bldr.clone();
+                for &(ref name, ref val) in &point.defines {
+                    bldr = bldr.define(name.clone(), val.clone());
+                }
+                program = Some(bldr.build_with_devices(context, &[*queue.device()])
+                    .map_err(|e| e.to_string()));
+            }
+
+            let outcome = match program {
+                Some(Ok(ref program)) => self.time(program, queue, &point, &mut set_args)
+                    .map_err(|e| e.to_string()),
+                Some(Err(ref msg)) => Err(msg.clone()),
+                None => unreachable!(),
+            };
+
+            results.push(SweepResult { point: point, outcome: outcome });
+        }
+
+        results.sort_by(|a, b| match (&a.outcome, &b.outcome) {
+            (&Ok(ref a), &Ok(ref b)) => a.mean().cmp(&b.mean()),
+            (&Ok(_), &Err(_)) => std::cmp::Ordering::Less,
+            (&Err(_), &Ok(_)) => std::cmp::Ordering::Greater,
+            (&Err(_), &Err(_)) => std::cmp::Ordering::Equal,
+        });
+
+        Ok(SweepReport { results: results })
+    }
+
+    /// Runs the kernel for one combination of parameters and returns its
+    /// timing statistics.
+    fn time<F>(&self, program: &Program, queue: &Queue, point: &SweepPoint,
+            set_args: &mut F) -> OclResult<CommandStats>
+            where F: FnMut(Kernel, &SweepPoint) -> OclResult<Kernel>
+    {
+        let kernel = try!(Kernel::new(self.kernel_name.clone(), program, queue))
+            .gws(self.gws)
+            .lws(point.lws);
+        let kernel = try!(set_args(kernel, point));
+
+        for _ in 0..self.warmup {
+            try!(kernel.enq());
+        }
+
+        let mut stats = CommandStats::new();
+
+        for _ in 0..self.iters {
+            let mut event = Event::empty();
+            try!(kernel.cmd().enew(&mut event).enq());
+            try!(event.wait());
+            stats.add(try!(event.duration()));
+        }
+
+        Ok(stats)
+    }
+}
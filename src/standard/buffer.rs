@@ -3,21 +3,101 @@
 use std;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use std::ptr;
+use libc::c_void;
+use log::{log, error};
 
-use ffi::cl_GLuint;
+use ffi::{self, cl_GLuint, cl_int, cl_map_flags};
 
 use core::{self, OclPrm, Mem as MemCore, MemFlags,
-    MemInfo, MemInfoResult, ClEventPtrNew, ClWaitList};
+    MemInfo, MemInfoResult, ClEventPtrNew, ClWaitList, CommandExecutionStatus, MapFlags};
 use core::error::{Error as OclError, Result as OclResult};
-use standard::{Queue, MemLen, SpatialDims};
+use standard::{Queue, MemLen, SpatialDims, Event, WriteTracker};
+use standard::queue::{slow_call_start, slow_call_check};
+use standard::event::{copy_to_new, check_wait_list};
+use standard::mem_flags::check_mem_flags;
+use standard::device::check_fp64;
+use standard::error_hints::status_err;
+#[cfg(feature = "futures")] use standard::{FutureEvent, FutureRead};
+#[cfg(feature = "futures")] use standard::future::future_read;
 
 
 fn check_len(mem_len: usize, data_len: usize, offset: usize) -> OclResult<()> {
     if offset >= mem_len { return OclError::err(format!(
         "ocl::Buffer::enq(): Offset out of range. (mem_len: {}, data_len: {}, offset: {}",
         mem_len, data_len, offset)); }
-    if data_len > (mem_len - offset) { return OclError::err(
-        "ocl::Buffer::enq(): Data length exceeds buffer length."); }
+    if data_len > (mem_len - offset) { return OclError::err(format!(
+        "ocl::Buffer::enq(): Data length exceeds buffer length. (mem_len: {}, data_len: {}, \
+        offset: {})", mem_len, data_len, offset)); }
+    Ok(())
+}
+
+/// Ensures that a length set with `BufferCmd::len` does not exceed the
+/// length of the host data.
+fn check_host_len(len: usize, data_len: usize) -> OclResult<()> {
+    if len > data_len {
+        OclError::err(format!("ocl::BufferCmd::enq(): The length set with '::len' ({}) exceeds \
+            the length of the host data ({}).", len, data_len))
+    } else {
+        Ok(())
+    }
+}
+
+/// Ensures that `MEM_USE_HOST_PTR` is not used by constructors which cannot
+/// keep the host memory alive.
+fn check_no_use_host_ptr(flags: MemFlags, caller: &str) -> OclResult<()> {
+    if flags.contains(::flags::MEM_USE_HOST_PTR) {
+        OclError::err(format!("ocl::Buffer::{}: 'MEM_USE_HOST_PTR' may not be used with this \
+            constructor. Use 'Buffer::from_host_vec' or 'Buffer::use_host_slice' instead.", caller))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the length, in elements of `T`, of a buffer.
+fn mem_len_of<T>(buffer: &MemCore) -> OclResult<usize> {
+    match core::get_mem_object_info(buffer, MemInfo::Size) {
+        MemInfoResult::Size(bytes) => Ok(bytes / std::mem::size_of::<T>()),
+        MemInfoResult::Error(err) => Err(*err),
+        _ => panic!("ocl::Buffer::enq(): Unexpected 'MemInfoResult' variant."),
+    }
+}
+
+/// Ensures that a copy of `len` elements fits into `dst_buffer` at
+/// `dst_offset`.
+fn check_copy_dst_len<T>(dst_buffer: &MemCore, len: usize, dst_offset: usize) -> OclResult<()> {
+    let dst_len = try!(mem_len_of::<T>(dst_buffer));
+
+    if dst_offset > dst_len || len > (dst_len - dst_offset) { return OclError::err(format!(
+        "ocl::Buffer::enq(): Copy destination range out of range. (dst_len: {}, len: {}, \
+        dst_offset: {})", dst_len, len, dst_offset)); }
+    Ok(())
+}
+
+/// Ensures that a rectangular region, with pitches given in elements (zero
+/// meaning tightly packed), lies within memory of length `len`.
+fn check_rect_len(len: usize, origin: [usize; 3], region: [usize; 3], row_pitch: usize,
+        slc_pitch: usize, side: &str) -> OclResult<()>
+{
+    if region.iter().any(|&r| r == 0) { return OclError::err(format!(
+        "ocl::Buffer::enq(): Rect region must be non-zero in every dimension. \
+        (region: {:?})", region)); }
+
+    let row_pitch = if row_pitch == 0 { region[0] } else { row_pitch };
+    let slc_pitch = if slc_pitch == 0 { region[1] * row_pitch } else { slc_pitch };
+
+    if row_pitch < region[0] || slc_pitch < region[1] * row_pitch { return OclError::err(format!(
+        "ocl::Buffer::enq(): Rect {} pitches are too small for the region. (region: {:?}, \
+        row_pitch: {}, slc_pitch: {})", side, region, row_pitch, slc_pitch)); }
+
+    let end = ((origin[2] + region[2] - 1) * slc_pitch) + ((origin[1] + region[1] - 1) * row_pitch)
+        + origin[0] + region[0];
+
+    if end > len { return OclError::err(format!(
+        "ocl::Buffer::enq(): Rect {} region out of range. (len: {}, origin: {:?}, \
+        region: {:?}, row_pitch: {}, slc_pitch: {})", side, len, origin, region, row_pitch,
+        slc_pitch)); }
     Ok(())
 }
 
@@ -60,10 +140,12 @@ pub enum BufferCmdDataShape {
     },
 }
 
-/// A buffer command builder used to enqueue reads, writes, fills, and copies.
+/// A buffer command builder used to enqueue reads, writes, maps, fills, and
+/// copies.
 ///
 /// Create one using `Buffer::cmd` or with shortcut methods such as
-/// `Buffer::read` and `Buffer::write`.
+/// `Buffer::read` and `Buffer::write`. Every operation shares the same
+/// settings: `::queue`, `::offset` (or `::rect`), `::ewait`, and `::enew`.
 ///
 /// ## Examples
 ///
@@ -81,6 +163,13 @@ pub enum BufferCmdDataShape {
 /// // Reads without blocking:
 /// buffer.cmd().read_async(&dst_vec).enew(&empty_event).enq().unwrap();
 ///
+/// // Fills 64 elements, starting at element 16, with zeros:
+/// buffer.cmd().offset(16).fill(0.0, Some(64)).enq().unwrap();
+///
+/// // Maps the buffer, after an event completes, and modifies it:
+/// let mut map = buffer.cmd().ewait(&event).map(flags::MAP_WRITE, None).unwrap();
+/// map[0] = 1.0;
+///
 /// ```
 ///
 pub struct BufferCmd<'b, T: 'b + OclPrm> {
@@ -93,6 +182,10 @@ pub struct BufferCmd<'b, T: 'b + OclPrm> {
     ewait: Option<&'b ClWaitList>,
     enew: Option<&'b mut ClEventPtrNew>,
     mem_len: usize,
+    write_tracker: Option<&'b WriteTracker>,
+    dst_write_tracker: Option<&'b WriteTracker>,
+    checked: bool,
+    len: Option<usize>,
 }
 
 /// [UNSTABLE]: All methods still in a state of tweakification.
@@ -113,6 +206,10 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
             ewait: None,
             enew: None,
             mem_len: mem_len,
+            write_tracker: None,
+            dst_write_tracker: None,
+            checked: true,
+            len: None,
         }
     }
 
@@ -156,6 +253,26 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
         self
     }
 
+    /// Sets the number of elements to read, write, or fill, allowing part of
+    /// a buffer to be transferred from or to the start of a larger host
+    /// slice.
+    ///
+    /// Reads and writes default to the length of the host data and fills
+    /// to the remainder of the buffer. Combined with `::offset`, only the
+    /// changed region of a large buffer need be transferred:
+    ///
+    /// `buffer.cmd().offset(start).len(len).write(&frame[start..]).enq()`
+    ///
+    /// ## Errors
+    ///
+    /// The range must lie within both the buffer and the host data. A length
+    /// may not be set for rectangular operations.
+    ///
+    pub fn len(mut self, len: usize) -> BufferCmd<'b, T> {
+        self.len = Some(len);
+        self
+    }
+
     /// Specifies that this command will be a blocking read operation.
     ///
     /// After calling this method, the blocking state of this command will
@@ -184,7 +301,8 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
     /// ## Safety
     ///
     /// Caller must ensure that the container referred to by `dst_data` lives
-    /// until the call completes.
+    /// until the call completes. Enqueuing with `::enq_unchecked` verifies
+    /// in debug builds that a new event is available to await completion.
     ///
     /// ## Panics
     ///
@@ -194,6 +312,7 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
         assert!(self.kind.is_unspec(), "ocl::BufferCmd::read(): Operation kind \
             already set for this command.");
         self.kind = BufferCmdKind::Read { data: dst_data };
+        self.block = false;
         self
     }
 
@@ -210,8 +329,38 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
         self
     }
 
+    /// Specifies that this command will be a non-blocking, asynchronous
+    /// write operation, allowing uploads to overlap with kernel execution.
+    ///
+    /// Sets the block mode to false automatically but it may still be freely
+    /// toggled back. If set back to `true` this method call becomes equivalent
+    /// to calling `::write`.
+    ///
+    /// ## Safety
+    ///
+    /// Caller must ensure that the container referred to by `src_data` lives,
+    /// and is not modified, until the call completes. Enqueuing with
+    /// `::enq_unchecked` verifies in debug builds that a new event is
+    /// available to await completion.
+    ///
+    /// ## Panics
+    ///
+    /// The command operation kind must not have already been specified
+    ///
+    pub unsafe fn write_async(mut self, src_data: &'b [T]) -> BufferCmd<'b, T> {
+        assert!(self.kind.is_unspec(), "ocl::BufferCmd::write_async(): Operation kind \
+            already set for this command.");
+        self.kind = BufferCmdKind::Write { data: src_data };
+        self.block = false;
+        self
+    }
+
     /// Specifies that this command will be a copy operation.
     ///
+    /// Copies `len` elements, starting at the source offset (see `::offset`),
+    /// to `dst_buffer` at `dst_offset`. Both ranges are checked against the
+    /// length of their buffer before enqueuing.
+    ///
     /// If `.block(..)` has been set it will be ignored.
     ///
     /// ## Errors
@@ -232,6 +381,7 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
             dst_offset: dst_offset,
             len: len,
         };
+        self.dst_write_tracker = dst_buffer.write_tracker.as_ref();
         self
     }
 
@@ -289,8 +439,8 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
     ///
     /// `pattern` is the vector or scalar value to repeat contiguously. `len`
     /// is the overall size expressed in units of sizeof(T) If `len` is `None`,
-    /// the pattern will fill the entire buffer, otherwise, `len` must be
-    /// divisible by sizeof(`pattern`).
+    /// the pattern will fill the buffer from the offset (see `::offset`) to
+    /// its end, otherwise, `len` must be divisible by sizeof(`pattern`).
     ///
     /// As an example if you want to fill the first 100 `cl_float4` sized
     /// elements of a buffer, `pattern` would be a `cl_float4` and `len` would
@@ -312,6 +462,13 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
     ///
     /// Only valid for 'read', 'write', and 'copy' modes. Will error if used
     /// with 'fill' or 'copy to image'.
+    ///
+    /// Origins, the region, and pitches are all expressed in elements (units
+    /// of sizeof(T)). The 'src' parameters describe this buffer and the 'dst'
+    /// parameters describe the host slice (for reads and writes) or the
+    /// destination buffer (for copies). A pitch of zero indicates tightly
+    /// packed data. Both regions are checked against the length of their
+    /// memory before enqueuing.
     pub fn rect(mut self, src_origin: [usize; 3], dst_origin: [usize; 3], region: [usize; 3],
                 src_row_pitch: usize, src_slc_pitch: usize, dst_row_pitch: usize,
                 dst_slc_pitch: usize) -> BufferCmd<'b, T>
@@ -327,6 +484,69 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
         self
     }
 
+    /// Maps `len` elements of the buffer, starting at the offset (see
+    /// `::offset`), into host memory, blocking until the map is complete,
+    /// and returns a guard providing access to them.
+    ///
+    /// `len` defaults to the remainder of the buffer. On devices sharing
+    /// memory with the host the map is typically zero-copy.
+    ///
+    /// The queue, offset, wait list, and new event destination set on this
+    /// builder are respected.
+    ///
+    /// ## Errors
+    ///
+    /// The range must lie within the buffer and this may not be a
+    /// rectangular command.
+    ///
+    /// ## Panics
+    ///
+    /// The command operation kind must not have already been specified
+    ///
+    pub fn map(mut self, flags: MapFlags, len: Option<usize>) -> OclResult<BufferMap<T>> {
+        assert!(self.kind.is_unspec(), "ocl::BufferCmd::map(): Operation kind \
+            already set for this command.");
+
+        let offset = match self.shape {
+            BufferCmdDataShape::Lin { offset } => offset,
+            BufferCmdDataShape::Rect { .. } => return OclError::err("ocl::BufferCmd::map(): \
+                Rectangular maps are not supported."),
+        };
+        let len = len.unwrap_or(self.mem_len.saturating_sub(offset));
+        try!(check_len(self.mem_len, len, offset));
+
+        let (wait_count, wait_ptr) = match self.ewait {
+            Some(ewait) if ewait.count() > 0 => (ewait.count(), unsafe { ewait.as_ptr_ptr() }),
+            _ => (0, ptr::null()),
+        };
+
+        let event_ptr = match self.enew.take() {
+            Some(enew) => try!(enew.ptr_mut_ptr_new()),
+            None => ptr::null_mut(),
+        };
+
+        let ele_size = std::mem::size_of::<T>();
+        let mut errcode: cl_int = 0;
+
+        let map_ptr = unsafe { ffi::clEnqueueMapBuffer(self.queue.core_as_ref().as_ptr(),
+            self.obj_core.as_ptr(), ffi::CL_TRUE, flags.bits() as cl_map_flags,
+            offset * ele_size, len * ele_size, wait_count, wait_ptr, event_ptr,
+            &mut errcode) };
+
+        try!(self.queue.timeline().record_result("Buffer::map", len * ele_size,
+            if errcode == ffi::CL_SUCCESS { Ok(()) } else { Err(status_err(errcode,
+                "clEnqueueMapBuffer", "ocl::BufferCmd::map(): Error mapping buffer.".to_owned())) }
+        ));
+
+        Ok(BufferMap {
+            queue: self.queue.clone(),
+            obj_core: self.obj_core.clone(),
+            ptr: map_ptr as *mut T,
+            len: len,
+            mapped: true,
+        })
+    }
+
     /// Specifies a list of events to wait on before the command will run.
     pub fn ewait(mut self, ewait: &'b ClWaitList) -> BufferCmd<'b, T> {
         self.ewait = Some(ewait);
@@ -359,95 +579,356 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
     //     copy_range.0, copy_range.0, copy_range.1 - copy_range.0, None,
     //     None).unwrap();
 
+    /// Returns a label and size in bytes describing this command for the
+    /// queue's command timeline.
+    fn timeline_entry(&self) -> (&'static str, usize) {
+        let ele_size = std::mem::size_of::<T>();
+        match self.kind {
+            BufferCmdKind::Unspecified => ("Buffer::unspecified", 0),
+            BufferCmdKind::Read { ref data } => {
+                ("Buffer::read", self.len.unwrap_or(data.len()) * ele_size)
+            },
+            BufferCmdKind::Write { ref data } => {
+                ("Buffer::write", self.len.unwrap_or(data.len()) * ele_size)
+            },
+            BufferCmdKind::Copy { len, .. } => ("Buffer::copy", len * ele_size),
+            BufferCmdKind::Fill { len, .. } => {
+                let offset = match self.shape {
+                    BufferCmdDataShape::Lin { offset } => offset,
+                    _ => 0,
+                };
+                ("Buffer::fill", len.or(self.len).unwrap_or(self.mem_len.saturating_sub(offset))
+                    * ele_size)
+            },
+            BufferCmdKind::CopyToImage { region, .. } => ("Buffer::copy_to_image",
+                region[0] * region[1] * region[2] * ele_size),
+            BufferCmdKind::GLAcquire => ("Buffer::gl_acquire", 0),
+            BufferCmdKind::GLRelease => ("Buffer::gl_release", 0),
+        }
+    }
+
     /// Enqueues this command.
-    pub fn enq(self) -> OclResult<()> {
+    ///
+    /// The command is recorded on the queue's command timeline if enabled
+    /// and by the queue's profiler if one is attached. Writes, fills, and
+    /// copies into a buffer with a write tracker are first checked for
+    /// conflicts (see `WriteTracker`).
+    pub fn enq(mut self) -> OclResult<()> {
+        let enew = self.enew.take();
+        self.enq_with_dest(enew)
+    }
+
+    /// Enqueues this command, returning a future which resolves when it
+    /// completes (see `FutureEvent`).
+    ///
+    /// Reads and writes started with `::read` or `::write` still block
+    /// before returning. Use `::read_future` to read without blocking.
+    ///
+    /// Requires the `futures` feature.
+    ///
+    /// ## Errors
+    ///
+    /// A new event destination (`::enew`) may not be set. Use the event of
+    /// the returned future instead.
+    ///
+    #[cfg(feature = "futures")]
+    pub fn enq_future(self) -> OclResult<FutureEvent> {
+        if self.enew.is_some() {
+            return OclError::err("ocl::BufferCmd::enq_future(): A new event destination \
+                ('::enew') can not be used with '::enq_future'. Use the event of the returned \
+                future instead.");
+        }
+
+        let mut event = Event::empty();
+        try!(self.enq_with_dest(Some(&mut event)));
+        FutureEvent::new(event)
+    }
+
+    /// Enqueues a non-blocking read into `data`, returning a future which
+    /// resolves to `data` once the read completes.
+    ///
+    /// The offset, length, queue, and wait list set on this builder are
+    /// respected (see `::read_then`).
+    ///
+    /// Requires the `futures` feature.
+    ///
+    /// ## Panics
+    ///
+    /// The command operation kind must not have already been specified.
+    ///
+    #[cfg(feature = "futures")]
+    pub fn read_future(self, data: Vec<T>) -> OclResult<FutureRead<T>>
+            where T: Send + 'static
+    {
+        future_read(|hook| self.read_then(data, move |result| hook.complete(result)))
+    }
+
+    fn enq_with_dest(self, enew: Option<&mut ClEventPtrNew>) -> OclResult<()> {
+        let queue = self.queue;
+        let (label, size) = self.timeline_entry();
+
+        let tracker = match self.kind {
+            BufferCmdKind::Write { .. } | BufferCmdKind::Fill { .. } => self.write_tracker,
+            BufferCmdKind::Copy { .. } => self.dst_write_tracker,
+            _ => None,
+        };
+
+        if let Some(tracker) = tracker { try!(tracker.check(label, queue, self.ewait)); }
+
+        let profiler = queue.profiler();
+        let start = slow_call_start(queue);
+
+        let result = if profiler.is_some() || tracker.is_some() {
+            let mut event = Event::empty();
+
+            self.enq_inner(Some(&mut event)).and_then(|_| {
+                if let Some(enew) = enew { try!(copy_to_new(&event, enew)); }
+                if let Some(profiler) = profiler { profiler.record(label, event.clone()); }
+                if let Some(tracker) = tracker { tracker.record(label, queue, event); }
+                Ok(())
+            })
+        } else {
+            self.enq_inner(enew)
+        };
+
+        slow_call_check(queue, start, || label.to_owned());
+        queue.timeline().record_result(label, size, result)
+    }
+
+    /// Enqueues this command, skipping host side validation in release
+    /// builds.
+    ///
+    /// In debug builds this behaves like `::enq` with additional checks: a
+    /// non-blocking read or write must specify a new event destination
+    /// (`::enew`) so that its completion can be awaited, and each event in the wait list
+    /// must be valid and must not have terminated abnormally. In release
+    /// builds, offsets and lengths are passed to the driver unchecked.
+    ///
+    /// ## Safety
+    ///
+    /// In release builds the caller must ensure that all offsets and lengths
+    /// are within bounds of both the buffer and any host data. The
+    /// requirements of `::read_async` and `::write_async` also apply.
+    ///
+    pub unsafe fn enq_unchecked(mut self) -> OclResult<()> {
+        if cfg!(debug_assertions) {
+            try!(self.check_unchecked());
+        } else {
+            self.checked = false;
+        }
+        self.enq()
+    }
+
+    /// Performs the debug build validation for `::enq_unchecked`.
+    fn check_unchecked(&self) -> OclResult<()> {
+        match self.kind {
+            BufferCmdKind::Read { .. } | BufferCmdKind::Write { .. } => {
+                if !self.block && self.enew.is_none() {
+                    return OclError::err("ocl::BufferCmd::enq_unchecked(): A non-blocking read \
+                        or write must specify a new event destination ('::enew').");
+                }
+            },
+            _ => (),
+        }
+
+        match self.ewait {
+            Some(ewait) => check_wait_list(ewait, "ocl::BufferCmd::enq_unchecked()"),
+            None => Ok(()),
+        }
+    }
+
+    /// Enqueues a non-blocking read into `data`, calling `hook` with the
+    /// filled vector as soon as the read completes.
+    ///
+    /// `hook` is called from a thread owned by the `OpenCL` driver (see
+    /// `Event::set_callback`) and may be used to convert or otherwise
+    /// post-process the data off of the host's critical path. It receives an
+    /// error instead if the read terminates abnormally. The returned event
+    /// is associated with the read.
+    ///
+    /// The offset, length, queue, and wait list set on this builder are respected.
+    ///
+    /// ## Panics
+    ///
+    /// The command operation kind must not have already been specified.
+    ///
+    pub fn read_then<F>(self, mut data: Vec<T>, hook: F) -> OclResult<Event>
+            where F: FnOnce(OclResult<Vec<T>>) + Send + 'static, T: Send + 'static
+    {
+        assert!(self.kind.is_unspec(), "ocl::BufferCmd::read_then(): Operation kind \
+            already set for this command.");
+
+        if self.enew.is_some() { return OclError::err("ocl::BufferCmd::read_then(): \
+            A new event destination ('::enew') can not be used with '::read_then'. \
+            Use the returned event instead."); }
+
+        let offset = match self.shape {
+            BufferCmdDataShape::Lin { offset } => offset,
+            BufferCmdDataShape::Rect { .. } => return OclError::err("ocl::BufferCmd::read_then(): \
+                Rectangular reads are not supported. Please use the default shape, linear."),
+        };
+
+        let queue = self.queue;
+        let label = "Buffer::read_then";
+        let len = self.len.unwrap_or(data.len());
+        let size = len * std::mem::size_of::<T>();
+        let start = slow_call_start(queue);
+        let mut event = Event::empty();
+
+        let result = check_host_len(len, data.len())
+            .and_then(|_| check_len(self.mem_len, len, offset))
+            .and_then(|_| unsafe {
+                core::enqueue_read_buffer(queue, self.obj_core, false, offset, &mut data[..len],
+                    self.ewait, Some(&mut event))
+            });
+
+        slow_call_check(queue, start, || label.to_owned());
+        try!(queue.timeline().record_result(label, size, result));
+
+        if let Some(profiler) = queue.profiler() { profiler.record(label, event.clone()); }
+
+        // The vector must outlive the read even if the callback is never set:
+        let data = Arc::new(Mutex::new(Some(data)));
+        let cb_data = data.clone();
+
+        let cb_result = event.set_callback(CommandExecutionStatus::Complete, move |_, status| {
+            let data = cb_data.lock().unwrap().take()
+                .expect("ocl::BufferCmd::read_then(): Data already taken.");
+
+            if status < 0 {
+                hook(OclError::err(format!("ocl::BufferCmd::read_then(): The read \
+                    terminated abnormally with status: {}.", status)))
+            } else {
+                hook(Ok(data))
+            }
+        });
+
+        if let Err(err) = cb_result {
+            try!(event.wait());
+            return Err(err);
+        }
+
+        Ok(event)
+    }
+
+    fn enq_inner(self, enew: Option<&mut ClEventPtrNew>) -> OclResult<()> {
+        if let (Some(_), &BufferCmdDataShape::Rect { .. }) = (self.len, &self.shape) {
+            return OclError::err("ocl::BufferCmd::enq(): A length ('::len') may not be set for \
+                rectangular operations.");
+        }
+
         match self.kind {
             BufferCmdKind::Read { data } => {
+                let data = match self.len {
+                    Some(len) => { try!(check_host_len(len, data.len())); &mut data[..len] },
+                    None => data,
+                };
+
                 match self.shape {
                     BufferCmdDataShape::Lin { offset } => {
-                        try!(check_len(self.mem_len, data.len(), offset));
+                        if self.checked { try!(check_len(self.mem_len, data.len(), offset)); }
 
                         unsafe { core::enqueue_read_buffer(self.queue, self.obj_core, self.block,
-                            offset, data, self.ewait, self.enew) }
+                            offset, data, self.ewait, enew) }
                     },
                     BufferCmdDataShape::Rect { src_origin, dst_origin, region, src_row_pitch, src_slc_pitch,
                             dst_row_pitch, dst_slc_pitch } =>
                     {
-                        // Verify dims given.
-                        // try!(Ok(()));
+                        if self.checked {
+                            try!(check_rect_len(self.mem_len, src_origin, region, src_row_pitch,
+                                src_slc_pitch, "buffer"));
+                            try!(check_rect_len(data.len(), dst_origin, region, dst_row_pitch,
+                                dst_slc_pitch, "host data"));
+                        }
 
                         unsafe { core::enqueue_read_buffer_rect(self.queue, self.obj_core,
                             self.block, src_origin, dst_origin, region, src_row_pitch,
                             src_slc_pitch, dst_row_pitch, dst_slc_pitch, data,
-                            self.ewait, self.enew) }
+                            self.ewait, enew) }
                     }
                 }
             },
             BufferCmdKind::Write { data } => {
+                let data = match self.len {
+                    Some(len) => { try!(check_host_len(len, data.len())); &data[..len] },
+                    None => data,
+                };
+
                 match self.shape {
                     BufferCmdDataShape::Lin { offset } => {
-                        try!(check_len(self.mem_len, data.len(), offset));
+                        if self.checked { try!(check_len(self.mem_len, data.len(), offset)); }
                         core::enqueue_write_buffer(self.queue, self.obj_core, self.block,
-                            offset, data, self.ewait, self.enew)
+                            offset, data, self.ewait, enew)
                     },
                     BufferCmdDataShape::Rect { src_origin, dst_origin, region, src_row_pitch, src_slc_pitch,
                             dst_row_pitch, dst_slc_pitch } =>
                     {
-                        // Verify dims given.
-                        // try!(Ok(()));
+                        if self.checked {
+                            try!(check_rect_len(self.mem_len, src_origin, region, src_row_pitch,
+                                src_slc_pitch, "buffer"));
+                            try!(check_rect_len(data.len(), dst_origin, region, dst_row_pitch,
+                                dst_slc_pitch, "host data"));
+                        }
 
                         core::enqueue_write_buffer_rect(self.queue, self.obj_core,
                             self.block, src_origin, dst_origin, region, src_row_pitch,
                             src_slc_pitch, dst_row_pitch, dst_slc_pitch, data,
-                            self.ewait, self.enew)
+                            self.ewait, enew)
                     }
                 }
             },
             BufferCmdKind::Copy { dst_buffer, dst_offset, len } => {
                 match self.shape {
                     BufferCmdDataShape::Lin { offset } => {
-                        try!(check_len(self.mem_len, len, offset));
+                        if self.checked {
+                            try!(check_len(self.mem_len, len, offset));
+                            try!(check_copy_dst_len::<T>(dst_buffer, len, dst_offset));
+                        }
                         core::enqueue_copy_buffer::<T>(self.queue,
                             self.obj_core, dst_buffer, offset, dst_offset, len,
-                            self.ewait, self.enew)
+                            self.ewait, enew)
                     },
                     BufferCmdDataShape::Rect { src_origin, dst_origin, region, src_row_pitch, src_slc_pitch,
                             dst_row_pitch, dst_slc_pitch } =>
                     {
-                        // Verify dims given.
-                        // try!(Ok(()));
-
                         if dst_offset != 0 || len != 0 { return OclError::err(
                             "ocl::BufferCmd::enq(): For 'rect' shaped copies, destination \
                             offset and length must be zero. Ex.: \
                             'cmd().copy(&{{buf_name}}, 0, 0)..'.");
                         }
+                        if self.checked {
+                            try!(check_rect_len(self.mem_len, src_origin, region, src_row_pitch,
+                                src_slc_pitch, "source"));
+                            try!(check_rect_len(try!(mem_len_of::<T>(dst_buffer)), dst_origin,
+                                region, dst_row_pitch, dst_slc_pitch, "destination"));
+                        }
+
                         core::enqueue_copy_buffer_rect::<T>(self.queue, self.obj_core, dst_buffer,
                         src_origin, dst_origin, region, src_row_pitch, src_slc_pitch,
-                        dst_row_pitch, dst_slc_pitch, self.ewait, self.enew)
+                        dst_row_pitch, dst_slc_pitch, self.ewait, enew)
                     },
                 }
             },
             BufferCmdKind::Fill { pattern, len } => {
                 match self.shape {
                     BufferCmdDataShape::Lin { offset } => {
-                        let len = match len {
+                        let len = match len.or(self.len) {
                             Some(l) => l,
-                            None => self.mem_len,
+                            None => self.mem_len.saturating_sub(offset),
                         };
-                        try!(check_len(self.mem_len, len, offset));
+                        if self.checked { try!(check_len(self.mem_len, len, offset)); }
                         core::enqueue_fill_buffer(self.queue, self.obj_core, pattern,
-                            offset, len, self.ewait, self.enew, Some(&self.queue.device_version()))
+                            offset, len, self.ewait, enew, Some(&self.queue.device_version()))
                     },
                     BufferCmdDataShape::Rect { .. } => OclError::err("ocl::BufferCmd::enq(): \
                         Rectangular fill is not a valid operation. Please use the default shape, linear.")
                 }
             },
             BufferCmdKind::GLAcquire => {
-                core::enqueue_acquire_gl_buffer(self.queue, self.obj_core, self.ewait, self.enew)
+                core::enqueue_acquire_gl_buffer(self.queue, self.obj_core, self.ewait, enew)
             },
             BufferCmdKind::GLRelease => {
-                core::enqueue_release_gl_buffer(self.queue, self.obj_core, self.ewait, self.enew)
+                core::enqueue_release_gl_buffer(self.queue, self.obj_core, self.ewait, enew)
             },
             BufferCmdKind::Unspecified => OclError::err("ocl::BufferCmd::enq(): No operation \
                 specified. Use '.read(...)', 'write(...)', etc. before calling '.enq()'."),
@@ -457,10 +938,102 @@ impl<'b, T: 'b + OclPrm> BufferCmd<'b, T> {
 }
 
 
+/// A range of a buffer mapped into host memory with `BufferCmd::map`.
+///
+/// Dereferences to the mapped elements. The range is unmapped when this
+/// guard is dropped or `::unmap` is called. Commands subsequently enqueued
+/// on an in-order queue see the changes.
+///
+pub struct BufferMap<T: OclPrm> {
+    queue: Queue,
+    obj_core: MemCore,
+    ptr: *mut T,
+    len: usize,
+    mapped: bool,
+}
+
+impl<T: OclPrm> BufferMap<T> {
+    /// Enqueues the unmapping of the range.
+    pub fn unmap(mut self) -> OclResult<()> {
+        self.unmap_inner()
+    }
+
+    fn unmap_inner(&mut self) -> OclResult<()> {
+        if !self.mapped { return Ok(()); }
+        self.mapped = false;
+
+        let errcode = unsafe { ffi::clEnqueueUnmapMemObject(self.queue.core_as_ref().as_ptr(),
+            self.obj_core.as_ptr(), self.ptr as *mut c_void, 0, ptr::null(), ptr::null_mut()) };
+
+        if errcode == ffi::CL_SUCCESS {
+            Ok(())
+        } else {
+            Err(status_err(errcode, "clEnqueueUnmapMemObject",
+                "ocl::BufferMap::unmap(): Error unmapping buffer.".to_owned()))
+        }
+    }
+}
+
+impl<T: OclPrm> Deref for BufferMap<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl<T: OclPrm> DerefMut for BufferMap<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<T: OclPrm> Drop for BufferMap<T> {
+    fn drop(&mut self) {
+        if let Err(err) = self.unmap_inner() {
+            error!("ocl::BufferMap::drop(): {}", err);
+        }
+    }
+}
+
+impl<T: OclPrm> std::fmt::Debug for BufferMap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("BufferMap")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+
+/// Host memory used as the storage of a buffer created with
+/// `Buffer::from_host_vec`.
+#[derive(Debug)]
+struct HostVec<T> {
+    ptr: *mut T,
+    len: usize,
+    cap: usize,
+}
+
+impl<T> Drop for HostVec<T> {
+    fn drop(&mut self) {
+        unsafe { Vec::from_raw_parts(self.ptr, self.len, self.cap); }
+    }
+}
+
+/// Frees the boxed `HostVec` passed as `user_data` once the driver has
+/// destroyed the buffer object using it (after every handle to it,
+/// including those held by enqueued commands and kernels, is released).
+extern fn _host_vec_destructor<T>(_: ffi::cl_mem, user_data: *mut c_void) {
+    unsafe { drop(Box::from_raw(user_data as *mut HostVec<T>)); }
+}
+
+
 /// A chunk of memory physically located on a device, such as a GPU.
 ///
 /// Data is stored remotely in a memory buffer on the device associated with
-/// `queue`.
+/// `queue`. No copy is kept in host memory; use `::read` and `::write` (or
+/// `::cmd`) to transfer data. Buffers which are never accessed by the host
+/// can be created with `::new_unmapped`.
 ///
 #[derive(Debug, Clone)]
 pub struct Buffer<T: OclPrm> {
@@ -468,6 +1041,8 @@ pub struct Buffer<T: OclPrm> {
     queue: Queue,
     dims: SpatialDims,
     len: usize,
+    padded_len: usize,
+    write_tracker: Option<WriteTracker>,
     _data: PhantomData<T>,
 }
 
@@ -480,26 +1055,99 @@ impl<T: OclPrm> Buffer<T> {
     /// optionally passed as the `data` argument. Also note that the names of
     /// the flags in this library have the `CL_` prefix removed for brevity.
     ///
+    /// Flags may also be created with a [`MemFlagsBuilder`]. Mutually
+    /// exclusive combinations return an error.
+    ///
+    /// To initialize the buffer with a copy of `data`, pass
+    /// `MEM_COPY_HOST_PTR`. `MEM_USE_HOST_PTR` may not be used because the
+    /// buffer cannot keep `data` alive; use `::from_host_vec` or
+    /// `::use_host_slice` instead.
+    ///
     /// [SDK Docs]: https://www.khronos.org/registry/cl/sdk/1.2/docs/man/xhtml/clCreateBuffer.html
+    /// [`MemFlagsBuilder`]: /ocl/ocl/builders/struct.MemFlagsBuilder.html
     ///
     ///
     /// [UNSTABLE]: Arguments may still be in a state of flux.
     ///
     pub fn new<D: Into<SpatialDims>>(queue: Queue, flags: Option<MemFlags>, dims: D,
                 data: Option<&[T]>) -> OclResult<Buffer<T>> {
-        let flags = flags.unwrap_or(::flags::MEM_READ_WRITE);
         let dims: SpatialDims = dims.into();
         let len = dims.to_len();
-        let obj_core = unsafe { try!(core::create_buffer(queue.context_core_as_ref(), flags, len,
-            data)) };
+        Buffer::new_padded(queue, flags, dims, data, len)
+    }
 
-        let buf = Buffer {
-            obj_core: obj_core,
-            queue: queue,
-            dims: dims,
-            len: len,
-            _data: PhantomData,
-        };
+    /// Creates a new buffer whose allocation size is padded up to a multiple
+    /// of `alignment` bytes.
+    ///
+    /// `alignment` must be a power of two and a multiple of the size of `T`.
+    /// The buffer's length (`::len`) remains the logical length given by
+    /// `dims` and all commands are bounds checked against it. The true,
+    /// padded length is available with `::padded_len`. Padding is zeroed.
+    ///
+    /// Base addresses are always aligned by the driver to at least
+    /// `CL_DEVICE_MEM_BASE_ADDR_ALIGN`.
+    ///
+    /// If `data` is passed and padding is needed, it is copied into a padded
+    /// temporary. As with `::new`, `flags` may not contain
+    /// `MEM_USE_HOST_PTR`.
+    ///
+    /// [UNSTABLE]: Arguments may still be in a state of flux.
+    ///
+    pub fn with_alignment<D: Into<SpatialDims>>(queue: Queue, flags: Option<MemFlags>, dims: D,
+                data: Option<&[T]>, alignment: usize) -> OclResult<Buffer<T>> {
+        let ele_size = std::mem::size_of::<T>();
+
+        if !alignment.is_power_of_two() || alignment % ele_size != 0 {
+            return OclError::err(format!("ocl::Buffer::with_alignment: The alignment ({} bytes) \
+                must be a power of two and a multiple of the element size ({} bytes).",
+                alignment, ele_size));
+        }
+
+        let dims: SpatialDims = dims.into();
+        let len = dims.to_len();
+        let ele_alignment = alignment / ele_size;
+        let padded_len = ((len + ele_alignment - 1) / ele_alignment) * ele_alignment;
+
+        match data {
+            Some(data) if padded_len != len => {
+                let mut padded_data = Vec::with_capacity(padded_len);
+                padded_data.extend_from_slice(data);
+                padded_data.resize(padded_len, Default::default());
+                Buffer::new_padded(queue, flags, dims, Some(&padded_data), padded_len)
+            },
+            data => Buffer::new_padded(queue, flags, dims, data, padded_len),
+        }
+    }
+
+    /// Creates a new buffer which lives only in device memory and is never
+    /// accessed by the host, such as an intermediate buffer in a pipeline
+    /// of kernels.
+    ///
+    /// `flags` defaults to `flags::MEM_READ_WRITE | flags::MEM_HOST_NO_ACCESS`
+    /// if `None` is passed, in which case reads and writes from the host
+    /// return errors (copies and fills remain available).
+    ///
+    /// Unlike `::new`, the contents are left uninitialized and no temporary
+    /// host allocation is made to zero them.
+    ///
+    /// [UNSTABLE]: Arguments may still be in a state of flux.
+    ///
+    pub fn new_unmapped<D: Into<SpatialDims>>(queue: Queue, flags: Option<MemFlags>, dims: D)
+            -> OclResult<Buffer<T>> {
+        let flags = flags.unwrap_or(::flags::MEM_READ_WRITE | ::flags::MEM_HOST_NO_ACCESS);
+        try!(check_no_use_host_ptr(flags, "new_unmapped"));
+        let dims: SpatialDims = dims.into();
+        let len = dims.to_len();
+        Buffer::create(queue, flags, dims, None, len)
+    }
+
+    /// Creates a new buffer of logical size `dims` with an allocation of
+    /// `padded_len` elements.
+    fn new_padded(queue: Queue, flags: Option<MemFlags>, dims: SpatialDims,
+                data: Option<&[T]>, padded_len: usize) -> OclResult<Buffer<T>> {
+        let flags = flags.unwrap_or(::flags::MEM_READ_WRITE);
+        try!(check_no_use_host_ptr(flags, "new"));
+        let buf = try!(Buffer::create(queue, flags, dims, data, padded_len));
 
         if data.is_none() {
             // Useful on platforms (PoCL) that have trouble with fill. Creates
@@ -507,10 +1155,12 @@ impl<T: OclPrm> Buffer<T> {
             // instead. Add `features = ["buffer_no_fill"]` to your Cargo.toml.
             if cfg!(feature = "buffer_no_fill") {
                 // println!("#### no fill");
-                try!(buf.cmd().fill(Default::default(), None).enq());
+                try!(BufferCmd::<T>::new(&buf.queue, &buf.obj_core, padded_len)
+                    .fill(Default::default(), None).enq());
             } else {
-                let zeros = vec![Default::default(); len];
-                try!(buf.cmd().write(&zeros).enq());
+                let zeros: Vec<T> = vec![Default::default(); padded_len];
+                try!(BufferCmd::<T>::new(&buf.queue, &buf.obj_core, padded_len)
+                    .write(&zeros).enq());
                 // println!("#### fill!");
             }
         }
@@ -518,6 +1168,119 @@ impl<T: OclPrm> Buffer<T> {
         Ok(buf)
     }
 
+    /// Creates a new buffer which uses `vec` as its storage
+    /// (`MEM_USE_HOST_PTR`), avoiding a copy on devices which share memory
+    /// with the host, such as integrated GPUs.
+    ///
+    /// `vec` is owned by the buffer object and freed by the driver once the
+    /// buffer object is destroyed: after every clone of the buffer has been
+    /// dropped and every enqueued command and kernel using it has released
+    /// it (`clSetMemObjectDestructorCallback`). Its contents are the initial
+    /// contents of the buffer. Access them afterwards with commands (reads
+    /// from such buffers are typically very fast) as the device may cache
+    /// them.
+    ///
+    /// `flags` defaults to `flags::MEM_READ_WRITE` if `None` is passed.
+    /// `MEM_USE_HOST_PTR` is always added.
+    ///
+    /// ## Errors
+    ///
+    /// The length of `vec` must equal the length of `dims`.
+    ///
+    /// [UNSTABLE]: Arguments may still be in a state of flux.
+    ///
+    pub fn from_host_vec<D: Into<SpatialDims>>(queue: Queue, flags: Option<MemFlags>, dims: D,
+                mut vec: Vec<T>) -> OclResult<Buffer<T>> {
+        let dims: SpatialDims = dims.into();
+        let len = dims.to_len();
+
+        if vec.len() != len {
+            return OclError::err(format!("ocl::Buffer::from_host_vec: The length of 'vec' ({}) \
+                does not match the length of 'dims' ({}).", vec.len(), len));
+        }
+
+        let host_vec = Box::new(HostVec { ptr: vec.as_mut_ptr(), len: vec.len(),
+            cap: vec.capacity() });
+        std::mem::forget(vec);
+
+        let flags = flags.unwrap_or(::flags::MEM_READ_WRITE) | ::flags::MEM_USE_HOST_PTR;
+        let data = unsafe { std::slice::from_raw_parts(host_vec.ptr, host_vec.len) };
+        // `host_vec` is simply dropped if creation fails:
+        let buf = try!(Buffer::create(queue, flags, dims, Some(data), len));
+
+        let host_vec_ptr = Box::into_raw(host_vec) as *mut c_void;
+        let errcode = unsafe { ffi::clSetMemObjectDestructorCallback(buf.obj_core.as_ptr(),
+            Some(_host_vec_destructor::<T>), host_vec_ptr) };
+
+        if errcode != ffi::CL_SUCCESS {
+            // The buffer object may outlive `buf` within the driver. Leak the
+            // vector rather than risk freeing memory still in use:
+            return Err(status_err(errcode, "clSetMemObjectDestructorCallback",
+                "ocl::Buffer::from_host_vec: Unable to set the destructor callback which \
+                frees the host vector.".to_owned()));
+        }
+
+        Ok(buf)
+    }
+
+    /// Creates a new buffer which uses the host memory of `data` as its
+    /// storage (`MEM_USE_HOST_PTR`), such as memory allocated by another
+    /// library.
+    ///
+    /// `flags` defaults to `flags::MEM_READ_WRITE` if `None` is passed.
+    /// `MEM_USE_HOST_PTR` is always added.
+    ///
+    /// Prefer `::from_host_vec`, which manages the lifetime of the host
+    /// memory, whenever possible.
+    ///
+    /// ## Safety
+    ///
+    /// The memory referred to by `data` is used by the device for as long as
+    /// the buffer object exists, beyond the lifetime of the borrow. The
+    /// caller must ensure that it is neither freed nor accessed from the
+    /// host (except between map and unmap commands) until this buffer, every
+    /// clone of it, and any kernel using it as an argument have been dropped.
+    ///
+    /// ## Errors
+    ///
+    /// The length of `data` must equal the length of `dims`.
+    ///
+    /// [UNSTABLE]: Arguments may still be in a state of flux.
+    ///
+    pub unsafe fn use_host_slice<D: Into<SpatialDims>>(queue: Queue, flags: Option<MemFlags>,
+                dims: D, data: &mut [T]) -> OclResult<Buffer<T>> {
+        let dims: SpatialDims = dims.into();
+        let len = dims.to_len();
+
+        if data.len() != len {
+            return OclError::err(format!("ocl::Buffer::use_host_slice: The length of 'data' \
+                ({}) does not match the length of 'dims' ({}).", data.len(), len));
+        }
+
+        let flags = flags.unwrap_or(::flags::MEM_READ_WRITE) | ::flags::MEM_USE_HOST_PTR;
+        Buffer::create(queue, flags, dims, Some(data), len)
+    }
+
+    /// Creates the buffer object without initializing its contents.
+    fn create(queue: Queue, flags: MemFlags, dims: SpatialDims, data: Option<&[T]>,
+                padded_len: usize) -> OclResult<Buffer<T>> {
+        try!(check_mem_flags(flags));
+        try!(check_fp64::<T>(queue.device(), "ocl::Buffer::new()"));
+        let len = dims.to_len();
+        let obj_core = unsafe { try!(core::create_buffer(queue.context_core_as_ref(), flags,
+            padded_len, data)) };
+
+        Ok(Buffer {
+            obj_core: obj_core,
+            queue: queue,
+            dims: dims,
+            len: len,
+            padded_len: padded_len,
+            write_tracker: None,
+            _data: PhantomData,
+        })
+    }
+
     /// [UNTESTED]
     /// Creates a buffer linked to a previously created OpenGL buffer object.
     ///
@@ -546,6 +1309,8 @@ impl<T: OclPrm> Buffer<T> {
             queue: queue.clone(),
             dims: dims,
             len: len,
+            padded_len: len,
+            write_tracker: None,
             _data: PhantomData,
         };
 
@@ -560,12 +1325,17 @@ impl<T: OclPrm> Buffer<T> {
     /// for more info.
     ///
     pub fn cmd(&self) -> BufferCmd<T> {
-        BufferCmd::new(&self.queue, &self.obj_core, self.len)
+        let mut cmd = BufferCmd::new(&self.queue, &self.obj_core, self.len);
+        cmd.write_tracker = self.write_tracker.as_ref();
+        cmd
     }
 
-    /// Returns a buffer command builder used to read.
+    /// Returns a buffer command builder used to read directly into `data`,
+    /// which may be any caller-owned slice.
     ///
-    /// Call `.enq()` to enqueue the command.
+    /// Reads `data.len()` elements starting at element `0` or at the offset
+    /// set with `.offset(..)`. Blocks until the read completes. Call
+    /// `.enq()` to enqueue the command.
     ///
     /// See the [`BufferCmd` docs](/ocl/ocl/build/struct.BufferCmd.html)
     /// for more info.
@@ -575,10 +1345,27 @@ impl<T: OclPrm> Buffer<T> {
         self.cmd().read(data)
     }
 
-    /// Returns a buffer command builder used to write.
+    /// Returns a buffer command builder used to read directly into `data`
+    /// without blocking.
     ///
     /// Call `.enq()` to enqueue the command.
     ///
+    /// ## Safety
+    ///
+    /// See `BufferCmd::read_async`: `data` must not be accessed or dropped
+    /// until the read completes (await the event set with `.enew(..)`).
+    ///
+    #[inline]
+    pub unsafe fn read_async<'b>(&'b self, data: &'b mut [T]) -> BufferCmd<'b, T> {
+        self.cmd().read_async(data)
+    }
+
+    /// Returns a buffer command builder used to write directly from `data`,
+    /// which may be any caller-owned slice.
+    ///
+    /// Writes `data.len()` elements starting at element `0` or at the offset
+    /// set with `.offset(..)`. Call `.enq()` to enqueue the command.
+    ///
     /// See the [`BufferCmd` docs](/ocl/ocl/build/struct.BufferCmd.html)
     /// for more info.
     ///
@@ -587,6 +1374,21 @@ impl<T: OclPrm> Buffer<T> {
         self.cmd().write(data)
     }
 
+    /// Returns a buffer command builder used to write directly from `data`
+    /// without blocking.
+    ///
+    /// Call `.enq()` to enqueue the command.
+    ///
+    /// ## Safety
+    ///
+    /// See `BufferCmd::write_async`: `data` must not be modified or dropped
+    /// until the write completes (await the event set with `.enew(..)`).
+    ///
+    #[inline]
+    pub unsafe fn write_async<'b>(&'b self, data: &'b [T]) -> BufferCmd<'b, T> {
+        self.cmd().write_async(data)
+    }
+
     /// Returns the length of the Buffer.
     #[inline]
     pub fn len(&self) -> usize {
@@ -595,6 +1397,13 @@ impl<T: OclPrm> Buffer<T> {
         self.len
     }
 
+    /// Returns the true, allocated length of the Buffer, including any
+    /// padding added by `::with_alignment`.
+    #[inline]
+    pub fn padded_len(&self) -> usize {
+        self.padded_len
+    }
+
     /// Returns if the Buffer is empty.
     #[inline]
     pub fn is_empty(&self) -> bool {
@@ -632,6 +1441,23 @@ impl<T: OclPrm> Buffer<T> {
         &self.queue
     }
 
+    /// Attaches a tracker which checks writes to this buffer for conflicts
+    /// with the previous, still outstanding, write (see `WriteTracker`).
+    /// Pass `None` to stop tracking.
+    ///
+    /// Only clones of this buffer made afterwards share the tracker.
+    /// Kernels must have this buffer set as an argument afterwards for their
+    /// writes to be checked.
+    ///
+    pub fn set_write_tracker(&mut self, tracker: Option<WriteTracker>) {
+        self.write_tracker = tracker;
+    }
+
+    /// Returns the write tracker attached to this buffer, if any.
+    pub fn write_tracker(&self) -> Option<&WriteTracker> {
+        self.write_tracker.as_ref()
+    }
+
     /// Returns a reference to the core pointer wrapper, usable by functions in
     /// the `core` module.
     pub fn core_as_ref(&self) -> &MemCore {
@@ -2,11 +2,83 @@
 
 use std;
 use std::ops::{Deref, DerefMut};
-use core::{self, Context as ContextCore, ContextProperties, ContextPropertyValue, ContextInfo,
-    ContextInfoResult, DeviceInfo, DeviceInfoResult, PlatformId as PlatformIdCore, PlatformInfo,
-    PlatformInfoResult, CreateContextCallbackFn, UserDataPtr};
+use std::mem;
+use std::sync::{Arc, Mutex, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use libc::{c_char, c_void, size_t};
+use ffi::{self, cl_int, cl_uint, cl_device_id, cl_context_properties};
+use core::{self, Context as ContextCore, CommandQueue as CommandQueueCore, ContextProperties, ContextPropertyValue, ContextInfo,
+    ContextInfoResult, DeviceInfo, DeviceInfoResult, DeviceType, PlatformId as PlatformIdCore, PlatformInfo,
+    PlatformInfoResult, CreateContextCallbackFn, UserDataPtr, ClDeviceIdPtr,
+    DeviceId as DeviceIdCore};
 use core::error::{Result as OclResult, Error as OclError};
 use standard::{Platform, Device, DeviceSpecifier};
+use standard::error_hints::status_err;
+
+/// `cl_arm_printf` context property: the printf callback function.
+const CL_PRINTF_CALLBACK_ARM: cl_context_properties = 0x40B0;
+/// `cl_arm_printf` context property: the printf buffer size in bytes.
+const CL_PRINTF_BUFFERSIZE_ARM: cl_context_properties = 0x40B1;
+
+
+/// Output of kernel `printf` calls captured from a context created with
+/// `ContextBuilder::arm_printf`.
+///
+/// Shared between all clones of the context.
+#[derive(Debug)]
+pub struct PrintfCapture {
+    output: Mutex<String>,
+    truncated: AtomicBool,
+}
+
+impl PrintfCapture {
+    fn new() -> PrintfCapture {
+        PrintfCapture {
+            output: Mutex::new(String::new()),
+            truncated: AtomicBool::new(false),
+        }
+    }
+
+    /// Removes and returns the output captured so far.
+    ///
+    /// Output is delivered by the driver when a command completes, wait for
+    /// commands (`Queue::finish`) before calling.
+    pub fn take_output(&self) -> String {
+        let mut output = self.output.lock().expect("ocl::PrintfCapture::take_output()");
+        std::mem::replace(&mut *output, String::new())
+    }
+
+    /// Returns true if the printf buffer has overflowed, and output been
+    /// lost, since the last call to `::clear_truncated`.
+    ///
+    /// Increase the buffer size passed to `ContextBuilder::arm_printf` if
+    /// this occurs.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated.load(Ordering::SeqCst)
+    }
+
+    /// Resets the overflow status.
+    pub fn clear_truncated(&self) {
+        self.truncated.store(false, Ordering::SeqCst)
+    }
+}
+
+/// Receives printf output from the driver (`cl_arm_printf`).
+///
+/// `complete` is zero if the output did not fit into the printf buffer.
+extern "C" fn _arm_printf_callback(buffer: *const c_char, len: size_t, complete: size_t,
+        user_data: *mut c_void)
+{
+    if buffer.is_null() || user_data.is_null() { return; }
+
+    let capture = unsafe { &*(user_data as *const Arc<PrintfCapture>) };
+    let bytes = unsafe { std::slice::from_raw_parts(buffer as *const u8, len as usize) };
+
+    if let Ok(mut output) = capture.output.lock() {
+        output.push_str(&String::from_utf8_lossy(bytes));
+    }
+    if complete == 0 { capture.truncated.store(true, Ordering::SeqCst); }
+}
 
 
 /// A builder for `Context`.
@@ -16,6 +88,8 @@ pub struct ContextBuilder {
     properties: ContextProperties,
     // platform: Option<Platform>,
     device_spec: Option<DeviceSpecifier>,
+    device_type: Option<DeviceType>,
+    arm_printf_buffer_size: Option<usize>,
 }
 
 impl ContextBuilder {
@@ -34,13 +108,18 @@ impl ContextBuilder {
     /// - Handle context creation callbacks.
     ///
     pub fn new() -> ContextBuilder {
-        let properties = ContextProperties::new()
-            .platform::<PlatformIdCore>(Platform::default().into());
+        // If no platform is available, building will return the error:
+        let properties = match Platform::try_default() {
+            Ok(platform) => ContextProperties::new().platform::<PlatformIdCore>(platform.into()),
+            Err(_) => ContextProperties::new(),
+        };
 
         ContextBuilder {
             properties: properties,
             // platform: None,
             device_spec: None,
+            device_type: None,
+            arm_printf_buffer_size: None,
         }
     }
 
@@ -48,7 +127,20 @@ impl ContextBuilder {
     ///
     /// Returns a newly created context with the specified platform and set of device types.
     pub fn build(&self) -> OclResult<Context> {
-        Context::new(Some(self.properties.clone()), self.device_spec.clone(), None, None)
+        if let Some(device_type) = self.device_type {
+            if self.arm_printf_buffer_size.is_some() {
+                return OclError::err("ocl::ContextBuilder::build: 'arm_printf' can not be used \
+                    together with 'device_type'. Specify devices with '::devices' instead.");
+            }
+            return Context::from_type(self.properties.clone(), device_type);
+        }
+
+        match self.arm_printf_buffer_size {
+            Some(buffer_size) => Context::with_arm_printf(self.properties.clone(),
+                self.device_spec.clone(), buffer_size),
+            None => Context::new(Some(self.properties.clone()), self.device_spec.clone(),
+                None, None),
+        }
     }
 
     /// Specify context properties directly.
@@ -102,10 +194,78 @@ impl ContextBuilder {
     pub fn devices<D: Into<DeviceSpecifier>>(&mut self, device_spec: D)
             -> &mut ContextBuilder
     {
-        assert!(self.device_spec.is_none(), "ocl::ContextBuilder::devices: Devices already specified");
+        assert!(self.device_spec.is_none() && self.device_type.is_none(),
+            "ocl::ContextBuilder::devices: Devices already specified");
         self.device_spec = Some(device_spec.into());
         self
     }
+
+    /// Specifies that the context will contain every device of the type or
+    /// types in `device_type` on the platform (`clCreateContextFromType`).
+    ///
+    /// For example, `Context::builder().device_type(core::DEVICE_TYPE_GPU)`
+    /// creates a context over all GPUs on the default platform.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if any devices have already been specified.
+    ///
+    pub fn device_type(&mut self, device_type: DeviceType) -> &mut ContextBuilder {
+        assert!(self.device_spec.is_none() && self.device_type.is_none(),
+            "ocl::ContextBuilder::device_type: Devices already specified");
+        self.device_type = Some(device_type);
+        self
+    }
+
+    /// Captures the output of kernel `printf` calls using the `cl_arm_printf`
+    /// extension, with a printf buffer of `buffer_size` bytes per device.
+    ///
+    /// The default buffer on Mali devices is small enough that output is
+    /// routinely truncated. Captured output and the overflow status are
+    /// available from `Context::printf_capture`.
+    ///
+    /// Building the context will fail if any of its devices do not support
+    /// `cl_arm_printf`.
+    ///
+    pub fn arm_printf(&mut self, buffer_size: usize) -> &mut ContextBuilder {
+        self.arm_printf_buffer_size = Some(buffer_size);
+        self
+    }
+}
+
+
+/// The queues created from a context and the hooks run when it is quiesced.
+struct Registry {
+    queues: Vec<Weak<CommandQueueCore>>,
+    flush_hooks: Vec<Box<FnMut() -> OclResult<()> + Send>>,
+}
+
+impl Registry {
+    fn new() -> Registry {
+        Registry { queues: Vec::new(), flush_hooks: Vec::new() }
+    }
+
+    /// Returns the queues still in use, forgetting the rest.
+    fn live_queues(&mut self) -> Vec<Arc<CommandQueueCore>> {
+        self.queues.retain(|q| q.upgrade().is_some());
+        self.queues.iter().filter_map(|q| q.upgrade()).collect()
+    }
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Registry")
+            .field("queues", &self.queues.len())
+            .field("flush_hooks", &self.flush_hooks.len())
+            .finish()
+    }
+}
+
+/// Registers a queue, created from `context`, to be finished by
+/// `Context::finish_all`. Only a weak reference is kept.
+pub fn register_queue(context: &Context, queue: &Arc<CommandQueueCore>) {
+    context.registry.lock().expect("ocl::Context::register_queue()")
+        .queues.push(Arc::downgrade(queue));
 }
 
 
@@ -122,6 +282,8 @@ pub struct Context {
     obj_core: ContextCore,
     platform: Option<Platform>,
     devices: Vec<Device>,
+    printf_capture: Option<Arc<PrintfCapture>>,
+    registry: Arc<Mutex<Registry>>,
 }
 
 impl Context {
@@ -180,6 +342,86 @@ impl Context {
             obj_core: obj_core,
             platform: platform,
             devices: device_list,
+            printf_capture: None,
+            registry: Arc::new(Mutex::new(Registry::new())),
+        })
+    }
+
+    /// Returns a newly created context capturing kernel `printf` output with
+    /// `cl_arm_printf`. Use `ContextBuilder::arm_printf` instead.
+    fn with_arm_printf(properties: ContextProperties, device_spec: Option<DeviceSpecifier>,
+            buffer_size: usize) -> OclResult<Context>
+    {
+        let platform = properties.get_platform().map(Platform::new);
+        let device_spec = device_spec.unwrap_or(DeviceSpecifier::All);
+        let device_list = try!(device_spec.to_device_list(platform.as_ref()));
+
+        for device in device_list.iter() {
+            if !try!(device.extensions()).contains("cl_arm_printf") {
+                return OclError::err(format!("ocl::Context::with_arm_printf: The device '{}' \
+                    does not support 'cl_arm_printf'.", device.name()));
+            }
+        }
+
+        let capture = Arc::new(PrintfCapture::new());
+
+        // The driver may call back for as long as the context exists, which
+        // may outlive every `Context` (queues and other objects hold their
+        // own references), so this handle is intentionally never freed.
+        let user_data = Box::into_raw(Box::new(capture.clone())) as *mut c_void;
+
+        let mut props_raw = properties.to_raw();
+        // Remove the terminating zero:
+        if props_raw.last() == Some(&0) { props_raw.pop(); }
+        props_raw.extend_from_slice(&[
+            CL_PRINTF_CALLBACK_ARM, _arm_printf_callback as cl_context_properties,
+            CL_PRINTF_BUFFERSIZE_ARM, buffer_size as cl_context_properties,
+            0,
+        ]);
+
+        let device_ptrs: Vec<cl_device_id> = device_list.iter()
+            .map(|d| unsafe { d.as_ptr() }).collect();
+        let mut errcode: cl_int = 0;
+
+        let obj_ptr = unsafe { ffi::clCreateContext(props_raw.as_ptr() as *const cl_context_properties,
+            device_ptrs.len() as cl_uint, device_ptrs.as_ptr(), None, user_data, &mut errcode) };
+
+        if errcode != ffi::CL_SUCCESS {
+            unsafe { drop(Box::from_raw(user_data as *mut Arc<PrintfCapture>)); }
+            return Err(status_err(errcode, "clCreateContext",
+                "ocl::Context::with_arm_printf: Error creating context.".to_owned()));
+        }
+
+        Ok(Context {
+            obj_core: unsafe { ContextCore::from_fresh_ptr(obj_ptr) },
+            platform: platform,
+            devices: device_list,
+            printf_capture: Some(capture),
+            registry: Arc::new(Mutex::new(Registry::new())),
+        })
+    }
+
+    /// Returns a newly created context containing every device of the
+    /// type or types in `device_type`. Use `ContextBuilder::device_type`
+    /// instead.
+    fn from_type(properties: ContextProperties, device_type: DeviceType) -> OclResult<Context> {
+        let platform = properties.get_platform().map(Platform::new);
+        let obj_core = try!(core::create_context_from_type::<DeviceIdCore>(Some(&properties),
+            device_type, None, None));
+
+        let devices = match core::get_context_info(&obj_core, ContextInfo::Devices) {
+            ContextInfoResult::Devices(devices) => Device::list_from_core(devices),
+            ContextInfoResult::Error(err) => return Err(*err),
+            _ => return OclError::err("ocl::Context::from_type: Unexpected \
+                'ContextInfoResult' variant."),
+        };
+
+        Ok(Context {
+            obj_core: obj_core,
+            platform: platform,
+            devices: devices,
+            printf_capture: None,
+            registry: Arc::new(Mutex::new(Registry::new())),
         })
     }
 
@@ -258,6 +500,74 @@ impl Context {
         self.platform.as_ref()
     }
 
+    /// Returns the captured output of kernel `printf` calls if this context
+    /// was built with `ContextBuilder::arm_printf`.
+    pub fn printf_capture(&self) -> Option<&PrintfCapture> {
+        self.printf_capture.as_ref().map(|c| &**c)
+    }
+
+    /// Blocks until all commands enqueued on every queue created from this
+    /// context (or a clone of it) and still in use have completed.
+    ///
+    /// Every queue is finished even if an earlier one fails, the first error
+    /// is returned.
+    pub fn finish_all(&self) -> OclResult<()> {
+        let queues = self.registry.lock().expect("ocl::Context::finish_all()").live_queues();
+        let mut result = Ok(());
+
+        for queue in queues.iter() {
+            let res = core::finish(&**queue);
+            if result.is_ok() { result = res; }
+        }
+        result
+    }
+
+    /// Returns the number of queues created from this context which are
+    /// still in use.
+    pub fn queue_count(&self) -> usize {
+        self.registry.lock().expect("ocl::Context::queue_count()").live_queues().len()
+    }
+
+    /// Adds a hook to be called by `::quiesce` before all queues are
+    /// finished, i.e. to flush commands or data batched by another module.
+    ///
+    /// Hooks are shared by all clones of this context and are called in the
+    /// order added. Hooks may enqueue commands and create queues but must
+    /// not call `::quiesce`.
+    pub fn add_flush_hook<F>(&self, hook: F) where F: FnMut() -> OclResult<()> + Send + 'static {
+        self.registry.lock().expect("ocl::Context::add_flush_hook()")
+            .flush_hooks.push(Box::new(hook));
+    }
+
+    /// Calls every flush hook then blocks until all commands on every queue
+    /// created from this context have completed (`::finish_all`).
+    ///
+    /// Intended for use before snapshotting buffers or tearing down when
+    /// queues are owned by many, separate, modules. All hooks are called and
+    /// all queues finished even if one fails, the first error is returned.
+    pub fn quiesce(&self) -> OclResult<()> {
+        // Hooks are called without holding the lock:
+        let mut hooks = mem::replace(&mut self.registry.lock()
+            .expect("ocl::Context::quiesce()").flush_hooks, Vec::new());
+        let mut result = Ok(());
+
+        for hook in hooks.iter_mut() {
+            let res = hook();
+            if result.is_ok() { result = res; }
+        }
+
+        {
+            let mut registry = self.registry.lock().expect("ocl::Context::quiesce()");
+            let added = mem::replace(&mut registry.flush_hooks, Vec::new());
+            hooks.extend(added);
+            registry.flush_hooks = hooks;
+        }
+
+        let res = self.finish_all();
+        if result.is_ok() { result = res; }
+        result
+    }
+
     fn fmt_info(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.debug_struct("Context")
             .field("ReferenceCount", &self.info(ContextInfo::ReferenceCount))
@@ -1,12 +1,54 @@
 //! An OpenCL device identifier and related types.
 
 use std;
+use std::env;
 use std::ops::{Deref, DerefMut};
 use std::convert::Into;
+use std::ptr;
+use std::collections::{HashMap, HashSet, hash_set};
+use std::sync::{Arc, Mutex, Once, ONCE_INIT};
 use core::error::{Error as OclError, Result as OclResult};
-use standard::Platform;
-use core::{self, DeviceId as DeviceIdCore, DeviceType, DeviceInfo, DeviceInfoResult, ClDeviceIdPtr};
+use standard::{Platform, cl_type_name_of};
+use standard::error_hints::status_err;
+use core::{self, DeviceId as DeviceIdCore, DeviceType, DeviceInfo, DeviceInfoResult, ClDeviceIdPtr,
+    DevicePartitionProperty, DeviceAffinityDomain};
 use core::util;
+use libc::c_void;
+use log::{log, error};
+use ffi::{self, cl_uint, cl_bitfield, cl_device_id, cl_device_partition_property,
+    cl_device_fp_config};
+
+/// Returns the value of the `DeviceInfoResult::$variant` queried from
+/// `$device`, or an error if the query failed or returned another variant.
+macro_rules! device_info {
+    ($device:expr, $variant:ident, $caller:expr) => {
+        match $device.info(DeviceInfo::$variant) {
+            DeviceInfoResult::$variant(v) => Ok(v),
+            DeviceInfoResult::Error(err) => Err(*err),
+            _ => OclError::err(format!("ocl::Device::{}(): Unexpected 'DeviceInfoResult' \
+                variant.", $caller)),
+        }
+    }
+}
+
+
+/// Returns an error if `T` is a double precision type (`double`,
+/// `double2`, etc.) and `device` does not support double precision.
+pub fn check_fp64<T>(device: &Device, caller: &str) -> OclResult<()> {
+    let type_name = match cl_type_name_of::<T>() {
+        Some(name) if name.starts_with("double") => name,
+        _ => return Ok(()),
+    };
+
+    if try!(device.supports_fp64()) {
+        Ok(())
+    } else {
+        OclError::err(format!("{}: The device '{}' does not support double precision \
+            floating point ('cl_khr_fp64'), required for elements of type '{}'.", caller,
+            device.name(), type_name))
+    }
+}
+
 
 /// Specifies [what boils down to] a list of devices.
 ///
@@ -18,6 +60,9 @@ use core::util;
 /// The `TypeFlags` variant is used for specifying a list of devices using a
 /// bitfield (`DeviceType`) and is the most robust / portable.
 ///
+/// The `VendorContains` and `NameContains` variants select the devices
+/// whose vendor or name contain a string, ignoring case (e.g. "nvidia").
+///
 ///
 /// [FIXME: Add some links to the SDK]
 ///
@@ -30,6 +75,8 @@ pub enum DeviceSpecifier {
     Indices(Vec<usize>),
     WrappingIndices(Vec<usize>),
     TypeFlags(DeviceType),
+    VendorContains(String),
+    NameContains(String),
 }
 
 impl DeviceSpecifier {
@@ -98,6 +145,20 @@ impl DeviceSpecifier {
         DeviceSpecifier::TypeFlags(flags)
     }
 
+    /// Returns a `DeviceSpecifier::VendorContains` variant which specifies
+    /// the devices whose vendor name contains `vendor`, ignoring case.
+    ///
+    pub fn vendor_contains<S: Into<String>>(self, vendor: S) -> DeviceSpecifier {
+        DeviceSpecifier::VendorContains(vendor.into())
+    }
+
+    /// Returns a `DeviceSpecifier::NameContains` variant which specifies the
+    /// devices whose name contains `name`, ignoring case.
+    ///
+    pub fn name_contains<S: Into<String>>(self, name: S) -> DeviceSpecifier {
+        DeviceSpecifier::NameContains(name.into())
+    }
+
     /// Returns the list of devices matching the parameters specified by this
     /// `DeviceSpecifier`
     ///
@@ -112,7 +173,7 @@ impl DeviceSpecifier {
     pub fn to_device_list(&self, platform: Option<&Platform>) -> OclResult<Vec<Device>> {
         let platform = match platform {
             Some(p) => p.clone(),
-            None => Platform::default(),
+            None => try!(Platform::try_default()),
         };
 
         match *self {
@@ -137,6 +198,28 @@ impl DeviceSpecifier {
             DeviceSpecifier::TypeFlags(flags) => {
                 Device::list(&platform, Some(flags))
             },
+            DeviceSpecifier::VendorContains(ref vendor) => {
+                let pattern = vendor.to_lowercase();
+                let devices: Vec<Device> = try!(Device::list_all(&platform)).into_iter()
+                    .filter(|d| d.vendor().to_lowercase().contains(&pattern)).collect();
+
+                if devices.is_empty() {
+                    return OclError::err(format!("DeviceSpecifier::to_device_list(): No devices \
+                        on the platform have a vendor containing '{}'.", vendor));
+                }
+                Ok(devices)
+            },
+            DeviceSpecifier::NameContains(ref name) => {
+                let pattern = name.to_lowercase();
+                let devices: Vec<Device> = try!(Device::list_all(&platform)).into_iter()
+                    .filter(|d| d.name().to_lowercase().contains(&pattern)).collect();
+
+                if devices.is_empty() {
+                    return OclError::err(format!("DeviceSpecifier::to_device_list(): No devices \
+                        on the platform have a name containing '{}'.", name));
+                }
+                Ok(devices)
+            },
         }
     }
 }
@@ -196,6 +279,99 @@ impl From<DeviceType> for DeviceSpecifier {
 }
 
 
+/// A scheme used to partition a device into sub-devices (see
+/// `Device::create_sub_devices`).
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartitionProperty {
+    /// As many sub-devices as possible, each containing the given number of
+    /// compute units.
+    Equally(u32),
+    /// One sub-device for each count, containing that many compute units.
+    ByCounts(Vec<u32>),
+    /// Sub-devices sharing the given level of the cache hierarchy or NUMA
+    /// node. `DEVICE_AFFINITY_DOMAIN_NEXT_PARTITIONABLE` partitions along
+    /// the next (outermost) partitionable domain.
+    ByAffinityDomain(DeviceAffinityDomain),
+}
+
+impl PartitionProperty {
+    /// Returns the zero-terminated property list passed to
+    /// `clCreateSubDevices`.
+    fn to_raw(&self) -> Vec<cl_device_partition_property> {
+        match *self {
+            PartitionProperty::Equally(units) => vec![
+                DevicePartitionProperty::Equally as cl_device_partition_property,
+                units as cl_device_partition_property, 0],
+            PartitionProperty::ByCounts(ref counts) => {
+                let mut props = Vec::with_capacity(counts.len() + 3);
+                props.push(DevicePartitionProperty::ByCounts as cl_device_partition_property);
+                props.extend(counts.iter().map(|&c| c as cl_device_partition_property));
+                props.push(DevicePartitionProperty::ByCountsListEnd
+                    as cl_device_partition_property);
+                props.push(0);
+                props
+            },
+            PartitionProperty::ByAffinityDomain(domain) => vec![
+                DevicePartitionProperty::ByAffinityDomain as cl_device_partition_property,
+                domain.bits() as cl_device_partition_property, 0],
+        }
+    }
+}
+
+
+/// The extensions supported by a device, as returned by
+/// `Device::extensions`.
+///
+/// Cheap to clone.
+///
+#[derive(Clone, Debug)]
+pub struct Extensions(Arc<HashSet<String>>);
+
+impl Extensions {
+    /// Parses a space separated list of extension names.
+    pub fn parse(extensions: &str) -> Extensions {
+        Extensions(Arc::new(extensions.split_whitespace().map(|ext| ext.to_owned()).collect()))
+    }
+
+    /// Returns true if `extension` (e.g. `"cl_khr_fp64"`) is supported.
+    pub fn contains(&self, extension: &str) -> bool {
+        self.0.contains(extension)
+    }
+
+    /// Returns an iterator over the extension names, in no particular order.
+    pub fn iter(&self) -> hash_set::Iter<String> {
+        self.0.iter()
+    }
+
+    /// Returns the number of extensions.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if no extensions are supported.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Returns the process-wide cache of parsed extensions, keyed by device id.
+fn extensions_cache() -> &'static Mutex<HashMap<usize, Extensions>> {
+    static INIT: Once = ONCE_INIT;
+    static mut CACHE: *const Mutex<HashMap<usize, Extensions>> = 0 as *const _;
+
+    unsafe {
+        INIT.call_once(|| CACHE = Box::into_raw(Box::new(Mutex::new(HashMap::new()))));
+        &*CACHE
+    }
+}
+
+// `CL_DEVICE_SVM_CAPABILITIES` and `CL_DEVICE_SVM_COARSE_GRAIN_BUFFER`
+// (OpenCL 2.0), not yet declared by `cl-sys`:
+const DEVICE_SVM_CAPABILITIES: cl_uint = 0x1053;
+const DEVICE_SVM_COARSE_GRAIN_BUFFER: cl_bitfield = 1 << 0;
+
+
 /// An individual device identifier (an OpenCL device_id).
 ///
 #[derive(Clone, Copy, Debug)]
@@ -205,14 +381,57 @@ impl Device {
     /// Returns the first available device on a platform.
     ///
     ///
-    /// ### Errors
-    ///
+    /// ### Panics
     ///
+    /// Panics if the device list cannot be retrieved or is empty. Use
+    /// `::try_first` to receive an error instead.
     ///
     pub fn first(platform: Platform) -> Device {
-        let first_core = core::get_device_ids(&platform, None, None)
-            .expect("ocl::Device::first: Error retrieving device list");
-        Device(first_core[0])
+        Device::try_first(platform).expect("ocl::Device::first")
+    }
+
+    /// Returns the first available device on a platform or an error if the
+    /// device list cannot be retrieved or is empty.
+    pub fn try_first(platform: Platform) -> OclResult<Device> {
+        let list_core = try!(core::get_device_ids(&platform, None, None));
+
+        match list_core.first() {
+            Some(&device) => Ok(Device(device)),
+            None => OclError::err("ocl::Device::try_first: The platform has no devices."),
+        }
+    }
+
+    /// Returns the default device on a platform.
+    ///
+    /// This is the device with the index given by the
+    /// `OCL_DEFAULT_DEVICE_IDX` environment variable if it is set, allowing
+    /// the device used by a program to be switched without recompiling, or
+    /// the first device otherwise. Use together with `Platform::default`,
+    /// which honors `OCL_DEFAULT_PLATFORM_IDX`.
+    ///
+    ///
+    /// ### Errors
+    ///
+    /// Returns an error if `OCL_DEFAULT_DEVICE_IDX` is not a valid index for
+    /// the platform or if the device list cannot be retrieved or is empty.
+    ///
+    pub fn default_for(platform: Platform) -> OclResult<Device> {
+        let device_list = try!(core::get_device_ids(&platform, None, None));
+
+        let idx = match env::var("OCL_DEFAULT_DEVICE_IDX") {
+            Ok(s) => match s.trim().parse::<usize>() {
+                Ok(idx) if idx < device_list.len() => idx,
+                _ => return OclError::err(format!("ocl::Device::default_for: The value of \
+                    'OCL_DEFAULT_DEVICE_IDX' ('{}') is not a valid device index. The platform \
+                    has {} devices.", s, device_list.len())),
+            },
+            Err(_) => 0,
+        };
+
+        match device_list.get(idx) {
+            Some(&device) => Ok(Device(device)),
+            None => OclError::err("ocl::Device::default_for: The platform has no devices."),
+        }
     }
 
     /// Returns a `DeviceSpecifier` useful for precisely specifying a set
@@ -319,6 +538,62 @@ impl Device {
         Ok(Self::resolve_idxs_wrap(idxs, &try!(Self::list(platform, device_types))))
     }
 
+    /// Partitions this device into sub-devices (`clCreateSubDevices`).
+    ///
+    /// The returned devices can be used like any other device, for
+    /// example to create a context and queues confined to a NUMA node:
+    ///
+    /// ```text
+    /// let sub_devices = device.create_sub_devices(
+    ///     PartitionProperty::ByAffinityDomain(DEVICE_AFFINITY_DOMAIN_NUMA))?;
+    /// let context = Context::builder().platform(platform)
+    ///     .devices(*sub_devices[0]).build()?;
+    /// ```
+    ///
+    /// Each sub-device is released when the returned `SubDevice` is
+    /// dropped. It dereferences to a (`Copy`) `Device`, which must not be
+    /// used once the `SubDevice` has been dropped.
+    ///
+    /// ### Errors
+    ///
+    /// The device must support the requested partitioning scheme (see
+    /// `DeviceInfo::PartitionProperties` and
+    /// `DeviceInfo::PartitionAffinityDomain`).
+    ///
+    pub fn create_sub_devices(&self, property: PartitionProperty) -> OclResult<Vec<SubDevice>> {
+        if let PartitionProperty::ByCounts(ref counts) = property {
+            if counts.is_empty() {
+                return OclError::err("ocl::Device::create_sub_devices(): \
+                    'PartitionProperty::ByCounts' requires at least one count.");
+            }
+        }
+
+        let props = property.to_raw();
+        let mut count: cl_uint = 0;
+
+        let errcode = unsafe { ffi::clCreateSubDevices(self.0.as_ptr(), props.as_ptr(), 0,
+            ptr::null_mut(), &mut count) };
+
+        if errcode != ffi::CL_SUCCESS {
+            return Err(status_err(errcode, "clCreateSubDevices", format!("ocl::Device::\
+                create_sub_devices(): Error partitioning the device using {:?}.", property)));
+        }
+
+        let mut ptrs: Vec<cl_device_id> = vec![ptr::null_mut(); count as usize];
+
+        let errcode = unsafe { ffi::clCreateSubDevices(self.0.as_ptr(), props.as_ptr(), count,
+            ptrs.as_mut_ptr(), ptr::null_mut()) };
+
+        if errcode != ffi::CL_SUCCESS {
+            return Err(status_err(errcode, "clCreateSubDevices", format!("ocl::Device::\
+                create_sub_devices(): Error partitioning the device using {:?}.", property)));
+        }
+
+        Ok(ptrs.into_iter()
+            .map(|ptr| SubDevice(Device(unsafe { DeviceIdCore::from_fresh_ptr(ptr) })))
+            .collect())
+    }
+
     /// Returns a list of `Device`s from a list of `DeviceIdCore`s
     pub fn list_from_core(devices: Vec<DeviceIdCore>) -> Vec<Device> {
         devices.into_iter().map(Device).collect()
@@ -336,20 +611,165 @@ impl Device {
 
     /// Returns the maximum workgroup size.
     ///
+    /// Equivalent to `::max_work_group_size`.
     ///
-    /// ### Errors
+    pub fn max_wg_size(&self) -> OclResult<usize> {
+        self.max_work_group_size()
+    }
+
+    /// Returns the maximum number of work items in a work group.
+    pub fn max_work_group_size(&self) -> OclResult<usize> {
+        device_info!(self, MaxWorkGroupSize, "max_work_group_size")
+    }
+
+    /// Returns the maximum number of work items in each dimension of a work group.
+    pub fn max_work_item_sizes(&self) -> OclResult<Vec<usize>> {
+        device_info!(self, MaxWorkItemSizes, "max_work_item_sizes")
+    }
+
+    /// Returns the number of parallel compute units.
+    pub fn max_compute_units(&self) -> OclResult<u32> {
+        device_info!(self, MaxComputeUnits, "max_compute_units")
+    }
+
+    /// Returns the maximum clock frequency, in MHz.
+    pub fn max_clock_frequency(&self) -> OclResult<u32> {
+        device_info!(self, MaxClockFrequency, "max_clock_frequency")
+    }
+
+    /// Returns the size of global memory, in bytes.
+    pub fn global_mem_size(&self) -> OclResult<u64> {
+        device_info!(self, GlobalMemSize, "global_mem_size")
+    }
+
+    /// Returns the size of local memory available to each work group, in bytes.
+    pub fn local_mem_size(&self) -> OclResult<u64> {
+        device_info!(self, LocalMemSize, "local_mem_size")
+    }
+
+    /// Returns the maximum size of a single memory object allocation, in bytes.
+    pub fn max_mem_alloc_size(&self) -> OclResult<u64> {
+        device_info!(self, MaxMemAllocSize, "max_mem_alloc_size")
+    }
+
+    /// Returns the maximum size of a constant buffer, in bytes.
+    pub fn max_constant_buffer_size(&self) -> OclResult<u64> {
+        device_info!(self, MaxConstantBufferSize, "max_constant_buffer_size")
+    }
+
+    /// Returns the alignment, in bits, of memory objects created with a host pointer.
+    pub fn mem_base_addr_align(&self) -> OclResult<u32> {
+        device_info!(self, MemBaseAddrAlign, "mem_base_addr_align")
+    }
+
+    /// Returns whether or not images are supported.
+    pub fn image_support(&self) -> OclResult<bool> {
+        device_info!(self, ImageSupport, "image_support")
+    }
+
+    /// Returns whether or not the device is available.
+    pub fn is_available(&self) -> OclResult<bool> {
+        device_info!(self, Available, "is_available")
+    }
+
+    /// Returns the type of the device.
+    pub fn device_type(&self) -> OclResult<DeviceType> {
+        device_info!(self, Type, "device_type")
+    }
+
+    /// Returns the extensions supported by the device.
+    ///
+    /// The list is queried and parsed only the first time it is requested
+    /// for a given device.
+    pub fn extensions(&self) -> OclResult<Extensions> {
+        let key = unsafe { self.as_core().as_ptr() } as usize;
+
+        if let Some(extensions) = extensions_cache().lock().expect("ocl::Device::extensions()")
+            .get(&key)
+        {
+            return Ok(extensions.clone());
+        }
+
+        let extensions = Extensions::parse(&try!(device_info!(self, Extensions, "extensions")));
+
+        extensions_cache().lock().expect("ocl::Device::extensions()")
+            .insert(key, extensions.clone());
+        Ok(extensions)
+    }
+
+    /// Returns true if the device supports images (`CL_DEVICE_IMAGE_SUPPORT`).
     ///
-    /// Returns any OpenCL error.
+    /// Equivalent to `::image_support`.
+    pub fn supports_images(&self) -> OclResult<bool> {
+        self.image_support()
+    }
+
+    /// Returns true if the device supports coarse-grained shared virtual
+    /// memory buffers (OpenCL 2.0). Always false for earlier devices.
+    pub fn supports_svm_coarse(&self) -> OclResult<bool> {
+        let mut caps: cl_bitfield = 0;
+        let errcode = unsafe { ffi::clGetDeviceInfo(self.as_core().as_ptr(),
+            DEVICE_SVM_CAPABILITIES, std::mem::size_of::<cl_bitfield>(),
+            &mut caps as *mut cl_bitfield as *mut c_void, ptr::null_mut()) };
+
+        match errcode {
+            ffi::CL_SUCCESS => Ok(caps & DEVICE_SVM_COARSE_GRAIN_BUFFER != 0),
+            ffi::CL_INVALID_VALUE => Ok(false),
+            _ => Err(status_err(errcode, "clGetDeviceInfo", "ocl::Device::supports_svm_coarse(): \
+                Error querying 'CL_DEVICE_SVM_CAPABILITIES'.".to_owned())),
+        }
+    }
+
+    /// Returns true if the device can execute native kernels, such as host
+    /// closures enqueued with `Queue::enqueue_native_kernel`
+    /// (`CL_EXEC_NATIVE_KERNEL`). Usually only CPU devices can.
+    pub fn supports_native_kernels(&self) -> OclResult<bool> {
+        let mut caps: cl_bitfield = 0;
+        let errcode = unsafe { ffi::clGetDeviceInfo(self.as_core().as_ptr(),
+            ffi::CL_DEVICE_EXECUTION_CAPABILITIES, std::mem::size_of::<cl_bitfield>(),
+            &mut caps as *mut cl_bitfield as *mut c_void, ptr::null_mut()) };
+
+        if errcode == ffi::CL_SUCCESS {
+            Ok(caps & ffi::CL_EXEC_NATIVE_KERNEL != 0)
+        } else {
+            Err(status_err(errcode, "clGetDeviceInfo", "ocl::Device::supports_native_kernels(): \
+                Error querying 'CL_DEVICE_EXECUTION_CAPABILITIES'.".to_owned()))
+        }
+    }
+
+    /// Returns true if the device supports double precision floating point
+    /// (`cl_khr_fp64`).
     ///
-    pub fn max_wg_size(&self) -> OclResult<usize> {
-        match self.info(DeviceInfo::MaxWorkGroupSize) {
-            DeviceInfoResult::MaxWorkGroupSize(s) => Ok(s),
-            DeviceInfoResult::Error(err) => Err(*err),
-            // Verify that this is unreachable and replace with `unreachable!()`:
-            _ => panic!("ocl::Device::max_wg_size: Unexpected 'DeviceInfoResult' variant."),
+    /// Determined from `CL_DEVICE_DOUBLE_FP_CONFIG`, which is non-zero for
+    /// supporting devices, or from the device extensions on devices which
+    /// predate that query.
+    pub fn supports_fp64(&self) -> OclResult<bool> {
+        let mut config: cl_device_fp_config = 0;
+        let errcode = unsafe { ffi::clGetDeviceInfo(self.as_core().as_ptr(),
+            ffi::CL_DEVICE_DOUBLE_FP_CONFIG, std::mem::size_of::<cl_device_fp_config>(),
+            &mut config as *mut cl_device_fp_config as *mut c_void, ptr::null_mut()) };
+
+        match errcode {
+            ffi::CL_SUCCESS => Ok(config != 0),
+            ffi::CL_INVALID_VALUE => {
+                let extensions = try!(self.extensions());
+                Ok(extensions.contains("cl_khr_fp64") || extensions.contains("cl_amd_fp64"))
+            },
+            _ => Err(status_err(errcode, "clGetDeviceInfo", "ocl::Device::supports_fp64(): \
+                Error querying 'CL_DEVICE_DOUBLE_FP_CONFIG'.".to_owned())),
         }
     }
 
+    /// Returns the maximum size, in bytes, of the internal buffer holding the
+    /// output of kernel `printf` calls.
+    ///
+    /// On devices supporting `cl_arm_printf` the size can be configured with
+    /// `ContextBuilder::arm_printf`.
+    ///
+    pub fn printf_buffer_size(&self) -> OclResult<usize> {
+        device_info!(self, PrintfBufferSize, "printf_buffer_size")
+    }
+
     /// Returns info about the device.
     pub fn info(&self, info_kind: DeviceInfo) -> DeviceInfoResult {
         core::get_device_info(&self.0, info_kind)
@@ -487,3 +907,47 @@ impl DerefMut for Device {
         &mut self.0
     }
 }
+
+
+/// A sub-device created by `Device::create_sub_devices`, released
+/// (`clReleaseDevice`) when dropped.
+///
+/// Dereferences to the `Device` it wraps. Contexts, queues, and copies of
+/// that `Device` do not keep the sub-device alive: keep the `SubDevice`
+/// until they are no longer used.
+///
+#[derive(Debug)]
+pub struct SubDevice(Device);
+
+impl SubDevice {
+    /// Returns a copy of the wrapped device, valid for as long as this
+    /// sub-device is.
+    pub fn device(&self) -> Device {
+        self.0
+    }
+}
+
+impl Deref for SubDevice {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.0
+    }
+}
+
+impl AsRef<Device> for SubDevice {
+    fn as_ref(&self) -> &Device {
+        &self.0
+    }
+}
+
+impl Drop for SubDevice {
+    fn drop(&mut self) {
+        let errcode = unsafe { ffi::clReleaseDevice(self.0.as_ptr()) };
+
+        if errcode != ffi::CL_SUCCESS {
+            error!("ocl::SubDevice::drop(): {}", status_err(errcode, "clReleaseDevice",
+                "Error releasing the sub-device.".to_owned()));
+        }
+    }
+}
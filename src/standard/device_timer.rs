@@ -0,0 +1,154 @@
+//! Host and device timer synchronization (OpenCL 2.1).
+//!
+//! Requires the `opencl_version_2_1` feature.
+
+use std::time::{Duration, Instant};
+use core::error::{Error as OclError, Result as OclResult};
+use core::OpenclVersion;
+use standard::Device;
+use standard::error_hints::status_err;
+use ffi::{self, cl_int, cl_ulong, cl_device_id};
+
+// Not yet declared by `cl-sys`. Only referenced when the
+// `opencl_version_2_1` feature is enabled so that linking against older
+// loaders still succeeds otherwise.
+extern "system" {
+    fn clGetDeviceAndHostTimer(device: cl_device_id, device_timestamp: *mut cl_ulong,
+        host_timestamp: *mut cl_ulong) -> cl_int;
+    fn clGetHostTimer(device: cl_device_id, host_timestamp: *mut cl_ulong) -> cl_int;
+}
+
+
+/// A device timestamp, the host timestamp, and the `Instant` recorded
+/// (as nearly as possible) at the same moment.
+///
+/// Used to convert event profiling timestamps (`ProfilingInfo::Start`,
+/// `::End`, etc.), which are measured by the device timer, into `Instant`s
+/// comparable with host side timings.
+///
+/// Timers drift relative to each other; create a new `TimerSync` with
+/// `Device::timer_sync` periodically when correlating over long periods.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct TimerSync {
+    device_ns: u64,
+    host_ns: u64,
+    instant: Instant,
+}
+
+impl TimerSync {
+    /// Returns the device timestamp, in nanoseconds.
+    pub fn device_ns(&self) -> u64 {
+        self.device_ns
+    }
+
+    /// Returns the host timestamp, in nanoseconds.
+    pub fn host_ns(&self) -> u64 {
+        self.host_ns
+    }
+
+    /// Returns the `Instant` corresponding to the timestamps.
+    pub fn instant(&self) -> Instant {
+        self.instant
+    }
+
+    /// Returns the `Instant` corresponding to the device timestamp
+    /// `device_ns`, such as an event profiling timestamp, or `None` if it
+    /// cannot be represented as an `Instant` (for example a timestamp from
+    /// before the host's monotonic clock started).
+    pub fn to_instant(&self, device_ns: u64) -> Option<Instant> {
+        if device_ns >= self.device_ns {
+            self.instant.checked_add(nanos_duration(device_ns - self.device_ns))
+        } else {
+            self.instant.checked_sub(nanos_duration(self.device_ns - device_ns))
+        }
+    }
+
+    /// Returns the host timestamp corresponding to the device timestamp
+    /// `device_ns`.
+    pub fn to_host_ns(&self, device_ns: u64) -> u64 {
+        (self.host_ns as i64 + (device_ns as i64 - self.device_ns as i64)) as u64
+    }
+}
+
+fn nanos_duration(ns: u64) -> Duration {
+    Duration::new(ns / 1_000_000_000, (ns % 1_000_000_000) as u32)
+}
+
+
+impl Device {
+    fn check_timer_support(&self, caller: &str) -> OclResult<()> {
+        let version = try!(self.version());
+        if version < OpenclVersion::new(2, 1) {
+            return OclError::err(format!("ocl::Device::{}(): Host and device timers require \
+                OpenCL 2.1 or later (device version: {:?}).", caller, version));
+        }
+        Ok(())
+    }
+
+    /// Returns the current value of the device timer and the host timer,
+    /// in nanoseconds, sampled at the same moment
+    /// (`clGetDeviceAndHostTimer`).
+    ///
+    /// The device timer is the one used for event profiling timestamps.
+    ///
+    /// ## Errors
+    ///
+    /// The device must support OpenCL 2.1 or later.
+    ///
+    pub fn device_and_host_timer(&self) -> OclResult<(u64, u64)> {
+        try!(self.check_timer_support("device_and_host_timer"));
+        let mut device_ts: cl_ulong = 0;
+        let mut host_ts: cl_ulong = 0;
+
+        let errcode = unsafe { clGetDeviceAndHostTimer(self.as_core().as_ptr(), &mut device_ts,
+            &mut host_ts) };
+
+        if errcode != ffi::CL_SUCCESS {
+            return Err(status_err(errcode, "clGetDeviceAndHostTimer",
+                "ocl::Device::device_and_host_timer(): Error reading the timers.".to_owned()));
+        }
+        Ok((device_ts, host_ts))
+    }
+
+    /// Returns the current value of the host timer, in nanoseconds, as seen
+    /// by this device (`clGetHostTimer`).
+    ///
+    /// Cheaper than `::device_and_host_timer`.
+    ///
+    /// ## Errors
+    ///
+    /// The device must support OpenCL 2.1 or later.
+    ///
+    pub fn host_timer(&self) -> OclResult<u64> {
+        try!(self.check_timer_support("host_timer"));
+        let mut host_ts: cl_ulong = 0;
+
+        let errcode = unsafe { clGetHostTimer(self.as_core().as_ptr(), &mut host_ts) };
+
+        if errcode != ffi::CL_SUCCESS {
+            return Err(status_err(errcode, "clGetHostTimer",
+                "ocl::Device::host_timer(): Error reading the host timer.".to_owned()));
+        }
+        Ok(host_ts)
+    }
+
+    /// Samples the device and host timers along with `Instant::now`,
+    /// returning a `TimerSync` used to convert device timestamps into
+    /// `Instant`s.
+    ///
+    /// The `Instant` is taken halfway between two samples bracketing the
+    /// timer call to reduce the error introduced by the call itself.
+    ///
+    pub fn timer_sync(&self) -> OclResult<TimerSync> {
+        let before = Instant::now();
+        let (device_ns, host_ns) = try!(self.device_and_host_timer());
+        let after = Instant::now();
+
+        Ok(TimerSync {
+            device_ns: device_ns,
+            host_ns: host_ns,
+            instant: before + (after - before) / 2,
+        })
+    }
+}
@@ -0,0 +1,242 @@
+//! Common causes of `OpenCL` status codes.
+
+use std;
+use num::FromPrimitive;
+use ffi::cl_int;
+use core::Status;
+use core::error::Error as OclError;
+
+/// Common real-world causes for each status code, indexed by code.
+static STATUS_HINTS: &'static [(i32, &'static [&'static str])] = &[
+    (-1, &[
+        "No device of the requested type exists on the selected platform.",
+        "The vendor's ICD is not installed or not registered with the ICD loader.",
+    ]),
+    (-2, &[
+        "The device is in use exclusively by another process or has been disabled.",
+    ]),
+    (-3, &[
+        "The platform does not ship an online compiler (some embedded platforms); \
+            load a pre-built binary instead.",
+    ]),
+    (-4, &[
+        "A single allocation exceeds the device's `MaxMemAllocSize` (often a quarter \
+            of global memory).",
+        "Device memory is exhausted. Buffers are often only allocated lazily on first \
+            use, so the error may surface at an enqueue rather than at creation.",
+    ]),
+    (-5, &[
+        "A previously enqueued kernel accessed memory out of bounds. The error is \
+            often only reported by the next blocking call.",
+        "The local work size is too large for the registers or local memory used by \
+            the kernel.",
+        "A kernel ran long enough to trigger the display driver watchdog (see \
+            `SplitLaunch`).",
+    ]),
+    (-6, &[
+        "The host is out of memory, often because of leaked events or mapped regions \
+            which were never unmapped.",
+    ]),
+    (-7, &[
+        "The queue was not created with `QUEUE_PROFILING_ENABLE` (see \
+            `Queue::with_properties`).",
+        "The command has not yet completed; wait on its event first.",
+    ]),
+    (-8, &[
+        "The source and destination regions of a copy within the same buffer overlap.",
+    ]),
+    (-10, &[
+        "The image channel order and data type combination is not supported by the \
+            device; query the supported formats first.",
+    ]),
+    (-11, &[
+        "The program source failed to compile; the build log for each device is \
+            included in the error message.",
+        "A header or definition expected by the source was not passed as a build \
+            option.",
+    ]),
+    (-12, &[
+        "The region could not be mapped, often because it is already mapped or \
+            because host memory is exhausted.",
+    ]),
+    (-13, &[
+        "A sub-buffer origin is not a multiple of the device's `MemBaseAddrAlign` \
+            (given in bits).",
+    ]),
+    (-14, &[
+        "An event in the wait list failed. Check the error of the earlier command.",
+    ]),
+    (-30, &[
+        "An argument is out of range or a required pointer is null, for example a \
+            zero-sized buffer or an invalid combination of flags.",
+    ]),
+    (-33, &[
+        "The device does not belong to the context or platform being used.",
+    ]),
+    (-34, &[
+        "Objects created in different contexts are being used together.",
+    ]),
+    (-36, &[
+        "The device was reset or lost, for example after a driver watchdog timeout \
+            or a kernel crash; all objects on it must be recreated.",
+        "The queue has already been released.",
+    ]),
+    (-38, &[
+        "A memory object has already been released or belongs to another context.",
+        "A kernel argument expected to be a buffer was set to something else.",
+    ]),
+    (-40, &[
+        "Image dimensions exceed the device's maximum image size.",
+    ]),
+    (-43, &[
+        "A build option is misspelled or unsupported by the device's compiler \
+            version (e.g. `-cl-std=CL2.0` on a 1.2 device).",
+    ]),
+    (-45, &[
+        "The program has not been built, or has not been built for the queue's \
+            device.",
+    ]),
+    (-46, &[
+        "No `__kernel` function with that name exists in the program. Check for \
+            typos and for kernels removed by the preprocessor.",
+    ]),
+    (-48, &[
+        "The kernel has already been released.",
+    ]),
+    (-49, &[
+        "The argument index is greater than or equal to the kernel's argument count.",
+    ]),
+    (-50, &[
+        "A buffer or image argument is null or belongs to another context.",
+        "A `__local` argument was given a value instead of a size.",
+    ]),
+    (-51, &[
+        "The size of an argument does not match its declared type, often a \
+            `float`/`double` or a scalar/vector mix-up.",
+    ]),
+    (-52, &[
+        "Not every kernel argument has been set before enqueuing.",
+    ]),
+    (-53, &[
+        "The work dimensions are not between 1 and the device's \
+            `MaxWorkItemDimensions`.",
+    ]),
+    (-54, &[
+        "The global work size is not divisible by the local work size (required \
+            before `OpenCL` 2.0).",
+        "The local work size exceeds the kernel's maximum work group size on the \
+            device (see `Kernel::wg_size`).",
+        "The local work size does not match the kernel's `reqd_work_group_size` \
+            attribute.",
+    ]),
+    (-55, &[
+        "A local work size dimension exceeds the device's `MaxWorkItemSizes`.",
+    ]),
+    (-56, &[
+        "A global work offset was passed to an `OpenCL` 1.0 device, or the offset \
+            plus the global size overflows.",
+    ]),
+    (-57, &[
+        "The event wait list contains an invalid or released event, or an empty \
+            event which was never enqueued.",
+    ]),
+    (-58, &[
+        "The event is empty (never used as a destination) or has been released.",
+    ]),
+    (-59, &[
+        "The operation is invalid in the current state, for example reading a \
+            buffer created with a host access flag forbidding it, or releasing an \
+            unacquired GL object.",
+    ]),
+    (-61, &[
+        "The buffer size is zero or exceeds the device's `MaxMemAllocSize`.",
+    ]),
+    (-63, &[
+        "A global work size dimension is zero or exceeds the range supported by \
+            the device.",
+    ]),
+    (-1001, &[
+        "No `OpenCL` platforms were found; the ICD loader could not find any vendor \
+            driver.",
+    ]),
+];
+
+/// Returns the most common real-world causes of a status code.
+///
+/// Returns an empty slice for status codes without known hints.
+pub fn status_hints(status: Status) -> &'static [&'static str] {
+    let code = status as i32;
+    STATUS_HINTS.iter()
+        .find(|&&(c, _)| c == code)
+        .map(|&(_, hints)| hints)
+        .unwrap_or(&[])
+}
+
+/// Returns an `Error::Status` for the code `errcode` returned by the
+/// `OpenCL` function `fn_name` when called directly by this library,
+/// allowing the status to be matched with `Error::status` as with errors
+/// from `core` functions. `desc` describes what was being attempted.
+///
+/// Codes which are not a known `Status` produce a plain error.
+pub fn status_err(errcode: cl_int, fn_name: &'static str, desc: String) -> OclError {
+    match Status::from_i32(errcode) {
+        Some(status) => OclError::Status {
+            status_string: format!("{:?}", status),
+            status: status,
+            fn_name: fn_name,
+            fn_info: String::new(),
+            desc: desc,
+        },
+        None => OclError::new(format!("{} ({} returned error code: {}).", desc, fn_name,
+            errcode)),
+    }
+}
+
+
+/// Wraps an error to display the common causes of its status code.
+///
+/// Formats exactly as the error itself unless the alternate flag is used
+/// (`{:#}`), in which case any known causes are listed after the message.
+///
+/// ## Example
+///
+/// ```text
+/// if let Err(err) = kernel.enq() {
+///     panic!("{:#}", ErrorHints(&err));
+/// }
+/// ```
+///
+pub struct ErrorHints<'e>(pub &'e OclError);
+
+impl<'e> ErrorHints<'e> {
+    /// Returns the common causes of the wrapped error's status code, if any.
+    pub fn hints(&self) -> &'static [&'static str] {
+        match self.0.status() {
+            Some(status) => status_hints(status),
+            None => &[],
+        }
+    }
+}
+
+impl<'e> std::fmt::Display for ErrorHints<'e> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        try!(write!(f, "{}", self.0));
+
+        if f.alternate() {
+            let hints = self.hints();
+            if !hints.is_empty() {
+                try!(write!(f, "\n\nCommon causes:"));
+                for hint in hints {
+                    try!(write!(f, "\n    * {}", hint));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'e> std::fmt::Debug for ErrorHints<'e> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:#}", self)
+    }
+}
@@ -3,11 +3,118 @@
 use std;
 use std::ops::{Deref, DerefMut};
 use std::convert::Into;
+use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex, Condvar};
+use std::panic::{self, AssertUnwindSafe};
 use libc::c_void;
-use ffi;
+use log::{log, error};
+use ffi::{self, cl_event, cl_int};
 use core::error::{Error as OclError, Result as OclResult};
 use core::{self, Event as EventCore, EventInfo, EventInfoResult, ProfilingInfo, ProfilingInfoResult,
     ClEventPtrNew, ClWaitList, EventList as EventListCore, CommandExecutionStatus, EventCallbackFn};
+use standard::error_hints::status_err;
+use standard::queue::annotate_with_timeline;
+
+/// Receives an event callback from the driver, calling and dropping the
+/// boxed closure passed as `user_data`.
+extern fn _event_closure_callback<F>(_: cl_event, status: cl_int, user_data: *mut c_void)
+        where F: FnOnce(Event, i32) + Send + 'static
+{
+    let (event, callback) = *unsafe { Box::from_raw(user_data as *mut (Event, F)) };
+
+    // Unwinding into the driver's thread is undefined behavior:
+    if panic::catch_unwind(AssertUnwindSafe(move || callback(event, status))).is_err() {
+        error!("ocl::Event::set_callback: A callback closure panicked.");
+    }
+}
+
+/// Stores a new reference to the non-empty `event` in `enew`.
+///
+/// Used when a command is enqueued with an internal event which must also be
+/// returned to the caller.
+pub fn copy_to_new(event: &Event, enew: &mut ClEventPtrNew) -> OclResult<()> {
+    let event_core = match event.0 {
+        // Cloning retains the event:
+        Some(ref core) => core.clone(),
+        None => return Err(event.err_empty()),
+    };
+
+    unsafe { *try!(enew.ptr_mut_ptr_new()) = *event_core.as_ptr_ptr(); }
+    std::mem::forget(event_core);
+    Ok(())
+}
+
+/// Checks that each event in `ewait` is a valid, live event which has not
+/// terminated abnormally.
+///
+/// Used by unchecked commands to validate their wait lists in debug builds.
+pub fn check_wait_list(ewait: &ClWaitList, caller: &str) -> OclResult<()> {
+    let count = ewait.count() as usize;
+    if count == 0 { return Ok(()); }
+
+    let ptrs = unsafe { std::slice::from_raw_parts(ewait.as_ptr_ptr(), count) };
+
+    for (idx, &ptr) in ptrs.iter().enumerate() {
+        if ptr.is_null() {
+            return OclError::err(format!("{}: Wait list event {} is null.", caller, idx));
+        }
+
+        let mut status: cl_int = 0;
+        let errcode = unsafe { ffi::clGetEventInfo(ptr, ffi::CL_EVENT_COMMAND_EXECUTION_STATUS,
+            std::mem::size_of::<cl_int>(), &mut status as *mut cl_int as *mut c_void,
+            std::ptr::null_mut()) };
+
+        if errcode != ffi::CL_SUCCESS {
+            return Err(status_err(errcode, "clGetEventInfo", format!("{}: Wait list event {} \
+                is not a valid event.", caller, idx)));
+        }
+        if status < 0 {
+            return OclError::err(format!("{}: Wait list event {} terminated abnormally \
+                with status: {}.", caller, idx, status));
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Flushes the queue of each command event in `ewait`, other than
+/// `except`, so that the commands are submitted to their devices.
+///
+/// A command waiting on an event from another queue may otherwise wait
+/// indefinitely if that queue is never flushed. User events are skipped.
+pub fn flush_event_queues(ewait: &ClWaitList, except: ffi::cl_command_queue, caller: &str)
+        -> OclResult<()>
+{
+    let count = ewait.count() as usize;
+    if count == 0 { return Ok(()); }
+
+    let ptrs = unsafe { std::slice::from_raw_parts(ewait.as_ptr_ptr(), count) };
+    let mut flushed: Vec<ffi::cl_command_queue> = vec![except];
+
+    for (idx, &ptr) in ptrs.iter().enumerate() {
+        let mut queue: ffi::cl_command_queue = std::ptr::null_mut();
+        let errcode = unsafe { ffi::clGetEventInfo(ptr, ffi::CL_EVENT_COMMAND_QUEUE,
+            std::mem::size_of::<ffi::cl_command_queue>(),
+            &mut queue as *mut ffi::cl_command_queue as *mut c_void, std::ptr::null_mut()) };
+
+        if errcode != ffi::CL_SUCCESS {
+            return Err(status_err(errcode, "clGetEventInfo", format!("{}: Wait list event {} \
+                is not a valid event.", caller, idx)));
+        }
+        if queue.is_null() || flushed.contains(&queue) { continue; }
+
+        let errcode = unsafe { ffi::clFlush(queue) };
+
+        if errcode != ffi::CL_SUCCESS {
+            return Err(status_err(errcode, "clFlush", format!("{}: Error flushing the queue \
+                of wait list event {}.", caller, idx)));
+        }
+        flushed.push(queue);
+    }
+
+    Ok(())
+}
 
 /// An event representing a command or user created event.
 #[derive(Clone, Debug)]
@@ -31,11 +138,58 @@ impl Event {
 
     /// Waits for all events in list to complete before returning.
     ///
-    /// Similar in function to `Queue::finish()`.
+    /// Similar in function to `Queue::finish()`. Errors are annotated with
+    /// the command timeline of the queue the event was enqueued on, if any.
     ///
     pub fn wait(&self) -> OclResult<()> {
         assert!(!self.is_empty(), "ocl::Event::wait(): {}", self.err_empty());
-        core::wait_for_event(self.0.as_ref().unwrap())
+        let event_core = self.0.as_ref().unwrap();
+
+        core::wait_for_event(event_core).map_err(|err| {
+            let mut queue: ffi::cl_command_queue = std::ptr::null_mut();
+            let errcode = unsafe { ffi::clGetEventInfo(*event_core.as_ptr_ref(),
+                ffi::CL_EVENT_COMMAND_QUEUE, std::mem::size_of::<ffi::cl_command_queue>(),
+                &mut queue as *mut ffi::cl_command_queue as *mut c_void, std::ptr::null_mut()) };
+
+            // User events have no queue:
+            if errcode != ffi::CL_SUCCESS || queue.is_null() { return err; }
+            annotate_with_timeline(queue, err)
+        })
+    }
+
+    /// Sets a closure, `callback`, to be called once when the command
+    /// associated with this event reaches the execution status `status`.
+    ///
+    /// The closure receives a copy of this event and the status reached. The
+    /// status is negative if the command terminated abnormally, in which
+    /// case it is an error code. The closure is owned by this library until
+    /// it has been called.
+    ///
+    /// The closure is called from a thread owned by the `OpenCL` driver and
+    /// must not call blocking `OpenCL` functions such as `::wait` or
+    /// `Queue::finish`.
+    ///
+    /// `OpenCL` 1.1 platforms only support `CommandExecutionStatus::Complete`.
+    ///
+    pub fn set_callback<F>(&self, status: CommandExecutionStatus, callback: F) -> OclResult<()>
+            where F: FnOnce(Event, i32) + Send + 'static
+    {
+        let event_core = match self.0 {
+            Some(ref core) => core,
+            None => return Err(self.err_empty()),
+        };
+
+        let user_data = Box::into_raw(Box::new((self.clone(), callback)));
+
+        let result = unsafe { core::set_event_callback(event_core, status,
+            Some(_event_closure_callback::<F>), user_data as *mut c_void) };
+
+        if result.is_err() {
+            // The callback will never be called:
+            unsafe { drop(Box::from_raw(user_data)); }
+        }
+
+        result
     }
 
     /// Returns info about the event.
@@ -66,6 +220,62 @@ impl Event {
         }
     }
 
+    /// Returns the device time counter, in nanoseconds, at which the command
+    /// associated with this event reached the point described by `info_kind`.
+    ///
+    /// The queue must have been created with profiling enabled and the
+    /// command must have completed.
+    pub fn profiling_nanos(&self, info_kind: ProfilingInfo) -> OclResult<u64> {
+        match self.profiling_info(info_kind) {
+            ProfilingInfoResult::Queued(ns) |
+            ProfilingInfoResult::Submit(ns) |
+            ProfilingInfoResult::Start(ns) |
+            ProfilingInfoResult::End(ns) => Ok(ns),
+            ProfilingInfoResult::Error(err) => Err(*err),
+        }
+    }
+
+    /// Returns the time elapsed between two profiling points.
+    fn profiling_span(&self, from: ProfilingInfo, to: ProfilingInfo) -> OclResult<Duration> {
+        let from = try!(self.profiling_nanos(from));
+        let to = try!(self.profiling_nanos(to));
+        let ns = to.saturating_sub(from);
+        Ok(Duration::new(ns / 1_000_000_000, (ns % 1_000_000_000) as u32))
+    }
+
+    /// Returns the time the command spent executing on the device.
+    ///
+    /// The queue must have been created with profiling enabled and the
+    /// command must have completed.
+    pub fn duration(&self) -> OclResult<Duration> {
+        self.profiling_span(ProfilingInfo::Start, ProfilingInfo::End)
+    }
+
+    /// Returns the time between the command being enqueued by the host and
+    /// submitted to the device.
+    ///
+    /// The queue must have been created with profiling enabled.
+    pub fn queued_to_submit(&self) -> OclResult<Duration> {
+        self.profiling_span(ProfilingInfo::Queued, ProfilingInfo::Submit)
+    }
+
+    /// Returns the time between the command being submitted to the device
+    /// and beginning execution.
+    ///
+    /// The queue must have been created with profiling enabled.
+    pub fn submit_to_start(&self) -> OclResult<Duration> {
+        self.profiling_span(ProfilingInfo::Submit, ProfilingInfo::Start)
+    }
+
+    /// Returns the total time between the command being enqueued by the host
+    /// and completing execution.
+    ///
+    /// The queue must have been created with profiling enabled and the
+    /// command must have completed.
+    pub fn queued_to_end(&self) -> OclResult<Duration> {
+        self.profiling_span(ProfilingInfo::Queued, ProfilingInfo::End)
+    }
+
     /// Returns a reference to the core pointer wrapper, usable by functions in
     /// the `core` module.
     pub fn core_as_ref(&self) -> Option<&EventCore> {
@@ -166,19 +376,111 @@ unsafe impl ClWaitList for Event {
 
 
 
+/// Decrements a tracker's pending count when dropped, whether or not the
+/// callback holding it was ever called.
+struct PendingGuard(Arc<(Mutex<usize>, Condvar)>);
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        let &(ref pending, ref cvar) = &*self.0;
+        let mut pending = pending.lock().expect("ocl::CallbackTracker");
+        *pending -= 1;
+        if *pending == 0 { cvar.notify_all(); }
+    }
+}
+
+
+/// Tracks event callbacks until they have actually finished executing.
+///
+/// An event completing does not mean its callbacks have run: they are
+/// called afterwards, on a thread owned by the driver. Callbacks set
+/// through a tracker can be waited on with `::wait`, preventing, for
+/// example, a process from exiting or tearing down shared state while
+/// callbacks are still running.
+///
+/// A callback is considered finished once it has returned (or panicked) or
+/// once the driver has discarded it. Clones share the same set of callbacks.
+///
+#[derive(Clone, Debug)]
+pub struct CallbackTracker {
+    pending: Arc<(Mutex<usize>, Condvar)>,
+}
+
+impl CallbackTracker {
+    /// Returns a new tracker with no pending callbacks.
+    pub fn new() -> CallbackTracker {
+        CallbackTracker { pending: Arc::new((Mutex::new(0), Condvar::new())) }
+    }
+
+    /// Sets a tracked callback on `event`. See `Event::set_callback`.
+    pub fn set_callback<F>(&self, event: &Event, status: CommandExecutionStatus, callback: F)
+            -> OclResult<()>
+            where F: FnOnce(Event, i32) + Send + 'static
+    {
+        *self.pending.0.lock().expect("ocl::CallbackTracker::set_callback()") += 1;
+        let guard = PendingGuard(self.pending.clone());
+
+        // The guard is dropped along with the closure, even if unset:
+        event.set_callback(status, move |event, status| {
+            let _guard = guard;
+            callback(event, status);
+        })
+    }
+
+    /// Returns the number of callbacks which have not yet finished.
+    pub fn pending(&self) -> usize {
+        *self.pending.0.lock().expect("ocl::CallbackTracker::pending()")
+    }
+
+    /// Blocks until all callbacks set so far have finished.
+    ///
+    /// Must not be called from within a tracked callback.
+    pub fn wait(&self) {
+        let &(ref pending, ref cvar) = &*self.pending;
+        let mut pending = pending.lock().expect("ocl::CallbackTracker::wait()");
+
+        while *pending > 0 {
+            pending = cvar.wait(pending).expect("ocl::CallbackTracker::wait()");
+        }
+    }
+
+    /// Blocks until all callbacks set so far have finished or `timeout` has
+    /// elapsed. Returns `true` if all callbacks have finished.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let &(ref pending, ref cvar) = &*self.pending;
+        let deadline = Instant::now() + timeout;
+        let mut pending = pending.lock().expect("ocl::CallbackTracker::wait_timeout()");
+
+        while *pending > 0 {
+            let now = Instant::now();
+            if now >= deadline { return false; }
+            pending = cvar.wait_timeout(pending, deadline - now)
+                .expect("ocl::CallbackTracker::wait_timeout()").0;
+        }
+        true
+    }
+}
+
+
 /// A list of events for coordinating enqueued commands.
 ///
 /// Events contain status information about the command that
 /// created them. Used to coordinate the activity of multiple commands with
 /// more fine-grained control than the queue alone.
 ///
-/// For access to individual events use `get_clone` and `last_clone` then
-/// either store or discard the result.
+/// For access to individual events use `get`, `first`, `last_clone`, or
+/// iterate over the list. Each returns a new copy of the event (with its
+/// reference count incremented) which may be stored or discarded.
+///
+/// `EventList` does not implement `Index` because events are stored as raw
+/// `cl_event` pointers contiguous in memory (as required by wait lists)
+/// rather than as `Event`s which could be referenced.
 ///
-// [FIXME] TODO: impl Index.
 #[derive(Debug, Clone)]
 pub struct EventList {
     event_list_core: EventListCore,
+    prune_len: Option<usize>,
+    next_prune: usize,
 }
 
 impl EventList {
@@ -186,9 +488,39 @@ impl EventList {
     pub fn new() -> EventList {
         EventList {
             event_list_core: EventListCore::new(),
+            prune_len: None,
+            next_prune: 0,
         }
     }
 
+    /// Returns a new, empty, `EventList` which drops its completed events
+    /// whenever a new event is added to a list holding `prune_len` or more.
+    ///
+    /// Use this for lists which collect events in long running non-blocking
+    /// loops and would otherwise grow (along with the reference counts of
+    /// their events) without bound. See `::set_prune_len`.
+    pub fn with_pruning(prune_len: usize) -> EventList {
+        let mut list = EventList::new();
+        list.set_prune_len(Some(prune_len));
+        list
+    }
+
+    /// Sets the length at which completed events are dropped when a new
+    /// event is added, or `None` (the default) to never prune automatically.
+    ///
+    /// If too few events have completed the list keeps growing and pruning
+    /// is next attempted at twice its length, keeping the cost of checking
+    /// each event's status proportional to the number of events added.
+    pub fn set_prune_len(&mut self, prune_len: Option<usize>) {
+        self.prune_len = prune_len;
+        self.next_prune = prune_len.unwrap_or(0);
+    }
+
+    /// Returns the length at which completed events are dropped, if set.
+    pub fn prune_len(&self) -> Option<usize> {
+        self.prune_len
+    }
+
     // pub fn push(&mut self, event: Event) {
     //     self.events.push(event);
     // }
@@ -213,6 +545,22 @@ impl EventList {
         }
     }
 
+    /// Returns a new copy of an event by index or `None` if `index` is out
+    /// of range.
+    pub fn get(&self, index: usize) -> Option<Event> {
+        self.get_clone(index)
+    }
+
+    /// Returns a copy of the first event in the list.
+    pub fn first(&self) -> Option<Event> {
+        self.get_clone(0)
+    }
+
+    /// Returns an iterator over copies of the events in the list.
+    pub fn iter(&self) -> EventListIter {
+        EventListIter { list: self, idx: 0 }
+    }
+
     /// Returns a copy of the last event in the list.
     pub fn last_clone(&self) -> Option<Event> {
         match self.event_list_core.last_clone() {
@@ -249,9 +597,10 @@ impl EventList {
                     callback_receiver, user_data as *mut _ as *mut c_void)
     }
 
-    // pub fn clear_completed(&mut self) -> OclResult<()> {
-    //     self.event_list_core.clear_completed()
-    // }
+    /// Drops every event which has completed (`CL_COMPLETE`) from the list.
+    pub fn clear_completed(&mut self) -> OclResult<()> {
+        self.event_list_core.clear_completed()
+    }
 
     /// Returns the number of events in the list.
     pub fn len(&self) -> usize {
@@ -283,6 +632,34 @@ impl EventList {
     }
 }
 
+impl std::fmt::Display for EventList {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        try!(writeln!(f, "EventList ({} events):", self.len()));
+        for (idx, event) in self.iter().enumerate() {
+            try!(writeln!(f, "[{}]: {}", idx, event));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a EventList {
+    type Item = Event;
+    type IntoIter = EventListIter<'a>;
+
+    fn into_iter(self) -> EventListIter<'a> {
+        self.iter()
+    }
+}
+
+impl IntoIterator for EventList {
+    type Item = Event;
+    type IntoIter = EventListIntoIter;
+
+    fn into_iter(self) -> EventListIntoIter {
+        EventListIntoIter { list: self, idx: 0 }
+    }
+}
+
 impl AsRef<EventListCore> for EventList {
     fn as_ref(&self) -> &EventListCore {
         &self.event_list_core
@@ -305,6 +682,12 @@ impl DerefMut for EventList {
 
 unsafe impl ClEventPtrNew for EventList {
     fn ptr_mut_ptr_new(&mut self) -> OclResult<*mut ffi::cl_event> {
+        if let Some(prune_len) = self.prune_len {
+            if self.event_list_core.len() >= self.next_prune {
+                try!(self.event_list_core.clear_completed());
+                self.next_prune = std::cmp::max(prune_len, self.event_list_core.len() * 2);
+            }
+        }
         Ok(self.event_list_core.allot())
     }
 }
@@ -318,3 +701,53 @@ unsafe impl ClWaitList for EventList {
         self.event_list_core.count()
     }
 }
+
+
+/// An iterator over copies of the events in an `EventList`.
+///
+/// Created by `EventList::iter`.
+#[derive(Debug, Clone)]
+pub struct EventListIter<'a> {
+    list: &'a EventList,
+    idx: usize,
+}
+
+impl<'a> Iterator for EventListIter<'a> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        let event = self.list.get_clone(self.idx);
+        if event.is_some() { self.idx += 1; }
+        event
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.list.len().saturating_sub(self.idx);
+        (0, Some(remaining))
+    }
+}
+
+
+/// An owning iterator over the events in an `EventList`.
+///
+/// Created by `EventList::into_iter`.
+#[derive(Debug)]
+pub struct EventListIntoIter {
+    list: EventList,
+    idx: usize,
+}
+
+impl Iterator for EventListIntoIter {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        let event = self.list.get_clone(self.idx);
+        if event.is_some() { self.idx += 1; }
+        event
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.list.len().saturating_sub(self.idx);
+        (0, Some(remaining))
+    }
+}
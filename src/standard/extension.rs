@@ -0,0 +1,75 @@
+//! Loading of extension functions.
+
+use std::mem;
+use std::ffi::CString;
+use libc::{c_void, size_t};
+use core::error::{Error as OclError, Result as OclResult};
+use standard::Platform;
+use ffi::{self, cl_int, cl_uint, cl_context, cl_program, cl_kernel, cl_device_id};
+
+
+/// `clCreateProgramWithILKHR` (`cl_khr_il_program`).
+pub type CreateProgramWithIlKhrFn = unsafe extern "system" fn(context: cl_context,
+    il: *const c_void, length: size_t, errcode_ret: *mut cl_int) -> cl_program;
+
+/// `clTerminateContextKHR` (`cl_khr_terminate_context`).
+pub type TerminateContextKhrFn = unsafe extern "system" fn(context: cl_context) -> cl_int;
+
+/// `clGetKernelSubGroupInfoKHR` (`cl_khr_subgroups`).
+pub type GetKernelSubGroupInfoKhrFn = unsafe extern "system" fn(kernel: cl_kernel,
+    device: cl_device_id, param_name: cl_uint, input_value_size: size_t,
+    input_value: *const c_void, param_value_size: size_t, param_value: *mut c_void,
+    param_value_size_ret: *mut size_t) -> cl_int;
+
+
+impl Platform {
+    /// Returns the extension function named `name`, as the function pointer
+    /// type `F` (`clGetExtensionFunctionAddressForPlatform`).
+    ///
+    /// Extension functions are not exported by the `OpenCL` library and must
+    /// be loaded this way. Prefer the pre-typed loaders, such as
+    /// `::il_program_fn`, where available.
+    ///
+    /// ## Errors
+    ///
+    /// `F` must be pointer-sized and the function must be available on this
+    /// platform.
+    ///
+    /// ## Safety
+    ///
+    /// `F` must be an `unsafe extern "system" fn` type matching the signature of
+    /// the function. Calling a function through a mismatched type is
+    /// undefined behavior.
+    ///
+    pub unsafe fn extension_fn<F: Copy>(&self, name: &str) -> OclResult<F> {
+        if mem::size_of::<F>() != mem::size_of::<*mut c_void>() {
+            return OclError::err(format!("ocl::Platform::extension_fn(): The function type for \
+                '{}' is not a function pointer.", name));
+        }
+
+        let c_name = try!(CString::new(name));
+        let ptr = ffi::clGetExtensionFunctionAddressForPlatform(self.as_core().as_ptr(),
+            c_name.as_ptr());
+
+        if ptr.is_null() {
+            return OclError::err(format!("ocl::Platform::extension_fn(): The function '{}' is \
+                not available on the platform '{}'.", name, self.name()));
+        }
+        Ok(mem::transmute_copy(&ptr))
+    }
+
+    /// Returns `clCreateProgramWithILKHR` (`cl_khr_il_program`).
+    pub fn il_program_fn(&self) -> OclResult<CreateProgramWithIlKhrFn> {
+        unsafe { self.extension_fn("clCreateProgramWithILKHR") }
+    }
+
+    /// Returns `clTerminateContextKHR` (`cl_khr_terminate_context`).
+    pub fn terminate_context_fn(&self) -> OclResult<TerminateContextKhrFn> {
+        unsafe { self.extension_fn("clTerminateContextKHR") }
+    }
+
+    /// Returns `clGetKernelSubGroupInfoKHR` (`cl_khr_subgroups`).
+    pub fn kernel_sub_group_info_fn(&self) -> OclResult<GetKernelSubGroupInfoKhrFn> {
+        unsafe { self.extension_fn("clGetKernelSubGroupInfoKHR") }
+    }
+}
@@ -0,0 +1,168 @@
+//! Futures resolving when enqueued commands complete.
+//!
+//! Requires the `futures` feature.
+
+use std::sync::{Arc, Mutex};
+use futures::{Future, Poll, Async};
+use futures::task::{self, Task};
+use core::error::{Error as OclError, Result as OclResult};
+use core::CommandExecutionStatus;
+use standard::Event;
+
+
+/// The result of a command and the task to notify once it is available.
+struct Shared<T> {
+    result: Option<OclResult<T>>,
+    task: Option<Task>,
+    taken: bool,
+}
+
+type SharedRef<T> = Arc<Mutex<Shared<T>>>;
+
+fn new_shared<T>() -> SharedRef<T> {
+    Arc::new(Mutex::new(Shared { result: None, task: None, taken: false }))
+}
+
+/// Stores `result` and wakes the task polling for it, if any.
+///
+/// Called from a driver thread. The task is notified after the lock is
+/// released.
+fn complete<T>(shared: &SharedRef<T>, result: OclResult<T>) {
+    let task = {
+        let mut shared = shared.lock().expect("ocl::future::complete()");
+        shared.result = Some(result);
+        shared.task.take()
+    };
+
+    if let Some(task) = task { task.notify(); }
+}
+
+/// Returns the result if available, otherwise registers the current task to
+/// be notified.
+///
+/// ## Panics
+///
+/// Panics if polled again after returning a result.
+///
+fn poll_shared<T>(shared: &SharedRef<T>, caller: &str) -> Poll<T, OclError> {
+    let mut shared = shared.lock().expect(caller);
+    assert!(!shared.taken, "{}: Polled after completion.", caller);
+
+    match shared.result.take() {
+        Some(result) => {
+            shared.taken = true;
+            result.map(Async::Ready)
+        },
+        None => {
+            shared.task = Some(task::current());
+            Ok(Async::NotReady)
+        },
+    }
+}
+
+
+/// A future which resolves when the command associated with an event
+/// completes.
+///
+/// Completion is signalled by an event callback (see `Event::set_callback`)
+/// rather than by blocking a thread, so any executor may drive it. An error
+/// is returned if the command terminates abnormally.
+///
+/// Created with `FutureEvent::new`, `KernelCmd::enq_future`, or
+/// `BufferCmd::enq_future`.
+///
+#[must_use = "futures do nothing unless polled"]
+pub struct FutureEvent {
+    event: Event,
+    shared: SharedRef<()>,
+}
+
+impl FutureEvent {
+    /// Returns a future which resolves when the command associated with
+    /// `event` completes.
+    ///
+    /// ## Errors
+    ///
+    /// `event` must not be empty.
+    ///
+    pub fn new(event: Event) -> OclResult<FutureEvent> {
+        let shared = new_shared();
+        let cb_shared = shared.clone();
+
+        try!(event.set_callback(CommandExecutionStatus::Complete, move |_, status| {
+            let result = if status < 0 {
+                OclError::err(format!("ocl::FutureEvent: The command terminated abnormally \
+                    with status: {}.", status))
+            } else {
+                Ok(())
+            };
+            complete(&cb_shared, result);
+        }));
+
+        Ok(FutureEvent { event: event, shared: shared })
+    }
+
+    /// Returns the event associated with this future.
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+}
+
+impl Future for FutureEvent {
+    type Item = ();
+    type Error = OclError;
+
+    fn poll(&mut self) -> Poll<(), OclError> {
+        poll_shared(&self.shared, "ocl::FutureEvent::poll()")
+    }
+}
+
+
+/// Completes a `FutureRead` with the result of its read.
+pub struct ReadHook<T>(SharedRef<Vec<T>>);
+
+impl<T> ReadHook<T> {
+    /// Stores the result of the read and wakes the task awaiting it.
+    pub fn complete(self, result: OclResult<Vec<T>>) {
+        complete(&self.0, result)
+    }
+}
+
+/// Returns a new `FutureRead` associated with the read enqueued by `enq`,
+/// which must arrange for the hook it is passed to be completed.
+pub fn future_read<T, E>(enq: E) -> OclResult<FutureRead<T>>
+        where E: FnOnce(ReadHook<T>) -> OclResult<Event>
+{
+    let shared = new_shared();
+    let event = try!(enq(ReadHook(shared.clone())));
+    Ok(FutureRead { event: event, shared: shared })
+}
+
+
+/// A future which resolves to the data read by a non-blocking buffer read
+/// once it completes.
+///
+/// The vector is owned by the read until it completes, so no `unsafe` is
+/// required. Created with `BufferCmd::read_future`.
+///
+#[must_use = "futures do nothing unless polled"]
+pub struct FutureRead<T> {
+    event: Event,
+    shared: SharedRef<Vec<T>>,
+}
+
+impl<T> FutureRead<T> {
+    /// Returns the event associated with the read.
+    pub fn event(&self) -> &Event {
+        &self.event
+    }
+}
+
+impl<T> Future for FutureRead<T> {
+    type Item = Vec<T>;
+    type Error = OclError;
+
+    fn poll(&mut self) -> Poll<Vec<T>, OclError> {
+        poll_shared(&self.shared, "ocl::FutureRead::poll()")
+    }
+}
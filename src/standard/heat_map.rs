@@ -0,0 +1,184 @@
+//! Per-work-group load diagnostics.
+//!
+//! [UNSTABLE]: Intended for diagnostic builds only.
+
+use std;
+use core::error::{Error as OclError, Result as OclResult};
+use standard::{Buffer, Kernel, SpatialDims};
+
+/// Source defining the heat map macros when instrumentation is enabled.
+pub const HEAT_MAP_ENABLED_SRC: &'static str = r#"
+    #define OCL_HEAT_MAP_ARG , __global uint* const ocl_heat_map
+    #define OCL_HEAT_MAP_COUNT(n) atomic_add(&ocl_heat_map[get_group_id(0) + get_num_groups(0) * \
+        (get_group_id(1) + get_num_groups(1) * get_group_id(2))], (n))
+"#;
+
+/// Source defining the heat map macros as no-ops.
+pub const HEAT_MAP_DISABLED_SRC: &'static str = r#"
+    #define OCL_HEAT_MAP_ARG
+    #define OCL_HEAT_MAP_COUNT(n)
+"#;
+
+
+/// A per-work-group counter buffer used to measure load imbalance across the
+/// work groups of a kernel launch.
+///
+/// ## Usage
+///
+/// Build the program with `ProgramBuilder::heat_map(true)`, which defines
+/// two macros for use within kernel source:
+///
+/// * `OCL_HEAT_MAP_ARG`: Placed at the end of a kernel's parameter list
+///   (without a preceding comma). Adds the counter buffer parameter.
+/// * `OCL_HEAT_MAP_COUNT(n)`: Adds `n` (e.g. a loop iteration count) to the
+///   counter of the current work group.
+///
+/// With `ProgramBuilder::heat_map(false)` both macros expand to nothing so
+/// the same source may be used in regular builds.
+///
+/// Pass `::buffer` as the last kernel argument then call `::run` to launch
+/// the kernel and collect a `HeatMapReport`.
+///
+/// ```text
+/// __kernel void trace(__global Ray* rays OCL_HEAT_MAP_ARG) {
+///     uint bounces = 0;
+///     ...
+///     OCL_HEAT_MAP_COUNT(bounces);
+/// }
+/// ```
+///
+pub struct HeatMap {
+    counts: Buffer<u32>,
+    group_dims: [usize; 3],
+}
+
+impl HeatMap {
+    /// Returns a new heat map sized for the default global and local work
+    /// sizes of `kernel`, both of which must be specified.
+    pub fn for_kernel(kernel: &Kernel) -> OclResult<HeatMap> {
+        let gws = try!(kernel.get_gws().to_lens());
+        let lws = match kernel.get_lws() {
+            SpatialDims::Unspecified => return OclError::err("ocl::HeatMap::for_kernel: The \
+                kernel's local work size must be specified."),
+            lws => try!(lws.to_lens()),
+        };
+
+        let mut group_dims = [1; 3];
+        for i in 0..3 {
+            group_dims[i] = (gws[i] + lws[i] - 1) / lws[i];
+        }
+
+        let group_count = group_dims[0] * group_dims[1] * group_dims[2];
+        let counts = try!(Buffer::new(kernel.default_queue().clone(), None, [group_count], None));
+
+        Ok(HeatMap {
+            counts: counts,
+            group_dims: group_dims,
+        })
+    }
+
+    /// Returns the counter buffer, to be passed as the kernel's final
+    /// argument.
+    pub fn buffer(&self) -> &Buffer<u32> {
+        &self.counts
+    }
+
+    /// Returns the number of work groups in each dimension.
+    pub fn group_dims(&self) -> [usize; 3] {
+        self.group_dims
+    }
+
+    /// Clears the counters, enqueues `kernel`, and returns a report of the
+    /// resulting counts.
+    pub fn run(&self, kernel: &Kernel) -> OclResult<HeatMapReport> {
+        try!(self.counts.cmd().fill(0, None).enq());
+        try!(kernel.enq());
+
+        let mut counts = vec![0u32; self.counts.len()];
+        try!(self.counts.read(&mut counts).enq());
+
+        Ok(HeatMapReport::new(counts, self.group_dims))
+    }
+}
+
+
+/// Per-work-group counts and load imbalance statistics produced by
+/// `HeatMap::run`.
+#[derive(Clone, Debug)]
+pub struct HeatMapReport {
+    counts: Vec<u32>,
+    group_dims: [usize; 3],
+    min: u32,
+    max: u32,
+    mean: f64,
+    std_dev: f64,
+}
+
+impl HeatMapReport {
+    fn new(counts: Vec<u32>, group_dims: [usize; 3]) -> HeatMapReport {
+        let min = counts.iter().cloned().min().unwrap_or(0);
+        let max = counts.iter().cloned().max().unwrap_or(0);
+        let len = std::cmp::max(counts.len(), 1) as f64;
+        let mean = counts.iter().map(|&c| c as f64).sum::<f64>() / len;
+        let variance = counts.iter().map(|&c| (c as f64 - mean).powi(2)).sum::<f64>() / len;
+
+        HeatMapReport {
+            counts: counts,
+            group_dims: group_dims,
+            min: min,
+            max: max,
+            mean: mean,
+            std_dev: variance.sqrt(),
+        }
+    }
+
+    /// Returns the count of each work group, indexed by linear group id
+    /// (dimension 0 varying fastest).
+    pub fn counts(&self) -> &[u32] { &self.counts }
+
+    /// Returns the number of work groups in each dimension.
+    pub fn group_dims(&self) -> [usize; 3] { self.group_dims }
+
+    /// Returns the smallest work group count.
+    pub fn min(&self) -> u32 { self.min }
+
+    /// Returns the largest work group count.
+    pub fn max(&self) -> u32 { self.max }
+
+    /// Returns the mean work group count.
+    pub fn mean(&self) -> f64 { self.mean }
+
+    /// Returns the standard deviation of the work group counts.
+    pub fn std_dev(&self) -> f64 { self.std_dev }
+
+    /// Returns the ratio of the largest count to the mean (`1.0` indicates
+    /// a perfectly balanced load).
+    pub fn imbalance(&self) -> f64 {
+        if self.mean > 0.0 { self.max as f64 / self.mean } else { 1.0 }
+    }
+
+    /// Returns the linear ids of the `n` busiest work groups, busiest first.
+    pub fn hottest(&self, n: usize) -> Vec<usize> {
+        let mut ids: Vec<usize> = (0..self.counts.len()).collect();
+        ids.sort_by(|&a, &b| self.counts[b].cmp(&self.counts[a]));
+        ids.truncate(n);
+        ids
+    }
+}
+
+impl std::fmt::Display for HeatMapReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        try!(writeln!(f, "Work groups: {} ({} x {} x {})", self.counts.len(),
+            self.group_dims[0], self.group_dims[1], self.group_dims[2]));
+        try!(writeln!(f, "Counts: min: {}, max: {}, mean: {:.2}, std dev: {:.2}", self.min,
+            self.max, self.mean, self.std_dev));
+        try!(writeln!(f, "Imbalance (max / mean): {:.2}", self.imbalance()));
+        try!(write!(f, "Hottest groups:"));
+
+        for id in self.hottest(5) {
+            try!(write!(f, " {} ({})", id, self.counts[id]));
+        }
+
+        Ok(())
+    }
+}
@@ -6,12 +6,323 @@ use std::mem;
 use std::ops::{Deref, DerefMut};
 use std::marker::PhantomData;
 use std::convert::Into;
+use std::ptr;
+use libc::{c_void, size_t};
+use log::{log, error};
 use core::error::{Error as OclError, Result as OclResult};
 use core::{self, OclPrm, Mem as MemCore, MemFlags, MemObjectType, ImageFormat, ImageDescriptor,
     ImageInfo, ImageInfoResult, MemInfo, MemInfoResult, ClEventPtrNew, ClWaitList,
-    ImageChannelOrder, ImageChannelDataType, GlTextureTarget};
-use standard::{Context, Queue, MemLen, SpatialDims};
-use ffi::{cl_GLuint, cl_GLint};
+    ImageChannelOrder, ImageChannelDataType, GlTextureTarget, MapFlags};
+use standard::{Context, Queue, MemLen, SpatialDims, Event};
+use standard::queue::{slow_call_start, slow_call_check};
+use standard::event::{copy_to_new, check_wait_list};
+use standard::error_hints::status_err;
+use standard::mem_flags::check_mem_flags;
+use ffi::{self, cl_GLuint, cl_GLint, cl_int, cl_uint, cl_bool, cl_map_flags, cl_command_queue,
+    cl_mem, cl_event};
+
+// The `cl-sys` binding of `clEnqueueMapImage` takes the row and slice pitches
+// by value rather than as the pointers they are written through.
+#[cfg_attr(target_os = "macos", link(name = "OpenCL", kind = "framework"))]
+#[cfg_attr(not(target_os = "macos"), link(name = "OpenCL"))]
+extern "system" {
+    fn clEnqueueMapImage(command_queue: cl_command_queue, image: cl_mem, blocking_map: cl_bool,
+        map_flags: cl_map_flags, origin: *const size_t, region: *const size_t,
+        image_row_pitch: *mut size_t, image_slice_pitch: *mut size_t,
+        num_events_in_wait_list: cl_uint, event_wait_list: *const cl_event,
+        event: *mut cl_event, errcode_ret: *mut cl_int) -> *mut c_void;
+}
+
+/// Returns the size, in pixels, of each dimension of an image. Unused
+/// dimensions have a size of one and the layers of an image array occupy
+/// the dimension following the last used one.
+fn image_dims(obj_core: &MemCore) -> OclResult<[usize; 3]> {
+    let info = |info_kind| -> OclResult<usize> {
+        match core::get_image_info(obj_core, info_kind) {
+            ImageInfoResult::Width(s) | ImageInfoResult::Height(s) |
+                ImageInfoResult::Depth(s) | ImageInfoResult::ArraySize(s) => Ok(s),
+            ImageInfoResult::Error(err) => Err(*err),
+            _ => OclError::err("ocl::Image: Unexpected 'ImageInfoResult' variant."),
+        }
+    };
+
+    let mut dims = [try!(info(ImageInfo::Width)), try!(info(ImageInfo::Height)),
+        try!(info(ImageInfo::Depth))];
+    let array_size = try!(info(ImageInfo::ArraySize));
+
+    if array_size > 0 {
+        if dims[1] == 0 { dims[1] = array_size; } else { dims[2] = array_size; }
+    }
+    for dim in dims.iter_mut() {
+        if *dim == 0 { *dim = 1; }
+    }
+    Ok(dims)
+}
+
+/// Ensures that `region`, placed at `origin`, lies within `dims`.
+fn check_region(dims: [usize; 3], origin: [usize; 3], region: [usize; 3], side: &str)
+        -> OclResult<()>
+{
+    for i in 0..3 {
+        let dim = if dims[i] == 0 { 1 } else { dims[i] };
+        if region[i] == 0 || origin[i] + region[i] > dim {
+            return OclError::err(format!("ocl::ImageCmd::enq(): Region out of range for the \
+                {}. (dims: {:?}, origin: {:?}, region: {:?})", side, dims, origin, region));
+        }
+    }
+    Ok(())
+}
+
+/// Ensures that host data of `data_len` elements is large enough to hold
+/// `region` with the given pitches (in bytes, zero meaning tightly packed).
+fn check_host_len<E>(obj_core: &MemCore, data_len: usize, region: [usize; 3], row_pitch: usize,
+        slc_pitch: usize) -> OclResult<()>
+{
+    let pixel_size = match core::get_image_info(obj_core, ImageInfo::ElementSize) {
+        ImageInfoResult::ElementSize(s) => s,
+        ImageInfoResult::Error(err) => return Err(*err),
+        _ => return OclError::err("ocl::ImageCmd::enq(): Unexpected 'ImageInfoResult' variant."),
+    };
+
+    let row_len = region[0] * pixel_size;
+    let row_pitch = if row_pitch == 0 { row_len } else { row_pitch };
+    let slc_pitch = if slc_pitch == 0 { row_pitch * region[1] } else { slc_pitch };
+
+    if row_pitch < row_len || slc_pitch < row_pitch * region[1] {
+        return OclError::err(format!("ocl::ImageCmd::enq(): Pitches too small for the region. \
+            (region: {:?}, row_pitch: {}, slc_pitch: {}, pixel size: {})", region, row_pitch,
+            slc_pitch, pixel_size));
+    }
+
+    let required = ((region[2] - 1) * slc_pitch) + ((region[1] - 1) * row_pitch) + row_len;
+    let data_bytes = data_len * mem::size_of::<E>();
+
+    if data_bytes < required {
+        return OclError::err(format!("ocl::ImageCmd::enq(): Host data too small for the \
+            region. (data: {} bytes, required: {} bytes, region: {:?})", data_bytes, required,
+            region));
+    }
+    Ok(())
+}
+
+/// Ensures that a fill color's channel type suits the image's channel data
+/// type.
+fn check_fill_color(obj_core: &MemCore, color: &FillColor) -> OclResult<()> {
+    let data_type = match core::get_image_info(obj_core, ImageInfo::Format) {
+        ImageInfoResult::Format(format) => format.channel_data_type,
+        ImageInfoResult::Error(err) => return Err(*err),
+        _ => return OclError::err("ocl::ImageCmd::enq(): Unexpected 'ImageInfoResult' variant."),
+    };
+
+    let matches = match (data_type, color) {
+        (ImageChannelDataType::SignedInt8, &FillColor::Int(_)) |
+            (ImageChannelDataType::SignedInt16, &FillColor::Int(_)) |
+            (ImageChannelDataType::SignedInt32, &FillColor::Int(_)) => true,
+        (ImageChannelDataType::UnsignedInt8, &FillColor::Uint(_)) |
+            (ImageChannelDataType::UnsignedInt16, &FillColor::Uint(_)) |
+            (ImageChannelDataType::UnsignedInt32, &FillColor::Uint(_)) => true,
+        (ImageChannelDataType::SignedInt8, _) | (ImageChannelDataType::SignedInt16, _) |
+            (ImageChannelDataType::SignedInt32, _) | (ImageChannelDataType::UnsignedInt8, _) |
+            (ImageChannelDataType::UnsignedInt16, _) |
+            (ImageChannelDataType::UnsignedInt32, _) => false,
+        (_, &FillColor::Float(_)) => true,
+        _ => false,
+    };
+
+    if !matches {
+        return OclError::err(format!("ocl::ImageCmd::enq(): The fill color, {:?}, does not \
+            suit the image's channel data type, '{:?}'. Use 'FillColor::Int' for signed \
+            integer, 'FillColor::Uint' for unsigned integer, and 'FillColor::Float' for all \
+            other channel data types.", color, data_type));
+    }
+    Ok(())
+}
+
+
+/// The four component color used to fill an image.
+///
+/// Normalized, half, and float channel data types are filled with a `Float`
+/// color, signed integer types with an `Int` color, and unsigned integer
+/// types with a `Uint` color. Colors are converted to the image's channel
+/// order and data type by the device.
+///
+/// Arrays of each component type convert into the appropriate variant,
+/// i.e.: `image.cmd().fill([0.0, 0.0, 0.0, 1.0])`.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillColor {
+    Float([f32; 4]),
+    Int([i32; 4]),
+    Uint([u32; 4]),
+}
+
+impl From<[f32; 4]> for FillColor {
+    fn from(color: [f32; 4]) -> FillColor { FillColor::Float(color) }
+}
+
+impl From<[i32; 4]> for FillColor {
+    fn from(color: [i32; 4]) -> FillColor { FillColor::Int(color) }
+}
+
+impl From<[u32; 4]> for FillColor {
+    fn from(color: [u32; 4]) -> FillColor { FillColor::Uint(color) }
+}
+
+/// Returns the size, in pixels, of each dimension of an image described by
+/// `desc`, laid out as by `image_dims`.
+fn desc_dims(desc: &ImageDescriptor) -> [usize; 3] {
+    let mut dims = match desc.image_type {
+        MemObjectType::Image1dArray => [desc.image_width, desc.image_array_size, 1],
+        MemObjectType::Image2d => [desc.image_width, desc.image_height, 1],
+        MemObjectType::Image2dArray => [desc.image_width, desc.image_height,
+            desc.image_array_size],
+        MemObjectType::Image3d => [desc.image_width, desc.image_height, desc.image_depth],
+        _ => [desc.image_width, 1, 1],
+    };
+    for dim in dims.iter_mut() {
+        if *dim == 0 { *dim = 1; }
+    }
+    dims
+}
+
+/// Validates the fields of an image descriptor against its image type,
+/// returning a copy with unused fields zeroed.
+///
+/// The number of layers of an image array is taken from the dimension
+/// following the last used one (height for 1D arrays, depth for 2D arrays)
+/// unless an array size has been set explicitly.
+fn check_desc(desc: &ImageDescriptor, has_data: bool) -> OclResult<ImageDescriptor> {
+    let mut desc = desc.clone();
+    let (w, h, d) = (desc.image_width, desc.image_height, desc.image_depth);
+    let (image_type, array_size) = (desc.image_type, desc.image_array_size);
+
+    let err = |msg: &str| -> OclResult<ImageDescriptor> {
+        OclError::err(format!("ocl::ImageBuilder::build(): {} (image type: {:?}, dims: {:?}, \
+            array size: {})", msg, image_type, [w, h, d], array_size))
+    };
+
+    // The number of layers given the dimension which may hold it:
+    let layers = |dim: usize| -> Option<usize> {
+        match (array_size, dim) {
+            (0, dim) => Some(dim),
+            (size, dim) if dim <= 1 || dim == size => Some(size),
+            _ => None,
+        }
+    };
+
+    if w == 0 { return err("Image width must be at least one."); }
+
+    match image_type {
+        MemObjectType::Image1d | MemObjectType::Image1dBuffer => {
+            if h > 1 || d > 1 { return err("1D images may not have a height or depth."); }
+            if array_size != 0 { return err("Only image arrays may have an array size."); }
+            desc.image_height = 0;
+            desc.image_depth = 0;
+        },
+        MemObjectType::Image1dArray => {
+            if d > 1 { return err("1D image arrays may not have a depth."); }
+            desc.image_array_size = match layers(h) {
+                Some(size) => size,
+                None => return err("The height of a 1D image array, if set, must equal its \
+                    array size."),
+            };
+            desc.image_height = 0;
+            desc.image_depth = 0;
+        },
+        MemObjectType::Image2d => {
+            if d > 1 { return err("2D images may not have a depth. Use 'Image2dArray' or \
+                'Image3d'."); }
+            if array_size != 0 { return err("Only image arrays may have an array size."); }
+            desc.image_depth = 0;
+        },
+        MemObjectType::Image2dArray => {
+            desc.image_array_size = match layers(d) {
+                Some(size) => size,
+                None => return err("The depth of a 2D image array, if set, must equal its \
+                    array size."),
+            };
+            desc.image_depth = 0;
+        },
+        MemObjectType::Image3d => {
+            if array_size != 0 { return err("Only image arrays may have an array size."); }
+        },
+        _ => return err("Invalid image type."),
+    }
+
+    match image_type {
+        MemObjectType::Image1dArray | MemObjectType::Image2dArray => {
+            if desc.image_array_size == 0 {
+                return err("Image arrays must have at least one layer.");
+            }
+        },
+        _ => (),
+    }
+
+    let is_buffer = match image_type { MemObjectType::Image1dBuffer => true, _ => false };
+
+    if is_buffer != desc.buffer.is_some() {
+        return err("A buffer must be set ('::buffer_sync') if and only if the image type is \
+            'Image1dBuffer'.");
+    }
+
+    let (row_pitch, slc_pitch) = (desc.image_row_pitch, desc.image_slice_pitch);
+
+    if !has_data && (row_pitch != 0 || slc_pitch != 0) {
+        return err("Pitches must be zero when no host data is given.");
+    }
+
+    match image_type {
+        MemObjectType::Image1d | MemObjectType::Image1dBuffer | MemObjectType::Image2d => {
+            if slc_pitch != 0 {
+                return err("Only 3D images and image arrays may have a slice pitch.");
+            }
+        },
+        _ => {
+            let rows = match image_type { MemObjectType::Image1dArray => 1, _ => h };
+            if row_pitch != 0 && slc_pitch != 0 &&
+                    (slc_pitch % row_pitch != 0 || slc_pitch < row_pitch * rows)
+            {
+                return err("The slice pitch must be a multiple of the row pitch spanning at \
+                    least one row (1D arrays) or the image height (otherwise).");
+            }
+        },
+    }
+
+    Ok(desc)
+}
+
+/// Returns an error listing the supported formats if `format` is not
+/// supported by the context of `queue` for `flags` and `image_type`.
+///
+/// Formats are not checked if the supported formats can not be queried,
+/// leaving any error to image creation.
+fn check_format(queue: &Queue, flags: MemFlags, format: &ImageFormat, image_type: MemObjectType)
+        -> OclResult<()>
+{
+    let supported = match core::get_supported_image_formats(queue.context_core_as_ref(), flags,
+            image_type) {
+        Ok(supported) => supported,
+        Err(_) => return Ok(()),
+    };
+
+    let is_supported = supported.iter().any(|fmt| fmt.channel_order == format.channel_order &&
+        fmt.channel_data_type == format.channel_data_type);
+
+    if is_supported {
+        Ok(())
+    } else {
+        let list = supported.iter()
+            .map(|fmt| format!("({:?}, {:?})", fmt.channel_order, fmt.channel_data_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        OclError::err(format!("ocl::ImageBuilder::build(): The image format ({:?}, {:?}) is not \
+            supported by this context for {:?} images with flags: {:?}. Supported formats \
+            (channel order, channel data type): [{}].", format.channel_order,
+            format.channel_data_type, image_type, flags, list))
+    }
+}
+
 
 /// A builder for `Image`.
 pub struct ImageBuilder<S: OclPrm> {
@@ -67,9 +378,13 @@ impl<S: OclPrm> ImageBuilder<S> {
 
     /// Builds with no host side image data memory specified and returns a
     /// new `Image`.
+    ///
+    /// The image descriptor is first validated against the image type (see
+    /// `::image_type`).
     pub fn build(&self, queue: &Queue) -> OclResult<Image<S>> {
-        Image::new(queue, self.flags, self.image_format.clone(), self.image_desc.clone(),
-            None)
+        let desc = try!(check_desc(&self.image_desc, false));
+        try!(check_format(queue, self.flags, &self.image_format, desc.image_type));
+        Image::new(queue, self.flags, self.image_format.clone(), desc, None)
     }
 
     /// Builds with the host side image data specified by `image_data`
@@ -83,8 +398,9 @@ impl<S: OclPrm> ImageBuilder<S> {
     ///
     /// [official SDK docs]: https://www.khronos.org/registry/cl/sdk/1.2/docs/man/xhtml/clCreateImage.html
     pub fn build_with_data(&self, queue: &Queue, image_data: &[S]) -> OclResult<Image<S>> {
-        Image::new(queue, self.flags, self.image_format.clone(), self.image_desc.clone(),
-            Some(image_data))
+        let desc = try!(check_desc(&self.image_desc, true));
+        try!(check_format(queue, self.flags, &self.image_format, desc.image_type));
+        Image::new(queue, self.flags, self.image_format.clone(), desc, Some(image_data))
     }
 
     pub fn channel_order(&mut self, order: ImageChannelOrder) -> &mut ImageBuilder<S> {
@@ -103,6 +419,19 @@ impl<S: OclPrm> ImageBuilder<S> {
     /// Describes the image type and must be either `Image1d`, `Image1dBuffer`,
     /// `Image1dArray`, `Image2d`, `Image2dArray`, or `Image3d`.
     ///
+    /// The remaining descriptor fields are validated against the image type
+    /// when built:
+    ///
+    /// * 1D images and buffers use only the width.
+    /// * 1D image arrays use the width and either the height or
+    ///   `::array_size` as the number of layers.
+    /// * 2D images use the width and height.
+    /// * 2D image arrays use the width, height, and either the depth or
+    ///   `::array_size` as the number of layers.
+    /// * 3D images use the width, height, and depth.
+    ///
+    /// Only 3D images and image arrays may have a slice pitch.
+    ///
     pub fn image_type(&mut self, image_type: MemObjectType) -> &mut ImageBuilder<S> {
         self.image_desc.image_type = image_type;
         self
@@ -137,6 +466,8 @@ impl<S: OclPrm> ImageBuilder<S> {
     ///
     /// * To set the dimensions of a 2d image use:
     ///   `SpatialDims::Two(width, height)`.
+    /// * To set the dimensions of a 1d image array use:
+    ///   `SpatialDims::Two(width, array_length)`.
     /// * To set the dimensions of a 2d image array use:
     ///   `SpatialDims::Three(width, height, array_length)`.
     /// * To set the dimensions of a 3d image use:
@@ -224,6 +555,10 @@ impl<S: OclPrm> ImageBuilder<S> {
     /// ocl::Image::builder().flags(ocl::MEM_WRITE_ONLY | ocl::MEM_COPY_HOST_PTR)...
     /// ```
     ///
+    /// or use a [`MemFlagsBuilder`](/ocl/ocl/builders/struct.MemFlagsBuilder.html).
+    /// Mutually exclusive combinations cause an error when the image is
+    /// built.
+    ///
     /// Defaults to `core::MEM_READ_WRITE` if not set.
     pub fn flags(&mut self, flags: MemFlags) -> &mut ImageBuilder<S> {
         self.flags = flags;
@@ -292,7 +627,7 @@ pub enum ImageCmdKind<'b, E: 'b> {
     Unspecified,
     Read { data: &'b mut [E] },
     Write { data: &'b [E] },
-    Fill { color: E },
+    Fill { color: FillColor },
     Copy { dst_image: &'b MemCore, dst_origin: [usize; 3] },
     CopyToBuffer { buffer: &'b MemCore, dst_origin: usize },
     GLAcquire,
@@ -309,7 +644,8 @@ impl<'b, E: 'b> ImageCmdKind<'b, E> {
     }
 }
 
-/// An image command builder for enqueuing reads, writes, fills, and copies.
+/// An image command builder for enqueuing reads, writes, fills, copies, and
+/// maps.
 ///
 /// ## Examples
 ///
@@ -328,9 +664,15 @@ impl<'b, E: 'b> ImageCmdKind<'b, E> {
 /// // Reads without blocking:
 /// image.cmd().read_async(&dst_vec).enew(&empty_event).enq().unwrap();
 ///
+/// // Clears an image on the device:
+/// image.cmd().fill([0.0, 0.0, 0.0, 1.0]).enq().unwrap();
+///
+/// // Maps a region for direct access, unmapping when dropped:
+/// let mut map = image.cmd().region([16, 16, 1]).map(flags::MAP_WRITE).unwrap();
+/// map.row_mut(0, 0)[0] = 255;
+///
 /// ```
 ///
-/// [FIXME]: Fills not yet implemented.
 #[allow(dead_code)]
 pub struct ImageCmd<'b, E: 'b + OclPrm> {
     queue: &'b Queue,
@@ -345,6 +687,7 @@ pub struct ImageCmd<'b, E: 'b + OclPrm> {
     ewait: Option<&'b ClWaitList>,
     enew: Option<&'b mut ClEventPtrNew>,
     mem_dims: [usize; 3],
+    checked: bool,
 }
 
 /// [UNSTABLE]: All methods still in a state of adjustifulsomeness.
@@ -368,6 +711,7 @@ impl<'b, E: 'b + OclPrm> ImageCmd<'b, E> {
             ewait: None,
             enew: None,
             mem_dims: dims,
+            checked: true,
         }
     }
 
@@ -413,38 +757,35 @@ impl<'b, E: 'b + OclPrm> ImageCmd<'b, E> {
     /// Defaults to the full region size of the image(s) as defined when first
     /// created if not set.
     ///
-    /// ## Panics [TENATIVE]
-    ///
-    /// Panics if the region is out of range on any of the three dimensions.
+    /// ## Errors
     ///
-    /// [FIXME]: Probably delay checking this until enq().
+    /// `::enq` will return an error if the region, placed at the origin, is
+    /// out of range on any of the three dimensions.
     ///
     pub fn region(mut self, region: [usize; 3]) -> ImageCmd<'b, E> {
         self.region = region;
         self
     }
 
-    /// [UNSTABLE] Sets the row and slice pitch for a read or write operation.
-    ///
-    /// `row_pitch`: Must be greater than or equal to the region width
-    /// (region[0]).
+    /// Sets the row and slice pitch, in bytes, of the host data for a read
+    /// or write operation.
     ///
-    /// `slice_pitch: Must be greater than or equal to `row_pitch` * region
-    /// height (region[1]).
+    /// `row_pitch`: Must be zero or greater than or equal to the region width
+    /// (region[0]) multiplied by the size of a pixel.
     ///
-    /// Only needs to be set if region has been set to something other than
-    /// the (default) image buffer size.
+    /// `slc_pitch`: Must be zero or greater than or equal to `row_pitch` *
+    /// region height (region[1]).
     ///
-    /// ## Stability
+    /// A pitch of zero (the default) indicates tightly packed host data. Set
+    /// these to read or write a region of an image from or into a larger
+    /// host side image.
     ///
-    /// Probably will be depricated unless I can think of a reason why you'd
-    /// set the pitches to something other than the image dims.
+    /// Copies must leave both pitches unset.
     ///
-    /// [FIXME]: Remove this or figure out if it's necessary at all.
-    ///
-    #[allow(unused_variables, unused_mut)]
-    pub unsafe fn pitch(mut self, row_pitch: usize, slc_pitch: usize) -> ImageCmd<'b, E> {
-        unimplemented!();
+    pub fn pitch(mut self, row_pitch: usize, slc_pitch: usize) -> ImageCmd<'b, E> {
+        self.row_pitch = row_pitch;
+        self.slc_pitch = slc_pitch;
+        self
     }
 
 
@@ -476,7 +817,8 @@ impl<'b, E: 'b + OclPrm> ImageCmd<'b, E> {
     /// ## Safety
     ///
     /// Caller must ensure that the container referred to by `dst_data` lives
-    /// until the call completes.
+    /// until the call completes. Enqueuing with `::enq_unchecked` verifies
+    /// in debug builds that a new event is available to await completion.
     ///
     /// ## Panics
     ///
@@ -569,7 +911,12 @@ impl<'b, E: 'b + OclPrm> ImageCmd<'b, E> {
         self
     }
 
-    /// Specifies that this command will be a fill.
+    /// Specifies that this command will be a fill of the region with
+    /// `color`.
+    ///
+    /// `color` must be a `FillColor` (or an array convertible into one) whose
+    /// component type suits the image's channel data type. See `FillColor`.
+    /// Requires `OpenCL` 1.2.
     ///
     /// If `.block(..)` has been set it will be ignored.
     ///
@@ -577,13 +924,78 @@ impl<'b, E: 'b + OclPrm> ImageCmd<'b, E> {
     ///
     /// The command operation kind must not have already been specified
     ///
-    pub fn fill(mut self, color: E) -> ImageCmd<'b, E> {
+    pub fn fill<C: Into<FillColor>>(mut self, color: C) -> ImageCmd<'b, E> {
         assert!(self.kind.is_unspec(), "ocl::ImageCmd::fill(): Operation kind \
             already set for this command.");
-        self.kind = ImageCmdKind::Fill { color: color };
+        self.kind = ImageCmdKind::Fill { color: color.into() };
         self
     }
 
+    /// Maps the region of the image into host memory, blocking until the
+    /// map is complete, and returns a guard providing access to it.
+    ///
+    /// The mapped data is laid out using the row and slice pitches, in
+    /// bytes, returned by `ImageMap::row_pitch` and `::slc_pitch`. On devices
+    /// sharing memory with the host the map is typically zero-copy.
+    ///
+    /// The queue, origin, region, and wait list set on this builder are
+    /// respected. Pitches may not be set.
+    ///
+    /// ## Panics
+    ///
+    /// The command operation kind must not have already been specified
+    ///
+    pub fn map(self, flags: MapFlags) -> OclResult<ImageMap<E>> {
+        assert!(self.kind.is_unspec(), "ocl::ImageCmd::map(): Operation kind \
+            already set for this command.");
+
+        if self.row_pitch != 0 || self.slc_pitch != 0 {
+            return OclError::err("ocl::ImageCmd::map(): Pitches may not be set for image maps.");
+        }
+
+        try!(check_region(try!(image_dims(self.obj_core)), self.origin, self.region, "image"));
+
+        let pixel_size = match core::get_image_info(self.obj_core, ImageInfo::ElementSize) {
+            ImageInfoResult::ElementSize(s) => s,
+            ImageInfoResult::Error(err) => return Err(*err),
+            _ => return OclError::err("ocl::ImageCmd::map(): Unexpected 'ImageInfoResult' \
+                variant."),
+        };
+
+        let (wait_count, wait_ptr) = match self.ewait {
+            Some(ewait) if ewait.count() > 0 => (ewait.count(), unsafe { ewait.as_ptr_ptr() }),
+            _ => (0, ptr::null()),
+        };
+
+        let mut row_pitch = 0usize;
+        let mut slc_pitch = 0usize;
+        let mut errcode: cl_int = 0;
+
+        let map_ptr = unsafe { clEnqueueMapImage(self.queue.core_as_ref().as_ptr(),
+            self.obj_core.as_ptr(), ffi::CL_TRUE, flags.bits() as cl_map_flags,
+            self.origin.as_ptr(), self.region.as_ptr(), &mut row_pitch, &mut slc_pitch,
+            wait_count, wait_ptr, ptr::null_mut(), &mut errcode) };
+
+        let bytes = ((self.region[2] - 1) * slc_pitch) + ((self.region[1] - 1) * row_pitch) +
+            (self.region[0] * pixel_size);
+
+        try!(self.queue.timeline().record_result("Image::map", bytes,
+            if errcode == ffi::CL_SUCCESS { Ok(()) } else { Err(status_err(errcode,
+                "clEnqueueMapImage", "ocl::ImageCmd::map(): Error mapping image.".to_owned())) }));
+
+        Ok(ImageMap {
+            queue: self.queue.clone(),
+            obj_core: self.obj_core.clone(),
+            ptr: map_ptr as *mut E,
+            len: bytes / mem::size_of::<E>(),
+            region: self.region,
+            row_len: (self.region[0] * pixel_size) / mem::size_of::<E>(),
+            row_pitch: row_pitch,
+            slc_pitch: slc_pitch,
+            mapped: true,
+        })
+    }
+
     /// Specifies a list of events to wait on before the command will run.
     pub fn ewait(mut self, ewait: &'b ClWaitList) -> ImageCmd<'b, E> {
         self.ewait = Some(ewait);
@@ -611,31 +1023,155 @@ impl<'b, E: 'b + OclPrm> ImageCmd<'b, E> {
         self
     }
 
+    /// Returns a label and size in bytes describing this command for the
+    /// queue's command timeline.
+    fn timeline_entry(&self) -> (&'static str, usize) {
+        let ele_size = mem::size_of::<E>();
+        let region_len = self.region[0] * self.region[1] * self.region[2];
+        match self.kind {
+            ImageCmdKind::Unspecified => ("Image::unspecified", 0),
+            ImageCmdKind::Read { ref data } => ("Image::read", data.len() * ele_size),
+            ImageCmdKind::Write { ref data } => ("Image::write", data.len() * ele_size),
+            ImageCmdKind::Fill { .. } => ("Image::fill", region_len),
+            ImageCmdKind::Copy { .. } => ("Image::copy", region_len),
+            ImageCmdKind::CopyToBuffer { .. } => ("Image::copy_to_buffer", region_len),
+            ImageCmdKind::GLAcquire => ("Image::gl_acquire", 0),
+            ImageCmdKind::GLRelease => ("Image::gl_release", 0),
+        }
+    }
+
     /// Enqueues this command.
     ///
-    /// TODO: FOR COPY, FILL, AND COPYTOBUFFER -- ENSURE PITCHES ARE BOTH UNSET.
-    pub fn enq(self) -> OclResult<()> {
+    /// The command is recorded on the queue's command timeline if enabled
+    /// and by the queue's profiler if one is attached.
+    pub fn enq(mut self) -> OclResult<()> {
+        let queue = self.queue;
+        let (label, size) = self.timeline_entry();
+        let start = slow_call_start(queue);
+
+        let enew = self.enew.take();
+
+        let result = match queue.profiler() {
+            Some(profiler) => {
+                let mut event = Event::empty();
+
+                self.enq_inner(Some(&mut event)).and_then(|_| {
+                    if let Some(enew) = enew { try!(copy_to_new(&event, enew)); }
+                    profiler.record(label, event);
+                    Ok(())
+                })
+            },
+            None => self.enq_inner(enew),
+        };
+
+        slow_call_check(queue, start, || label.to_owned());
+        queue.timeline().record_result(label, size, result)
+    }
+
+    /// Enqueues this command, skipping host side validation in release
+    /// builds.
+    ///
+    /// In debug builds this behaves like `::enq` with the same additional
+    /// checks as `BufferCmd::enq_unchecked`: a non-blocking read must
+    /// specify a new event destination (`::enew`) and each event in the wait
+    /// list must be valid and must not have terminated abnormally. In
+    /// release builds, regions and host data lengths are passed to the
+    /// driver unchecked.
+    ///
+    /// ## Safety
+    ///
+    /// In release builds the caller must ensure that the origin and region
+    /// lie within the image and that any host data is large enough. The
+    /// requirements of `::read_async` also apply.
+    ///
+    pub unsafe fn enq_unchecked(mut self) -> OclResult<()> {
+        if cfg!(debug_assertions) {
+            try!(self.check_unchecked());
+        } else {
+            self.checked = false;
+        }
+        self.enq()
+    }
+
+    /// Performs the debug build validation for `::enq_unchecked`.
+    fn check_unchecked(&self) -> OclResult<()> {
+        if let ImageCmdKind::Read { .. } = self.kind {
+            if !self.block && self.enew.is_none() {
+                return OclError::err("ocl::ImageCmd::enq_unchecked(): A non-blocking read \
+                    must specify a new event destination ('::enew').");
+            }
+        }
+
+        match self.ewait {
+            Some(ewait) => check_wait_list(ewait, "ocl::ImageCmd::enq_unchecked()"),
+            None => Ok(()),
+        }
+    }
+
+    /// TODO: FOR COPYTOBUFFER -- ENSURE PITCHES ARE BOTH UNSET.
+    fn enq_inner(self, enew: Option<&mut ClEventPtrNew>) -> OclResult<()> {
+        match self.kind {
+            ImageCmdKind::Read { .. } | ImageCmdKind::Write { .. } |
+                    ImageCmdKind::Copy { .. } | ImageCmdKind::Fill { .. } if self.checked => {
+                try!(check_region(try!(image_dims(self.obj_core)), self.origin, self.region,
+                    "image"));
+            },
+            _ => (),
+        }
+
         match self.kind {
             ImageCmdKind::Read { data } => {
-                // try!(check_len(self.to_len, data.len(), offset));
+                if self.checked {
+                    try!(check_host_len::<E>(self.obj_core, data.len(), self.region,
+                        self.row_pitch, self.slc_pitch));
+                }
                 unsafe { core::enqueue_read_image(self.queue, self.obj_core, self.block,
                     self.origin, self.region, self.row_pitch, self.slc_pitch, data, self.ewait,
-                    self.enew) }
+                    enew) }
             },
             ImageCmdKind::Write { data } => {
+                if self.checked {
+                    try!(check_host_len::<E>(self.obj_core, data.len(), self.region,
+                        self.row_pitch, self.slc_pitch));
+                }
                 core::enqueue_write_image(self.queue, self.obj_core, self.block,
                     self.origin, self.region, self.row_pitch, self.slc_pitch, data, self.ewait,
-                    self.enew)
+                    enew)
             },
             ImageCmdKind::Copy { dst_image, dst_origin } => {
+                if self.row_pitch != 0 || self.slc_pitch != 0 {
+                    return OclError::err("ocl::ImageCmd::enq(): Pitches may not be set for \
+                        image copies.");
+                }
+                if self.checked {
+                    try!(check_region(try!(image_dims(dst_image)), dst_origin, self.region,
+                        "destination image"));
+                }
                 core::enqueue_copy_image::<E>(self.queue, self.obj_core, dst_image, self.origin,
-                    dst_origin, self.region, self.ewait, self.enew)
+                    dst_origin, self.region, self.ewait, enew)
+            },
+            ImageCmdKind::Fill { color } => {
+                if self.row_pitch != 0 || self.slc_pitch != 0 {
+                    return OclError::err("ocl::ImageCmd::enq(): Pitches may not be set for \
+                        image fills.");
+                }
+                if self.checked { try!(check_fill_color(self.obj_core, &color)); }
+                let device_version = self.queue.device_version();
+
+                match color {
+                    FillColor::Float(c) => core::enqueue_fill_image(self.queue, self.obj_core,
+                        &c[..], self.origin, self.region, self.ewait, enew, Some(&device_version)),
+                    FillColor::Int(c) => core::enqueue_fill_image(self.queue, self.obj_core,
+                        &c[..], self.origin, self.region, self.ewait, enew, Some(&device_version)),
+                    FillColor::Uint(c) => core::enqueue_fill_image(self.queue, self.obj_core,
+                        &c[..], self.origin, self.region, self.ewait, enew, Some(&device_version)),
+                }
             },
             ImageCmdKind::GLAcquire => {
-                core::enqueue_acquire_gl_buffer(self.queue, self.obj_core, self.ewait, self.enew)
+                core::enqueue_acquire_gl_buffer(self.queue, self.obj_core, self.ewait, enew)
             },
             ImageCmdKind::GLRelease => {
-                core::enqueue_release_gl_buffer(self.queue, self.obj_core, self.ewait, self.enew)
+                core::enqueue_release_gl_buffer(self.queue, self.obj_core, self.ewait, enew)
             },
             ImageCmdKind::Unspecified => OclError::err("ocl::ImageCmd::enq(): No operation \
                 specified. Use '.read(...)', 'write(...)', etc. before calling '.enq()'."),
@@ -644,6 +1180,177 @@ impl<'b, E: 'b + OclPrm> ImageCmd<'b, E> {
     }
 }
 
+/// An iterator over the tiles of an image, returned by `Image::tiles`.
+///
+/// Yields an `(origin, region)` pair for each tile, ordered by x, then y,
+/// then z. Tiles along the far edge of a dimension which does not divide
+/// evenly are clamped to the image, making their regions smaller.
+///
+#[derive(Debug, Clone)]
+pub struct Tiles {
+    image_dims: [usize; 3],
+    tile_dims: [usize; 3],
+    next: Option<[usize; 3]>,
+}
+
+impl Tiles {
+    /// Returns the total number of tiles in each dimension.
+    pub fn counts(&self) -> [usize; 3] {
+        [(self.image_dims[0] + self.tile_dims[0] - 1) / self.tile_dims[0],
+            (self.image_dims[1] + self.tile_dims[1] - 1) / self.tile_dims[1],
+            (self.image_dims[2] + self.tile_dims[2] - 1) / self.tile_dims[2]]
+    }
+}
+
+impl Iterator for Tiles {
+    type Item = ([usize; 3], [usize; 3]);
+
+    fn next(&mut self) -> Option<([usize; 3], [usize; 3])> {
+        let origin = match self.next {
+            Some(origin) => origin,
+            None => return None,
+        };
+
+        let mut region = [0; 3];
+        for i in 0..3 {
+            region[i] = std::cmp::min(self.tile_dims[i], self.image_dims[i] - origin[i]);
+        }
+
+        // Advance x, carrying into y then z:
+        let mut next = origin;
+        self.next = None;
+        for i in 0..3 {
+            next[i] += self.tile_dims[i];
+            if next[i] < self.image_dims[i] {
+                self.next = Some(next);
+                break;
+            }
+            next[i] = 0;
+        }
+
+        Some((origin, region))
+    }
+}
+
+/// A region of an image mapped into host memory with `ImageCmd::map`.
+///
+/// Dereferences to the mapped data, spanning from the first pixel of the
+/// region to the last and laid out using the row and slice pitches. The
+/// region is unmapped when this guard is dropped or `::unmap` is called.
+/// Commands subsequently enqueued on an in-order queue see the changes.
+///
+pub struct ImageMap<E: OclPrm> {
+    queue: Queue,
+    obj_core: MemCore,
+    ptr: *mut E,
+    len: usize,
+    region: [usize; 3],
+    row_len: usize,
+    row_pitch: usize,
+    slc_pitch: usize,
+    mapped: bool,
+}
+
+impl<E: OclPrm> ImageMap<E> {
+    /// Returns the number of bytes between the start of each mapped row.
+    pub fn row_pitch(&self) -> usize {
+        self.row_pitch
+    }
+
+    /// Returns the number of bytes between the start of each mapped slice
+    /// of a 3D image or layer of an image array. Zero for 1D and 2D images.
+    pub fn slc_pitch(&self) -> usize {
+        self.slc_pitch
+    }
+
+    /// Returns the size, in pixels, of the mapped region.
+    pub fn region(&self) -> [usize; 3] {
+        self.region
+    }
+
+    /// Returns the pixel data of row `y` of slice `z`, relative to the
+    /// origin of the region.
+    ///
+    /// ## Panics
+    ///
+    /// `y` and `z` must lie within the region.
+    pub fn row(&self, y: usize, z: usize) -> &[E] {
+        let start = self.row_start(y, z);
+        &self[start..start + self.row_len]
+    }
+
+    /// Returns the mutable pixel data of row `y` of slice `z`, relative to
+    /// the origin of the region.
+    ///
+    /// ## Panics
+    ///
+    /// `y` and `z` must lie within the region.
+    pub fn row_mut(&mut self, y: usize, z: usize) -> &mut [E] {
+        let start = self.row_start(y, z);
+        let row_len = self.row_len;
+        &mut self[start..start + row_len]
+    }
+
+    /// Enqueues the unmapping of the region.
+    pub fn unmap(mut self) -> OclResult<()> {
+        self.unmap_inner()
+    }
+
+    fn row_start(&self, y: usize, z: usize) -> usize {
+        assert!(y < self.region[1] && z < self.region[2], "ocl::ImageMap::row(): Row ({}, {}) \
+            out of range for the region: {:?}.", y, z, self.region);
+        ((z * self.slc_pitch) + (y * self.row_pitch)) / mem::size_of::<E>()
+    }
+
+    fn unmap_inner(&mut self) -> OclResult<()> {
+        if !self.mapped { return Ok(()); }
+        self.mapped = false;
+
+        let errcode = unsafe { ffi::clEnqueueUnmapMemObject(self.queue.core_as_ref().as_ptr(),
+            self.obj_core.as_ptr(), self.ptr as *mut c_void, 0, ptr::null(), ptr::null_mut()) };
+
+        if errcode == ffi::CL_SUCCESS {
+            Ok(())
+        } else {
+            Err(status_err(errcode, "clEnqueueUnmapMemObject",
+                "ocl::ImageMap::unmap(): Error unmapping image.".to_owned()))
+        }
+    }
+}
+
+impl<E: OclPrm> Deref for ImageMap<E> {
+    type Target = [E];
+
+    fn deref(&self) -> &[E] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl<E: OclPrm> DerefMut for ImageMap<E> {
+    fn deref_mut(&mut self) -> &mut [E] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<E: OclPrm> Drop for ImageMap<E> {
+    fn drop(&mut self) {
+        if let Err(err) = self.unmap_inner() {
+            error!("ocl::ImageMap::drop(): {}", err);
+        }
+    }
+}
+
+impl<E: OclPrm> std::fmt::Debug for ImageMap<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ImageMap")
+            .field("region", &self.region)
+            .field("row_pitch", &self.row_pitch)
+            .field("slc_pitch", &self.slc_pitch)
+            .finish()
+    }
+}
+
+
 /// A section of device memory which represents one or many images.
 ///
 /// Use `::builder` for an easy way to create. [UNIMPLEMENTED]
@@ -658,7 +1365,12 @@ pub struct Image<E: OclPrm> {
 }
 
 impl<E: OclPrm> Image<E> {
-    /// Returns a list of supported image formats.
+    /// Returns the image formats supported by `context` for images of
+    /// `mem_obj_type` created with `flags`.
+    ///
+    /// Each format's `channel_order` and `channel_data_type` together make
+    /// up a supported combination. `ImageBuilder::build` checks the
+    /// requested format against this list.
     pub fn supported_formats(context: &Context, flags: MemFlags, mem_obj_type: MemObjectType,
                 ) -> OclResult<Vec<ImageFormat>> {
         core::get_supported_image_formats(context, flags, mem_obj_type)
@@ -676,6 +1388,8 @@ impl<E: OclPrm> Image<E> {
     pub fn new(queue: &Queue, flags: MemFlags, image_format: ImageFormat,
             image_desc: ImageDescriptor, image_data: Option<&[E]>) -> OclResult<Image<E>>
     {
+        try!(check_mem_flags(flags));
+
         let obj_core = unsafe { try!(core::create_image(
             queue.context_core_as_ref(),
             flags,
@@ -692,7 +1406,7 @@ impl<E: OclPrm> Image<E> {
                 Unexpected 'ImageInfoResult' variant."),
         };
 
-        let dims = [image_desc.image_width, image_desc.image_height, image_desc.image_depth].into();
+        let dims = desc_dims(&image_desc).into();
 
         let new_img = Image {
             obj_core: obj_core,
@@ -815,6 +1529,35 @@ impl<E: OclPrm> Image<E> {
         self.cmd().write(data)
     }
 
+    /// Returns an iterator over `(origin, region)` pairs covering the image
+    /// in tiles of at most `tile_dims` pixels.
+    ///
+    /// Each pair can be passed directly to `ImageCmd::origin` and
+    /// `ImageCmd::region` or used as the global work offset and size of a
+    /// kernel processing one tile. Unspecified tile dimensions are one.
+    ///
+    /// ```rust,ignore
+    /// for (origin, region) in image.tiles([256, 256]).unwrap() {
+    ///     image.read(&mut tile_data).origin(origin).region(region).enq().unwrap();
+    ///     // Process the tile...
+    /// }
+    /// ```
+    pub fn tiles<D: Into<SpatialDims>>(&self, tile_dims: D) -> OclResult<Tiles> {
+        let tile_dims = try!(tile_dims.into().to_lens());
+        let image_dims = try!(self.dims.to_lens());
+
+        if tile_dims.iter().any(|&d| d == 0) {
+            return OclError::err(format!("ocl::Image::tiles(): Tile dimensions must be \
+                non-zero: {:?}.", tile_dims));
+        }
+
+        Ok(Tiles {
+            image_dims: image_dims,
+            tile_dims: tile_dims,
+            next: if image_dims.iter().all(|&d| d > 0) { Some([0; 3]) } else { None },
+        })
+    }
+
     /// Changes the default queue.
     ///
     /// Returns a ref for chaining i.e.:
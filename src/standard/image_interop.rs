@@ -0,0 +1,142 @@
+//! Conversions between images and those of the `image` crate.
+//!
+//! Requires the `image` feature.
+
+use std::ops::Deref;
+use image::{ImageBuffer, DynamicImage, Pixel, Luma, LumaA, Rgba};
+use core::error::{Error as OclError, Result as OclResult};
+use core::{OclPrm, MemFlags, ImageFormat, ImageChannelOrder, ImageChannelDataType,
+    MemObjectType, MEM_COPY_HOST_PTR};
+use standard::{Queue, Image, SpatialDims};
+
+
+/// A pixel type of the `image` crate stored, without conversion, using an
+/// `OpenCL` image format.
+///
+/// Implemented for `Luma`, `LumaA`, and `Rgba` pixels with `u8` or `u16`
+/// channels, which use the `R`, `RG`, and `RGBA` channel orders and
+/// normalized integer channel types (read as floats in `[0.0, 1.0]` with
+/// `read_imagef`). `Rgb` pixels have no widely supported equivalent; use
+/// `Image::from_dynamic_image`, which converts them to `Rgba`.
+///
+pub trait ClPixel: Pixel {
+    /// Returns the image format used to store pixels of this type.
+    fn image_format() -> ImageFormat;
+}
+
+macro_rules! impl_cl_pixel {
+    ($( $pixel:ident<$sub:ty> => ($order:ident, $data_type:ident) ),+) => {
+        $(
+            impl ClPixel for $pixel<$sub> {
+                fn image_format() -> ImageFormat {
+                    ImageFormat {
+                        channel_order: ImageChannelOrder::$order,
+                        channel_data_type: ImageChannelDataType::$data_type,
+                    }
+                }
+            }
+        )+
+    }
+}
+
+impl_cl_pixel!(
+    Luma<u8> => (R, UnormInt8), LumaA<u8> => (Rg, UnormInt8), Rgba<u8> => (Rgba, UnormInt8),
+    Luma<u16> => (R, UnormInt16), LumaA<u16> => (Rg, UnormInt16), Rgba<u16> => (Rgba, UnormInt16)
+);
+
+
+/// Returns the width and height of a two dimensional image.
+fn dims_2d(dims: &SpatialDims, caller: &str) -> OclResult<(u32, u32)> {
+    match *dims {
+        SpatialDims::Two(w, h) => Ok((w as u32, h as u32)),
+        SpatialDims::Three(w, h, 1) => Ok((w as u32, h as u32)),
+        ref dims => OclError::err(format!("ocl::Image::{}(): Only two dimensional images can be \
+            read into an image buffer (dimensions: {:?}).", caller, dims)),
+    }
+}
+
+
+impl<S: OclPrm> Image<S> {
+    /// Creates a new two dimensional image with the format corresponding to
+    /// the pixel type of `buffer` (see `ClPixel`), initialized with its
+    /// contents.
+    ///
+    /// `MEM_COPY_HOST_PTR` is added to `flags`.
+    ///
+    /// ```text
+    /// let img = image::open("input.png").unwrap().to_rgba();
+    /// let src = Image::from_image_buffer(&queue, flags::MEM_READ_ONLY, &img).unwrap();
+    /// ```
+    ///
+    pub fn from_image_buffer<P, C>(queue: &Queue, flags: MemFlags, buffer: &ImageBuffer<P, C>)
+            -> OclResult<Image<S>>
+            where P: ClPixel<Subpixel=S> + 'static, C: Deref<Target=[S]>
+    {
+        let (width, height) = buffer.dimensions();
+
+        Image::builder()
+            .image_format(P::image_format())
+            .image_type(MemObjectType::Image2d)
+            .dims([width as usize, height as usize])
+            .flags(flags | MEM_COPY_HOST_PTR)
+            .build_with_data(queue, &**buffer)
+    }
+
+    /// Reads the contents of this two dimensional image into a new image
+    /// buffer with pixels of type `P`, blocking until complete.
+    ///
+    /// ## Errors
+    ///
+    /// The number of channels of `P` must match that of this image.
+    ///
+    pub fn read_image_buffer<P>(&self) -> OclResult<ImageBuffer<P, Vec<S>>>
+            where P: ClPixel<Subpixel=S> + 'static
+    {
+        let (width, height) = try!(dims_2d(self.dims(), "read_image_buffer"));
+
+        if P::channel_count() as usize != self.pixel_element_len() {
+            return OclError::err(format!("ocl::Image::read_image_buffer(): The pixel type has {} \
+                channels but the image has {}.", P::channel_count(), self.pixel_element_len()));
+        }
+
+        let mut data = vec![S::default(); self.element_count()];
+        try!(self.read(&mut data).enq());
+
+        ImageBuffer::from_raw(width, height, data).ok_or_else(|| OclError::new(
+            "ocl::Image::read_image_buffer(): Unable to create the image buffer."))
+    }
+}
+
+impl Image<u8> {
+    /// Creates a new two dimensional image initialized with the contents of
+    /// `img`.
+    ///
+    /// Grey images (with or without alpha) keep their channels. All others
+    /// are converted to 8-bit RGBA first.
+    ///
+    pub fn from_dynamic_image(queue: &Queue, flags: MemFlags, img: &DynamicImage)
+            -> OclResult<Image<u8>>
+    {
+        match *img {
+            DynamicImage::ImageLuma8(ref buffer) => Image::from_image_buffer(queue, flags, buffer),
+            DynamicImage::ImageLumaA8(ref buffer) => Image::from_image_buffer(queue, flags, buffer),
+            ref img => Image::from_image_buffer(queue, flags, &img.to_rgba()),
+        }
+    }
+
+    /// Reads the contents of this two dimensional image into a new dynamic
+    /// image, blocking until complete.
+    ///
+    /// Images with one, two, and four channels become grey, grey with
+    /// alpha, and RGBA images respectively.
+    ///
+    pub fn read_dynamic_image(&self) -> OclResult<DynamicImage> {
+        match self.pixel_element_len() {
+            1 => self.read_image_buffer().map(DynamicImage::ImageLuma8),
+            2 => self.read_image_buffer().map(DynamicImage::ImageLumaA8),
+            4 => self.read_image_buffer().map(DynamicImage::ImageRgba8),
+            n => OclError::err(format!("ocl::Image::read_dynamic_image(): Images with {} \
+                channels are not supported.", n)),
+        }
+    }
+}
@@ -3,47 +3,72 @@
 use std;
 use std::convert::Into;
 use std::collections::HashMap;
-use core::{self, OclPrm, Kernel as KernelCore, CommandQueue as CommandQueueCore, Mem as MemCore,
+use std::sync::{Arc, Mutex, MutexGuard};
+use core::{self, OclPrm, Kernel as KernelCore, Mem as MemCore, Program as ProgramCore,
+    Sampler as SamplerCore,
     KernelArg, KernelInfo, KernelInfoResult, KernelArgInfo, KernelArgInfoResult,
-    KernelWorkGroupInfo, KernelWorkGroupInfoResult, ClEventPtrNew, ClWaitList};
+    KernelArgAddressQualifier, KernelArgAccessQualifier, KernelArgTypeQualifier,
+    KernelWorkGroupInfo, KernelWorkGroupInfoResult, ClEventPtrNew, ClWaitList,
+    ProgramBuildInfo, ProgramBuildInfoResult,
+    KERNEL_ARG_TYPE_CONST};
 use core::error::{Result as OclResult, Error as OclError};
-use standard::{SpatialDims, Buffer, Image, Program, Queue, WorkDims, Sampler, Device};
+use standard::{SpatialDims, Buffer, Image, Program, Queue, WorkDims, Sampler, Device, Event,
+    WriteTracker, cl_type_name_of};
+use standard::queue::{slow_call_start, slow_call_check};
+use standard::event::copy_to_new;
+use standard::pro_que::ProQueLink;
+use standard::device::check_fp64;
+#[cfg(feature = "futures")] use standard::FutureEvent;
 
 const PRINT_DEBUG: bool = false;
 
 /// A kernel command builder used to queue a kernel with a mix of default
 /// and optionally specified arguments.
 pub struct KernelCmd<'k> {
-    queue: &'k CommandQueueCore,
+    queue: &'k Queue,
     kernel: &'k KernelCore,
     gwo: SpatialDims,
     gws: SpatialDims,
     lws: SpatialDims,
     wait_list: Option<&'k ClWaitList>,
     dest_list: Option<&'k mut ClEventPtrNew>,
+    write_trackers: &'k [(u32, WriteTracker)],
+    pro_que_link: Option<&'k ProQueLink>,
+    owner: &'k Kernel,
+    args: Vec<(u32, ArgValue, Option<MemCore>, Option<WriteTracker>)>,
+    arg_err: Option<OclError>,
 }
 
 /// [UNSTABLE]: All methods still being tuned.
 impl<'k> KernelCmd<'k> {
     /// Specifies a queue to use for this call only.
-    pub fn queue<Q: AsRef<CommandQueueCore>>(mut self, queue: &'k Q) -> KernelCmd<'k> {
-        self.queue = queue.as_ref();
+    pub fn queue(mut self, queue: &'k Queue) -> KernelCmd<'k> {
+        self.queue = queue;
         self
     }
 
     /// Specifies a global work offset for this call only.
-     pub fn gwo<D: Into<SpatialDims>>(mut self, gwo: D) -> KernelCmd<'k> {
+    ///
+    /// The kernel's default (see `Kernel::gwo`) is left unchanged.
+    pub fn gwo<D: Into<SpatialDims>>(mut self, gwo: D) -> KernelCmd<'k> {
         self.gwo = gwo.into();
         self
     }
 
     /// Specifies a global work size for this call only.
+    ///
+    /// The kernel's default (see `Kernel::gws`) is left unchanged. The
+    /// offset and local work size, if set, must have the same number of
+    /// dimensions.
     pub fn gws<D: Into<SpatialDims>>(mut self, gws: D) -> KernelCmd<'k> {
         self.gws = gws.into();
         self
     }
 
     /// Specifies a local work size for this call only.
+    ///
+    /// The kernel's default (see `Kernel::lws`) is left unchanged. Pass
+    /// `SpatialDims::Unspecified` to let the implementation choose.
     pub fn lws<D: Into<SpatialDims>>(mut self, lws: D) -> KernelCmd<'k> {
         self.lws = lws.into();
         self
@@ -75,8 +100,199 @@ impl<'k> KernelCmd<'k> {
         self
     }
 
+    /// Binds `buffer` to the argument at `arg_idx` for this call only.
+    ///
+    /// Arguments bound to a command are set immediately before enqueuing it
+    /// and the kernel's own arguments are restored afterwards, so a single
+    /// kernel can be launched with different buffers without changing its
+    /// arguments:
+    ///
+    /// `kernel.cmd().arg_buf(0, &buffer).arg_scl(1, 2.0f32).enq()`
+    ///
+    /// The argument is verified as described for `Kernel::set_arg_buf`. Any
+    /// error is returned by `::enq`.
+    pub fn arg_buf<T: OclPrm>(mut self, arg_idx: u32, buffer: &Buffer<T>) -> KernelCmd<'k> {
+        if self.check_arg::<T>(arg_idx, ArgKind::Buffer) {
+            let tracker = if self.owner.is_const_arg(arg_idx) {
+                None
+            } else {
+                buffer.write_tracker().cloned()
+            };
+            self.args.push((arg_idx, ArgValue::Mem, Some((**buffer).clone()), tracker));
+        }
+        self
+    }
+
+    /// Binds `image` to the argument at `arg_idx` for this call only.
+    ///
+    /// See `::arg_buf`. The argument must be declared as an image.
+    pub fn arg_img<P: OclPrm>(mut self, arg_idx: u32, image: &Image<P>) -> KernelCmd<'k> {
+        if self.check_arg::<u8>(arg_idx, ArgKind::Image) {
+            self.args.push((arg_idx, ArgValue::Mem, Some((**image).clone()), None));
+        }
+        self
+    }
+
+    /// Binds `scalar` to the argument at `arg_idx` for this call only.
+    ///
+    /// See `::arg_buf`. The value is verified as described for
+    /// `Kernel::set_arg`.
+    pub fn arg_scl<T: OclPrm>(mut self, arg_idx: u32, scalar: T) -> KernelCmd<'k> {
+        if self.check_arg::<T>(arg_idx, ArgKind::Value) {
+            self.args.push((arg_idx, ArgValue::of(&KernelArg::Scalar(scalar)), None, None));
+        }
+        self
+    }
+
+    /// Binds `vector` to the argument at `arg_idx` for this call only.
+    ///
+    /// See `::arg_scl`.
+    pub fn arg_vec<T: OclPrm>(mut self, arg_idx: u32, vector: T) -> KernelCmd<'k> {
+        if self.check_arg::<T>(arg_idx, ArgKind::Value) {
+            self.args.push((arg_idx, ArgValue::of(&KernelArg::Vector(vector)), None, None));
+        }
+        self
+    }
+
     /// Enqueues this kernel command.
-    pub fn enq(self) -> OclResult<()> {
+    ///
+    /// The command is recorded on the queue's command timeline if enabled
+    /// and by the queue's profiler if one is attached. Writes to buffer
+    /// arguments with a write tracker are first checked for conflicts (see
+    /// `WriteTracker`).
+    pub fn enq(mut self) -> OclResult<()> {
+        let dest_list = self.dest_list.take();
+        self.enq_with_dest(dest_list)
+    }
+
+    /// Enqueues this kernel command, returning a future which resolves when
+    /// the kernel completes (see `FutureEvent`).
+    ///
+    /// Requires the `futures` feature.
+    ///
+    /// ## Errors
+    ///
+    /// A new event destination (`::enew`) may not be set. Use the event of
+    /// the returned future instead.
+    ///
+    #[cfg(feature = "futures")]
+    pub fn enq_future(self) -> OclResult<FutureEvent> {
+        if self.dest_list.is_some() {
+            return OclError::err("ocl::KernelCmd::enq_future(): A new event destination \
+                ('::enew') can not be used with '::enq_future'. Use the event of the returned \
+                future instead.");
+        }
+
+        let mut event = Event::empty();
+        try!(self.enq_with_dest(Some(&mut event)));
+        FutureEvent::new(event)
+    }
+
+    /// Verifies an argument bound to this command, keeping the first error.
+    fn check_arg<T: OclPrm>(&mut self, arg_idx: u32, kind: ArgKind) -> bool {
+        if self.arg_err.is_some() { return false; }
+
+        match self.owner.check_bound_arg::<T>(arg_idx, kind) {
+            Ok(()) => true,
+            Err(err) => {
+                self.arg_err = Some(err);
+                false
+            },
+        }
+    }
+
+    /// Sets the arguments bound to this command, enqueues it, then restores
+    /// the kernel's own arguments.
+    fn enq_with_dest(mut self, dest_list: Option<&mut ClEventPtrNew>) -> OclResult<()> {
+        if let Some(err) = self.arg_err.take() { return Err(err); }
+
+        if !self.owner.arg_errs.is_empty() || self.owner.declared_arg_count
+                .map(|count| self.owner.arg_count < count).unwrap_or(false) {
+            try!(self.owner.verify_args());
+        }
+
+        if self.args.is_empty() {
+            let trackers = self.write_trackers;
+            return self.enq_tracked(trackers, dest_list);
+        }
+
+        let owner = self.owner;
+        let kernel = self.kernel;
+        let args = std::mem::replace(&mut self.args, Vec::new());
+
+        let mut trackers: Vec<(u32, WriteTracker)> = self.write_trackers.iter()
+            .filter(|&&(idx, _)| !args.iter().any(|arg| arg.0 == idx))
+            .cloned()
+            .collect();
+        trackers.extend(args.iter().filter_map(|arg| arg.3.clone().map(|t| (arg.0, t))));
+
+        let result = args.iter()
+            .map(|&(idx, ref value, ref mem, _)| set_arg_value(kernel, idx, value, mem.as_ref()))
+            .collect::<OclResult<Vec<_>>>()
+            .and_then(|_| self.enq_tracked(&trackers, dest_list));
+
+        let reset = args.iter().map(|arg| owner.reset_arg(arg.0)).collect::<OclResult<Vec<_>>>();
+        result.and(reset.map(|_| ()))
+    }
+
+    fn enq_tracked(self, trackers: &[(u32, WriteTracker)], dest_list: Option<&mut ClEventPtrNew>)
+            -> OclResult<()>
+    {
+        if let Some(link) = self.pro_que_link {
+            if link.is_stale() {
+                let name: String = core::get_kernel_info(self.kernel, KernelInfo::FunctionName)
+                    .into();
+                try!(link.check(&name));
+            }
+        }
+
+        let queue = self.queue;
+        let profiler = queue.profiler();
+
+        let label = if queue.timeline().is_enabled() || profiler.is_some() || !trackers.is_empty() {
+            let name: String = core::get_kernel_info(self.kernel, KernelInfo::FunctionName).into();
+            format!("Kernel::{}", name)
+        } else {
+            String::new()
+        };
+
+        for &(arg_idx, ref tracker) in trackers {
+            try!(tracker.check(&format!("{}(arg {})", label, arg_idx), queue, self.wait_list));
+        }
+
+        let work_items = self.gws.to_len();
+        let kernel = self.kernel;
+        let start = slow_call_start(queue);
+
+        let result = if profiler.is_some() || !trackers.is_empty() {
+            let mut event = Event::empty();
+
+            self.enq_inner(Some(&mut event)).and_then(|_| {
+                if let Some(dest_list) = dest_list { try!(copy_to_new(&event, dest_list)); }
+                if let Some(ref profiler) = profiler { profiler.record(label.as_str(), event.clone()); }
+
+                for &(arg_idx, ref tracker) in trackers {
+                    tracker.record(format!("{}(arg {})", label, arg_idx), queue, event.clone());
+                }
+                Ok(())
+            })
+        } else {
+            self.enq_inner(dest_list)
+        };
+
+        slow_call_check(queue, start, || {
+            let name: String = core::get_kernel_info(kernel, KernelInfo::FunctionName).into();
+            format!("Kernel::{}", name)
+        });
+
+        if label.is_empty() {
+            result
+        } else {
+            queue.timeline().record_result(label, work_items, result)
+        }
+    }
+
+    fn enq_inner(self, dest_list: Option<&mut ClEventPtrNew>) -> OclResult<()> {
         let dim_count = self.gws.dim_count();
 
         let gws = match self.gws.to_work_size() {
@@ -85,17 +301,295 @@ impl<'k> KernelCmd<'k> {
                 cannot be left unspecified. Set a default for the kernel or pass a valid parameter."),
         };
 
+        match self.gwo {
+            SpatialDims::Unspecified => (),
+            gwo if gwo.dim_count() == dim_count => (),
+            gwo => return OclError::err(format!("ocl::KernelCmd::enqueue: The global work offset \
+                ('gwo': {:?}) and size ('gws': {:?}) must have the same number of dimensions.",
+                gwo, self.gws)),
+        }
+
+        match self.lws {
+            SpatialDims::Unspecified => (),
+            lws if lws.dim_count() == dim_count => (),
+            lws => return OclError::err(format!("ocl::KernelCmd::enqueue: The local work size \
+                ('lws': {:?}) and global work size ('gws': {:?}) must have the same number of \
+                dimensions.", lws, self.gws)),
+        }
+
         if PRINT_DEBUG {
             println!("Enqueuing kernel: '{}'...",
                 core::get_kernel_info(self.kernel, KernelInfo::FunctionName));
         }
 
         core::enqueue_kernel(self.queue, self.kernel, dim_count, self.gwo.to_work_offset(),
-            &gws, self.lws.to_work_size(), self.wait_list, self.dest_list)
+            &gws, self.lws.to_work_size(), self.wait_list, dest_list)
+    }
+}
+
+
+
+
+/// The value last assigned to an argument, kept so that the argument can be
+/// set again on a duplicate kernel (see `Kernel::duplicate`).
+///
+/// The `MemCore` of a `Mem` argument is kept in `Kernel::mem_args`.
+#[derive(Clone, Debug)]
+enum ArgValue {
+    Mem,
+    MemNull,
+    Sampler(SamplerCore),
+    SamplerNull,
+    Bytes(Vec<u8>),
+    Local(usize),
+}
+
+impl ArgValue {
+    /// Returns the value of `arg`.
+    fn of<T: OclPrm>(arg: &KernelArg<T>) -> ArgValue {
+        fn bytes_of(ptr: *const u8, size: usize) -> ArgValue {
+            if ptr.is_null() { return ArgValue::Bytes(Vec::new()); }
+            ArgValue::Bytes(unsafe { std::slice::from_raw_parts(ptr, size) }.to_vec())
+        }
+
+        match *arg {
+            KernelArg::Mem(_) => ArgValue::Mem,
+            KernelArg::MemNull => ArgValue::MemNull,
+            KernelArg::Sampler(sampler) => ArgValue::Sampler(sampler.clone()),
+            KernelArg::SamplerNull => ArgValue::SamplerNull,
+            KernelArg::Scalar(ref value) | KernelArg::Vector(ref value) => {
+                bytes_of(value as *const T as *const u8, std::mem::size_of::<T>())
+            },
+            KernelArg::Local(length) => ArgValue::Local(*length * std::mem::size_of::<T>()),
+            KernelArg::UnsafePointer { size, value } => bytes_of(value as *const u8, size),
+        }
+    }
+}
+
+
+/// A description of a single kernel argument as declared in the kernel's
+/// source.
+///
+/// Argument information is only available if the program was built with the
+/// `-cl-kernel-arg-info` compiler option (or from source on some platforms).
+#[derive(Clone, Debug)]
+pub struct ArgInfo {
+    index: u32,
+    name: String,
+    type_name: String,
+    address: KernelArgAddressQualifier,
+    access: KernelArgAccessQualifier,
+    type_qualifier: KernelArgTypeQualifier,
+}
+
+impl ArgInfo {
+    /// Returns the argument index.
+    pub fn index(&self) -> u32 { self.index }
+
+    /// Returns the argument name.
+    pub fn name(&self) -> &str { &self.name }
+
+    /// Returns the argument type name (e.g. `float*` or `uint4`).
+    pub fn type_name(&self) -> &str { &self.type_name }
+
+    /// Returns the argument address qualifier.
+    pub fn address(&self) -> KernelArgAddressQualifier { self.address }
+
+    /// Returns the argument access qualifier.
+    pub fn access(&self) -> KernelArgAccessQualifier { self.access }
+
+    /// Returns the argument type qualifier.
+    pub fn type_qualifier(&self) -> KernelArgTypeQualifier { self.type_qualifier }
+}
+
+impl std::fmt::Display for ArgInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {:?} {} {} ({:?})", self.index, self.address, self.type_name,
+            self.name, self.access)
+    }
+}
+
+
+/// The kind of value assigned to a kernel argument, used when verifying it
+/// against the argument's declaration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ArgKind {
+    Buffer,
+    Value,
+    Local,
+    Image,
+}
+
+impl ArgKind {
+    fn description(&self) -> &'static str {
+        match *self {
+            ArgKind::Buffer => "buffer",
+            ArgKind::Value => "scalar or vector",
+            ArgKind::Local => "local allocation",
+            ArgKind::Image => "image",
+        }
+    }
+}
+
+
+/// A problem with an added argument, reported by `Kernel::verify_args` and
+/// when enqueuing.
+#[derive(Clone, Debug)]
+enum ArgError {
+    /// The value does not match the declaration. Cleared once the argument
+    /// is set again with a matching value.
+    Mismatched(String),
+    /// The argument was added beyond those declared by the kernel.
+    Extra,
+    /// The argument was added under a name other than its declared one.
+    Misnamed(String),
+    /// The local allocation brought the kernel's local memory use beyond
+    /// that available on the device, described by the breakdown. Cleared
+    /// once a local argument is set again within the budget.
+    LocalMemExceeded(String),
+}
+
+
+/// The kind and Rust element type of the value first assigned to a kernel
+/// argument. Later assignments must match.
+#[derive(Clone, Copy, Debug)]
+struct ArgType {
+    kind: ArgKind,
+    rust_type: &'static str,
+    type_name: Option<&'static str>,
+    size: usize,
+}
+
+impl ArgType {
+    fn of<T: OclPrm>(kind: ArgKind) -> ArgType {
+        ArgType {
+            kind: kind,
+            rust_type: std::any::type_name::<T>(),
+            type_name: cl_type_name_of::<T>(),
+            size: std::mem::size_of::<T>(),
+        }
+    }
+
+    fn matches(&self, other: &ArgType) -> bool {
+        self.kind == other.kind && self.rust_type == other.rust_type
+    }
+}
+
+impl std::fmt::Display for ArgType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} with element type '{}' ({} bytes)", self.kind.description(),
+            self.type_name.unwrap_or(self.rust_type), self.size)
+    }
+}
+
+
+/// Returns the scalar component name and size in bytes of a built-in
+/// `OpenCL` C scalar or vector type name such as `float4`, or `None` for any
+/// other name (typedefs, structs, etc.).
+fn builtin_type(name: &str) -> Option<(&str, usize)> {
+    const SCALARS: [(&'static str, usize); 11] = [("char", 1), ("uchar", 1), ("short", 2),
+        ("ushort", 2), ("int", 4), ("uint", 4), ("long", 8), ("ulong", 8), ("half", 2),
+        ("float", 4), ("double", 8)];
+
+    let split = name.find(|c: char| c.is_digit(10)).unwrap_or(name.len());
+    let (scalar, width) = name.split_at(split);
+
+    let scalar_size = match SCALARS.iter().find(|&&(n, _)| n == scalar) {
+        Some(&(_, size)) => size,
+        None => return None,
+    };
+
+    // Three component vectors have the size of four:
+    match width {
+        "" => Some((scalar, scalar_size)),
+        "2" => Some((scalar, scalar_size * 2)),
+        "3" | "4" => Some((scalar, scalar_size * 4)),
+        "8" => Some((scalar, scalar_size * 8)),
+        "16" => Some((scalar, scalar_size * 16)),
+        _ => None,
+    }
+}
+
+
+/// A breakdown of the local memory used by a kernel on a device.
+///
+/// Created by `Kernel::local_mem_budget`.
+#[derive(Clone, Debug)]
+pub struct LocalMemBudget {
+    device_total: u64,
+    kernel_total: u64,
+    args: Vec<(u32, u64)>,
+}
+
+impl LocalMemBudget {
+    /// Returns the amount of local memory available on the device.
+    pub fn device_total(&self) -> u64 { self.device_total }
+
+    /// Returns the total amount of local memory used by the kernel.
+    pub fn kernel_total(&self) -> u64 { self.kernel_total }
+
+    /// Returns the amount of local memory used by local arguments, listed
+    /// as `(argument index, size in bytes)`.
+    pub fn args(&self) -> &[(u32, u64)] { &self.args }
+
+    /// Returns the amount of local memory used by statically declared
+    /// `__local` variables and the implementation.
+    pub fn static_size(&self) -> u64 {
+        self.kernel_total.saturating_sub(self.args.iter().map(|&(_, size)| size).sum())
+    }
+
+    /// Returns the amount of local memory remaining, or zero if exceeded.
+    pub fn remaining(&self) -> u64 {
+        self.device_total.saturating_sub(self.kernel_total)
+    }
+
+    /// Returns `true` if the kernel uses more local memory than is
+    /// available.
+    pub fn is_exceeded(&self) -> bool {
+        self.kernel_total > self.device_total
+    }
+}
+
+impl std::fmt::Display for LocalMemBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        try!(write!(f, "local memory: {} of {} bytes used (static: {} bytes", self.kernel_total,
+            self.device_total, self.static_size()));
+
+        for &(idx, size) in &self.args {
+            try!(write!(f, ", arg {}: {} bytes", idx, size));
+        }
+
+        write!(f, ")")
     }
 }
 
 
+/// An iterator over the argument descriptions of a kernel.
+///
+/// Created by `Kernel::args`.
+pub struct ArgInfos<'k> {
+    kernel: &'k Kernel,
+    idx: u32,
+    count: u32,
+}
+
+impl<'k> Iterator for ArgInfos<'k> {
+    type Item = OclResult<ArgInfo>;
+
+    fn next(&mut self) -> Option<OclResult<ArgInfo>> {
+        if self.idx < self.count {
+            self.idx += 1;
+            Some(self.kernel.arg_info(self.idx - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.count - self.idx) as usize;
+        (remaining, Some(remaining))
+    }
+}
 
 
 /// A kernel which represents a 'procedure'.
@@ -108,7 +602,40 @@ impl<'k> KernelCmd<'k> {
 ///
 /// ## Thread Safety
 ///
-/// Now handled automatically.
+/// A `Kernel` is `Send` but not `Sync`: it may be moved to another thread
+/// but not shared between threads. Setting arguments (`clSetKernelArg`) is
+/// the only `OpenCL` operation which is not thread-safe and requires
+/// `&mut self`.
+///
+/// To enqueue one kernel from several threads, share it as a `SyncKernel`
+/// (see `::into_sync`). Each thread may then enqueue on its own queue with
+/// `.cmd().queue(..)`. Arguments bound for a single command with
+/// `.cmd().arg_buf(..)` are set on the kernel itself until the command has
+/// been enqueued, so binding them is only safe while holding the lock
+/// returned by `SyncKernel::lock()`:
+///
+/// `kernel.lock().cmd().queue(&queue).arg_buf(0, &buffer).enq()`
+///
+/// Alternatively, create a kernel per thread from a shared `Program` (which
+/// is `Send` and `Sync`), or one with independent argument state but the
+/// same arguments and work sizes with `::duplicate`.
+///
+/// ## Argument Verification
+///
+/// If the kernel's program was built with the `-cl-kernel-arg-info`
+/// compiler option, buffer, scalar, vector, and local arguments are checked
+/// against the declared type and address space of each argument. Buffers
+/// may be viewed as vectors of their element type and declared types which
+/// are not built-in `OpenCL` C types (such as typedefs) are only checked by
+/// address space. The `::set_arg_*` methods return an error on mismatch.
+/// A mismatch in the `::arg_*` builder methods is recorded and returned
+/// when enqueuing unless the argument is set again correctly. The names
+/// given to the `::arg_*_named` methods are checked against the declared
+/// names and a mismatch is likewise returned when enqueuing.
+///
+/// Enqueuing a kernel with more arguments added than it declares, or
+/// before all of its declared arguments have been added, returns an error
+/// listing the extra or missing arguments (see `::verify_args`).
 ///
 /// TODO: Add more details, examples, etc.
 /// TODO: Add information about panics and errors.
@@ -116,9 +643,18 @@ impl<'k> KernelCmd<'k> {
 #[derive(Debug)]
 pub struct Kernel {
     obj_core: KernelCore,
+    program: ProgramCore,
     named_args: HashMap<&'static str, u32>,
     mem_args: Vec<Option<MemCore>>,
+    arg_types: Vec<Option<ArgType>>,
+    arg_values: Vec<Option<ArgValue>>,
     arg_count: u32,
+    arg_infos: Option<Vec<ArgInfo>>,
+    declared_arg_count: Option<u32>,
+    arg_errs: Vec<(u32, ArgError)>,
+    local_args: Vec<(u32, u64)>,
+    write_trackers: Vec<(u32, WriteTracker)>,
+    pro_que_link: Option<ProQueLink>,
     queue: Queue,
     gwo: SpatialDims,
     gws: SpatialDims,
@@ -150,22 +686,56 @@ impl Kernel {
         let name = name.into();
         let obj_core = try!(core::create_kernel(program, &name));
 
-        Ok(Kernel {
+        let mut kernel = Kernel {
             obj_core: obj_core,
+            program: program.core_as_ref().clone(),
             named_args: HashMap::with_capacity(5),
             arg_count: 0,
             mem_args: Vec::with_capacity(16),
+            arg_types: Vec::with_capacity(16),
+            arg_values: Vec::with_capacity(16),
+            arg_infos: None,
+            declared_arg_count: None,
+            arg_errs: Vec::new(),
+            local_args: Vec::new(),
+            write_trackers: Vec::new(),
+            pro_que_link: None,
             queue: queue.clone(),
             gwo: SpatialDims::Unspecified,
             gws: SpatialDims::Unspecified,
             lws: SpatialDims::Unspecified,
-        })
+        };
+
+        let has_arg_info = match program.build_info(*queue.device(), ProgramBuildInfo::BuildOptions) {
+            ProgramBuildInfoResult::BuildOptions(ref opts) => opts.contains("-cl-kernel-arg-info"),
+            _ => false,
+        };
+
+        if let KernelInfoResult::NumArgs(count) = kernel.info(KernelInfo::NumArgs) {
+            kernel.declared_arg_count = Some(count);
+        }
+
+        if has_arg_info {
+            let arg_infos = kernel.args().and_then(|args| args.collect::<OclResult<Vec<_>>>());
+            kernel.arg_infos = arg_infos.ok();
+        }
+
+        Ok(kernel)
     }
 
     /// Sets the default global work offset (builder-style).
     ///
     /// Used when enqueuing kernel commands. Superseded if specified while
     /// making a call to enqueue or building a queue command with `::cmd`.
+    ///
+    /// The offset is added to the values returned by `get_global_id` within
+    /// the kernel, allowing a large problem to be processed in passes over
+    /// sub-ranges, for example:
+    ///
+    /// `kernel.cmd().gwo([pass * pass_len]).gws([pass_len]).enq()`
+    ///
+    /// The offset must have the same number of dimensions as the global
+    /// work size.
     pub fn gwo<D: Into<SpatialDims>>(mut self, gwo: D) -> Kernel {
         self.gwo = gwo.into();
         self
@@ -177,6 +747,7 @@ impl Kernel {
     /// making a call to enqueue or building a queue command with `::cmd`.
     pub fn gws<D: Into<SpatialDims>>(mut self, gws: D) -> Kernel {
         self.gws = gws.into();
+        if let Some(ref mut link) = self.pro_que_link { link.unfollow_dims(); }
         self
     }
 
@@ -232,6 +803,10 @@ impl Kernel {
     ///
     /// Local variables are used to share data between work items in the same
     /// workgroup.
+    ///
+    /// If the kernel's total local memory use would exceed that available on
+    /// the default queue's device, a breakdown of local memory usage is
+    /// recorded and returned when enqueuing (see `::verify_args`).
     pub fn arg_loc<T: OclPrm>(mut self, length: usize) -> Kernel {
         self.new_arg_loc::<T>(length);
         self
@@ -243,7 +818,7 @@ impl Kernel {
     /// Named arguments can be easily modified later using `::set_arg_scl_named()`.
     pub fn arg_scl_named<T: OclPrm>(mut self, name: &'static str, scalar_opt: Option<T>) -> Kernel {
         let arg_idx = self.new_arg_scl(scalar_opt);
-        self.named_args.insert(name, arg_idx);
+        self.insert_named_arg(name, arg_idx);
         self
     }
 
@@ -253,7 +828,19 @@ impl Kernel {
     /// Named arguments can be easily modified later using `::set_arg_vec_named()`.
     pub fn arg_vec_named<T: OclPrm>(mut self, name: &'static str, vector_opt: Option<T>) -> Kernel {
         let arg_idx = self.new_arg_vec(vector_opt);
-        self.named_args.insert(name, arg_idx);
+        self.insert_named_arg(name, arg_idx);
+        self
+    }
+
+    /// Adds a new named argument (in order) specifying the allocation of a
+    /// local variable of size `length * sizeof(T)` bytes (builder-style).
+    ///
+    /// Named arguments can be easily modified later using `::set_arg_loc_named()`.
+    ///
+    /// See `::arg_loc` regarding local memory use.
+    pub fn arg_loc_named<T: OclPrm>(mut self, name: &'static str, length: usize) -> Kernel {
+        let arg_idx = self.new_arg_loc::<T>(length);
+        self.insert_named_arg(name, arg_idx);
         self
     }
 
@@ -263,7 +850,7 @@ impl Kernel {
     /// Named arguments can be easily modified later using `::set_arg_scl_named()`.
     pub fn arg_buf_named<T: OclPrm>(mut self, name: &'static str, buffer_opt: Option<&Buffer<T>>) -> Kernel {
         let arg_idx = self.new_arg_buf(buffer_opt);
-        self.named_args.insert(name, arg_idx);
+        self.insert_named_arg(name, arg_idx);
         self
     }
 
@@ -273,7 +860,7 @@ impl Kernel {
     /// Named arguments can be easily modified later using `::set_arg_scl_named()`.
     pub fn arg_img_named<P: OclPrm>(mut self, name: &'static str, image_opt: Option<&Image<P>>) -> Kernel {
         let arg_idx = self.new_arg_img(image_opt);
-        self.named_args.insert(name, arg_idx);
+        self.insert_named_arg(name, arg_idx);
         self
     }
 
@@ -283,7 +870,7 @@ impl Kernel {
     /// Named arguments can be easily modified later using `::set_arg_scl_named()`.
     pub fn arg_smp_named(mut self, name: &'static str, sampler_opt: Option<&Sampler>) -> Kernel {
         let arg_idx = self.new_arg_smp(sampler_opt);
-        self.named_args.insert(name, arg_idx);
+        self.insert_named_arg(name, arg_idx);
         self
     }
 
@@ -295,7 +882,9 @@ impl Kernel {
             -> OclResult<&'a mut Kernel>
     {
         let arg_idx = try!(self.resolve_named_arg_idx(name));
-        self.set_arg::<T>(arg_idx, KernelArg::Scalar(scalar))
+        try!(self.verify_arg::<T>(arg_idx, ArgKind::Value));
+        try!(self.record_arg_type::<T>(arg_idx, ArgKind::Value));
+        self.set_arg_core::<T>(arg_idx, KernelArg::Scalar(scalar))
             .and(Ok(self))
     }
 
@@ -307,10 +896,53 @@ impl Kernel {
             -> OclResult<&'a mut Kernel>
     {
         let arg_idx = try!(self.resolve_named_arg_idx(name));
-        self.set_arg::<T>(arg_idx, KernelArg::Vector(vector))
+        try!(self.verify_arg::<T>(arg_idx, ArgKind::Value));
+        try!(self.record_arg_type::<T>(arg_idx, ArgKind::Value));
+        self.set_arg_core::<T>(arg_idx, KernelArg::Vector(vector))
             .and(Ok(self))
     }
 
+    /// Modifies the size of the local variable argument named: `name`, to
+    /// `length * sizeof(T)` bytes.
+    ///
+    /// Returns an error with a breakdown of local memory usage, leaving the
+    /// argument unchanged, if the kernel's total local memory use would
+    /// exceed that available on the default queue's device.
+    pub fn set_arg_loc_named<'a, T: OclPrm>(&'a mut self, name: &'static str, length: usize)
+            -> OclResult<&'a mut Kernel>
+    {
+        let arg_idx = try!(self.resolve_named_arg_idx(name));
+        try!(self.verify_arg::<T>(arg_idx, ArgKind::Local));
+        try!(self.record_arg_type::<T>(arg_idx, ArgKind::Local));
+
+        try!(self.set_arg_core::<T>(arg_idx, KernelArg::Local(&length)));
+
+        // Only update the recorded size once the argument has been set:
+        let size = (length * std::mem::size_of::<T>()) as u64;
+        let prev_pos = self.local_args.iter().position(|&(idx, _)| idx == arg_idx);
+        let prev_size = prev_pos.map(|pos| self.local_args.remove(pos).1);
+        self.local_args.push((arg_idx, size));
+
+        if let Ok(budget) = self.local_mem_budget(self.queue.device()) {
+            if budget.is_exceeded() {
+                let msg = format!("ocl::Kernel::set_arg_loc_named(): Kernel '{}' would exceed \
+                    the device's {}.", self.name(), budget);
+                self.local_args.pop();
+
+                if let Some(prev_size) = prev_size {
+                    self.local_args.push((arg_idx, prev_size));
+                    try!(self.set_arg_core::<u8>(arg_idx, KernelArg::Local(&(prev_size as usize))));
+                }
+                return OclError::err(msg);
+            }
+        }
+
+        self.arg_errs.retain(|&(_, ref err)| {
+            match *err { ArgError::LocalMemExceeded(_) => false, _ => true }
+        });
+        Ok(self)
+    }
+
     /// Modifies the kernel argument named: `name`.
     ///
     /// ## Panics [FIXME]
@@ -320,12 +952,15 @@ impl Kernel {
     {
         //  TODO: ADD A CHECK FOR A VALID NAME (KEY)
         let arg_idx = try!(self.resolve_named_arg_idx(name));
+        try!(self.verify_arg::<T>(arg_idx, ArgKind::Buffer));
+        try!(self.record_arg_type::<T>(arg_idx, ArgKind::Buffer));
+        self.set_write_tracker(arg_idx, buffer_opt.and_then(|b| b.write_tracker()));
         match buffer_opt {
             Some(buffer) => {
-                self.set_arg::<T>(arg_idx, KernelArg::Mem(buffer))
+                self.set_arg_core::<T>(arg_idx, KernelArg::Mem(buffer))
             },
             None => {
-                self.set_arg::<T>(arg_idx, KernelArg::MemNull)
+                self.set_arg_core::<T>(arg_idx, KernelArg::MemNull)
             },
         }.and(Ok(self))
     }
@@ -341,10 +976,10 @@ impl Kernel {
         let arg_idx = try!(self.resolve_named_arg_idx(name));
         match image_opt {
             Some(buffer) => {
-                self.set_arg::<T>(arg_idx, KernelArg::Mem(buffer))
+                self.set_arg_core::<T>(arg_idx, KernelArg::Mem(buffer))
             },
             None => {
-                self.set_arg::<T>(arg_idx, KernelArg::MemNull)
+                self.set_arg_core::<T>(arg_idx, KernelArg::MemNull)
             },
         }.and(Ok(self))
     }
@@ -360,12 +995,76 @@ impl Kernel {
         unimplemented!();
     }
 
+    /// Sets the scalar or vector argument at `arg_idx` to `value`.
+    ///
+    /// The Rust type of the value first assigned to each argument, whether
+    /// by this method, a builder method such as `::arg_scl`, or a named
+    /// setter, is recorded. Assigning a value of a different type returns
+    /// an error, as does a value not matching the kernel's declaration (see
+    /// `::arg_info`).
+    pub fn set_arg<'a, T: OclPrm>(&'a mut self, arg_idx: u32, value: T)
+            -> OclResult<&'a mut Kernel>
+    {
+        try!(self.check_arg_idx(arg_idx));
+        try!(self.verify_arg::<T>(arg_idx, ArgKind::Value));
+        try!(self.record_arg_type::<T>(arg_idx, ArgKind::Value));
+        self.set_arg_core::<T>(arg_idx, KernelArg::Scalar(value)).and(Ok(self))
+    }
+
+    /// Sets the buffer argument at `arg_idx` to `buffer`.
+    ///
+    /// The element type is checked as described for `::set_arg`.
+    pub fn set_arg_buf<'a, T: OclPrm>(&'a mut self, arg_idx: u32, buffer: &Buffer<T>)
+            -> OclResult<&'a mut Kernel>
+    {
+        try!(self.check_arg_idx(arg_idx));
+        try!(self.verify_arg::<T>(arg_idx, ArgKind::Buffer));
+        try!(self.record_arg_type::<T>(arg_idx, ArgKind::Buffer));
+        self.set_write_tracker(arg_idx, buffer.write_tracker());
+        self.set_arg_core::<T>(arg_idx, KernelArg::Mem(buffer)).and(Ok(self))
+    }
+
+    /// Sets the argument at `arg_idx` directly, skipping argument
+    /// verification in release builds.
+    ///
+    /// Accepts any `KernelArg`, including `KernelArg::UnsafePointer`. In
+    /// debug builds the index is checked against the argument count, scalar,
+    /// vector, and local arguments are verified against the kernel's
+    /// declaration (see `::arg_info`), and raw pointers must be non-null with
+    /// a non-zero size. Any failure is returned as an error. Buffers set
+    /// this way are not tracked by a `WriteTracker`.
+    ///
+    /// ## Safety
+    ///
+    /// In release builds, `arg` must match the kernel's declaration and any
+    /// raw pointer must be valid for `size` bytes for the duration of the
+    /// call.
+    ///
+    /// ## Panics
+    ///
+    /// Release builds panic if `arg_idx` is out of range.
+    ///
+    pub unsafe fn set_arg_unchecked<'a, T: OclPrm>(&'a mut self, arg_idx: u32, arg: KernelArg<T>)
+            -> OclResult<&'a mut Kernel>
+    {
+        if cfg!(debug_assertions) { try!(self.check_arg_unchecked(arg_idx, &arg)); }
+
+        match arg {
+            KernelArg::Mem(_) | KernelArg::MemNull => self.set_write_tracker(arg_idx, None),
+            _ => (),
+        }
+
+        self.set_arg_core::<T>(arg_idx, arg).and(Ok(self))
+    }
+
     /// Returns a command builder which is used to chain parameters of an
     /// 'enqueue' command together.
     pub fn cmd(&self) -> KernelCmd {
         KernelCmd { queue: &self.queue, kernel: &self.obj_core,
-            gwo: self.gwo, gws: self.gws, lws: self.lws,
-            wait_list: None, dest_list: None }
+            gwo: self.gwo, gws: self.get_gws(), lws: self.lws,
+            wait_list: None, dest_list: None, write_trackers: &self.write_trackers,
+            pro_que_link: self.pro_que_link.as_ref(), owner: self, args: Vec::new(),
+            arg_err: None }
     }
 
     /// Enqueues this kernel on the default queue and returns the result.
@@ -379,6 +1078,61 @@ impl Kernel {
         self.cmd().enq()
     }
 
+    /// Returns a new kernel with the same arguments, work sizes, and default
+    /// queue as this one but independent argument state.
+    ///
+    /// Arguments set on either kernel afterwards do not affect the other,
+    /// allowing each thread, or each in-flight iteration of a loop, to hold
+    /// its own kernel rather than sharing one and racing to set arguments.
+    /// The kernel object is created again from the same program, which is
+    /// much cheaper than building the program.
+    ///
+    /// ```text
+    /// let kernels: Vec<Kernel> = (0..4).map(|_| kernel.duplicate().unwrap()).collect();
+    ///
+    /// for (kernel, buffer) in kernels.iter_mut().zip(buffers.iter()) {
+    ///     kernel.set_arg_buf_named("buffer", Some(buffer)).unwrap();
+    ///     kernel.enq().unwrap();
+    /// }
+    /// ```
+    ///
+    pub fn duplicate(&self) -> OclResult<Kernel> {
+        let obj_core = try!(core::create_kernel(&self.program, &self.name()));
+
+        for (arg_idx, value) in self.arg_values.iter().enumerate() {
+            if let Some(ref value) = *value {
+                try!(set_arg_value(&obj_core, arg_idx as u32, value,
+                    self.mem_args[arg_idx].as_ref()));
+            }
+        }
+
+        Ok(Kernel {
+            obj_core: obj_core,
+            program: self.program.clone(),
+            named_args: self.named_args.clone(),
+            mem_args: self.mem_args.clone(),
+            arg_types: self.arg_types.clone(),
+            arg_values: self.arg_values.clone(),
+            arg_count: self.arg_count,
+            arg_infos: self.arg_infos.clone(),
+            declared_arg_count: self.declared_arg_count,
+            arg_errs: self.arg_errs.clone(),
+            local_args: self.local_args.clone(),
+            write_trackers: self.write_trackers.clone(),
+            pro_que_link: self.pro_que_link.clone(),
+            queue: self.queue.clone(),
+            gwo: self.gwo,
+            gws: self.gws,
+            lws: self.lws,
+        })
+    }
+
+    /// Converts this kernel into a `SyncKernel` which can be shared between
+    /// threads.
+    pub fn into_sync(self) -> SyncKernel {
+        SyncKernel::new(self)
+    }
+
     /// Changes the default queue.
     ///
     /// Returns a ref for chaining i.e.:
@@ -412,8 +1166,14 @@ impl Kernel {
     }
 
     /// Returns the default global work size.
+    ///
+    /// For kernels created with `ProQue::create_kernel`, this follows the
+    /// `ProQue`'s dimensions until set explicitly.
     pub fn get_gws(&self) -> SpatialDims {
-        self.gws
+        match self.pro_que_link.as_ref().and_then(|link| link.dims()) {
+            Some(dims) => dims,
+            None => self.gws,
+        }
     }
 
     /// Returns the default local work size.
@@ -442,8 +1202,122 @@ impl Kernel {
         core::get_kernel_info(&self.obj_core, info_kind)
     }
 
-    /// Returns argument information for this kernel.
-    pub fn arg_info(&self, arg_index: u32, info_kind: KernelArgInfo) -> KernelArgInfoResult {
+    /// Returns a description of the argument at `arg_index` as declared
+    /// within the kernel source.
+    ///
+    /// The program must have been built with the `-cl-kernel-arg-info`
+    /// compiler option for this information to be available on all
+    /// platforms.
+    pub fn arg_info(&self, arg_index: u32) -> OclResult<ArgInfo> {
+        let name = match self.arg_info_raw(arg_index, KernelArgInfo::Name) {
+            KernelArgInfoResult::Name(name) => name,
+            other => return Kernel::arg_info_err(arg_index, other),
+        };
+        let type_name = match self.arg_info_raw(arg_index, KernelArgInfo::TypeName) {
+            KernelArgInfoResult::TypeName(type_name) => type_name,
+            other => return Kernel::arg_info_err(arg_index, other),
+        };
+        let address = match self.arg_info_raw(arg_index, KernelArgInfo::AddressQualifier) {
+            KernelArgInfoResult::AddressQualifier(address) => address,
+            other => return Kernel::arg_info_err(arg_index, other),
+        };
+        let access = match self.arg_info_raw(arg_index, KernelArgInfo::AccessQualifier) {
+            KernelArgInfoResult::AccessQualifier(access) => access,
+            other => return Kernel::arg_info_err(arg_index, other),
+        };
+        let type_qualifier = match self.arg_info_raw(arg_index, KernelArgInfo::TypeQualifier) {
+            KernelArgInfoResult::TypeQualifier(type_qualifier) => type_qualifier,
+            other => return Kernel::arg_info_err(arg_index, other),
+        };
+
+        Ok(ArgInfo {
+            index: arg_index,
+            name: name,
+            type_name: type_name,
+            address: address,
+            access: access,
+            type_qualifier: type_qualifier,
+        })
+    }
+
+    /// Returns an iterator over descriptions of every argument declared by
+    /// the kernel. See `::arg_info`.
+    pub fn args(&self) -> OclResult<ArgInfos> {
+        let count = match self.info(KernelInfo::NumArgs) {
+            KernelInfoResult::NumArgs(count) => count,
+            KernelInfoResult::Error(err) => return Err(*err),
+            other => return OclError::err(format!("Kernel::args(): Unexpected result \
+                querying the argument count: {:?}", other)),
+        };
+
+        Ok(ArgInfos { kernel: self, idx: 0, count: count })
+    }
+
+    /// Verifies that every argument declared by the kernel has been added
+    /// and, if argument information is available (see `::arg_info`), that
+    /// named arguments match their declared names and that arguments added
+    /// with builder-style methods (such as `::arg_buf`) match their
+    /// declarations.
+    ///
+    /// Called automatically when enqueuing a kernel which is missing
+    /// arguments or has mismatched arguments.
+    ///
+    /// ## Errors
+    ///
+    /// The error lists each mismatched, missing, or extra argument and each
+    /// mismatched name.
+    ///
+    pub fn verify_args(&self) -> OclResult<()> {
+        let declared = match self.declared_arg_count {
+            Some(count) => count,
+            None => self.arg_count,
+        };
+
+        let describe = |arg_idx: u32| {
+            match self.arg_infos.as_ref().and_then(|infos| infos.get(arg_idx as usize)) {
+                Some(info) => format!("{} ('{} {}')", arg_idx, info.type_name, info.name),
+                None => arg_idx.to_string(),
+            }
+        };
+
+        let mut problems = Vec::new();
+        let mut extra = Vec::new();
+        let mut misnamed = Vec::new();
+
+        for &(arg_idx, ref err) in self.arg_errs.iter() {
+            match *err {
+                ArgError::Mismatched(ref err) => {
+                    problems.push(format!("mismatched argument: {}", err))
+                },
+                ArgError::Extra => extra.push(arg_idx.to_string()),
+                ArgError::Misnamed(ref err) => misnamed.push(err.clone()),
+                ArgError::LocalMemExceeded(ref budget) => problems.push(format!("argument {} \
+                    exceeds the device's {}", arg_idx, budget)),
+            }
+        }
+
+        if self.arg_count < declared {
+            let missing: Vec<String> = (self.arg_count..declared).map(&describe).collect();
+            problems.push(format!("missing arguments: {}", missing.join(", ")));
+        }
+
+        if !extra.is_empty() {
+            problems.push(format!("extra arguments: {}", extra.join(", ")));
+        }
+
+        problems.extend(misnamed);
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            OclError::err(format!("Kernel::verify_args(): Kernel '{}' declares {} arguments \
+                and {} have been added: {}.", self.name(), declared, self.arg_count,
+                problems.join("; ")))
+        }
+    }
+
+    /// Returns a single piece of raw argument information for this kernel.
+    pub fn arg_info_raw(&self, arg_index: u32, info_kind: KernelArgInfo) -> KernelArgInfoResult {
         // match core::get_kernel_arg_info(&self.obj_core, arg_index, info_kind) {
         //     Ok(res) => res,
         //     Err(err) => KernelArgInfoResult::Error(Box::new(err)),
@@ -463,6 +1337,133 @@ impl Kernel {
         core::get_kernel_work_group_info(&self.obj_core, device, info_kind)
     }
 
+    /// Returns the maximum work group size which can be used to execute this
+    /// kernel on `device`.
+    ///
+    /// ### Errors
+    ///
+    /// Returns any OpenCL error.
+    ///
+    pub fn wg_size(&self, device: &Device) -> OclResult<usize> {
+        match self.wg_info(device, KernelWorkGroupInfo::WorkGroupSize) {
+            KernelWorkGroupInfoResult::WorkGroupSize(s) => Ok(s),
+            KernelWorkGroupInfoResult::Error(err) => Err(*err),
+            _ => panic!("ocl::Kernel::wg_size: Unexpected 'KernelWorkGroupInfoResult' variant."),
+        }
+    }
+
+    /// Returns the work group size specified in the kernel source by the
+    /// `reqd_work_group_size` attribute, or `[0, 0, 0]` if unspecified.
+    ///
+    /// ### Errors
+    ///
+    /// Returns any OpenCL error.
+    ///
+    pub fn compile_wg_size(&self, device: &Device) -> OclResult<[usize; 3]> {
+        match self.wg_info(device, KernelWorkGroupInfo::CompileWorkGroupSize) {
+            KernelWorkGroupInfoResult::CompileWorkGroupSize(s) => Ok(s),
+            KernelWorkGroupInfoResult::Error(err) => Err(*err),
+            _ => panic!("ocl::Kernel::compile_wg_size: Unexpected 'KernelWorkGroupInfoResult' \
+                variant."),
+        }
+    }
+
+    /// Returns the amount of local memory, in bytes, used by this kernel on
+    /// `device`, including statically declared `__local` variables and any
+    /// local arguments already set.
+    ///
+    /// ### Errors
+    ///
+    /// Returns any OpenCL error.
+    ///
+    pub fn local_mem_size(&self, device: &Device) -> OclResult<u64> {
+        match self.wg_info(device, KernelWorkGroupInfo::LocalMemSize) {
+            KernelWorkGroupInfoResult::LocalMemSize(s) => Ok(s),
+            KernelWorkGroupInfoResult::Error(err) => Err(*err),
+            _ => panic!("ocl::Kernel::local_mem_size: Unexpected 'KernelWorkGroupInfoResult' \
+                variant."),
+        }
+    }
+
+    /// Returns the preferred multiple of work group size for this kernel on
+    /// `device`.
+    ///
+    /// ### Errors
+    ///
+    /// Returns any OpenCL error.
+    ///
+    pub fn preferred_wg_size_multiple(&self, device: &Device) -> OclResult<usize> {
+        match self.wg_info(device, KernelWorkGroupInfo::PreferredWorkGroupSizeMultiple) {
+            KernelWorkGroupInfoResult::PreferredWorkGroupSizeMultiple(s) => Ok(s),
+            KernelWorkGroupInfoResult::Error(err) => Err(*err),
+            _ => panic!("ocl::Kernel::preferred_wg_size_multiple: Unexpected \
+                'KernelWorkGroupInfoResult' variant."),
+        }
+    }
+
+    /// Returns the minimum amount of private memory, in bytes, used by each
+    /// work item of this kernel on `device`.
+    ///
+    /// ### Errors
+    ///
+    /// Returns any OpenCL error.
+    ///
+    pub fn private_mem_size(&self, device: &Device) -> OclResult<u64> {
+        match self.wg_info(device, KernelWorkGroupInfo::PrivateMemSize) {
+            KernelWorkGroupInfoResult::PrivateMemSize(s) => Ok(s),
+            KernelWorkGroupInfoResult::Error(err) => Err(*err),
+            _ => panic!("ocl::Kernel::private_mem_size: Unexpected 'KernelWorkGroupInfoResult' \
+                variant."),
+        }
+    }
+
+    /// Returns a breakdown of the local memory used by this kernel on
+    /// `device`, including statically declared `__local` variables and any
+    /// local arguments already set.
+    ///
+    /// ### Errors
+    ///
+    /// Returns any OpenCL error.
+    ///
+    pub fn local_mem_budget(&self, device: &Device) -> OclResult<LocalMemBudget> {
+        Ok(LocalMemBudget {
+            device_total: try!(device.local_mem_size()),
+            kernel_total: try!(self.local_mem_size(device)),
+            args: self.local_args.clone(),
+        })
+    }
+
+    /// Returns the amount of local memory, in bytes, remaining for each work
+    /// item of a work group of size `lws` on the device of this kernel's
+    /// default queue, after accounting for the kernel's current usage.
+    ///
+    /// Useful for sizing a local argument in proportion to the work group
+    /// size:
+    ///
+    /// ```text
+    /// let per_item = kernel.remaining_local_mem([64])?;
+    /// kernel.set_arg_loc_named::<f32>("scratch", 64 * (per_item as usize / 4))?;
+    /// ```
+    ///
+    /// ### Errors
+    ///
+    /// Returns an error if `lws` is empty or exceeds the maximum work group
+    /// size of this kernel on the device, or any OpenCL error.
+    ///
+    pub fn remaining_local_mem<D: Into<SpatialDims>>(&self, lws: D) -> OclResult<u64> {
+        let device = self.queue.device();
+        let lws_len = lws.into().to_len();
+        let max_wg_size = try!(self.wg_size(device));
+
+        if lws_len == 0 || lws_len > max_wg_size {
+            return OclError::err(format!("ocl::Kernel::remaining_local_mem(): The local work \
+                size ({} work items) must be between 1 and the maximum work group size of \
+                kernel '{}' ({}).", lws_len, self.name(), max_wg_size));
+        }
+
+        self.local_mem_budget(device).map(|b| b.remaining() / lws_len as u64)
+    }
+
     pub fn name(&self) -> String {
         core::get_kernel_info(&self.obj_core, KernelInfo::FunctionName).into()
     }
@@ -499,6 +1500,15 @@ impl Kernel {
             .finish()
     }
 
+    /// Converts an unexpected argument info result into an error.
+    fn arg_info_err<T>(arg_index: u32, result: KernelArgInfoResult) -> OclResult<T> {
+        match result {
+            KernelArgInfoResult::Error(err) => Err(*err),
+            other => OclError::err(format!("Kernel::arg_info(): Unexpected result for \
+                argument {}: {:?}", arg_index, other)),
+        }
+    }
+
     /// Resolves the index of a named argument.
     fn resolve_named_arg_idx(&self, name: &'static str) -> OclResult<u32> {
         match self.named_args.get(name) {
@@ -510,15 +1520,105 @@ impl Kernel {
         }
     }
 
+    /// Ensures that `arg_idx` refers to an argument which has been added.
+    fn check_arg_idx(&self, arg_idx: u32) -> OclResult<()> {
+        if arg_idx < self.arg_count {
+            Ok(())
+        } else {
+            OclError::err(format!("Kernel::set_arg(): Argument index {} is out of range for \
+                kernel '{}', which has {} arguments.", arg_idx, self.name(), self.arg_count))
+        }
+    }
+
+    /// Records the type of the value assigned to the argument at `arg_idx`
+    /// or, if one has already been recorded, ensures that it matches.
+    ///
+    /// Called once the value has been verified against the declaration. A
+    /// type recorded from a value which failed verification (see
+    /// `::verify_new_arg`) is replaced.
+    fn record_arg_type<T: OclPrm>(&mut self, arg_idx: u32, kind: ArgKind) -> OclResult<()> {
+        let arg_type = ArgType::of::<T>(kind);
+        let was_mismatched = self.arg_errs.iter().any(|&(idx, ref err)| {
+            idx == arg_idx && match *err { ArgError::Mismatched(_) => true, _ => false }
+        });
+
+        match self.arg_types[arg_idx as usize] {
+            Some(prev) if !prev.matches(&arg_type) && !was_mismatched => {
+                return OclError::err(format!("Kernel::set_arg(): Argument {} of kernel '{}' \
+                    was first set with a {} but was given a {}.", arg_idx, self.name(), prev,
+                    arg_type));
+            },
+            Some(_) if !was_mismatched => (),
+            _ => self.arg_types[arg_idx as usize] = Some(arg_type),
+        }
+
+        self.arg_errs.retain(|&(idx, ref err)| {
+            idx != arg_idx || match *err { ArgError::Mismatched(_) => false, _ => true }
+        });
+        Ok(())
+    }
+
     /// Non-builder-style version of `::arg_buf()`.
     fn new_arg_buf<T: OclPrm>(&mut self, buffer_opt: Option<&Buffer<T>>) -> u32 {
-        match buffer_opt {
+        self.verify_new_arg::<T>(ArgKind::Buffer);
+        let arg_idx = match buffer_opt {
             Some(buffer) => {
                 self.new_arg::<T>(KernelArg::Mem(buffer))
             },
             None => {
                 self.new_arg::<T>(KernelArg::MemNull)
             },
+        };
+        self.arg_types[arg_idx as usize] = Some(ArgType::of::<T>(ArgKind::Buffer));
+        self.set_write_tracker(arg_idx, buffer_opt.and_then(|b| b.write_tracker()));
+        arg_idx
+    }
+
+    /// Replaces the write tracker checked when enqueuing for the buffer
+    /// argument at `arg_idx`. Arguments known to be `const` are not writes
+    /// and are never tracked.
+    fn set_write_tracker(&mut self, arg_idx: u32, tracker: Option<&WriteTracker>) {
+        self.write_trackers.retain(|&(idx, _)| idx != arg_idx);
+        let is_const = self.is_const_arg(arg_idx);
+
+        if let Some(tracker) = tracker {
+            if !is_const { self.write_trackers.push((arg_idx, tracker.clone())); }
+        }
+    }
+
+    /// Returns true if the argument at `arg_idx` is known to be `const`.
+    fn is_const_arg(&self, arg_idx: u32) -> bool {
+        self.arg_infos.as_ref()
+            .and_then(|infos| infos.get(arg_idx as usize))
+            .map(|info| info.type_qualifier().contains(KERNEL_ARG_TYPE_CONST))
+            .unwrap_or(false)
+    }
+
+    /// Verifies a value bound to the argument at `arg_idx` for a single
+    /// command (see `KernelCmd::arg_buf`) against both the kernel's
+    /// declaration and the type recorded for the argument.
+    fn check_bound_arg<T: OclPrm>(&self, arg_idx: u32, kind: ArgKind) -> OclResult<()> {
+        try!(self.check_arg_idx(arg_idx));
+        try!(self.verify_arg::<T>(arg_idx, kind));
+
+        let arg_type = ArgType::of::<T>(kind);
+
+        match self.arg_types[arg_idx as usize] {
+            Some(prev) if !prev.matches(&arg_type) => {
+                OclError::err(format!("KernelCmd::arg(): Argument {} of kernel '{}' was set \
+                    with a {} but was given a {}.", arg_idx, self.name(), prev, arg_type))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Sets the argument at `arg_idx` back to the value last assigned to it,
+    /// if any.
+    fn reset_arg(&self, arg_idx: u32) -> OclResult<()> {
+        match self.arg_values[arg_idx as usize] {
+            Some(ref value) => set_arg_value(&self.obj_core, arg_idx, value,
+                self.mem_args[arg_idx as usize].as_ref()),
+            None => Ok(()),
         }
     }
 
@@ -550,16 +1650,20 @@ impl Kernel {
 
     /// Non-builder-style version of `::arg_scl()`.
     fn new_arg_scl<T: OclPrm>(&mut self, scalar_opt: Option<T>) -> u32 {
+        self.verify_new_arg::<T>(ArgKind::Value);
         let scalar = match scalar_opt {
             Some(s) => s,
             None => Default::default(),
         };
 
-        self.new_arg::<T>(KernelArg::Scalar(scalar))
+        let arg_idx = self.new_arg::<T>(KernelArg::Scalar(scalar));
+        self.arg_types[arg_idx as usize] = Some(ArgType::of::<T>(ArgKind::Value));
+        arg_idx
     }
 
     /// Non-builder-style version of `::arg_vec()`.
     fn new_arg_vec<T: OclPrm>(&mut self, vector_opt: Option<T>) -> u32 {
+        self.verify_new_arg::<T>(ArgKind::Value);
         // match vector_opt {
         //     Some(v) => self.new_arg::<T>(KernelArg::Vector(v)),
         //     None => self.new_arg::<T>(KernelArg::Vector(&[Default::default(); 4])),
@@ -569,22 +1673,166 @@ impl Kernel {
             None => Default::default(),
         };
 
-        self.new_arg::<T>(KernelArg::Vector(vector))
+        let arg_idx = self.new_arg::<T>(KernelArg::Vector(vector));
+        self.arg_types[arg_idx as usize] = Some(ArgType::of::<T>(ArgKind::Value));
+        arg_idx
     }
 
     /// Non-builder-style version of `::arg_loc()`.
     fn new_arg_loc<T: OclPrm>(&mut self, length: usize) -> u32 {
-        self.new_arg::<T>(KernelArg::Local(&length))
+        self.verify_new_arg::<T>(ArgKind::Local);
+        let arg_idx = self.new_arg::<T>(KernelArg::Local(&length));
+        self.arg_types[arg_idx as usize] = Some(ArgType::of::<T>(ArgKind::Local));
+        self.local_args.push((arg_idx, (length * std::mem::size_of::<T>()) as u64));
+
+        if let Ok(budget) = self.local_mem_budget(self.queue.device()) {
+            if budget.is_exceeded() {
+                self.arg_errs.push((arg_idx, ArgError::LocalMemExceeded(budget.to_string())));
+            }
+        }
+
+        arg_idx
+    }
+
+    /// Verifies that a value of kind `kind` with element type `T` matches the
+    /// declaration of the argument at `arg_idx`.
+    ///
+    /// Always succeeds if argument information is unavailable. The address
+    /// space is always checked. Buffers and local allocations may view
+    /// their elements as vectors of the same component type (e.g. a buffer
+    /// of `f32` passed as `float4*`). Declared types which are not built-in
+    /// `OpenCL` C types, such as typedefs and structs, are not checked
+    /// further. Types without a known `OpenCL` C equivalent are checked by
+    /// size only.
+    fn verify_arg<T: OclPrm>(&self, arg_idx: u32, kind: ArgKind) -> OclResult<()> {
+        try!(check_fp64::<T>(self.queue.device(), "Kernel::set_arg()"));
+
+        let info = match self.arg_infos {
+            Some(ref infos) => match infos.get(arg_idx as usize) {
+                Some(info) => info,
+                None => return OclError::err(format!("Kernel::set_arg(): Argument index {} is \
+                    out of range for kernel '{}', which declares {} arguments.", arg_idx,
+                    self.name(), infos.len())),
+            },
+            None => return Ok(()),
+        };
+
+        let address_ok = match kind {
+            ArgKind::Buffer => info.address == KernelArgAddressQualifier::Global ||
+                info.address == KernelArgAddressQualifier::Constant,
+            ArgKind::Value => info.address == KernelArgAddressQualifier::Private,
+            ArgKind::Local => info.address == KernelArgAddressQualifier::Local,
+            ArgKind::Image => info.address == KernelArgAddressQualifier::Global,
+        };
+
+        let declared_type: String = info.type_name.chars()
+            .filter(|c| !c.is_whitespace() && *c != '*').collect();
+        let ele_type = cl_type_name_of::<T>();
+        let ele_size = std::mem::size_of::<T>();
+
+        let type_ok = match (builtin_type(&declared_type), ele_type.and_then(builtin_type)) {
+            // Image element types are described by their format instead:
+            _ if kind == ArgKind::Image => declared_type.starts_with("image"),
+            // Typedefs, structs, etc.:
+            (None, _) => true,
+            (Some((declared_scalar, _)), Some((ele_scalar, _))) => match kind {
+                ArgKind::Value => ele_type == Some(declared_type.as_str()),
+                _ => ele_scalar == declared_scalar,
+            },
+            (Some((_, declared_size)), None) => match kind {
+                ArgKind::Value => ele_size == declared_size,
+                _ => declared_size % ele_size == 0 || ele_size % declared_size == 0,
+            },
+        };
+
+        if address_ok && type_ok {
+            Ok(())
+        } else {
+            OclError::err(format!("Kernel::set_arg(): Argument {} ('{}') of kernel '{}' is \
+                declared as '{:?} {}' but was given a {} with element type '{}' ({} bytes).",
+                arg_idx, info.name, self.name(), info.address, info.type_name,
+                kind.description(), ele_type.unwrap_or("[unknown]"), ele_size))
+        }
+    }
+
+    /// Performs the debug build validation for `::set_arg_unchecked`.
+    fn check_arg_unchecked<T: OclPrm>(&self, arg_idx: u32, arg: &KernelArg<T>) -> OclResult<()> {
+        if arg_idx >= self.arg_count {
+            return OclError::err(format!("Kernel::set_arg_unchecked(): Argument index {} is \
+                out of range for kernel '{}', which has {} arguments.", arg_idx, self.name(),
+                self.arg_count));
+        }
+
+        match *arg {
+            KernelArg::Scalar(_) | KernelArg::Vector(_) => {
+                self.verify_arg::<T>(arg_idx, ArgKind::Value)
+            },
+            KernelArg::Local(_) => self.verify_arg::<T>(arg_idx, ArgKind::Local),
+            KernelArg::UnsafePointer { size, value } => {
+                if value.is_null() || size == 0 {
+                    OclError::err(format!("Kernel::set_arg_unchecked(): The raw pointer given \
+                        for argument {} of kernel '{}' is null or has a size of zero.", arg_idx,
+                        self.name()))
+                } else {
+                    Ok(())
+                }
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Verifies the next argument to be added.
+    ///
+    /// A mismatch, or an argument beyond those declared by the kernel, is
+    /// recorded and returned when enqueuing (see `::verify_args`). A
+    /// mismatch is cleared if the argument is set again correctly.
+    fn verify_new_arg<T: OclPrm>(&mut self, kind: ArgKind) {
+        let arg_idx = self.arg_count;
+
+        if self.is_extra_arg(arg_idx) {
+            self.arg_errs.push((arg_idx, ArgError::Extra));
+        } else if let Err(err) = self.verify_arg::<T>(arg_idx, kind) {
+            self.arg_errs.push((arg_idx, ArgError::Mismatched(err.to_string())));
+        }
+    }
+
+    /// Returns true if the kernel declares fewer than `arg_idx + 1`
+    /// arguments.
+    fn is_extra_arg(&self, arg_idx: u32) -> bool {
+        self.declared_arg_count.map(|declared| arg_idx >= declared).unwrap_or(false)
+    }
+
+    /// Records the index of a named argument. A name differing from the
+    /// declared one is recorded and returned when enqueuing (see
+    /// `::verify_args`).
+    fn insert_named_arg(&mut self, name: &'static str, arg_idx: u32) {
+        let misnamed = match self.arg_infos.as_ref().and_then(|infos| infos.get(arg_idx as usize)) {
+            Some(info) if info.name != name => Some(format!("argument {} is declared as '{}' \
+                but was added as '{}'", arg_idx, info.name, name)),
+            _ => None,
+        };
+
+        if let Some(err) = misnamed {
+            self.arg_errs.push((arg_idx, ArgError::Misnamed(err)));
+        }
+
+        self.named_args.insert(name, arg_idx);
     }
 
     /// Adds a new argument to the kernel and returns the index.
     fn new_arg<T: OclPrm>(&mut self, arg: KernelArg<T>) -> u32 {
         let arg_idx = self.arg_count;
 
-        // Push an empty `mem_arg` to the list just to make room.
+        // Push an empty `mem_arg`, type, and value to the lists just to make room.
         self.mem_args.push(None);
+        self.arg_types.push(None);
+        self.arg_values.push(None);
 
-        self.set_arg(arg_idx, arg).expect("Kernel::new_arg()");
+        // Arguments beyond those declared are only recorded (see
+        // `::verify_new_arg`), setting them would fail.
+        if !self.is_extra_arg(arg_idx) {
+            self.set_arg_core(arg_idx, arg).expect("Kernel::new_arg()");
+        }
 
         self.arg_count += 1;
         debug_assert!(self.arg_count as usize == self.mem_args.len());
@@ -592,7 +1840,7 @@ impl Kernel {
     }
 
     /// Sets an argument.
-    fn set_arg<T: OclPrm>(&mut self, arg_idx: u32, arg: KernelArg<T>) -> OclResult<()> {
+    fn set_arg_core<T: OclPrm>(&mut self, arg_idx: u32, arg: KernelArg<T>) -> OclResult<()> {
         // If the `KernelArg` is a `Mem` variant, clone the `MemCore` it
         // refers to, store it in `self.mem_args`, and create a new
         // `KernelArg::Mem` refering to the locally stored copy. This prevents
@@ -600,6 +1848,8 @@ impl Kernel {
         // to when this kernel is enqueued and causing either a misleading
         // error message or a hard to debug segfault depending on the
         // platform.
+        let value = ArgValue::of(&arg);
+
         let arg = match arg {
             KernelArg::Mem(mem) => {
                 self.mem_args[arg_idx as usize] = Some(mem.clone());
@@ -612,11 +1862,56 @@ impl Kernel {
             },
         };
 
-        core::set_kernel_arg::<T>(&self.obj_core, arg_idx, arg)
+        let start = slow_call_start(&self.queue);
+        let result = core::set_kernel_arg::<T>(&self.obj_core, arg_idx, arg);
+        slow_call_check(&self.queue, start,
+            || format!("Kernel::{}::set_arg({})", self.name(), arg_idx));
+
+        if result.is_ok() { self.arg_values[arg_idx as usize] = Some(value); }
+        result
+    }
+
+    /// Returns true if this kernel was created with `ProQue::create_kernel`
+    /// and the `ProQue`'s program has since been replaced.
+    pub fn is_stale(&self) -> bool {
+        self.pro_que_link.as_ref().map(|link| link.is_stale()).unwrap_or(false)
     }
 }
 
 
+/// Sets the argument at `arg_idx` of `obj_core` to `value`. `mem` is used
+/// for `ArgValue::Mem`.
+fn set_arg_value(obj_core: &KernelCore, arg_idx: u32, value: &ArgValue, mem: Option<&MemCore>)
+        -> OclResult<()>
+{
+    match *value {
+        ArgValue::Mem => match mem {
+            Some(mem) => core::set_kernel_arg::<u8>(obj_core, arg_idx, KernelArg::Mem(mem)),
+            None => core::set_kernel_arg::<u8>(obj_core, arg_idx, KernelArg::MemNull),
+        },
+        ArgValue::MemNull => core::set_kernel_arg::<u8>(obj_core, arg_idx, KernelArg::MemNull),
+        ArgValue::Sampler(ref sampler) => {
+            core::set_kernel_arg::<u8>(obj_core, arg_idx, KernelArg::Sampler(sampler))
+        },
+        ArgValue::SamplerNull => {
+            core::set_kernel_arg::<u8>(obj_core, arg_idx, KernelArg::SamplerNull)
+        },
+        ArgValue::Bytes(ref bytes) => {
+            core::set_kernel_arg::<u8>(obj_core, arg_idx, KernelArg::UnsafePointer {
+                size: bytes.len(), value: bytes.as_ptr() as *const _ })
+        },
+        ArgValue::Local(size) => {
+            core::set_kernel_arg::<u8>(obj_core, arg_idx, KernelArg::Local(&size))
+        },
+    }
+}
+
+
+/// Links `kernel` to the `ProQue` which created it.
+pub fn link_to_pro_que(kernel: &mut Kernel, link: ProQueLink) {
+    kernel.pro_que_link = Some(link);
+}
+
 
 impl std::fmt::Display for Kernel {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -626,6 +1921,23 @@ impl std::fmt::Display for Kernel {
     }
 }
 
+// Only `clSetKernelArg` (through `&mut self`) is unsafe to call
+// concurrently. Moving a kernel between threads is permitted by the spec.
+// Field by field:
+//
+// * `obj_core` (`KernelCore`): a `cl_kernel`, which may be used from any
+//   thread. Setting its arguments requires `&mut self`, and commands binding
+//   arguments (`KernelCmd`) borrow the kernel for their whole lifetime.
+// * `program` (`ProgramCore`) and `mem_args` (`MemCore`s): reference counted
+//   `OpenCL` objects, all thread-safe (`clRetain*`/`clRelease*` are atomic).
+// * `arg_values`: `ArgValue::Sampler` holds a `SamplerCore`, likewise a
+//   thread-safe reference counted `cl_sampler`. Other variants are plain
+//   data.
+// * `write_trackers` (`WriteTracker`) and `pro_que_link` (`ProQueLink`):
+//   `Arc<Mutex<..>>`s, `Send` in their own right.
+// * `queue` (`Queue`) and everything else: `Send` in their own right.
+unsafe impl Send for Kernel {}
+
 use std::ops::{Deref, DerefMut};
 
 impl Deref for Kernel {
@@ -641,3 +1953,50 @@ impl DerefMut for Kernel {
         &mut self.obj_core
     }
 }
+
+
+/// A kernel which can be shared between threads.
+///
+/// Cloning a `SyncKernel` is cheap and all clones refer to the same kernel.
+/// Access is serialized by a lock, which is only held while setting
+/// arguments or enqueuing:
+///
+/// ```text
+/// let kernel = pro_que.create_kernel("add")?.arg_buf(&buffer).arg_scl(1.0f32).into_sync();
+///
+/// for queue in queues {
+///     let kernel = kernel.clone();
+///     thread::spawn(move || kernel.lock().cmd().queue(&queue).enq());
+/// }
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct SyncKernel(Arc<Mutex<Kernel>>);
+
+impl SyncKernel {
+    /// Returns a new shareable kernel.
+    pub fn new(kernel: Kernel) -> SyncKernel {
+        SyncKernel(Arc::new(Mutex::new(kernel)))
+    }
+
+    /// Locks the kernel, blocking until no other thread is using it.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if another thread panicked while holding the lock.
+    ///
+    pub fn lock(&self) -> MutexGuard<Kernel> {
+        self.0.lock().expect("ocl::SyncKernel::lock()")
+    }
+
+    /// Enqueues the kernel on its default queue with its current arguments.
+    pub fn enq(&self) -> OclResult<()> {
+        self.lock().enq()
+    }
+}
+
+impl From<Kernel> for SyncKernel {
+    fn from(kernel: Kernel) -> SyncKernel {
+        SyncKernel::new(kernel)
+    }
+}
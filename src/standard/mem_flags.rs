@@ -0,0 +1,133 @@
+//! A builder for memory object flags.
+
+use core::error::{Error as OclError, Result as OclResult};
+use core::{MemFlags, MEM_READ_WRITE, MEM_WRITE_ONLY, MEM_READ_ONLY, MEM_USE_HOST_PTR,
+    MEM_ALLOC_HOST_PTR, MEM_COPY_HOST_PTR, MEM_HOST_WRITE_ONLY, MEM_HOST_READ_ONLY,
+    MEM_HOST_NO_ACCESS};
+
+
+/// Ensures that `flags` contains no mutually exclusive combinations:
+///
+/// - At most one of `MEM_READ_WRITE`, `MEM_WRITE_ONLY`, and `MEM_READ_ONLY`.
+/// - At most one of `MEM_HOST_WRITE_ONLY`, `MEM_HOST_READ_ONLY`, and
+///   `MEM_HOST_NO_ACCESS`.
+/// - `MEM_USE_HOST_PTR` with neither `MEM_ALLOC_HOST_PTR` nor
+///   `MEM_COPY_HOST_PTR`.
+///
+pub fn check_mem_flags(flags: MemFlags) -> OclResult<()> {
+    let count = |set: &[MemFlags]| set.iter().filter(|&&f| flags.contains(f)).count();
+
+    if count(&[MEM_READ_WRITE, MEM_WRITE_ONLY, MEM_READ_ONLY]) > 1 {
+        return OclError::err(format!("ocl::MemFlags: Only one of 'MEM_READ_WRITE', \
+            'MEM_WRITE_ONLY', and 'MEM_READ_ONLY' may be set (flags: {:?}).", flags));
+    }
+    if count(&[MEM_HOST_WRITE_ONLY, MEM_HOST_READ_ONLY, MEM_HOST_NO_ACCESS]) > 1 {
+        return OclError::err(format!("ocl::MemFlags: Only one of 'MEM_HOST_WRITE_ONLY', \
+            'MEM_HOST_READ_ONLY', and 'MEM_HOST_NO_ACCESS' may be set (flags: {:?}).", flags));
+    }
+    if flags.contains(MEM_USE_HOST_PTR) && count(&[MEM_ALLOC_HOST_PTR, MEM_COPY_HOST_PTR]) > 0 {
+        return OclError::err(format!("ocl::MemFlags: 'MEM_USE_HOST_PTR' may not be combined \
+            with 'MEM_ALLOC_HOST_PTR' or 'MEM_COPY_HOST_PTR' (flags: {:?}).", flags));
+    }
+    Ok(())
+}
+
+
+/// A builder for the flags used to create buffers and images.
+///
+/// Setting an access mode replaces any previously set access mode of the
+/// same kind (device or host), so only valid combinations can be built
+/// except for the host pointer flags, which are checked by `::build`.
+///
+/// ```text
+/// let flags = MemFlagsBuilder::new().read_only().alloc_host_ptr().build().unwrap();
+/// let buffer = Buffer::<f32>::new(queue, Some(flags), [1024], None).unwrap();
+/// ```
+///
+/// ## Defaults
+///
+/// - Device access: `MEM_READ_WRITE`
+/// - Host access: unrestricted
+///
+#[derive(Debug, Clone)]
+pub struct MemFlagsBuilder {
+    flags: MemFlags,
+}
+
+impl MemFlagsBuilder {
+    /// Returns a new builder with the default flags.
+    pub fn new() -> MemFlagsBuilder {
+        MemFlagsBuilder { flags: MEM_READ_WRITE }
+    }
+
+    fn device_access(&mut self, access: MemFlags) -> &mut MemFlagsBuilder {
+        self.flags.remove(MEM_READ_WRITE | MEM_WRITE_ONLY | MEM_READ_ONLY);
+        self.flags.insert(access);
+        self
+    }
+
+    fn host_access(&mut self, access: MemFlags) -> &mut MemFlagsBuilder {
+        self.flags.remove(MEM_HOST_WRITE_ONLY | MEM_HOST_READ_ONLY | MEM_HOST_NO_ACCESS);
+        self.flags.insert(access);
+        self
+    }
+
+    /// Kernels may read and write the memory (`MEM_READ_WRITE`).
+    pub fn read_write(&mut self) -> &mut MemFlagsBuilder {
+        self.device_access(MEM_READ_WRITE)
+    }
+
+    /// Kernels may only write the memory (`MEM_WRITE_ONLY`).
+    pub fn write_only(&mut self) -> &mut MemFlagsBuilder {
+        self.device_access(MEM_WRITE_ONLY)
+    }
+
+    /// Kernels may only read the memory (`MEM_READ_ONLY`).
+    pub fn read_only(&mut self) -> &mut MemFlagsBuilder {
+        self.device_access(MEM_READ_ONLY)
+    }
+
+    /// The host may only write the memory (`MEM_HOST_WRITE_ONLY`).
+    pub fn host_write_only(&mut self) -> &mut MemFlagsBuilder {
+        self.host_access(MEM_HOST_WRITE_ONLY)
+    }
+
+    /// The host may only read the memory (`MEM_HOST_READ_ONLY`).
+    pub fn host_read_only(&mut self) -> &mut MemFlagsBuilder {
+        self.host_access(MEM_HOST_READ_ONLY)
+    }
+
+    /// The host may neither read nor write the memory
+    /// (`MEM_HOST_NO_ACCESS`).
+    pub fn host_no_access(&mut self) -> &mut MemFlagsBuilder {
+        self.host_access(MEM_HOST_NO_ACCESS)
+    }
+
+    /// The memory is allocated from host accessible memory
+    /// (`MEM_ALLOC_HOST_PTR`).
+    pub fn alloc_host_ptr(&mut self) -> &mut MemFlagsBuilder {
+        self.flags.insert(MEM_ALLOC_HOST_PTR);
+        self
+    }
+
+    /// The memory is initialized with a copy of the host data passed on
+    /// creation (`MEM_COPY_HOST_PTR`).
+    pub fn copy_host_ptr(&mut self) -> &mut MemFlagsBuilder {
+        self.flags.insert(MEM_COPY_HOST_PTR);
+        self
+    }
+
+    /// The host data passed on creation is used as the storage of the
+    /// memory object (`MEM_USE_HOST_PTR`). See `Buffer::from_host_vec`.
+    pub fn use_host_ptr(&mut self) -> &mut MemFlagsBuilder {
+        self.flags.insert(MEM_USE_HOST_PTR);
+        self
+    }
+
+    /// Returns the flags or an error if they contain a mutually exclusive
+    /// combination.
+    pub fn build(&self) -> OclResult<MemFlags> {
+        try!(check_mem_flags(self.flags));
+        Ok(self.flags)
+    }
+}
@@ -22,30 +22,63 @@ mod pro_que;
 mod event;
 // mod event_list;
 mod spatial_dims;
+mod heat_map;
+mod stencil_plan;
+mod split_launch;
+mod error_hints;
+mod write_tracker;
+mod raw_handle;
+mod ocl_struct;
+mod pinned;
+mod mem_flags;
+mod task_graph;
+mod streaming;
+mod extension;
+mod native_kernel;
+#[cfg(feature = "ndarray")] mod ndarray_interop;
+#[cfg(feature = "opencl_version_2_1")] mod device_timer;
+#[cfg(feature = "futures")] mod future;
+#[cfg(feature = "image")] mod image_interop;
 // mod work_dims;
 
 // #[cfg(not(release))] pub use self::buffer::tests::BufferTest;
 pub use self::platform::Platform;
-pub use self::device::{Device, DeviceSpecifier};
+pub use self::device::{Device, DeviceSpecifier, PartitionProperty, Extensions, SubDevice};
+#[cfg(feature = "opencl_version_2_1")] pub use self::device_timer::TimerSync;
+#[cfg(feature = "futures")] pub use self::future::{FutureEvent, FutureRead};
+#[cfg(feature = "image")] pub use self::image_interop::ClPixel;
 // pub use self::device_specifier::DeviceSpecifier;
 // pub use self::context_builder::ContextBuilder;
-pub use self::context::{Context, ContextBuilder};
+pub use self::context::{Context, ContextBuilder, PrintfCapture};
 // pub use self::program_builder::{ProgramBuilder, BuildOpt};
-pub use self::program::{Program, ProgramBuilder, BuildOpt};
-pub use self::queue::Queue;
-pub use self::kernel::{Kernel, KernelCmd};
-pub use self::buffer::{Buffer, BufferCmd, BufferCmdKind, BufferCmdDataShape};
+pub use self::program::{Program, ProgramBuilder, BuildOpt, ClStd, OptFlag, BuildProfile};
+pub use self::queue::{Queue, QueueBuilder, CommandTimeline, CommandRecord};
+pub use self::kernel::{Kernel, KernelCmd, ArgInfo, ArgInfos, LocalMemBudget, SyncKernel};
+pub use self::buffer::{Buffer, BufferCmd, BufferCmdKind, BufferCmdDataShape, BufferMap};
 // pub use self::buffer_cmd::{BufferCmd, BufferCmdKind, BufferCmdDataShape};
 // pub use self::image_builder::ImageBuilder;
-pub use self::image::{Image, ImageCmd, ImageCmdKind, ImageBuilder};
+pub use self::image::{Image, ImageCmd, ImageCmdKind, ImageBuilder, FillColor, ImageMap, Tiles};
 // pub use self::image_cmd::{ImageCmd, ImageCmdKind};
-pub use self::sampler::Sampler;
+pub use self::sampler::{Sampler, SamplerBuilder};
+pub use self::ocl_struct::{OclStruct, StructField};
+pub use self::pinned::PinnedVec;
+pub use self::mem_flags::MemFlagsBuilder;
+pub use self::task_graph::{TaskGraph, Task};
+pub use self::streaming::StreamingBuffer;
+pub use self::extension::{CreateProgramWithIlKhrFn, TerminateContextKhrFn,
+    GetKernelSubGroupInfoKhrFn};
 // pub use self::pro_que_builder::ProQueBuilder;
-pub use self::pro_que::{ProQue, ProQueBuilder};
-pub use self::event::{Event, EventList};
+pub use self::pro_que::{ProQue, ProQueBuilder, ProgramHandle};
+pub use self::event::{Event, EventList, EventListIter, EventListIntoIter, CallbackTracker};
 // pub use self::event_list::EventList;
-pub use self::spatial_dims::SpatialDims;
-pub use self::traits::{MemLen, WorkDims};
+pub use self::spatial_dims::{SpatialDims, Indices};
+pub use self::heat_map::{HeatMap, HeatMapReport};
+pub use self::stencil_plan::{StencilPlan, BorderMode};
+pub use self::split_launch::SplitLaunch;
+pub use self::error_hints::{ErrorHints, status_hints};
+pub use self::write_tracker::{WriteTracker, WriteConflict};
+pub use self::raw_handle::{RawQueueHandle, RawMemHandle};
+pub use self::traits::{MemLen, WorkDims, ClTypeName, cl_type_name_of};
 
 
 //=============================================================================
@@ -60,11 +93,22 @@ pub use self::traits::{MemLen, WorkDims};
 
 mod traits {
     use std::fmt::Debug;
+    use std::any;
     // use std::convert::Into;
     use num::{Num, ToPrimitive};
     // use core::error::{Result as OclResult};
     use super::{SpatialDims};
     use super::spatial_dims::to_usize;
+    use core::{ClChar2, ClChar3, ClChar4, ClChar8, ClChar16,
+        ClUchar2, ClUchar3, ClUchar4, ClUchar8, ClUchar16,
+        ClShort2, ClShort3, ClShort4, ClShort8, ClShort16,
+        ClUshort2, ClUshort3, ClUshort4, ClUshort8, ClUshort16,
+        ClInt2, ClInt3, ClInt4, ClInt8, ClInt16,
+        ClUint2, ClUint3, ClUint4, ClUint8, ClUint16,
+        ClLong1, ClLong2, ClLong3, ClLong4, ClLong8, ClLong16,
+        ClUlong1, ClUlong2, ClUlong3, ClUlong4, ClUlong8, ClUlong16,
+        ClFloat2, ClFloat3, ClFloat4, ClFloat8, ClFloat16,
+        ClDouble2, ClDouble3, ClDouble4, ClDouble8, ClDouble16};
 
 
     /// Types which have properties describing the amount of work to be done
@@ -88,6 +132,67 @@ mod traits {
         fn to_work_offset(&self) -> Option<[usize; 3]>;
     }
 
+    /// Types which have a corresponding `OpenCL` C type, used when generating
+    /// kernel source.
+    ///
+    pub trait ClTypeName {
+        /// Returns the name of the equivalent `OpenCL` C type (e.g. `"float4"`).
+        fn cl_type_name() -> &'static str;
+
+        /// Returns the alignment of the equivalent `OpenCL` C type, in bytes.
+        ///
+        /// Scalars and vectors are aligned to their size, rounded up to a
+        /// power of two (three component vectors are aligned as four
+        /// component ones), which usually exceeds their Rust alignment.
+        fn cl_align() -> usize where Self: Sized {
+            ::std::mem::size_of::<Self>().next_power_of_two()
+        }
+    }
+
+    macro_rules! impl_cl_type_name {
+        ($( $ty:ty => $name:expr ),+) => {
+            $( impl ClTypeName for $ty {
+                fn cl_type_name() -> &'static str { $name }
+            } )+
+
+            /// Returns the name of the `OpenCL` C type equivalent to `T`, if
+            /// `T` implements `ClTypeName`.
+            ///
+            /// Unlike `ClTypeName::cl_type_name`, usable with any type,
+            /// including generic parameters bounded only by `OclPrm`.
+            pub fn cl_type_name_of<T>() -> Option<&'static str> {
+                let type_name = any::type_name::<T>();
+                $( if type_name == any::type_name::<$ty>() { return Some($name); } )+
+                None
+            }
+        }
+    }
+
+    impl_cl_type_name!(i8 => "char", u8 => "uchar", i16 => "short", u16 => "ushort",
+        i32 => "int", u32 => "uint", i64 => "long", u64 => "ulong", f32 => "float",
+        f64 => "double",
+        ClChar2 => "char2", ClChar3 => "char3", ClChar4 => "char4", ClChar8 => "char8",
+            ClChar16 => "char16",
+        ClUchar2 => "uchar2", ClUchar3 => "uchar3", ClUchar4 => "uchar4", ClUchar8 => "uchar8",
+            ClUchar16 => "uchar16",
+        ClShort2 => "short2", ClShort3 => "short3", ClShort4 => "short4", ClShort8 => "short8",
+            ClShort16 => "short16",
+        ClUshort2 => "ushort2", ClUshort3 => "ushort3", ClUshort4 => "ushort4",
+            ClUshort8 => "ushort8", ClUshort16 => "ushort16",
+        ClInt2 => "int2", ClInt3 => "int3", ClInt4 => "int4", ClInt8 => "int8",
+            ClInt16 => "int16",
+        ClUint2 => "uint2", ClUint3 => "uint3", ClUint4 => "uint4", ClUint8 => "uint8",
+            ClUint16 => "uint16",
+        ClLong1 => "long", ClLong2 => "long2", ClLong3 => "long3", ClLong4 => "long4",
+            ClLong8 => "long8", ClLong16 => "long16",
+        ClUlong1 => "ulong", ClUlong2 => "ulong2", ClUlong3 => "ulong3", ClUlong4 => "ulong4",
+            ClUlong8 => "ulong8", ClUlong16 => "ulong16",
+        ClFloat2 => "float2", ClFloat3 => "float3", ClFloat4 => "float4", ClFloat8 => "float8",
+            ClFloat16 => "float16",
+        ClDouble2 => "double2", ClDouble3 => "double3", ClDouble4 => "double4",
+            ClDouble8 => "double8", ClDouble16 => "double16"
+    );
+
     /// Types which have properties allowing them to be used to define the size
     /// of a volume of memory.
     ///
@@ -0,0 +1,123 @@
+//! Host closures enqueued as native kernels.
+
+use std;
+use std::ptr;
+use std::panic::{self, AssertUnwindSafe};
+use libc::c_void;
+use log::{log, error};
+use ffi::{self, cl_mem, cl_uint};
+use core::{Mem as MemCore, ClWaitList, ClEventPtrNew};
+use core::error::{Error as OclError, Result as OclResult};
+use standard::{Queue, Event};
+use standard::error_hints::status_err;
+
+
+/// Receives a native kernel call from the driver, calling and dropping the
+/// boxed closure referred to by the argument block.
+///
+/// The block holds the closure, the number of memory objects, and the
+/// memory objects themselves, replaced by the driver with host pointers.
+extern fn _native_kernel_callback<F>(args: *mut c_void)
+        where F: FnOnce(&[*mut c_void]) + Send + 'static
+{
+    let args = args as *mut *mut c_void;
+
+    let (callback, mems) = unsafe {
+        let callback = *Box::from_raw(*args as *mut F);
+        let count = *args.offset(1) as usize;
+        (callback, std::slice::from_raw_parts(args.offset(2) as *const *mut c_void, count))
+    };
+
+    // Unwinding into the driver's thread is undefined behavior:
+    if panic::catch_unwind(AssertUnwindSafe(move || callback(mems))).is_err() {
+        error!("ocl::Queue::enqueue_native_kernel: A native kernel closure panicked.");
+    }
+}
+
+
+impl Queue {
+    /// Enqueues `f` as a native kernel (`clEnqueueNativeKernel`) and returns
+    /// its event.
+    ///
+    /// `f` runs on a thread owned by the `OpenCL` implementation once every
+    /// event in `ewait` has completed, and takes part in event dependencies
+    /// like any other command. This allows a host side step in the middle
+    /// of a device pipeline without blocking the host.
+    ///
+    /// `f` is passed a host pointer to the contents of each memory object in
+    /// `mems` (use `Buffer::core_as_ref`), in order, valid for the duration
+    /// of the call:
+    ///
+    /// ```text
+    /// let event = queue.enqueue_native_kernel(&[buffer.core_as_ref()], &ewait, move |ptrs| {
+    ///     let data = unsafe { slice::from_raw_parts_mut(ptrs[0] as *mut f32, len) };
+    ///     for x in data.iter_mut() { *x = x.sqrt(); }
+    /// })?;
+    /// ```
+    ///
+    /// `f` is boxed and freed after it is called. If the command never
+    /// executes, for example because an event in `ewait` terminates with an
+    /// error, the box (and anything `f` owns) is leaked.
+    ///
+    /// ## Errors
+    ///
+    /// The queue's device must support native kernels (see
+    /// `Device::supports_native_kernels`). Usually only CPU devices do.
+    ///
+    pub fn enqueue_native_kernel<F>(&self, mems: &[&MemCore], ewait: &ClWaitList, f: F)
+            -> OclResult<Event>
+            where F: FnOnce(&[*mut c_void]) + Send + 'static
+    {
+        if !try!(self.device().supports_native_kernels()) {
+            return OclError::err(format!("ocl::Queue::enqueue_native_kernel(): The device '{}' \
+                does not support native kernels ('CL_EXEC_NATIVE_KERNEL').",
+                self.device().name()));
+        }
+
+        let callback_ptr = Box::into_raw(Box::new(f)) as *mut c_void;
+
+        // The argument block is copied by the driver:
+        let mut args: Vec<*mut c_void> = Vec::with_capacity(mems.len() + 2);
+        args.push(callback_ptr);
+        args.push(mems.len() as *mut c_void);
+        args.extend(mems.iter().map(|mem| unsafe { mem.as_ptr() } as *mut c_void));
+
+        let mem_list: Vec<cl_mem> = mems.iter().map(|mem| unsafe { mem.as_ptr() }).collect();
+        let args_mem_loc: Vec<*const c_void> = (0..mems.len())
+            .map(|i| &args[i + 2] as *const *mut c_void as *const c_void).collect();
+
+        let (wait_count, wait_ptr) = if ewait.count() > 0 {
+            (ewait.count(), unsafe { ewait.as_ptr_ptr() })
+        } else {
+            (0, ptr::null())
+        };
+
+        let mut event = Event::empty();
+        let event_ptr = try!(event.ptr_mut_ptr_new());
+
+        let errcode = unsafe { ffi::clEnqueueNativeKernel(
+            self.core_as_ref().as_ptr(),
+            Some(_native_kernel_callback::<F>),
+            args.as_mut_ptr() as *mut c_void,
+            args.len() * std::mem::size_of::<*mut c_void>(),
+            mem_list.len() as cl_uint,
+            if mem_list.is_empty() { ptr::null() } else { mem_list.as_ptr() },
+            if args_mem_loc.is_empty() { ptr::null() } else { args_mem_loc.as_ptr() },
+            wait_count,
+            wait_ptr,
+            event_ptr,
+        ) };
+
+        if errcode != ffi::CL_SUCCESS {
+            // The closure will never be called:
+            unsafe { drop(Box::from_raw(callback_ptr as *mut F)); }
+        }
+
+        try!(self.timeline().record_result("Queue::enqueue_native_kernel", 0,
+            if errcode == ffi::CL_SUCCESS { Ok(()) } else { Err(status_err(errcode,
+                "clEnqueueNativeKernel", "ocl::Queue::enqueue_native_kernel(): Error enqueuing \
+                command.".to_owned())) }));
+
+        Ok(event)
+    }
+}
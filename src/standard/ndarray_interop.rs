@@ -0,0 +1,157 @@
+//! Conversions between buffers and `ndarray` arrays.
+//!
+//! Requires the `ndarray` feature.
+
+use std;
+use ndarray::{ArrayBase, Data, DataMut, Dimension};
+use core::error::{Error as OclError, Result as OclResult};
+use core::{OclPrm, MemFlags};
+use standard::{Queue, Buffer, SpatialDims};
+
+
+/// Returns the region, host row and slice pitches (in elements), and the
+/// length of host memory spanned by an array with `shape` and `strides`
+/// which can be transferred with a single rectangular command.
+///
+/// Returns `None` if the array has more than three dimensions, negative
+/// strides, or a non-contiguous last axis.
+fn rect_params(shape: &[usize], strides: &[isize]) -> Option<([usize; 3], usize, usize, usize)> {
+    let ndim = shape.len();
+    if ndim == 0 || ndim > 3 || strides[ndim - 1] != 1 || strides.iter().any(|&s| s < 0) {
+        return None;
+    }
+
+    let mut region = [1usize; 3];
+    for i in 0..ndim {
+        region[i] = shape[ndim - 1 - i];
+    }
+
+    // The strides of axes of length one are irrelevant and may be anything:
+    let row_pitch = if ndim >= 2 && region[1] > 1 { strides[ndim - 2] as usize }
+        else { region[0] };
+    let slc_pitch = if ndim == 3 && region[2] > 1 { strides[0] as usize }
+        else { region[1] * row_pitch };
+
+    if row_pitch < region[0] || slc_pitch < region[1] * row_pitch {
+        return None;
+    }
+
+    let host_len = ((region[2] - 1) * slc_pitch) + ((region[1] - 1) * row_pitch) + region[0];
+    Some((region, row_pitch, slc_pitch, host_len))
+}
+
+fn check_len(buffer_len: usize, array_len: usize, caller: &str) -> OclResult<()> {
+    if buffer_len == array_len {
+        Ok(())
+    } else {
+        OclError::err(format!("ocl::Buffer::{}(): The length of the array ({}) does not match \
+            the length of the buffer ({}).", caller, array_len, buffer_len))
+    }
+}
+
+
+impl SpatialDims {
+    /// Returns the dimensions corresponding to an array with `shape`.
+    ///
+    /// Arrays are stored in row-major order, their last axis being
+    /// contiguous, so the order of the dimensions is reversed: the first
+    /// dimension (`get_global_id(0)` within a kernel) is the length of the
+    /// last axis.
+    ///
+    /// ## Errors
+    ///
+    /// `shape` must have between one and three dimensions.
+    ///
+    pub fn from_shape(shape: &[usize]) -> OclResult<SpatialDims> {
+        match shape.len() {
+            1 => Ok(SpatialDims::One(shape[0])),
+            2 => Ok(SpatialDims::Two(shape[1], shape[0])),
+            3 => Ok(SpatialDims::Three(shape[2], shape[1], shape[0])),
+            n => OclError::err(format!("ocl::SpatialDims::from_shape(): Arrays with {} \
+                dimensions are not supported. Only one, two, or three dimensions may be used.",
+                n)),
+        }
+    }
+}
+
+
+impl<T: OclPrm> Buffer<T> {
+    /// Creates a new buffer with dimensions matching those of `array` (see
+    /// `SpatialDims::from_shape`) and writes its contents.
+    ///
+    /// `flags` defaults to `flags::MEM_READ_WRITE` if `None` is passed.
+    ///
+    pub fn from_array<S, D>(queue: Queue, flags: Option<MemFlags>, array: &ArrayBase<S, D>)
+            -> OclResult<Buffer<T>>
+            where S: Data<Elem = T>, D: Dimension
+    {
+        let dims = try!(SpatialDims::from_shape(array.shape()));
+        let flags = flags.unwrap_or(::flags::MEM_READ_WRITE);
+        let buffer = try!(Buffer::new_unmapped(queue, Some(flags), dims));
+        try!(buffer.write_array(array));
+        Ok(buffer)
+    }
+
+    /// Writes the contents of `array` to this buffer, blocking until
+    /// complete.
+    ///
+    /// Arrays in standard (row-major) layout are written directly. Strided
+    /// arrays of up to three dimensions with a contiguous last axis, such as
+    /// slices of a larger array, are written with a single rectangular
+    /// command. Any other array is first copied into a temporary `Vec`.
+    ///
+    /// ## Errors
+    ///
+    /// The length of `array` must equal the length of this buffer.
+    ///
+    pub fn write_array<S, D>(&self, array: &ArrayBase<S, D>) -> OclResult<()>
+            where S: Data<Elem = T>, D: Dimension
+    {
+        try!(check_len(self.len(), array.len(), "write_array"));
+
+        if let Some(slice) = array.as_slice() {
+            return self.write(slice).enq();
+        }
+
+        match rect_params(array.shape(), array.strides()) {
+            Some((region, row_pitch, slc_pitch, host_len)) => {
+                let host = unsafe { std::slice::from_raw_parts(array.as_ptr(), host_len) };
+                self.write(host).rect([0, 0, 0], [0, 0, 0], region, 0, 0, row_pitch, slc_pitch)
+                    .enq()
+            },
+            None => {
+                let vec: Vec<T> = array.iter().cloned().collect();
+                self.write(&vec).enq()
+            },
+        }
+    }
+
+    /// Reads the contents of this buffer into `array`, blocking until
+    /// complete.
+    ///
+    /// Arrays in standard (row-major) layout are read directly. Any other
+    /// array is first read into a temporary `Vec`.
+    ///
+    /// ## Errors
+    ///
+    /// The length of `array` must equal the length of this buffer.
+    ///
+    pub fn read_array<S, D>(&self, array: &mut ArrayBase<S, D>) -> OclResult<()>
+            where S: DataMut<Elem = T>, D: Dimension
+    {
+        try!(check_len(self.len(), array.len(), "read_array"));
+
+        if let Some(slice) = array.as_slice_mut() {
+            return self.read(slice).enq();
+        }
+
+        // A rectangular read would need a mutable slice spanning elements
+        // between the rows which the array does not own.
+        let mut vec = vec![Default::default(); array.len()];
+        try!(self.read(&mut vec).enq());
+        for (dst, src) in array.iter_mut().zip(vec.into_iter()) {
+            *dst = src;
+        }
+        Ok(())
+    }
+}
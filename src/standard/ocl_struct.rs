@@ -0,0 +1,117 @@
+//! User-defined structs usable as buffer elements and kernel arguments.
+
+use std::mem;
+use core::OclPrm;
+use core::error::{Error as OclError, Result as OclResult};
+use standard::ClTypeName;
+
+
+/// A field of an `OclStruct`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructField {
+    name: &'static str,
+    cl_type_name: &'static str,
+    offset: usize,
+    size: usize,
+    cl_align: usize,
+}
+
+impl StructField {
+    /// Returns a new field description.
+    pub fn new(name: &'static str, cl_type_name: &'static str, offset: usize, size: usize,
+            cl_align: usize) -> StructField
+    {
+        StructField { name: name, cl_type_name: cl_type_name, offset: offset, size: size,
+            cl_align: cl_align }
+    }
+
+    /// Returns the field name.
+    pub fn name(&self) -> &'static str { self.name }
+
+    /// Returns the name of the field's `OpenCL` C type.
+    pub fn cl_type_name(&self) -> &'static str { self.cl_type_name }
+
+    /// Returns the offset of the field within the Rust struct, in bytes.
+    pub fn offset(&self) -> usize { self.offset }
+
+    /// Returns the size of the field within the Rust struct, in bytes.
+    pub fn size(&self) -> usize { self.size }
+
+    /// Returns the alignment of the field's `OpenCL` C type, in bytes.
+    pub fn cl_align(&self) -> usize { self.cl_align }
+}
+
+
+/// A plain-old-data struct which mirrors a struct declared in `OpenCL` C.
+///
+/// Implement using the `ocl_struct!` macro, which also implements `OclPrm`
+/// and `ClTypeName`, allowing the struct to be used as a buffer element
+/// (`Buffer<Particle>`) or scalar kernel argument (`::arg_scl`).
+///
+/// Rust and `OpenCL` C lay out the fields of a struct differently when a
+/// vector field follows a field not aligned to the vector's `OpenCL` C
+/// alignment (see `ClTypeName::cl_align`), which is larger than its Rust
+/// alignment. Call `::check_layout` before use, or add explicit padding
+/// fields, to avoid kernels reading fields at the wrong offsets.
+///
+pub trait OclStruct: OclPrm + ClTypeName {
+    /// Returns the fields of the struct, in declaration order.
+    fn fields() -> Vec<StructField>;
+
+    /// Returns the `OpenCL` C declaration of the struct, suitable for
+    /// prepending to program source.
+    fn cl_decl() -> String {
+        let mut decl = String::from("typedef struct {\n");
+        for field in Self::fields() {
+            decl.push_str(&format!("    {} {};\n", field.cl_type_name, field.name));
+        }
+        decl.push_str(&format!("}} {};\n", Self::cl_type_name()));
+        decl
+    }
+
+    /// Returns an error describing the first difference between the layout
+    /// of the Rust struct and that of its `OpenCL` C declaration.
+    fn check_layout() -> OclResult<()> {
+        let mut cl_offset = 0;
+        let mut cl_align = 1;
+
+        for field in Self::fields() {
+            // Three component vectors occupy as much space as four in `OpenCL` C:
+            let cl_size = round_up(field.size, field.cl_align);
+
+            if field.size != cl_size {
+                return OclError::err(format!("ocl::OclStruct::check_layout(): Field '{}.{}' \
+                    ({}) is {} bytes in Rust but {} bytes in OpenCL C.", Self::cl_type_name(),
+                    field.name, field.cl_type_name, field.size, cl_size));
+            }
+
+            cl_offset = round_up(cl_offset, field.cl_align);
+            cl_align = ::std::cmp::max(cl_align, field.cl_align);
+
+            if field.offset != cl_offset {
+                return OclError::err(format!("ocl::OclStruct::check_layout(): Field '{}.{}' \
+                    ({}) is at byte offset {} in Rust but {} in OpenCL C. Reorder the fields \
+                    from largest to smallest or add explicit padding fields before it.",
+                    Self::cl_type_name(), field.name, field.cl_type_name, field.offset,
+                    cl_offset));
+            }
+
+            cl_offset += field.size;
+        }
+
+        let cl_size = round_up(cl_offset, cl_align);
+
+        if mem::size_of::<Self>() != cl_size {
+            return OclError::err(format!("ocl::OclStruct::check_layout(): '{}' is {} bytes in \
+                Rust but {} bytes in OpenCL C. Add explicit padding fields at the end to make \
+                up the difference.", Self::cl_type_name(), mem::size_of::<Self>(), cl_size));
+        }
+
+        Ok(())
+    }
+}
+
+
+fn round_up(offset: usize, align: usize) -> usize {
+    if align == 0 { offset } else { ((offset + align - 1) / align) * align }
+}
@@ -9,6 +9,7 @@ use std;
 use std::ops::{Deref, DerefMut};
 use std::convert::Into;
 use core::{self, PlatformId as PlatformIdCore, PlatformInfo, PlatformInfoResult, ClPlatformIdPtr};
+use core::error::Result as OclResult;
 
 #[derive(Clone, Copy, Debug)]
 /// A platform identifier.
@@ -16,11 +17,26 @@ pub struct Platform(PlatformIdCore);
 
 impl Platform {
     /// Returns a list of all platforms avaliable on the host machine.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the platform list cannot be retrieved. Use `::try_list` to
+    /// receive an error instead.
     pub fn list() -> Vec<Platform> {
-        let list_core = core::get_platform_ids()
-            .expect("Platform::list: Error retrieving platform list");
+        Platform::try_list().expect("Platform::list: Error retrieving platform list")
+    }
 
-        list_core.into_iter().map(Platform::new).collect()
+    /// Returns a list of all platforms avaliable on the host machine or an
+    /// error if the list cannot be retrieved.
+    pub fn try_list() -> OclResult<Vec<Platform>> {
+        let list_core = try!(core::get_platform_ids());
+        Ok(list_core.into_iter().map(Platform::new).collect())
+    }
+
+    /// Returns the default platform (see `Platform::default`) or an error if
+    /// no platform is available.
+    pub fn try_default() -> OclResult<Platform> {
+        core::default_platform().map(Platform::new)
     }
 
     // DEPRICATED:
@@ -159,6 +175,11 @@ unsafe impl ClPlatformIdPtr for Platform {}
 unsafe impl<'a> ClPlatformIdPtr for &'a Platform {}
 
 impl Default for Platform {
+    /// Returns the default platform.
+    ///
+    /// This is the platform with the index given by the
+    /// `OCL_DEFAULT_PLATFORM_IDX` environment variable if it is set, or the
+    /// first platform otherwise (see `core::default_platform`).
     fn default() -> Platform {
         // let list_core = core::get_platform_ids()
         //     .expect("Platform::default: Error retrieving platform");
@@ -166,8 +187,7 @@ impl Default for Platform {
         // // let first_idx = list_core.len() - 1;
         // let first_idx = 0;
 
-        let dflt_plat_core = core::default_platform().expect("Platform::default()");
-        Platform::new(dflt_plat_core)
+        Platform::try_default().expect("Platform::default()")
     }
 }
 
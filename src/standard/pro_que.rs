@@ -2,16 +2,88 @@
 
 use std::convert::Into;
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use core::error::{Result as OclResult, Error as OclError};
-use core::OclPrm;
+use core::{self, OclPrm, MemFlags};
 use standard::{Platform, Device, Context, ProgramBuilder, Program, Queue, Kernel, Buffer,
-    MemLen, SpatialDims, WorkDims, DeviceSpecifier};
+    MemLen, SpatialDims, WorkDims, DeviceSpecifier, BuildOpt};
+use standard::kernel::link_to_pro_que;
 
 static DIMS_ERR_MSG: &'static str = "This 'ProQue' has not had any dimensions specified. Use
     'ProQueBuilder::dims' during creation or 'ProQue::set_dims' after creation to specify.";
 
 const DEBUG_PRINT: bool = false;
 
+
+/// State shared between a `ProQue`, its clones, and the kernels created
+/// from them.
+#[derive(Debug)]
+struct Shared {
+    program_gen: usize,
+    dims: Option<SpatialDims>,
+    additional_programs: Vec<Program>,
+}
+
+
+/// Identifies a program added to a `ProQue` with `ProQue::build_additional`.
+///
+/// Handles are shared by all clones of the `ProQue` which returned them.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgramHandle(usize);
+
+
+/// A kernel's link back to the `ProQue` which created it, through which it
+/// is notified of changes made to the `ProQue` afterwards.
+#[derive(Debug, Clone)]
+pub struct ProQueLink {
+    shared: Arc<Mutex<Shared>>,
+    program_gen: Option<usize>,
+    follows_dims: bool,
+}
+
+impl ProQueLink {
+    /// Stops the kernel's global work size from following the `ProQue`'s
+    /// dimensions. Called when a global work size is set explicitly.
+    pub fn unfollow_dims(&mut self) {
+        self.follows_dims = false;
+    }
+
+    /// Returns the `ProQue`'s current dimensions if the kernel's global work
+    /// size follows them.
+    pub fn dims(&self) -> Option<SpatialDims> {
+        if self.follows_dims {
+            self.shared.lock().expect("ocl::ProQueLink::dims()").dims
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the program the kernel was created from has since
+    /// been replaced. Additional programs are never replaced.
+    pub fn is_stale(&self) -> bool {
+        match self.program_gen {
+            Some(gen) => {
+                self.shared.lock().expect("ocl::ProQueLink::is_stale()").program_gen != gen
+            },
+            None => false,
+        }
+    }
+
+    /// Returns an error if the program the kernel was created from has since
+    /// been replaced.
+    pub fn check(&self, kernel_name: &str) -> OclResult<()> {
+        if self.is_stale() {
+            OclError::err(format!("ocl::Kernel::enq(): The kernel '{}' was created from a program \
+                which has since been replaced using 'ProQue::set_program'. Create a new kernel \
+                with 'ProQue::create_kernel' and set its arguments again.", kernel_name))
+        } else {
+            Ok(())
+        }
+    }
+}
+
 /// A builder for `ProQue`.
 pub struct ProQueBuilder {
     platform: Option<Platform>,
@@ -28,11 +100,14 @@ impl ProQueBuilder {
     /// The minimum amount of configuration possible before calling `::build` is to
     /// simply assign some source code using `::src`.
     ///
-    /// For full configuration options, separately create a `Context` and
+    /// Common program options can be set directly with `::src_file`,
+    /// `::define`, `::incl_dir`, `::cmplr_opt`, and `::bo`. For full
+    /// configuration options, separately create a `Context` and
     /// `ProgramBuilder` (do not `::build` the `ProgramBuilder`, its device
     /// list must be set by this `ProQueBuilder` to assure consistency) then
     /// pass them as arguments to the `::context` and `::prog_bldr` methods
-    /// respectively.
+    /// respectively, or modify the program builder in place with
+    /// `::prog_bldr_with`.
     ///
     pub fn new() -> ProQueBuilder {
         ProQueBuilder {
@@ -65,16 +140,18 @@ impl ProQueBuilder {
         // If no platform is set or no context platform is set, use the first available:
         let platform = match self.platform {
             Some(ref plt) => {
-                assert!(self.context.is_none(), "ocl::ProQueBuilder::build: \
-                    platform and context cannot both be set.");
+                if self.context.is_some() {
+                    return OclError::err("ocl::ProQueBuilder::build(): A platform and a context \
+                        cannot both be set. The platform of the context is always used.");
+                }
                 plt.clone()
             },
             None => match self.context {
                 Some(ref context) => match context.platform() {
                     Some(platform) => platform.clone(),
-                    None => Platform::default(),
+                    None => try!(Platform::try_default()),
                 },
-                None => Platform::default(),
+                None => try!(Platform::try_default()),
             },
         };
 
@@ -84,7 +161,12 @@ impl ProQueBuilder {
             Some(ref ds) => {
                 let device_list = try!(ds.to_device_list(Some(&platform)));
 
-                if device_list.len() == 1 {
+                let is_filter = match *ds {
+                    DeviceSpecifier::VendorContains(_) | DeviceSpecifier::NameContains(_) => true,
+                    _ => false,
+                };
+
+                if device_list.len() == 1 || (is_filter && !device_list.is_empty()) {
                     device_list[0]
                 } else {
                     return OclError::err(format!("Invalid number of devices specified ({}). Each 'ProQue' \
@@ -92,9 +174,25 @@ impl ProQueBuilder {
                         'Queue' separately for multi-device configurations.", device_list.len()));
                 }
             },
-            None => Device::first(platform),
+            None => match self.context {
+                // Use the first device of the context rather than the first
+                // device of its platform, which may not belong to it:
+                Some(ref context) => match context.devices().first() {
+                    Some(&device) => device,
+                    None => return OclError::err("ocl::ProQueBuilder::build(): The context \
+                        has no devices."),
+                },
+                None => try!(Device::default_for(platform)),
+            },
         };
 
+        if let Some(ref context) = self.context {
+            if !context.devices().iter().any(|d| unsafe { d.as_ptr() == device.as_ptr() }) {
+                return OclError::err(format!("ocl::ProQueBuilder::build(): The device specified \
+                    ('{}') is not associated with the context.", device.name()));
+            }
+        }
+
         if DEBUG_PRINT { println!("ProQue::build(): device: {:?}", device); }
 
         // If no context was set, creates one using the above platform and
@@ -113,39 +211,35 @@ impl ProQueBuilder {
 
         let queue = try!(Queue::new(&context, device));
 
-        // println!("PROQUEBUILDER: About to load SRC_STRINGS.");
-        let src_strings = try!(program_builder.get_src_strings().map_err(|e| e.to_string()));
-        // println!("PROQUEBUILDER: About to load CMPLR_OPTS.");
-        let cmplr_opts = try!(program_builder.get_compiler_options().map_err(|e| e.to_string()));
-        // println!("PROQUEBUILDER: All done.");
-
-        let program = try!(Program::new(
-            src_strings,
-            cmplr_opts,
-            &context,
-            &[device],
-        ));
+        let program = try!(program_builder.build_with_devices(&context, &[device]));
 
         Ok(ProQue::new(context, queue, program, self.dims))
     }
 
     /// Sets the platform to be used and returns the builder.
     ///
-    /// # Panics
+    /// Defaults to `Platform::default`, which honors the
+    /// `OCL_DEFAULT_PLATFORM_IDX` environment variable.
+    ///
+    /// # Errors
     ///
-    /// If context is set, this will panic upon building. Only one or the other
-    /// can be configured.
+    /// If a context is also set, building will return an error. Only one or
+    /// the other can be configured.
     pub fn platform(&mut self, platform: Platform) -> &mut ProQueBuilder {
         self.platform = Some(platform);
         self
     }
 
-    /// Sets the context and returns the `ProQueBuilder`.
+    /// Sets an existing context to be used and returns the `ProQueBuilder`.
     ///
-    /// # Panics
+    /// Unless a device is specified with `::device`, the first device of the
+    /// context is used. A device which is specified must belong to the
+    /// context.
     ///
-    /// If platform is set, this will panic upon building. Only one or the other
-    /// can be configured.
+    /// # Errors
+    ///
+    /// If a platform is also set, building will return an error. Only one or
+    /// the other can be configured.
     pub fn context(&mut self, context: Context) -> &mut ProQueBuilder {
         self.context = Some(context);
         self
@@ -154,7 +248,21 @@ impl ProQueBuilder {
     /// Sets a device or devices to be used and returns a `ProQueBuilder`
     /// reference.
     ///
-    /// Must specify only a single device.
+    /// Must specify only a single device, except when filtering by vendor or
+    /// name (`DeviceSpecifier::VendorContains` or `NameContains`), in which
+    /// case the first matching device is used.
+    ///
+    /// Defaults to `Device::default_for` the platform, which honors the
+    /// `OCL_DEFAULT_DEVICE_IDX` environment variable, or to the first device
+    /// of the context if one has been set with `::context`. For example, to
+    /// prefer a discrete card over an integrated GPU enumerated before it:
+    ///
+    /// ```text
+    /// let pro_que = ProQue::builder()
+    ///     .device(Device::specifier().vendor_contains("nvidia"))
+    ///     .src(src)
+    ///     .build()?;
+    /// ```
     ///
     pub fn device<D: Into<DeviceSpecifier>>(&mut self, device_spec: D)
             -> &mut ProQueBuilder
@@ -178,20 +286,70 @@ impl ProQueBuilder {
 
     /// Adds some source code to be compiled and returns the `ProQueBuilder`.
     ///
-    /// Creates a `ProgramBuilder` if one has not already been added. Attempts
-    /// to call `::program_builder` after calling this method will cause a panic.
-    ///
-    /// If you need a more complex build configuration or to add multiple
-    /// source files. Pass an *unbuilt* `ProgramBuilder` to the
-    /// `::program_builder` method (described below).
+    /// Creates a `ProgramBuilder` if one has not already been added. May be
+    /// called any number of times, including after `::prog_bldr`, to add
+    /// more source strings (see `ProgramBuilder::src`).
     pub fn src<S: Into<String>>(&mut self, src: S) -> &mut ProQueBuilder {
-        if self.program_builder.is_some() {
-            panic!("ocl::ProQueBuilder::src: Cannot set src if a 'ProgramBuilder' is already \
-                defined. Please use the '::program_builder' method for more complex build \
-                configurations.");
-        } else {
-            self.program_builder = Some(Program::builder().src(src))
-        }
+        self.prog_bldr_with(|pb| pb.src(src))
+    }
+
+    /// Adds the contents of a file to the program (see
+    /// `ProgramBuilder::src_file`).
+    pub fn src_file<P: Into<PathBuf>>(&mut self, file_path: P) -> &mut ProQueBuilder {
+        self.prog_bldr_with(|pb| pb.src_file(file_path))
+    }
+
+    /// Adds a compiler command line definition, formatted as
+    /// `-D {name}={val}` (see `ProgramBuilder::define`).
+    pub fn define<S: Into<String>, V: ToString>(&mut self, name: S, val: V)
+            -> &mut ProQueBuilder
+    {
+        self.prog_bldr_with(|pb| pb.define(name, val))
+    }
+
+    /// Adds an include directory (see `ProgramBuilder::incl_dir`).
+    pub fn incl_dir<P: AsRef<Path>>(&mut self, path: P) -> &mut ProQueBuilder {
+        self.prog_bldr_with(|pb| pb.incl_dir(path))
+    }
+
+    /// Adds a raw compiler option (see `ProgramBuilder::cmplr_opt`).
+    pub fn cmplr_opt<S: Into<String>>(&mut self, co: S) -> &mut ProQueBuilder {
+        self.prog_bldr_with(|pb| pb.cmplr_opt(co))
+    }
+
+    /// Adds a build option (see `ProgramBuilder::bo`).
+    pub fn bo(&mut self, bo: BuildOpt) -> &mut ProQueBuilder {
+        self.prog_bldr_with(|pb| pb.bo(bo))
+    }
+
+    /// Modifies the `ProgramBuilder` in place, creating an empty one if none
+    /// has been added, and returns the `ProQueBuilder`.
+    ///
+    /// Gives access to every program option, for example:
+    ///
+    /// ```text
+    /// let pro_que = ProQue::builder()
+    ///     .src(src)
+    ///     .prog_bldr_with(|pb| pb.cl_std(ClStd::Cl1_2).cache_dir("target/kernels"))
+    ///     .dims([1024])
+    ///     .build()?;
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// The `ProgramBuilder` may not be given any devices, as described for
+    /// `::prog_bldr`.
+    ///
+    pub fn prog_bldr_with<F>(&mut self, f: F) -> &mut ProQueBuilder
+            where F: FnOnce(ProgramBuilder) -> ProgramBuilder
+    {
+        let program_builder = f(self.program_builder.take().unwrap_or_else(Program::builder));
+
+        assert!(program_builder.get_device_spec().is_none(), "ProQueBuilder::prog_bldr_with(): \
+            The 'ProgramBuilder' may not have any devices set as they will be unused. See \
+            'ProQueBuilder' documentation for more information.");
+
+        self.program_builder = Some(program_builder);
         self
     }
 
@@ -199,7 +357,8 @@ impl ProQueBuilder {
     ///
     /// ## Panics
     ///
-    /// This `ProQueBuilder` may not already contain a `ProgramBuilder`.
+    /// This `ProQueBuilder` may not already contain a `ProgramBuilder`,
+    /// including one created by `::src` or another program option.
     ///
     /// `program_builder` must not have any device indices configured (via its
     /// `::device_idxs` method). `ProQueBuilder` will only build programs for
@@ -207,8 +366,8 @@ impl ProQueBuilder {
     /// been specified.
     pub fn prog_bldr(&mut self, program_builder: ProgramBuilder) -> &mut ProQueBuilder {
         assert!(self.program_builder.is_none(), "ProQueBuilder::prog_bldr(): Cannot set the \
-            'ProgramBuilder' using this method after one has already been set or after '::src' has \
-            been called.");
+            'ProgramBuilder' using this method after one has already been set or after '::src' or \
+            another program option has been used.");
 
         assert!(program_builder.get_device_spec().is_none(), "ProQueBuilder::prog_bldr(): The \
             'ProgramBuilder' passed may not have any device indices set as they will be unused. \
@@ -263,7 +422,7 @@ pub struct ProQue {
     context: Context,
     queue: Queue,
     program: Program,
-    dims: Option<SpatialDims>,
+    shared: Arc<Mutex<Shared>>,
 }
 
 impl ProQue {
@@ -288,17 +447,108 @@ impl ProQue {
             context: context,
             queue: queue,
             program: program,
-            dims: dims.map(|d| d.into()),
+            shared: Arc::new(Mutex::new(Shared {
+                program_gen: 0,
+                dims: dims.map(|d| d.into()),
+                additional_programs: Vec::new(),
+            })),
         }
     }
 
     /// Creates a kernel with pre-assigned dimensions.
+    ///
+    /// The kernel remains linked to this `ProQue` (and its clones). Until a
+    /// global work size is set on the kernel explicitly, its global work size
+    /// follows the dimensions set with `::set_dims`. Once the program is
+    /// replaced with `::set_program`, enqueuing the kernel returns an error.
     pub fn create_kernel(&self, name: &str) -> OclResult<Kernel> {
-        let kernel = try!(Kernel::new(name.to_string(), &self.program, &self.queue));
+        let program_gen = self.shared.lock().expect("ocl::ProQue::create_kernel()").program_gen;
+        self.create_linked_kernel(name, &self.program, Some(program_gen))
+    }
+
+    /// Creates a kernel with pre-assigned dimensions from a program added
+    /// with `::build_additional`.
+    ///
+    /// The kernel follows the dimensions set with `::set_dims` as described
+    /// for `::create_kernel`. It is unaffected by `::set_program`.
+    ///
+    /// # Errors
+    ///
+    /// `handle` must have been returned by this `ProQue` or one of its
+    /// clones.
+    ///
+    pub fn create_kernel_from(&self, handle: ProgramHandle, name: &str) -> OclResult<Kernel> {
+        let program = try!(self.additional_program(handle));
+        self.create_linked_kernel(name, &program, None)
+    }
+
+    fn create_linked_kernel(&self, name: &str, program: &Program, program_gen: Option<usize>)
+            -> OclResult<Kernel>
+    {
+        let mut kernel = try!(Kernel::new(name.to_string(), program, &self.queue));
 
-        match self.dims {
-            Some(d) => Ok(kernel.gws(d)),
-            None => Ok(kernel),
+        if let Ok(d) = self.dims_result() {
+            kernel = kernel.gws(d);
+        }
+
+        link_to_pro_que(&mut kernel, ProQueLink {
+            shared: self.shared.clone(),
+            program_gen: program_gen,
+            follows_dims: true,
+        });
+
+        Ok(kernel)
+    }
+
+    /// Builds an additional program from `src` for the device of this
+    /// `ProQue`, returning a handle used to create kernels from it with
+    /// `::create_kernel_from`.
+    ///
+    /// Allows separately maintained sources to share a context and queue
+    /// without being concatenated into one program.
+    ///
+    pub fn build_additional<S: Into<String>>(&self, src: S) -> OclResult<ProgramHandle> {
+        self.build_additional_with(Program::builder().src(src))
+    }
+
+    /// Builds an additional program using a pre-configured (unbuilt)
+    /// `ProgramBuilder`. See `::build_additional`.
+    ///
+    /// # Errors
+    ///
+    /// `program_builder` must not have any devices specified.
+    ///
+    pub fn build_additional_with(&self, program_builder: ProgramBuilder)
+            -> OclResult<ProgramHandle>
+    {
+        if program_builder.get_device_spec().is_some() {
+            return OclError::err("ocl::ProQue::build_additional_with(): The 'ProgramBuilder' \
+                may not have any devices specified. Programs are always built for the device of \
+                the 'ProQue'.");
+        }
+
+        let program = try!(program_builder.build_with_devices(&self.context,
+            &[*self.queue.device()]));
+
+        let mut shared = self.shared.lock().expect("ocl::ProQue::build_additional_with()");
+        shared.additional_programs.push(program);
+        Ok(ProgramHandle(shared.additional_programs.len() - 1))
+    }
+
+    /// Returns the additional program identified by `handle`.
+    ///
+    /// # Errors
+    ///
+    /// `handle` must have been returned by this `ProQue` or one of its
+    /// clones.
+    ///
+    pub fn additional_program(&self, handle: ProgramHandle) -> OclResult<Program> {
+        let shared = self.shared.lock().expect("ocl::ProQue::additional_program()");
+        match shared.additional_programs.get(handle.0) {
+            Some(program) => Ok(program.clone()),
+            None => OclError::err(format!("ocl::ProQue::additional_program(): Invalid program \
+                handle ({:?}). Handles are only valid for the 'ProQue' which returned them.",
+                handle)),
         }
     }
 
@@ -317,9 +567,64 @@ impl ProQue {
         Buffer::<T>::new(self.queue.clone(), None, dims, None)
     }
 
+    /// Returns a new, uninitialized, buffer with the default dimensions for
+    /// this `ProQue` which is never accessed by the host. See
+    /// `Buffer::new_unmapped`.
+    ///
+    /// # Errors
+    ///
+    /// This `ProQue` must have been pre-configured with default dimensions.
+    ///
+    pub fn create_buffer_unmapped<T: OclPrm>(&self) -> OclResult<Buffer<T>> {
+        let dims = try!(self.dims_result());
+        Buffer::<T>::new_unmapped(self.queue.clone(), None, dims)
+    }
+
+    /// Returns a new buffer, with the default dimensions for this `ProQue`,
+    /// initialized with a copy of `data`.
+    ///
+    /// `flags` defaults to `MEM_READ_WRITE`. `MEM_COPY_HOST_PTR` is always
+    /// added.
+    ///
+    /// # Errors
+    ///
+    /// This `ProQue` must have been pre-configured with default dimensions
+    /// and the length of `data` must equal their length.
+    ///
+    pub fn create_buffer_with_data<T: OclPrm>(&self, flags: Option<MemFlags>, data: &[T])
+            -> OclResult<Buffer<T>>
+    {
+        let dims = try!(self.dims_result());
+
+        if data.len() != dims.to_len() {
+            return OclError::err(format!("ocl::ProQue::create_buffer_with_data(): The length \
+                of 'data' ({}) does not match the length of the default dimensions ({}).",
+                data.len(), dims.to_len()));
+        }
+
+        let flags = flags.unwrap_or(core::MEM_READ_WRITE) | core::MEM_COPY_HOST_PTR;
+        Buffer::<T>::new(self.queue.clone(), Some(flags), dims, Some(data))
+    }
+
     /// Sets the default dimensions used when creating buffers and kernels.
+    ///
+    /// The dimensions are shared by all clones of this `ProQue`. Kernels
+    /// previously created with `::create_kernel`, whose global work size has
+    /// not since been set explicitly, use the new dimensions the next time
+    /// they are enqueued. Existing buffers are not resized.
     pub fn set_dims<S: Into<SpatialDims>>(&mut self, dims: S) {
-        self.dims = Some(dims.into());
+        self.shared.lock().expect("ocl::ProQue::set_dims()").dims = Some(dims.into());
+    }
+
+    /// Replaces the program used to create kernels, for example with one
+    /// rebuilt from modified source or build options.
+    ///
+    /// Kernels previously created with `::create_kernel` are marked stale:
+    /// enqueuing one returns an error explaining that it must be recreated,
+    /// rather than silently running the old program's kernel.
+    pub fn set_program(&mut self, program: Program) {
+        self.program = program;
+        self.shared.lock().expect("ocl::ProQue::set_program()").program_gen += 1;
     }
 
     /// Returns the maximum workgroup size supported by the device associated
@@ -349,7 +654,7 @@ impl ProQue {
     ///
     /// [UNSTABLE]: Evaluate which 'dims' method to keep. Leaning towards this
     /// version at the moment.
-    pub fn dims(&self) -> &SpatialDims {
+    pub fn dims(&self) -> SpatialDims {
         self.dims_result().expect(DIMS_ERR_MSG)
     }
 
@@ -357,9 +662,9 @@ impl ProQue {
     ///
     /// [UNSTABLE]: Evaluate which 'dims' method to keep. Leaning towards the
     /// above, panicing version at the moment.
-    pub fn dims_result(&self) -> OclResult<&SpatialDims> {
-        match self.dims {
-            Some(ref dims) => Ok(dims),
+    pub fn dims_result(&self) -> OclResult<SpatialDims> {
+        match self.shared.lock().expect("ocl::ProQue::dims_result()").dims {
+            Some(dims) => Ok(dims),
             None => OclError::err(DIMS_ERR_MSG),
         }
     }
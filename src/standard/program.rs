@@ -2,16 +2,327 @@
 use std;
 use std::ops::{Deref, DerefMut};
 use std::ffi::CString;
-use std::io::Read;
-use std::fs::File;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
 use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::convert::Into;
+use std::ptr;
+use log::{log, info, warn};
 
 use core::error::{Result as OclResult, Error as OclError};
-use core::{self, Program as ProgramCore, Context as ContextCore,
-    ProgramInfo, ProgramInfoResult, ProgramBuildInfo, ProgramBuildInfoResult};
+use ffi::{self, cl_program, cl_device_id, cl_int, cl_uint};
+use core::{self, Program as ProgramCore, Context as ContextCore, ClDeviceIdPtr, Status,
+    ProgramInfo, ProgramInfoResult, ProgramBuildInfo, ProgramBuildInfoResult, DeviceInfo};
 use standard::{Context, Device, DeviceSpecifier};
+use standard::heat_map;
+use standard::error_hints::status_err;
+
+
+/// Maps double precision types, conversions, and constants to their single
+/// precision equivalents. See `ProgramBuilder::fp64_shim`.
+const FP64_SHIM_SRC: &'static str = r#"
+    #define OCL_FP64_SHIM 1
+    #define double float
+    #define double2 float2
+    #define double3 float3
+    #define double4 float4
+    #define double8 float8
+    #define double16 float16
+    #define convert_double convert_float
+    #define convert_double2 convert_float2
+    #define convert_double3 convert_float3
+    #define convert_double4 convert_float4
+    #define convert_double8 convert_float8
+    #define convert_double16 convert_float16
+    #ifndef DBL_MAX
+        #define DBL_DIG FLT_DIG
+        #define DBL_EPSILON FLT_EPSILON
+        #define DBL_MANT_DIG FLT_MANT_DIG
+        #define DBL_MAX FLT_MAX
+        #define DBL_MAX_10_EXP FLT_MAX_10_EXP
+        #define DBL_MAX_EXP FLT_MAX_EXP
+        #define DBL_MIN FLT_MIN
+        #define DBL_MIN_10_EXP FLT_MIN_10_EXP
+        #define DBL_MIN_EXP FLT_MIN_EXP
+    #endif
+    #ifndef M_PI
+        #define M_E M_E_F
+        #define M_LOG2E M_LOG2E_F
+        #define M_LOG10E M_LOG10E_F
+        #define M_LN2 M_LN2_F
+        #define M_LN10 M_LN10_F
+        #define M_PI M_PI_F
+        #define M_PI_2 M_PI_2_F
+        #define M_PI_4 M_PI_4_F
+        #define M_1_PI M_1_PI_F
+        #define M_2_PI M_2_PI_F
+        #define M_2_SQRTPI M_2_SQRTPI_F
+        #define M_SQRT2 M_SQRT2_F
+        #define M_SQRT1_2 M_SQRT1_2_F
+    #endif
+"#;
+
+/// Returns true if `device` supports double precision floating point.
+fn device_has_fp64(device: &Device) -> bool {
+    device.supports_fp64().unwrap_or(false)
+}
+
+
+/// Returns `binary` as a string if it is text, ignoring trailing nul bytes.
+fn binary_as_text(binary: &[u8]) -> Option<String> {
+    let len = binary.iter().rposition(|&b| b != 0).map(|pos| pos + 1).unwrap_or(0);
+    if len == 0 { return None; }
+
+    match std::str::from_utf8(&binary[..len]) {
+        Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) =>
+            Some(text.to_owned()),
+        _ => None,
+    }
+}
+
+
+/// The maximum number of source excerpts added to a build failure message.
+const MAX_BUILD_EXCERPTS: usize = 16;
+
+/// Returns the one-based line and column of a compiler message in the
+/// common `<source>:line:col: error: ...` form.
+fn parse_line_col(log_line: &str) -> Option<(usize, usize)> {
+    let parts: Vec<&str> = log_line.split(':').collect();
+
+    for i in 1..parts.len().saturating_sub(2) {
+        if let (Ok(line), Ok(col)) = (parts[i].trim().parse::<usize>(),
+                parts[i + 1].trim().parse::<usize>()) {
+            if line > 0 { return Some((line, col)); }
+        }
+    }
+    None
+}
+
+/// Returns the line `line` of `src` followed by a caret marking `col`.
+fn source_excerpt(src: &str, line: usize, col: usize) -> Option<String> {
+    let src_line = match src.lines().nth(line - 1) {
+        Some(l) => l,
+        None => return None,
+    };
+
+    // Preserve tabs so that the caret lines up:
+    let pad: String = src_line.chars().take(col.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+    let gutter = format!("{}", line).len();
+
+    Some(format!("{:>w$} | {}\n{:>w$} | {}^", line, src_line, "", pad, w = gutter))
+}
+
+/// Returns the name of the source fragment containing `line` of the combined
+/// source and the line within that fragment.
+///
+/// `fragments` holds the name and first line of each fragment, in order.
+fn locate_line<'a>(fragments: &[(&'a str, usize)], line: usize) -> Option<(&'a str, usize)> {
+    fragments.iter().rev()
+        .find(|&&(_, start)| start <= line)
+        .map(|&(name, start)| (name, line - start + 1))
+}
+
+/// Appends the originating fragment (see `locate_line`) and an excerpt of
+/// `src` to each compiler message in `log` which refers to a line and
+/// column.
+fn annotate_build_log(log: &str, src: &str, fragments: &[(&str, usize)]) -> String {
+    let mut annotated = String::with_capacity(log.len());
+    let mut excerpts = 0;
+
+    for log_line in log.lines() {
+        annotated.push_str(log_line);
+        annotated.push('\n');
+
+        if excerpts < MAX_BUILD_EXCERPTS {
+            if let Some((line, col)) = parse_line_col(log_line) {
+                if let Some(excerpt) = source_excerpt(src, line, col) {
+                    if let Some((name, frag_line)) = locate_line(fragments, line) {
+                        annotated.push_str(&format!("  --> {}:{}:{}\n", name, frag_line, col));
+                    }
+                    annotated.push_str(&excerpt);
+                    annotated.push('\n');
+                    excerpts += 1;
+                }
+            }
+        }
+    }
+    annotated
+}
+
+
+/// An `OpenCL` C language version, passed to the compiler as `-cl-std`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClStd {
+    Cl1_1,
+    Cl1_2,
+    Cl2_0,
+}
+
+impl ClStd {
+    fn as_opt(&self) -> &'static str {
+        match *self {
+            ClStd::Cl1_1 => "-cl-std=CL1.1",
+            ClStd::Cl1_2 => "-cl-std=CL1.2",
+            ClStd::Cl2_0 => "-cl-std=CL2.0",
+        }
+    }
+}
+
+
+/// An optimization or math option recognized by all `OpenCL` compilers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptFlag {
+    /// `-cl-opt-disable`
+    OptDisable,
+    /// `-cl-mad-enable`
+    MadEnable,
+    /// `-cl-no-signed-zeros`
+    NoSignedZeros,
+    /// `-cl-unsafe-math-optimizations`
+    UnsafeMathOptimizations,
+    /// `-cl-finite-math-only`
+    FiniteMathOnly,
+    /// `-cl-fast-relaxed-math`
+    FastRelaxedMath,
+    /// `-cl-single-precision-constant`
+    SinglePrecisionConstant,
+    /// `-cl-denorms-are-zero`
+    DenormsAreZero,
+}
+
+impl OptFlag {
+    fn as_opt(&self) -> &'static str {
+        match *self {
+            OptFlag::OptDisable => "-cl-opt-disable",
+            OptFlag::MadEnable => "-cl-mad-enable",
+            OptFlag::NoSignedZeros => "-cl-no-signed-zeros",
+            OptFlag::UnsafeMathOptimizations => "-cl-unsafe-math-optimizations",
+            OptFlag::FiniteMathOnly => "-cl-finite-math-only",
+            OptFlag::FastRelaxedMath => "-cl-fast-relaxed-math",
+            OptFlag::SinglePrecisionConstant => "-cl-single-precision-constant",
+            OptFlag::DenormsAreZero => "-cl-denorms-are-zero",
+        }
+    }
+}
+
+
+/// A device vendor, as far as compiler options are concerned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Vendor {
+    Nvidia,
+    Amd,
+    Intel,
+    Other,
+}
+
+impl Vendor {
+    fn of(device: &Device) -> Vendor {
+        let vendor = device.vendor().to_lowercase();
+
+        if vendor.contains("nvidia") {
+            Vendor::Nvidia
+        } else if vendor.contains("advanced micro devices") || vendor.contains("amd") {
+            Vendor::Amd
+        } else if vendor.contains("intel") {
+            Vendor::Intel
+        } else {
+            Vendor::Other
+        }
+    }
+}
+
+
+/// A named set of compiler options, expanded to the vendor specific flags
+/// of the devices a program is built for.
+///
+/// Profiles which do not apply to a device's vendor are skipped with a
+/// warning (logged using the `log` crate). Output which the compiler writes
+/// to the build log is logged at the `info` level after a successful build.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildProfile {
+    /// NVIDIA: Reports register, local, and constant memory usage per kernel
+    /// in the build log (`-cl-nv-verbose`).
+    NvidiaVerbose,
+    /// NVIDIA: Limits the number of registers per work item
+    /// (`-cl-nv-maxrregcount`).
+    NvidiaMaxRegisters(u32),
+    /// AMD: Saves intermediate files, including the ISA disassembly, to the
+    /// working directory (`-save-temps`).
+    AmdDumpIsa,
+    /// AMD and Intel: Generates debugging information (`-g`).
+    Debug,
+    /// All vendors: Enables optimizations which may violate IEEE 754
+    /// (`-cl-fast-relaxed-math -cl-mad-enable`).
+    FastMath,
+}
+
+impl BuildProfile {
+    /// Returns the compiler options for `vendor` or `None` if the profile
+    /// does not apply.
+    fn options(&self, vendor: Vendor) -> Option<Vec<String>> {
+        match (*self, vendor) {
+            (BuildProfile::NvidiaVerbose, Vendor::Nvidia) =>
+                Some(vec!["-cl-nv-verbose".to_owned()]),
+            (BuildProfile::NvidiaMaxRegisters(count), Vendor::Nvidia) =>
+                Some(vec![format!("-cl-nv-maxrregcount={}", count)]),
+            (BuildProfile::AmdDumpIsa, Vendor::Amd) => Some(vec!["-save-temps".to_owned()]),
+            (BuildProfile::Debug, Vendor::Amd) | (BuildProfile::Debug, Vendor::Intel) =>
+                Some(vec!["-g".to_owned()]),
+            (BuildProfile::FastMath, _) => Some(vec![OptFlag::FastRelaxedMath.as_opt().to_owned(),
+                OptFlag::MadEnable.as_opt().to_owned()]),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the profile causes the compiler to write to the build
+    /// log.
+    fn logs_output(&self) -> bool {
+        *self == BuildProfile::NvidiaVerbose
+    }
+}
+
+
+/// Returns `arg`, quoted and escaped if it contains whitespace, quotes, or
+/// backslashes.
+/// Returns true if `ident` is a valid macro name, optionally followed by a
+/// parameter list without whitespace (`NAME` or `NAME(a,b)`).
+fn is_define_ident(ident: &str) -> bool {
+    fn is_name(s: &str) -> bool {
+        !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    match ident.find('(') {
+        Some(idx) => {
+            let params = &ident[idx + 1..];
+            is_name(&ident[..idx]) && params.ends_with(')') &&
+                (params.len() == 1 || params[..params.len() - 1].split(',')
+                    .all(|p| is_name(p) || p == "..."))
+        },
+        None => is_name(ident),
+    }
+}
+
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.chars().any(|c| c.is_whitespace() || c == '"' || c == '\\') {
+        return arg.to_owned();
+    }
+
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+
+    for c in arg.chars() {
+        if c == '"' || c == '\\' { quoted.push('\\'); }
+        quoted.push(c);
+    }
+
+    quoted.push('"');
+    quoted
+}
 
 
 /// A build option used by ProgramBuilder.
@@ -21,13 +332,16 @@ use standard::{Context, Device, DeviceSpecifier};
 ///
 /// A few of the often used variants have constructors for convenience.
 ///
-/// [FIXME] TODO: Explain how each variant is used.
+/// Compiler definitions and include directories are quoted and escaped as
+/// necessary when passed to the compiler. `CmplrOther` is passed verbatim.
 ///
 /// [FIXME] TODO: Examples.
 #[derive(Clone, Debug)]
 pub enum BuildOpt {
     CmplrDefine { ident: String, val: String },
     CmplrInclDir { path: String },
+    CmplrStd(ClStd),
+    CmplrOpt(OptFlag),
     CmplrOther(String),
     IncludeDefine { ident: String, val: String },
     IncludeRaw(String),
@@ -43,6 +357,21 @@ impl BuildOpt {
         }
     }
 
+    /// Returns a `BuildOpt::CmplrDefine` with a value of any type.
+    pub fn define<S: Into<String>, V: ToString>(ident: S, val: V) -> BuildOpt {
+        BuildOpt::CmplrDefine {
+            ident: ident.into(),
+            val: val.to_string(),
+        }
+    }
+
+    /// Returns a `BuildOpt::CmplrInclDir`.
+    pub fn incl_dir<P: AsRef<Path>>(path: P) -> BuildOpt {
+        BuildOpt::CmplrInclDir {
+            path: path.as_ref().to_string_lossy().into_owned(),
+        }
+    }
+
     /// Returns a `BuildOpt::IncludeDefine`.
     pub fn include_def<S: Into<String>>(ident: S, val: String) -> BuildOpt {
         BuildOpt::IncludeDefine {
@@ -50,19 +379,38 @@ impl BuildOpt {
             val: val,
         }
     }
+
+    /// Returns the compiler command line text for this option, if any.
+    fn to_cmplr_opt(&self) -> OclResult<Option<String>> {
+        match *self {
+            BuildOpt::CmplrDefine { ref ident, ref val } => {
+                if !is_define_ident(ident) {
+                    return OclError::err(format!("ocl::BuildOpt: Invalid definition \
+                        identifier: '{}'.", ident));
+                }
+                Ok(Some(format!("-D {}={}", ident, quote_arg(val))))
+            },
+            BuildOpt::CmplrInclDir { ref path } => Ok(Some(format!("-I {}", quote_arg(path)))),
+            BuildOpt::CmplrStd(std) => Ok(Some(std.as_opt().to_owned())),
+            BuildOpt::CmplrOpt(flag) => Ok(Some(flag.as_opt().to_owned())),
+            BuildOpt::CmplrOther(ref s) => Ok(Some(s.clone())),
+            _ => Ok(None),
+        }
+    }
 }
 
 
 /// A builder for `Program`.
-///
-// [SOMEDAY TODO]: Keep track of line number range for each string and print
-// out during build failure.
-//
 #[derive(Clone, Debug)]
 pub struct ProgramBuilder {
     options: Vec<BuildOpt>,
     src_files: Vec<PathBuf>,
     device_spec: Option<DeviceSpecifier>,
+    cache_dir: Option<PathBuf>,
+    headers: Vec<(String, String)>,
+    compile_only: bool,
+    fp64_shim: bool,
+    profiles: Vec<BuildProfile>,
 }
 
 impl ProgramBuilder {
@@ -72,6 +420,11 @@ impl ProgramBuilder {
             options: Vec::with_capacity(64),
             src_files: Vec::with_capacity(16),
             device_spec: None,
+            cache_dir: None,
+            headers: Vec::new(),
+            compile_only: false,
+            fp64_shim: false,
+            profiles: Vec::new(),
         }
     }
 
@@ -92,11 +445,96 @@ impl ProgramBuilder {
             return OclError::err("ocl::ProgramBuilder::build: No devices found.");
         }
 
-        Program::new(
-            try!(self.get_src_strings().map_err(|e| e.to_string())),
-            try!(self.get_compiler_options().map_err(|e| e.to_string())),
-            context,
-            &device_list[..])
+        self.build_with_devices(context, &device_list[..])
+    }
+
+    /// Builds a program for an explicit list of devices, ignoring any device
+    /// specifier, and using the binary cache if one has been set.
+    pub fn build_with_devices(&self, context: &Context, devices: &[Device]) -> OclResult<Program> {
+        if self.fp64_shim {
+            let lacking: Vec<String> = devices.iter().filter(|d| !device_has_fp64(d))
+                .map(|d| d.name()).collect();
+
+            if !lacking.is_empty() {
+                warn!("ocl::ProgramBuilder: The device(s) {:?} do not support double precision. \
+                    The fp64 shim is enabled: ALL DOUBLE PRECISION MATH IN THIS PROGRAM WILL BE \
+                    COMPUTED IN SINGLE PRECISION ON ALL {} DEVICE(S) IT IS BUILT FOR AND \
+                    RESULTS WILL ONLY BE APPROXIMATE.", lacking, devices.len());
+
+                let mut shimmed = self.clone();
+                shimmed.fp64_shim = false;
+                shimmed.options.insert(0, BuildOpt::IncludeRaw(FP64_SHIM_SRC.to_owned()));
+                shimmed.options.push(BuildOpt::CmplrOpt(OptFlag::SinglePrecisionConstant));
+                return shimmed.build_with_devices(context, devices);
+            }
+        }
+
+        if !self.profiles.is_empty() {
+            return self.build_with_profiles(context, devices);
+        }
+
+        let (src_strings, src_names) = try!(self.get_src_fragments()
+            .map_err(|e| e.to_string()));
+        let cmplr_opts = try!(self.get_compiler_options().map_err(|e| e.to_string()));
+
+        if self.compile_only {
+            return Program::compile_named(src_strings, &src_names, cmplr_opts, &self.headers,
+                context, devices);
+        }
+
+        match self.cache_dir {
+            Some(ref dir) => Program::with_binary_cache_named(src_strings, &src_names,
+                cmplr_opts, context, devices, dir),
+            None => Program::new_named(src_strings, &src_names, cmplr_opts, context, devices),
+        }
+    }
+
+    /// Expands each build profile to the options of the vendors of `devices`
+    /// and builds.
+    ///
+    /// Program options are shared by all devices, so a profile whose options
+    /// differ between the vendors present is an error.
+    fn build_with_profiles(&self, context: &Context, devices: &[Device]) -> OclResult<Program> {
+        let mut vendors: Vec<Vendor> = Vec::with_capacity(devices.len());
+        for device in devices {
+            let vendor = Vendor::of(device);
+            if !vendors.contains(&vendor) { vendors.push(vendor); }
+        }
+
+        let mut expanded = self.clone();
+        expanded.profiles.clear();
+        let mut added: HashSet<String> = HashSet::new();
+
+        for profile in &self.profiles {
+            let vendor_opts: Vec<_> = vendors.iter().map(|&v| profile.options(v)).collect();
+
+            if vendor_opts.iter().any(|opts| *opts != vendor_opts[0]) {
+                return OclError::err(format!("ocl::ProgramBuilder: The build profile '{:?}' \
+                    expands to different options for the vendors {:?} of the devices {:?}. \
+                    Build a separate program for each vendor.", profile, vendors,
+                    devices.iter().map(|d| d.name()).collect::<Vec<_>>()));
+            }
+
+            if let Some(opts) = vendor_opts.into_iter().next().and_then(|opts| opts) {
+                for opt in opts {
+                    if added.insert(opt.clone()) {
+                        expanded.options.push(BuildOpt::CmplrOther(opt));
+                    }
+                }
+            } else {
+                warn!("ocl::ProgramBuilder: The build profile '{:?}' does not apply to any of \
+                    the devices {:?} and has been skipped.", profile,
+                    devices.iter().map(|d| d.vendor()).collect::<Vec<_>>());
+            }
+        }
+
+        let program = try!(expanded.build_with_devices(context, devices));
+
+        if self.profiles.iter().any(|p| p.logs_output()) {
+            info!("ocl::ProgramBuilder: Build log:\n{}", program.build_logs());
+        }
+
+        Ok(program)
     }
 
     /// Adds a build option containing a compiler command line definition.
@@ -111,6 +549,98 @@ impl ProgramBuilder {
         self
     }
 
+    /// Adds a build option containing a compiler command line definition
+    /// with a value of any type. Formatted as `-D {name}={val}`, quoting `val`
+    /// if necessary.
+    ///
+    /// `name` may define a function-like macro with a parameter list
+    /// containing no whitespace.
+    ///
+    /// ## Example
+    ///
+    /// `...define("GREETING", "hello world")...`
+    /// `...define("SQUARE(x)", "((x) * (x))")...`
+    ///
+    pub fn define<S: Into<String>, V: ToString>(mut self, name: S, val: V) -> ProgramBuilder {
+        self.options.push(BuildOpt::define(name, val));
+        self
+    }
+
+    /// Adds an include directory. Formatted as `-I {path}`, quoting `path` if
+    /// necessary.
+    pub fn incl_dir<P: AsRef<Path>>(mut self, path: P) -> ProgramBuilder {
+        self.options.push(BuildOpt::incl_dir(path));
+        self
+    }
+
+    /// Selects the `OpenCL` C language version to compile against.
+    pub fn cl_std(mut self, std: ClStd) -> ProgramBuilder {
+        self.options.push(BuildOpt::CmplrStd(std));
+        self
+    }
+
+    /// Adds an optimization or math option.
+    pub fn opt(mut self, flag: OptFlag) -> ProgramBuilder {
+        self.options.push(BuildOpt::CmplrOpt(flag));
+        self
+    }
+
+    /// Defines the `OCL_HEAT_MAP_ARG` and `OCL_HEAT_MAP_COUNT(n)` macros used
+    /// to instrument kernels for a `HeatMap`. When `enabled` is `false` both
+    /// macros expand to nothing.
+    ///
+    /// See [`HeatMap`](/ocl/ocl/struct.HeatMap.html) for details.
+    pub fn heat_map(mut self, enabled: bool) -> ProgramBuilder {
+        let src = if enabled {
+            heat_map::HEAT_MAP_ENABLED_SRC
+        } else {
+            heat_map::HEAT_MAP_DISABLED_SRC
+        };
+        self.options.push(BuildOpt::IncludeRaw(src.to_owned()));
+        self
+    }
+
+    /// Enables the fp64 compatibility shim (default: disabled).
+    ///
+    /// When enabled and any device the program is built for lacks double
+    /// precision support, a header is included before all other source which
+    /// maps `double` (and its vector types, conversions, and constants) to
+    /// `float`, floating point constants are made single precision, and a
+    /// warning is logged using the `log` crate. `OCL_FP64_SHIM` is defined
+    /// in that case. Programs are built unchanged only if every device
+    /// supports double precision.
+    ///
+    /// A program shares its source and options between all of its devices:
+    /// when building for several devices of which only some lack double
+    /// precision support, the shim applies to *all* of them. Build a
+    /// separate program for each device to keep full precision on those
+    /// supporting it.
+    ///
+    /// Intended for running kernels written for double precision on
+    /// consumer devices for exploratory, approximate, results only.
+    /// Reinterpretation (`as_double`) and kernel arguments of type `double`
+    /// are not translated: the host must pass `f32` values instead.
+    ///
+    pub fn fp64_shim(mut self, enabled: bool) -> ProgramBuilder {
+        self.fp64_shim = enabled;
+        self
+    }
+
+    /// Adds a named compiler option profile, expanded to the vendor specific
+    /// flags of the devices the program is built for.
+    ///
+    /// Building fails if the program's devices are of several vendors for
+    /// which the profile expands to different flags.
+    ///
+    /// ## Example
+    ///
+    /// `...profile(BuildProfile::AmdDumpIsa)...`
+    ///
+    pub fn profile(mut self, profile: BuildProfile) -> ProgramBuilder {
+        if !self.profiles.contains(&profile) { self.profiles.push(profile); }
+        self
+    }
+
     /// Adds a build option containing a raw compiler command line parameter.
     /// Formatted as `{}` (exact text).
     ///
@@ -160,31 +690,53 @@ impl ProgramBuilder {
         self
     }
 
+    /// Enables the on-disk program binary cache, storing compiled binaries
+    /// in the directory `dir` (created if necessary).
+    ///
+    /// See [`Program::with_binary_cache`](/ocl/ocl/struct.Program.html#method.with_binary_cache)
+    /// for details.
+    pub fn cache_dir<P: Into<PathBuf>>(mut self, dir: P) -> ProgramBuilder {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Adds an embedded header, available to the program source via
+    /// `#include "{include_name}"`. Only used when compiling with
+    /// `::compile_only`.
+    pub fn header<S: Into<String>, T: Into<String>>(mut self, include_name: S, src: T)
+            -> ProgramBuilder
+    {
+        self.headers.push((include_name.into(), src.into()));
+        self
+    }
+
+    /// Compiles, but does not link, the program when built. The result can
+    /// then be linked with other compiled programs using `Program::link`.
+    ///
+    /// The binary cache is not used for compiled-only programs.
+    pub fn compile_only(mut self) -> ProgramBuilder {
+        self.compile_only = true;
+        self
+    }
+
     /// Returns the devices specified to be associated the program.
     pub fn get_device_spec(&self) -> &Option<DeviceSpecifier> {
         &self.device_spec
     }
 
+    /// Returns the binary cache directory, if set.
+    pub fn get_cache_dir(&self) -> Option<&Path> {
+        self.cache_dir.as_ref().map(|d| d.as_path())
+    }
+
     /// Returns a contatenated string of command line options to be passed to
     /// the compiler when building this program.
     pub fn get_compiler_options(&self) -> OclResult<CString> {
         let mut opts: Vec<String> = Vec::with_capacity(64);
 
         for option in &self.options {
-            match *option {
-                BuildOpt::CmplrDefine { ref ident, ref val } => {
-                    opts.push(format!("-D {}={}", ident, val))
-                },
-
-                BuildOpt::CmplrInclDir { ref path } => {
-                    opts.push(format!("-I {}", path))
-                },
-
-                BuildOpt::CmplrOther(ref s) => {
-                    opts.push(s.clone())
-                },
-
-                _ => (),
+            if let Some(opt) = try!(option.to_cmplr_opt()) {
+                opts.push(opt);
             }
         }
 
@@ -201,11 +753,22 @@ impl ProgramBuilder {
     /// 3. Contents of strings specified via `::src` or a
     ///   `BuildOpt::IncludeRawEof` via `::bo`
     ///
+    /// Any number of files and strings may be added. If the build fails,
+    /// compiler messages are attributed to the file or string (numbered
+    /// from one, in order) containing the line referred to.
+    ///
     pub fn get_src_strings(&self) -> OclResult<Vec<CString>> {
+        self.get_src_fragments().map(|(src_strings, _)| src_strings)
+    }
+
+    /// Returns the source strings (see `::get_src_strings`) along with a
+    /// name for each, used to attribute build errors.
+    fn get_src_fragments(&self) -> OclResult<(Vec<CString>, Vec<String>)> {
         let mut src_strings: Vec<CString> = Vec::with_capacity(64);
         let mut src_file_history: HashSet<PathBuf> = HashSet::with_capacity(64);
 
         src_strings.extend_from_slice(&try!(self.get_includes()));
+        let mut src_names = vec!["[build options]".to_owned(); src_strings.len()];
 
         for srcpath in &self.src_files {
             let mut src_bytes: Vec<u8> = Vec::with_capacity(100000);
@@ -218,11 +781,15 @@ impl ProgramBuilder {
             try!(src_file_handle.read_to_end(&mut src_bytes));
             src_bytes.shrink_to_fit();
             src_strings.push(try!(CString::new(src_bytes)));
+            src_names.push(srcpath.display().to_string());
         }
 
-        src_strings.extend_from_slice(&try!(self.get_includes_eof()));
+        let includes_eof = try!(self.get_includes_eof());
+        // The first string is a separator:
+        src_names.extend((0..includes_eof.len()).map(|i| format!("[source string {}]", i)));
+        src_strings.extend_from_slice(&includes_eof);
 
-        Ok(src_strings)
+        Ok((src_strings, src_names))
     }
 
     /// Parses `self.options` for options intended for inclusion at the beginning of
@@ -299,15 +866,345 @@ impl Program {
     pub fn new(src_strings: Vec<CString>, cmplr_opts: CString, context_obj_core: &ContextCore,
                 device_ids: &[Device]) -> OclResult<Program>
     {
-        let obj_core = try!(core::create_build_program(context_obj_core, &src_strings, &cmplr_opts,
-             device_ids).map_err(|e| e.to_string()));
+        Program::new_named(src_strings, &[], cmplr_opts, context_obj_core, device_ids)
+    }
+
+    /// Returns a new program, attributing build errors to the named source
+    /// strings (see `ProgramBuilder::get_src_strings`).
+    fn new_named(src_strings: Vec<CString>, src_names: &[String], cmplr_opts: CString,
+                context_obj_core: &ContextCore, device_ids: &[Device]) -> OclResult<Program>
+    {
+        let obj_core = try!(core::create_program_with_source(context_obj_core, &src_strings));
 
-        Ok(Program {
+        let program = Program {
+            obj_core: obj_core,
+            devices: Vec::from(device_ids),
+        };
+
+        if let Err(err) = core::build_program(&program.obj_core, device_ids, &cmplr_opts, None,
+                None) {
+            if err.status() != Some(Status::CL_BUILD_PROGRAM_FAILURE) { return Err(err); }
+
+            return Err(status_err(Status::CL_BUILD_PROGRAM_FAILURE as cl_int, "clBuildProgram",
+                format!("ocl::Program::new: Build failed:\n\n{}",
+                program.build_diagnostics(&src_strings, src_names))));
+        }
+
+        Ok(program)
+    }
+
+    /// Returns a new program, loading previously compiled binaries from
+    /// `cache_dir` when available and otherwise building from source and
+    /// storing the resulting binaries there.
+    ///
+    /// Cached binaries are keyed by a hash of the source strings, compiler
+    /// options, and the name and driver version of each device. A binary
+    /// which fails to load or build is ignored and the program is rebuilt
+    /// from source. Failures to write the cache are also ignored.
+    ///
+    /// The hash is not guaranteed to be stable between Rust versions; a
+    /// change simply causes a rebuild.
+    ///
+    pub fn with_binary_cache(src_strings: Vec<CString>, cmplr_opts: CString,
+                context_obj_core: &ContextCore, device_ids: &[Device], cache_dir: &Path)
+            -> OclResult<Program>
+    {
+        Program::with_binary_cache_named(src_strings, &[], cmplr_opts, context_obj_core,
+            device_ids, cache_dir)
+    }
+
+    /// Returns a new program using a binary cache (see `::with_binary_cache`),
+    /// attributing build errors to the named source strings.
+    fn with_binary_cache_named(src_strings: Vec<CString>, src_names: &[String],
+                cmplr_opts: CString, context_obj_core: &ContextCore, device_ids: &[Device],
+                cache_dir: &Path) -> OclResult<Program>
+    {
+        let paths = Program::binary_cache_paths(&src_strings, &cmplr_opts, device_ids, cache_dir);
+
+        if let Some(program) = Program::load_binaries(&cmplr_opts, context_obj_core, device_ids,
+                &paths) {
+            return Ok(program);
+        }
+
+        let program = try!(Program::new_named(src_strings, src_names, cmplr_opts,
+            context_obj_core, device_ids));
+
+        if let ProgramInfoResult::Binaries(binaries) = program.info(ProgramInfo::Binaries) {
+            if fs::create_dir_all(cache_dir).is_ok() {
+                for (path, binary) in paths.iter().zip(binaries.iter()) {
+                    let _ = File::create(path).and_then(|mut f| f.write_all(binary));
+                }
+            }
+        }
+
+        Ok(program)
+    }
+
+    /// Returns the path of the cached binary for each device.
+    fn binary_cache_paths(src_strings: &[CString], cmplr_opts: &CString, device_ids: &[Device],
+            cache_dir: &Path) -> Vec<PathBuf>
+    {
+        let mut hasher = DefaultHasher::new();
+        src_strings.hash(&mut hasher);
+        cmplr_opts.hash(&mut hasher);
+
+        for device in device_ids {
+            device.name().hash(&mut hasher);
+            let driver_version: String = device.info(DeviceInfo::DriverVersion).into();
+            driver_version.hash(&mut hasher);
+        }
+
+        let key = hasher.finish();
+        (0..device_ids.len()).map(|i| cache_dir.join(format!("{:016x}-{}.bin", key, i))).collect()
+    }
+
+    /// Creates and builds a program from cached binaries, returning `None`
+    /// if any are missing or unusable.
+    fn load_binaries(cmplr_opts: &CString, context_obj_core: &ContextCore, device_ids: &[Device],
+            paths: &[PathBuf]) -> Option<Program>
+    {
+        let mut binaries = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let mut binary = Vec::new();
+            match File::open(path).and_then(|mut f| f.read_to_end(&mut binary)) {
+                Ok(_) => binaries.push(binary),
+                Err(_) => return None,
+            }
+        }
+
+        let binary_refs: Vec<&[u8]> = binaries.iter().map(|b| &b[..]).collect();
+
+        let obj_core = match core::create_program_with_binary(context_obj_core, device_ids,
+                &binary_refs) {
+            Ok(p) => p,
+            Err(_) => return None,
+        };
+
+        match core::build_program(&obj_core, device_ids, cmplr_opts, None, None) {
+            Ok(_) => Some(Program {
+                obj_core: obj_core,
+                devices: Vec::from(device_ids),
+            }),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns a new compiled, but unlinked, program.
+    ///
+    /// `headers` is a list of `(include_name, source)` pairs which may be
+    /// included by name from `src_strings`.
+    ///
+    /// Prefer `::builder` with `ProgramBuilder::compile_only`.
+    ///
+    pub fn compile(src_strings: Vec<CString>, cmplr_opts: CString, headers: &[(String, String)],
+                context_obj_core: &ContextCore, device_ids: &[Device]) -> OclResult<Program>
+    {
+        Program::compile_named(src_strings, &[], cmplr_opts, headers, context_obj_core,
+            device_ids)
+    }
+
+    /// Returns a new compiled program, attributing compilation errors to the
+    /// named source strings.
+    fn compile_named(src_strings: Vec<CString>, src_names: &[String], cmplr_opts: CString,
+                headers: &[(String, String)], context_obj_core: &ContextCore,
+                device_ids: &[Device]) -> OclResult<Program>
+    {
+        let obj_core = try!(core::create_program_with_source(context_obj_core, &src_strings));
+
+        let mut header_progs = Vec::with_capacity(headers.len());
+        let mut header_names = Vec::with_capacity(headers.len());
+
+        for &(ref name, ref src) in headers {
+            header_progs.push(try!(core::create_program_with_source(context_obj_core,
+                &[try!(CString::new(src.clone()))])));
+            header_names.push(try!(CString::new(name.clone())));
+        }
+
+        let device_ptrs: Vec<cl_device_id> = device_ids.iter()
+            .map(|d| unsafe { d.as_ptr() }).collect();
+        let header_ptrs: Vec<cl_program> = header_progs.iter()
+            .map(|p| unsafe { p.as_ptr() }).collect();
+        let name_ptrs: Vec<*const _> = header_names.iter().map(|n| n.as_ptr()).collect();
+
+        let errcode = unsafe { ffi::clCompileProgram(
+            obj_core.as_ptr(),
+            device_ptrs.len() as cl_uint,
+            device_ptrs.as_ptr(),
+            cmplr_opts.as_ptr(),
+            header_ptrs.len() as cl_uint,
+            if header_ptrs.is_empty() { ptr::null() } else { header_ptrs.as_ptr() },
+            if name_ptrs.is_empty() { ptr::null() } else { name_ptrs.as_ptr() },
+            None,
+            ptr::null_mut(),
+        ) };
+
+        let program = Program {
             obj_core: obj_core,
             devices: Vec::from(device_ids),
+        };
+
+        if errcode != ffi::CL_SUCCESS {
+            return Err(status_err(errcode, "clCompileProgram", format!(
+                "ocl::Program::compile: Compilation failed:\n\n{}",
+                program.build_diagnostics(&src_strings, src_names))));
+        }
+
+        Ok(program)
+    }
+
+    /// Links a list of compiled programs (see `ProgramBuilder::compile_only`)
+    /// into a new executable program.
+    ///
+    /// The new program is associated with the context and devices of the
+    /// first program in the list. Use `-create-library` within `options` to
+    /// produce a library rather than an executable.
+    ///
+    pub fn link(programs: &[Program], options: &str) -> OclResult<Program> {
+        let first = match programs.first() {
+            Some(p) => p,
+            None => return OclError::err("ocl::Program::link: No programs to link."),
+        };
+
+        let context_obj_core = match first.info(ProgramInfo::Context) {
+            ProgramInfoResult::Context(context) => context,
+            _ => return OclError::err("ocl::Program::link: Unable to determine program context."),
+        };
+
+        let options = try!(CString::new(options));
+        let device_ptrs: Vec<cl_device_id> = first.devices.iter()
+            .map(|d| unsafe { d.as_ptr() }).collect();
+        let program_ptrs: Vec<cl_program> = programs.iter()
+            .map(|p| unsafe { p.obj_core.as_ptr() }).collect();
+        let mut errcode: cl_int = 0;
+
+        let program_ptr = unsafe { ffi::clLinkProgram(
+            context_obj_core.as_ptr(),
+            device_ptrs.len() as cl_uint,
+            device_ptrs.as_ptr(),
+            options.as_ptr(),
+            program_ptrs.len() as cl_uint,
+            program_ptrs.as_ptr(),
+            None,
+            ptr::null_mut(),
+            &mut errcode,
+        ) };
+
+        if program_ptr.is_null() || errcode != ffi::CL_SUCCESS {
+            let logs: Vec<String> = programs.iter().map(|p| p.build_logs()).collect();
+            return Err(status_err(errcode, "clLinkProgram", format!(
+                "ocl::Program::link: Linking failed:\n\n{}", logs.join("\n"))));
+        }
+
+        Ok(Program {
+            obj_core: unsafe { ProgramCore::from_fresh_ptr(program_ptr) },
+            devices: first.devices.clone(),
         })
     }
 
+    /// Returns the build logs of every device, each preceded by the device
+    /// name.
+    fn build_logs(&self) -> String {
+        let mut logs = String::new();
+
+        for &device in &self.devices {
+            let log: String = self.build_info(device, ProgramBuildInfo::BuildLog).into();
+            logs.push_str(&format!("{}:\n{}\n", device.name(), log));
+        }
+
+        logs
+    }
+
+    /// Returns the build logs of every device with an excerpt of the source
+    /// marking each line and column referred to by the compiler.
+    ///
+    /// Each message is attributed to the source string containing the line,
+    /// named by `src_names` if given.
+    fn build_diagnostics(&self, src_strings: &[CString], src_names: &[String]) -> String {
+        let default_names: Vec<String>;
+        let src_names = if src_names.len() == src_strings.len() {
+            src_names
+        } else {
+            default_names = (0..src_strings.len())
+                .map(|i| format!("[source string {}]", i)).collect();
+            &default_names[..]
+        };
+
+        // The strings are compiled as a single source:
+        let mut src = String::new();
+        let mut fragments = Vec::with_capacity(src_strings.len());
+
+        for (src_string, name) in src_strings.iter().zip(src_names.iter()) {
+            let text = src_string.to_string_lossy();
+
+            // Skip separators and other strings without code:
+            if !text.trim().is_empty() {
+                fragments.push((name.as_str(), src.matches('\n').count() + 1));
+            }
+            src.push_str(&text);
+        }
+
+        let mut logs = String::new();
+
+        for &device in &self.devices {
+            let log: String = self.build_info(device, ProgramBuildInfo::BuildLog).into();
+            logs.push_str(&format!("{}:\n{}\n", device.name(),
+                annotate_build_log(&log, &src, &fragments)));
+        }
+
+        logs
+    }
+
+    /// Returns the textual intermediate code or assembly generated for
+    /// `device`, where the vendor exposes it through the program binary.
+    ///
+    /// NVIDIA devices return PTX assembly. Other platforms returning a
+    /// textual binary (such as LLVM IR or SPIR) are also supported.
+    ///
+    /// ### Errors
+    ///
+    /// Returns an error if `device` is not associated with this program or
+    /// its binary is not text. AMD binaries are ELF objects: build with
+    /// `BuildProfile::AmdDumpIsa` to have the compiler save the ISA to the
+    /// working directory instead.
+    ///
+    pub fn intermediate_code(&self, device: Device) -> OclResult<String> {
+        let idx = match self.devices.iter()
+            .position(|d| unsafe { d.as_ptr() == device.as_ptr() }) {
+            Some(idx) => idx,
+            None => return OclError::err(format!("ocl::Program::intermediate_code(): The device \
+                '{}' is not associated with this program.", device.name())),
+        };
+
+        let binaries = match self.info(ProgramInfo::Binaries) {
+            ProgramInfoResult::Binaries(binaries) => binaries,
+            ProgramInfoResult::Error(err) => return Err(*err),
+            _ => return OclError::err("ocl::Program::intermediate_code(): Unexpected \
+                'ProgramInfoResult' variant."),
+        };
+
+        let binary = match binaries.get(idx) {
+            Some(binary) => binary,
+            None => return OclError::err("ocl::Program::intermediate_code(): No binary is \
+                available for the device."),
+        };
+
+        match binary_as_text(binary) {
+            Some(text) => Ok(text),
+            None => {
+                let hint = if Vendor::of(&device) == Vendor::Amd {
+                    " Build with 'BuildProfile::AmdDumpIsa' to save the ISA to the working \
+                        directory."
+                } else {
+                    ""
+                };
+
+                OclError::err(format!("ocl::Program::intermediate_code(): The binary for device \
+                    '{}' ({} bytes) is not text.{}", device.name(), binary.len(), hint))
+            },
+        }
+    }
+
     /// Returns a reference to the core pointer wrapper, usable by functions in
     /// the `core` module.
     pub fn core_as_ref(&self) -> &ProgramCore {
@@ -1,15 +1,303 @@
 //! An `OpenCL` command queue.
 
 use std;
+use std::cmp;
 use std::ops::{Deref, DerefMut};
-use core::error::{Result as OclResult};
-use core::{self, CommandQueue as CommandQueueCore, Context as ContextCore,
-    CommandQueueInfo, CommandQueueInfoResult, OpenclVersion};
-use standard::{Context, Device};
+use std::sync::{Arc, Mutex, Weak, Once, ONCE_INIT};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use std::collections::{VecDeque, HashMap};
+use std::ffi::CString;
+use std::ptr;
+use log::{log, warn};
+use core::error::{Result as OclResult, Error as OclError};
+use ffi::{self, cl_int, cl_uint, cl_mem, cl_command_queue, cl_command_queue_properties,
+    cl_queue_properties};
+use core::{self, CommandQueue as CommandQueueCore, Context as ContextCore, Mem as MemCore,
+    CommandQueueInfo, CommandQueueInfoResult, CommandQueueProperties, OpenclVersion, MemMigrationFlags,
+    ClDeviceIdPtr, ClWaitList, ClEventPtrNew, QUEUE_PROFILING_ENABLE, QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE};
+use standard::{Context, Device, Program, Event, ErrorHints};
+use standard::context::register_queue;
+use standard::event::flush_event_queues;
+use standard::error_hints::status_err;
+use profiling::Profiler;
+
+const QUEUE_PROPERTIES: cl_queue_properties = 0x1093;
+const QUEUE_SIZE: cl_queue_properties = 0x1094;
+
+
+/// A record of a single command kept by a `CommandTimeline`.
+#[derive(Clone, Debug)]
+pub struct CommandRecord {
+    label: String,
+    size: usize,
+    error: Option<String>,
+}
+
+impl CommandRecord {
+    /// Returns the label describing the command (a kernel name or buffer
+    /// operation, etc.).
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the size of the command: bytes for host transfers and buffer
+    /// commands, pixels for image region commands, or total work items for
+    /// kernels.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the error message if the command failed to enqueue.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_ref().map(|e| &e[..])
+    }
+}
+
+impl std::fmt::Display for CommandRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.error {
+            Some(ref err) => write!(f, "{} (size: {}): FAILED: {}", self.label, self.size, err),
+            None => write!(f, "{} (size: {}): ok", self.label, self.size),
+        }
+    }
+}
+
+
+/// A small ring buffer of the most recent commands enqueued on a queue.
+///
+/// Disabled (zero length) by default. Once enabled with
+/// `Queue::set_timeline_len`, every command enqueued through a buffer, image,
+/// or kernel command builder is recorded and the timeline is appended to the
+/// message of any error returned by an enqueue or by `Queue::finish`.
+///
+/// Shared between all clones of a `Queue`.
+///
+#[derive(Debug)]
+pub struct CommandTimeline {
+    len: AtomicUsize,
+    records: Mutex<VecDeque<CommandRecord>>,
+}
+
+impl CommandTimeline {
+    fn new() -> CommandTimeline {
+        CommandTimeline {
+            len: AtomicUsize::new(0),
+            records: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Returns true if commands are being recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.len.load(Ordering::Relaxed) > 0
+    }
+
+    /// Sets the maximum number of commands retained. A length of zero
+    /// disables recording and clears any existing records.
+    pub fn set_len(&self, len: usize) {
+        self.len.store(len, Ordering::Relaxed);
+        let mut records = self.records.lock().expect("ocl::CommandTimeline::set_len()");
+        while records.len() > len { records.pop_front(); }
+    }
+
+    /// Records a command along with the result of enqueuing it.
+    ///
+    /// Does nothing if the timeline is disabled.
+    pub fn record<S: Into<String>>(&self, label: S, size: usize, result: &OclResult<()>) {
+        let len = self.len.load(Ordering::Relaxed);
+        if len == 0 { return; }
+
+        let mut records = self.records.lock().expect("ocl::CommandTimeline::record()");
+        while records.len() >= len { records.pop_front(); }
+        records.push_back(CommandRecord {
+            label: label.into(),
+            size: size,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+    }
+
+    /// Records a command and, if `result` is an error, appends the contents
+    /// of the timeline to its message.
+    pub fn record_result<S: Into<String>>(&self, label: S, size: usize, result: OclResult<()>)
+            -> OclResult<()>
+    {
+        self.record(label, size, &result);
+        result.map_err(|err| self.annotate(err))
+    }
+
+    /// Appends the contents of the timeline to the message of `err`.
+    ///
+    /// `Error::Status` errors keep their variant, and therefore their
+    /// status, with the contents appended to their description.
+    ///
+    /// Returns `err` unchanged if the timeline is disabled or empty.
+    pub fn annotate(&self, err: OclError) -> OclError {
+        if !self.is_enabled() { return err; }
+        let dump = self.dump();
+        if dump.is_empty() { return err; }
+
+        match err {
+            OclError::Status { status, status_string, fn_name, fn_info, desc } => {
+                OclError::Status {
+                    status: status,
+                    status_string: status_string,
+                    fn_name: fn_name,
+                    fn_info: fn_info,
+                    desc: format!("{}\n\nMost recent commands (oldest first):\n{}", desc, dump),
+                }
+            },
+            err => OclError::new(format!("{}\n\nMost recent commands (oldest first):\n{}", err,
+                dump)),
+        }
+    }
+
+    /// Returns a copy of the retained records, oldest first.
+    pub fn records(&self) -> Vec<CommandRecord> {
+        self.records.lock().expect("ocl::CommandTimeline::records()").iter().cloned().collect()
+    }
+
+    /// Returns the retained records formatted one per line, oldest first.
+    pub fn dump(&self) -> String {
+        let records = self.records.lock().expect("ocl::CommandTimeline::dump()");
+        records.iter().enumerate()
+            .map(|(i, rec)| format!("    [{}] {}\n", i, rec))
+            .collect()
+    }
+}
+
+/// Returns the process-wide table of command timelines, keyed by queue
+/// pointer.
+fn timelines() -> &'static Mutex<HashMap<usize, Weak<CommandTimeline>>> {
+    static INIT: Once = ONCE_INIT;
+    static mut TIMELINES: *const Mutex<HashMap<usize, Weak<CommandTimeline>>> = 0 as *const _;
+
+    unsafe {
+        INIT.call_once(|| TIMELINES = Box::into_raw(Box::new(Mutex::new(HashMap::new()))));
+        &*TIMELINES
+    }
+}
+
+/// Registers the timeline of a newly created queue so that errors from
+/// events enqueued on it can be annotated. Only a weak reference is kept.
+fn register_timeline(queue: &CommandQueueCore, timeline: &Arc<CommandTimeline>) {
+    let mut timelines = timelines().lock().expect("ocl::queue::register_timeline()");
+    timelines.retain(|_, tl| tl.upgrade().is_some());
+    timelines.insert(unsafe { queue.as_ptr() } as usize, Arc::downgrade(timeline));
+}
+
+/// Appends the command timeline of the queue pointed to by `queue` to the
+/// message of `err`.
+///
+/// Returns `err` unchanged if the queue was not created by this library or
+/// has since been dropped.
+pub fn annotate_with_timeline(queue: cl_command_queue, err: OclError) -> OclError {
+    let timeline = timelines().lock().expect("ocl::queue::annotate_with_timeline()")
+        .get(&(queue as usize)).and_then(|tl| tl.upgrade());
+
+    match timeline {
+        Some(tl) => tl.annotate(err),
+        None => err,
+    }
+}
+
+
+/// A command queue builder.
+///
+/// Queues without a size are created with `clCreateCommandQueue` and are
+/// available on every platform. Setting a size requires OpenCL 2.0; such
+/// queues are created with `clCreateCommandQueueWithProperties`.
+///
+/// ## Defaults
+///
+/// - `properties`: none (in-order execution without profiling)
+///
+#[derive(Debug, Clone)]
+pub struct QueueBuilder {
+    properties: CommandQueueProperties,
+    size: Option<u32>,
+}
+
+impl QueueBuilder {
+    /// Returns a new queue builder with the default settings.
+    pub fn new() -> QueueBuilder {
+        QueueBuilder {
+            properties: CommandQueueProperties::empty(),
+            size: None,
+        }
+    }
+
+    /// Sets the queue properties directly, overwriting any previously set.
+    pub fn properties(&mut self, properties: CommandQueueProperties) -> &mut QueueBuilder {
+        self.properties = properties;
+        self
+    }
+
+    /// Sets whether or not profiling is enabled (`QUEUE_PROFILING_ENABLE`).
+    ///
+    /// Profiling is required to query the profiling info of events or to
+    /// attach a [`Profiler`](/ocl/ocl/profiling/struct.Profiler.html).
+    pub fn profiling(&mut self, enabled: bool) -> &mut QueueBuilder {
+        if enabled {
+            self.properties.insert(QUEUE_PROFILING_ENABLE);
+        } else {
+            self.properties.remove(QUEUE_PROFILING_ENABLE);
+        }
+        self
+    }
+
+    /// Sets whether or not commands may execute out of order
+    /// (`QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE`).
+    pub fn out_of_order(&mut self, enabled: bool) -> &mut QueueBuilder {
+        if enabled {
+            self.properties.insert(QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE);
+        } else {
+            self.properties.remove(QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE);
+        }
+        self
+    }
+
+    /// Sets the size in bytes of the queue (OpenCL 2.0).
+    ///
+    /// Only valid for on-device queues, which must also be specified with
+    /// `::properties`.
+    pub fn size(&mut self, size: u32) -> &mut QueueBuilder {
+        self.size = Some(size);
+        self
+    }
+
+    /// Creates and returns a new queue on `device`.
+    pub fn build(&self, context: &Context, device: Device) -> OclResult<Queue> {
+        let size = match self.size {
+            Some(size) => size,
+            None => return Queue::with_properties(context, device, self.properties),
+        };
+
+        let props: [cl_queue_properties; 5] = [
+            QUEUE_PROPERTIES, self.properties.bits() as cl_queue_properties,
+            QUEUE_SIZE, size as cl_queue_properties,
+            0,
+        ];
+        let mut errcode: cl_int = 0;
+
+        let obj_ptr = unsafe { ffi::clCreateCommandQueueWithProperties(
+            context.core_as_ref().as_ptr(), device.as_ptr(), props.as_ptr(), &mut errcode) };
+
+        if errcode != ffi::CL_SUCCESS {
+            return Err(status_err(errcode, "clCreateCommandQueueWithProperties", format!(
+                "ocl::QueueBuilder::build: Error creating command queue with properties '{:?}' \
+                and size {}. Queue sizes require OpenCL 2.0.", self.properties, size)));
+        }
+
+        let obj_core = unsafe { CommandQueueCore::from_fresh_ptr(obj_ptr) };
+        Queue::from_core(obj_core, context, device)
+    }
+}
 
 /// A command queue which manages all actions taken on kernels, buffers, and
 /// images.
 ///
+/// Use `Queue::builder` to set properties such as profiling.
+///
 ///
 /// ## Destruction
 ///
@@ -24,26 +312,316 @@ pub struct Queue {
     context_obj_core: ContextCore,
     device: Device,
     device_version: OpenclVersion,
+    timeline: Arc<CommandTimeline>,
+    program_cache: Arc<Mutex<HashMap<String, Program>>>,
+    profiler: Arc<Mutex<Option<Profiler>>>,
+    slow_call_threshold: Arc<AtomicUsize>,
+    slow_call_count: Arc<AtomicUsize>,
+    // Dropped with the last clone, unregistering the queue from its context:
+    _registered_core: Arc<CommandQueueCore>,
 }
 
 impl Queue {
+    /// Returns a new `QueueBuilder`.
+    pub fn builder() -> QueueBuilder {
+        QueueBuilder::new()
+    }
+
     /// Returns a new Queue on the device specified by `device`.
     pub fn new(context: &Context, device: Device) -> OclResult<Queue> {
         let obj_core = try!(core::create_command_queue(context, &device));
+        Queue::from_core(obj_core, context, device)
+    }
+
+    /// Returns a new Queue on the device specified by `device`, created with
+    /// the specified properties.
+    ///
+    /// Pass `QUEUE_PROFILING_ENABLE` to be able to query the profiling info
+    /// of events or attach a [`Profiler`](/ocl/ocl/profiling/struct.Profiler.html).
+    pub fn with_properties(context: &Context, device: Device, properties: CommandQueueProperties)
+            -> OclResult<Queue>
+    {
+        let mut errcode: cl_int = 0;
+
+        let obj_ptr = unsafe { ffi::clCreateCommandQueue(context.core_as_ref().as_ptr(),
+            device.as_ptr(), properties.bits() as cl_command_queue_properties, &mut errcode) };
+
+        if errcode != ffi::CL_SUCCESS {
+            return Err(status_err(errcode, "clCreateCommandQueue", format!(
+                "ocl::Queue::with_properties: Error creating command queue with properties \
+                '{:?}'.", properties)));
+        }
+
+        let obj_core = unsafe { CommandQueueCore::from_fresh_ptr(obj_ptr) };
+        Queue::from_core(obj_core, context, device)
+    }
+
+    fn from_core(obj_core: CommandQueueCore, context: &Context, device: Device)
+            -> OclResult<Queue>
+    {
         // let device_version = try!(core::get_device_version(&device));
         let device_version = try!(device.version());
+        let registered_core = Arc::new(obj_core.clone());
+        register_queue(context, &registered_core);
+        let timeline = Arc::new(CommandTimeline::new());
+        register_timeline(&obj_core, &timeline);
 
         Ok(Queue {
             obj_core: obj_core,
             context_obj_core: context.core_as_ref().clone(),
             device: device,
             device_version: device_version,
+            timeline: timeline,
+            program_cache: Arc::new(Mutex::new(HashMap::new())),
+            profiler: Arc::new(Mutex::new(None)),
+            slow_call_threshold: Arc::new(AtomicUsize::new(0)),
+            slow_call_count: Arc::new(AtomicUsize::new(0)),
+            _registered_core: registered_core,
         })
     }
 
     /// Blocks until all commands in this queue have completed before returning.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if any command has failed. Use `::try_finish` to receive an
+    /// error instead.
     pub fn finish(&self) {
-        core::finish(&self.obj_core).unwrap();
+        if let Err(err) = self.try_finish() {
+            panic!("ocl::Queue::finish(): {:#}", ErrorHints(&err));
+        }
+    }
+
+    /// Blocks until all commands in this queue have completed, returning
+    /// an error, annotated with the queue's command timeline, if any
+    /// command has failed.
+    pub fn try_finish(&self) -> OclResult<()> {
+        core::finish(&self.obj_core).map_err(|err| self.timeline.annotate(err))
+    }
+
+    /// Enqueues a marker which waits on every event in `ewait` (or, if
+    /// `ewait` is empty, on all previously enqueued commands) and returns
+    /// its event. The marker does not block subsequent commands.
+    ///
+    /// Requires OpenCL 1.2 (`clEnqueueMarkerWithWaitList`).
+    pub fn enqueue_marker(&self, ewait: &ClWaitList) -> OclResult<Event> {
+        self.enqueue_sync_point(ewait, false)
+    }
+
+    /// Enqueues a barrier which waits on every event in `ewait` (or, if
+    /// `ewait` is empty, on all previously enqueued commands) and returns
+    /// its event. Commands enqueued afterwards do not start until the
+    /// barrier completes, making barriers useful as synchronization points
+    /// on out-of-order queues.
+    ///
+    /// Requires OpenCL 1.2 (`clEnqueueBarrierWithWaitList`).
+    pub fn enqueue_barrier(&self, ewait: &ClWaitList) -> OclResult<Event> {
+        self.enqueue_sync_point(ewait, true)
+    }
+
+    /// Makes all commands subsequently enqueued on this queue wait for every
+    /// event in `ewait`, which may have been produced by other queues.
+    ///
+    /// The queue of each event is flushed first so that the commands being
+    /// waited on are guaranteed to be submitted. Does nothing if `ewait` is
+    /// empty.
+    ///
+    /// This is typically used to overlap transfers with computation using
+    /// two queues:
+    ///
+    /// ```text
+    /// let mut uploaded = Event::empty();
+    /// unsafe { buffer.write_async(&data).queue(&transfer_queue).enew(&mut uploaded).enq()?; }
+    /// compute_queue.enqueue_wait_for_events(&uploaded)?;
+    /// kernel.cmd().queue(&compute_queue).enq()?;
+    /// ```
+    ///
+    /// Individual commands can instead wait on events from other queues
+    /// using their `::ewait` methods.
+    ///
+    /// Requires OpenCL 1.2 (`clEnqueueBarrierWithWaitList`).
+    pub fn enqueue_wait_for_events(&self, ewait: &ClWaitList) -> OclResult<()> {
+        if ewait.count() == 0 { return Ok(()); }
+        try!(flush_event_queues(ewait, unsafe { self.obj_core.as_ptr() },
+            "ocl::Queue::enqueue_wait_for_events()"));
+        self.enqueue_barrier(ewait).map(|_| ())
+    }
+
+    /// Enqueues the migration of each of `mems` to this queue's device, or
+    /// to the host if `flags` contains `MIGRATE_MEM_OBJECT_HOST`, once every
+    /// event in `ewait` has completed, and returns its event.
+    ///
+    /// In contexts with several devices this allows buffers to be prefetched
+    /// to the device which will use them next rather than migrated on first
+    /// access:
+    ///
+    /// ```text
+    /// let migrated = queue_b.enqueue_migrate_mem_objects(&[&buffer], MemMigrationFlags::empty(),
+    ///     &kernel_a_event)?;
+    /// kernel_b.cmd().queue(&queue_b).ewait(&migrated).enq()?;
+    /// ```
+    ///
+    /// With `MIGRATE_MEM_OBJECT_CONTENT_UNDEFINED`, the contents are not
+    /// preserved, which avoids the transfer for buffers about to be
+    /// overwritten.
+    ///
+    /// Requires OpenCL 1.2 (`clEnqueueMigrateMemObjects`).
+    pub fn enqueue_migrate_mem_objects(&self, mems: &[&MemCore], flags: MemMigrationFlags,
+            ewait: &ClWaitList) -> OclResult<Event>
+    {
+        if mems.is_empty() {
+            return OclError::err("ocl::Queue::enqueue_migrate_mem_objects(): No memory objects \
+                specified.");
+        }
+
+        let mem_list: Vec<cl_mem> = mems.iter().map(|mem| unsafe { mem.as_ptr() }).collect();
+        let (wait_count, wait_ptr) = if ewait.count() > 0 {
+            (ewait.count(), unsafe { ewait.as_ptr_ptr() })
+        } else {
+            (0, ptr::null())
+        };
+
+        let mut event = Event::empty();
+        let event_ptr = try!(event.ptr_mut_ptr_new());
+
+        let errcode = unsafe { ffi::clEnqueueMigrateMemObjects(self.obj_core.as_ptr(),
+            mem_list.len() as cl_uint, mem_list.as_ptr(), flags.bits(), wait_count, wait_ptr,
+            event_ptr) };
+
+        try!(self.timeline.record_result("Queue::enqueue_migrate_mem_objects", 0,
+            if errcode == ffi::CL_SUCCESS { Ok(()) } else { Err(status_err(errcode,
+                "clEnqueueMigrateMemObjects", "ocl::Queue::enqueue_migrate_mem_objects(): \
+                Error enqueuing command (requires OpenCL 1.2).".to_owned())) }));
+
+        Ok(event)
+    }
+
+    fn enqueue_sync_point(&self, ewait: &ClWaitList, barrier: bool) -> OclResult<Event> {
+        let fn_name = if barrier { "clEnqueueBarrierWithWaitList" }
+            else { "clEnqueueMarkerWithWaitList" };
+        let (wait_count, wait_ptr) = if ewait.count() > 0 {
+            (ewait.count(), unsafe { ewait.as_ptr_ptr() })
+        } else {
+            (0, ptr::null())
+        };
+
+        let mut event = Event::empty();
+        let event_ptr = try!(event.ptr_mut_ptr_new());
+
+        let errcode = unsafe {
+            if barrier {
+                ffi::clEnqueueBarrierWithWaitList(self.obj_core.as_ptr(), wait_count, wait_ptr,
+                    event_ptr)
+            } else {
+                ffi::clEnqueueMarkerWithWaitList(self.obj_core.as_ptr(), wait_count, wait_ptr,
+                    event_ptr)
+            }
+        };
+
+        let label = if barrier { "Queue::enqueue_barrier" } else { "Queue::enqueue_marker" };
+        try!(self.timeline.record_result(label, 0,
+            if errcode == ffi::CL_SUCCESS { Ok(()) } else { Err(status_err(errcode, fn_name,
+                format!("ocl::{}(): Error enqueuing command (requires OpenCL 1.2).",
+                label))) }));
+
+        Ok(event)
+    }
+
+    /// Sets the number of recent commands retained by this queue's command
+    /// timeline (shared by all clones of this queue). Zero (the default)
+    /// disables recording.
+    ///
+    /// See [`CommandTimeline`](/ocl/ocl/struct.CommandTimeline.html).
+    pub fn set_timeline_len(&self, len: usize) {
+        self.timeline.set_len(len)
+    }
+
+    /// Returns the command timeline for this queue.
+    pub fn timeline(&self) -> &CommandTimeline {
+        &self.timeline
+    }
+
+    /// Returns true if this queue was created with profiling enabled.
+    pub fn is_profiling_enabled(&self) -> bool {
+        match self.info(CommandQueueInfo::Properties) {
+            CommandQueueInfoResult::Properties(props) => props.contains(QUEUE_PROFILING_ENABLE),
+            _ => false,
+        }
+    }
+
+    /// Attaches a profiler which will record every buffer, image, and kernel
+    /// command subsequently enqueued on this queue (shared by all clones of
+    /// this queue). Pass `None` to detach the current profiler.
+    ///
+    /// Returns an error if the queue was not created with profiling enabled.
+    pub fn set_profiler(&self, profiler: Option<Profiler>) -> OclResult<()> {
+        if profiler.is_some() && !self.is_profiling_enabled() {
+            return OclError::err("ocl::Queue::set_profiler: This queue was not created with \
+                profiling enabled. Use 'Queue::with_properties' with 'QUEUE_PROFILING_ENABLE'.");
+        }
+        *self.profiler.lock().expect("ocl::Queue::set_profiler()") = profiler;
+        Ok(())
+    }
+
+    /// Returns the profiler attached to this queue, if any.
+    pub fn profiler(&self) -> Option<Profiler> {
+        self.profiler.lock().expect("ocl::Queue::profiler()").clone()
+    }
+
+    /// Sets a threshold above which individual driver calls made through
+    /// this queue (command enqueues and the setting of kernel arguments) are
+    /// logged as warnings (shared by all clones of this queue). `None` (the
+    /// default) disables the check.
+    ///
+    /// Useful for uncovering calls which some drivers silently perform
+    /// synchronously, such as non-blocking writes which block until
+    /// complete.
+    pub fn set_slow_call_threshold(&self, threshold: Option<Duration>) {
+        // Stored in nanoseconds, offset by one so that zero means disabled:
+        let encoded = match threshold {
+            Some(t) => {
+                let ns = t.as_secs().saturating_mul(1_000_000_000)
+                    .saturating_add(t.subsec_nanos() as u64);
+                cmp::min(ns, usize::max_value() as u64 - 1) as usize + 1
+            },
+            None => 0,
+        };
+        self.slow_call_threshold.store(encoded, Ordering::Relaxed);
+    }
+
+    /// Returns the slow call warning threshold, if set.
+    pub fn slow_call_threshold(&self) -> Option<Duration> {
+        match self.slow_call_threshold.load(Ordering::Relaxed) {
+            0 => None,
+            encoded => {
+                let ns = (encoded - 1) as u64;
+                Some(Duration::new(ns / 1_000_000_000, (ns % 1_000_000_000) as u32))
+            },
+        }
+    }
+
+    /// Returns the number of driver calls which have exceeded the slow call
+    /// threshold.
+    pub fn slow_call_count(&self) -> usize {
+        self.slow_call_count.load(Ordering::Relaxed)
+    }
+
+    /// Returns a program built from `src` for this queue's device, building
+    /// it only the first time a given source string is requested.
+    ///
+    /// The cache is shared between all clones of this queue. Used by the
+    /// generated kernels in the [`ops`](/ocl/ocl/ops/index.html) module.
+    pub fn cached_program(&self, src: &str) -> OclResult<Program> {
+        let mut cache = self.program_cache.lock().expect("ocl::Queue::cached_program()");
+
+        if let Some(program) = cache.get(src) {
+            return Ok(program.clone());
+        }
+
+        let program = try!(Program::new(vec![try!(CString::new(src))],
+            try!(CString::new("")), &self.context_obj_core, &[self.device]));
+        cache.insert(src.to_owned(), program.clone());
+        Ok(program)
     }
 
     /// Returns a reference to the core pointer wrapper, usable by functions in
@@ -83,6 +661,34 @@ impl Queue {
     }
 }
 
+/// Returns the current time if slow call warnings are enabled for `queue`.
+pub fn slow_call_start(queue: &Queue) -> Option<Instant> {
+    if queue.slow_call_threshold.load(Ordering::Relaxed) == 0 { return None; }
+    Some(Instant::now())
+}
+
+/// Logs a warning, describing the call with `describe`, if the call started
+/// at `start` has exceeded the slow call threshold of `queue`.
+pub fn slow_call_check<F>(queue: &Queue, start: Option<Instant>, describe: F)
+        where F: FnOnce() -> String
+{
+    let (start, threshold) = match (start, queue.slow_call_threshold()) {
+        (Some(s), Some(t)) => (s, t),
+        _ => return,
+    };
+
+    let elapsed = start.elapsed();
+    if elapsed > threshold {
+        queue.slow_call_count.fetch_add(1, Ordering::Relaxed);
+        warn!("ocl: Slow driver call: '{}' took {:.3}ms (threshold: {:.3}ms).", describe(),
+            millis(elapsed), millis(threshold));
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 / 1_000_000.0
+}
+
 impl std::fmt::Display for Queue {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         self.fmt_info(f)
@@ -0,0 +1,186 @@
+//! Plain-old-data handles for passing queues and memory objects across FFI
+//! boundaries.
+
+use ffi::{self, cl_int, cl_command_queue, cl_mem};
+use core::{CommandQueue as CommandQueueCore, Mem as MemCore};
+use core::error::Result as OclResult;
+use standard::Queue;
+use standard::error_hints::status_err;
+
+
+fn check(errcode: cl_int, cl_fn_name: &'static str, fn_name: &str) -> OclResult<()> {
+    if errcode == ffi::CL_SUCCESS {
+        Ok(())
+    } else {
+        Err(status_err(errcode, cl_fn_name, format!("ocl::{}: Error.", fn_name)))
+    }
+}
+
+
+/// A raw, unmanaged command queue pointer suitable for embedding in a C API
+/// (`#[repr(C)]`, the size of a pointer).
+///
+/// Creating a handle neither retains nor releases the queue: the reference
+/// count is managed explicitly with `::retain` and `::release`. A host
+/// passing a handle to a plugin which outlives the host's own `Queue` should
+/// call `::retain` first and the plugin should call `::release` when done.
+///
+/// ## Thread Safety
+///
+/// Handles are `Send` and `Sync`. All `OpenCL` API calls except
+/// `clSetKernelArg` are thread-safe, so the underlying queue may be used
+/// from any thread as long as it has not been released.
+///
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawQueueHandle {
+    ptr: cl_command_queue,
+}
+
+unsafe impl Send for RawQueueHandle {}
+unsafe impl Sync for RawQueueHandle {}
+
+impl RawQueueHandle {
+    /// Returns a handle to `queue` without retaining it.
+    pub fn new(queue: &Queue) -> RawQueueHandle {
+        RawQueueHandle { ptr: unsafe { queue.core_as_ref().as_ptr() } }
+    }
+
+    /// Returns a handle wrapping a raw queue pointer.
+    ///
+    /// ## Safety
+    ///
+    /// `ptr` must be a valid command queue.
+    pub unsafe fn from_ptr(ptr: cl_command_queue) -> RawQueueHandle {
+        RawQueueHandle { ptr: ptr }
+    }
+
+    /// Returns the raw queue pointer.
+    pub fn as_ptr(&self) -> cl_command_queue {
+        self.ptr
+    }
+
+    /// Increments the queue's reference count.
+    ///
+    /// ## Safety
+    ///
+    /// The queue must not have been released.
+    pub unsafe fn retain(&self) -> OclResult<()> {
+        check(ffi::clRetainCommandQueue(self.ptr), "clRetainCommandQueue",
+            "RawQueueHandle::retain")
+    }
+
+    /// Decrements the queue's reference count, destroying it when it reaches
+    /// zero.
+    ///
+    /// ## Safety
+    ///
+    /// Each call must be balanced by an earlier `::retain` (or be the last
+    /// use of a reference otherwise owned by the caller).
+    pub unsafe fn release(&self) -> OclResult<()> {
+        check(ffi::clReleaseCommandQueue(self.ptr), "clReleaseCommandQueue",
+            "RawQueueHandle::release")
+    }
+
+    /// Retains the queue and returns it wrapped in a core queue which will
+    /// release it when dropped.
+    ///
+    /// ## Safety
+    ///
+    /// The queue must not have been released.
+    pub unsafe fn to_core(&self) -> OclResult<CommandQueueCore> {
+        try!(self.retain());
+        Ok(CommandQueueCore::from_fresh_ptr(self.ptr))
+    }
+}
+
+impl<'a> From<&'a Queue> for RawQueueHandle {
+    fn from(queue: &'a Queue) -> RawQueueHandle {
+        RawQueueHandle::new(queue)
+    }
+}
+
+
+/// A raw, unmanaged memory object (buffer or image) pointer suitable for
+/// embedding in a C API (`#[repr(C)]`, the size of a pointer).
+///
+/// Creating a handle neither retains nor releases the memory object: the
+/// reference count is managed explicitly with `::retain` and `::release`.
+/// See `RawQueueHandle` for details.
+///
+/// ## Thread Safety
+///
+/// Handles are `Send` and `Sync`. Memory objects may be used from any
+/// thread as long as they have not been released, though concurrent
+/// commands accessing the same memory must still be ordered with events.
+///
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawMemHandle {
+    ptr: cl_mem,
+}
+
+unsafe impl Send for RawMemHandle {}
+unsafe impl Sync for RawMemHandle {}
+
+impl RawMemHandle {
+    /// Returns a handle to a memory object without retaining it.
+    ///
+    /// Use `Buffer::core_as_ref` or `Image::core_as_ref` to obtain `mem`.
+    pub fn new(mem: &MemCore) -> RawMemHandle {
+        RawMemHandle { ptr: unsafe { mem.as_ptr() } }
+    }
+
+    /// Returns a handle wrapping a raw memory object pointer.
+    ///
+    /// ## Safety
+    ///
+    /// `ptr` must be a valid memory object.
+    pub unsafe fn from_ptr(ptr: cl_mem) -> RawMemHandle {
+        RawMemHandle { ptr: ptr }
+    }
+
+    /// Returns the raw memory object pointer.
+    pub fn as_ptr(&self) -> cl_mem {
+        self.ptr
+    }
+
+    /// Increments the memory object's reference count.
+    ///
+    /// ## Safety
+    ///
+    /// The memory object must not have been released.
+    pub unsafe fn retain(&self) -> OclResult<()> {
+        check(ffi::clRetainMemObject(self.ptr), "clRetainMemObject",
+            "RawMemHandle::retain")
+    }
+
+    /// Decrements the memory object's reference count, destroying it when
+    /// it reaches zero.
+    ///
+    /// ## Safety
+    ///
+    /// Each call must be balanced by an earlier `::retain` (or be the last
+    /// use of a reference otherwise owned by the caller).
+    pub unsafe fn release(&self) -> OclResult<()> {
+        check(ffi::clReleaseMemObject(self.ptr), "clReleaseMemObject",
+            "RawMemHandle::release")
+    }
+
+    /// Retains the memory object and returns it wrapped in a core memory
+    /// object which will release it when dropped.
+    ///
+    /// ## Safety
+    ///
+    /// The memory object must not have been released.
+    pub unsafe fn to_core(&self) -> OclResult<MemCore> {
+        try!(self.retain());
+        Ok(MemCore::from_fresh_ptr(self.ptr))
+    }
+}
+
+impl<'a> From<&'a MemCore> for RawMemHandle {
+    fn from(mem: &'a MemCore) -> RawMemHandle {
+        RawMemHandle::new(mem)
+    }
+}
@@ -1,15 +1,160 @@
 //! An image sampler.
 
 use std;
+use std::ptr;
 use std::ops::{Deref, DerefMut};
-use core::error::{Result as OclResult};
+use core::error::{Error as OclError, Result as OclResult};
 use core::{self, Sampler as SamplerCore, AddressingMode, FilterMode, SamplerInfo, SamplerInfoResult};
+use ffi::{self, cl_int, cl_sampler_properties};
 use standard::Context;
+use standard::error_hints::status_err;
+
+const SAMPLER_NORMALIZED_COORDS: cl_sampler_properties = 0x1152;
+const SAMPLER_ADDRESSING_MODE: cl_sampler_properties = 0x1153;
+const SAMPLER_FILTER_MODE: cl_sampler_properties = 0x1154;
+const SAMPLER_MIP_FILTER_MODE: cl_sampler_properties = 0x1155;
+const SAMPLER_LOD_MIN: cl_sampler_properties = 0x1156;
+const SAMPLER_LOD_MAX: cl_sampler_properties = 0x1157;
+
+
+/// A sampler builder.
+///
+/// Samplers using only the normalized coordinates, addressing mode, and
+/// filter mode settings are created with `clCreateSampler` and are
+/// available on every platform.
+///
+/// Setting a mipmap filter mode or level of detail range requires OpenCL 2.0
+/// and the `cl_khr_mipmap_image` extension. Such samplers are created with
+/// `clCreateSamplerWithProperties`.
+///
+/// ## Defaults
+///
+/// - `normalized_coords`: false
+/// - `addressing_mode`: `AddressingMode::None`
+/// - `filter_mode`: `FilterMode::Nearest`
+///
+#[derive(Debug, Clone)]
+pub struct SamplerBuilder {
+    normalized_coords: bool,
+    addressing_mode: AddressingMode,
+    filter_mode: FilterMode,
+    mip_filter_mode: Option<FilterMode>,
+    lod_range: Option<(f32, f32)>,
+}
+
+impl SamplerBuilder {
+    /// Returns a new sampler builder with the default settings.
+    pub fn new() -> SamplerBuilder {
+        SamplerBuilder {
+            normalized_coords: false,
+            addressing_mode: AddressingMode::None,
+            filter_mode: FilterMode::Nearest,
+            mip_filter_mode: None,
+            lod_range: None,
+        }
+    }
+
+    /// Sets whether or not image coordinates are normalized (in the range
+    /// [0.0, 1.0]).
+    pub fn normalized_coords(&mut self, normalized: bool) -> &mut SamplerBuilder {
+        self.normalized_coords = normalized;
+        self
+    }
+
+    /// Sets how out-of-range image coordinates are handled.
+    pub fn addressing_mode(&mut self, mode: AddressingMode) -> &mut SamplerBuilder {
+        self.addressing_mode = mode;
+        self
+    }
+
+    /// Sets the filter applied when reading an image.
+    pub fn filter_mode(&mut self, mode: FilterMode) -> &mut SamplerBuilder {
+        self.filter_mode = mode;
+        self
+    }
+
+    /// Sets the filter applied between mipmap levels (OpenCL 2.0 with
+    /// `cl_khr_mipmap_image`).
+    pub fn mip_filter_mode(&mut self, mode: FilterMode) -> &mut SamplerBuilder {
+        self.mip_filter_mode = Some(mode);
+        self
+    }
+
+    /// Sets the minimum and maximum level of detail used when reading a
+    /// mipmapped image (OpenCL 2.0 with `cl_khr_mipmap_image`).
+    pub fn lod_range(&mut self, min: f32, max: f32) -> &mut SamplerBuilder {
+        self.lod_range = Some((min, max));
+        self
+    }
+
+    /// Returns the sampler property list, terminated by zero.
+    fn properties(&self) -> Vec<cl_sampler_properties> {
+        let mut props = vec![
+            SAMPLER_NORMALIZED_COORDS, self.normalized_coords as cl_sampler_properties,
+            SAMPLER_ADDRESSING_MODE, self.addressing_mode as cl_sampler_properties,
+            SAMPLER_FILTER_MODE, self.filter_mode as cl_sampler_properties,
+        ];
+
+        if let Some(mode) = self.mip_filter_mode {
+            props.push(SAMPLER_MIP_FILTER_MODE);
+            props.push(mode as cl_sampler_properties);
+        }
+
+        // Float properties occupy the low bytes of their value:
+        if let Some((min, max)) = self.lod_range {
+            props.push(SAMPLER_LOD_MIN);
+            props.push(min.to_bits() as cl_sampler_properties);
+            props.push(SAMPLER_LOD_MAX);
+            props.push(max.to_bits() as cl_sampler_properties);
+        }
+
+        props.push(0);
+        props
+    }
+
+    /// Creates and returns a new sampler.
+    pub fn build(&self, context: &Context) -> OclResult<Sampler> {
+        if self.mip_filter_mode.is_none() && self.lod_range.is_none() {
+            return Sampler::new(context, self.normalized_coords, self.addressing_mode,
+                self.filter_mode);
+        }
+
+        if let Some((min, max)) = self.lod_range {
+            if !(min <= max) {
+                return OclError::err(format!("ocl::SamplerBuilder::build(): The minimum level \
+                    of detail ({}) must not exceed the maximum ({}).", min, max));
+            }
+        }
+
+        let props = self.properties();
+        let mut errcode: cl_int = 0;
+
+        let sampler_ptr = unsafe { ffi::clCreateSamplerWithProperties(
+            context.core_as_ref().as_ptr(), props.as_ptr(), &mut errcode) };
+
+        if errcode != ffi::CL_SUCCESS || sampler_ptr == ptr::null_mut() {
+            return Err(status_err(errcode, "clCreateSamplerWithProperties",
+                "ocl::SamplerBuilder::build(): Error creating sampler. Mipmap filter modes and \
+                levels of detail require OpenCL 2.0 and the 'cl_khr_mipmap_image' extension."
+                .to_owned()));
+        }
+
+        Ok(Sampler(unsafe { SamplerCore::from_fresh_ptr(sampler_ptr) }))
+    }
+}
 
 /// An image sampler used to process images.
 pub struct Sampler(SamplerCore);
 
 impl Sampler {
+    /// Returns a new `SamplerBuilder`.
+    ///
+    /// This is the recommended method to create a sampler with anything
+    /// other than the default settings.
+    pub fn builder() -> SamplerBuilder {
+        SamplerBuilder::new()
+    }
+
     /// Creates and returns a new sampler.
     ///
     /// ## Enum Quick Reference
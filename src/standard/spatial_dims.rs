@@ -120,8 +120,103 @@ impl SpatialDims {
     pub fn is_unspecified(&self) -> bool {
         if let SpatialDims::Unspecified = *self { true } else { false }
     }
+
+    /// Returns the linear index of the coordinates, `coords`, within these
+    /// dimensions.
+    ///
+    /// Dimension 0 varies fastest, matching the usual in-kernel index:
+    /// `x + (y * dim0) + (z * dim0 * dim1)`. Coordinates of unused
+    /// dimensions must be zero, so those returned by `::coords` and
+    /// `::iter_indices` are always accepted.
+    ///
+    /// ## Errors
+    ///
+    /// These dimensions must be specified and `coords` must be within range.
+    ///
+    /// ## Example
+    ///
+    /// ```text
+    /// let dims = SpatialDims::from((4, 3, 2));
+    /// assert_eq!(dims.linear_index((1, 2, 1)).unwrap(), 1 + (2 * 4) + (1 * 4 * 3));
+    /// ```
+    ///
+    pub fn linear_index<C: Into<SpatialDims>>(&self, coords: C) -> OclResult<usize> {
+        let coords: SpatialDims = coords.into();
+        let lens = try!(self.to_lens());
+        let offset = try!(coords.to_offset());
+
+        if (0..3).any(|i| offset[i] >= lens[i]) {
+            return OclError::err(format!("ocl::SpatialDims::linear_index(): Coordinates: {:?}, \
+                out of range for {:?}.", coords, self));
+        }
+
+        Ok(offset[0] + (lens[0] * (offset[1] + (lens[1] * offset[2]))))
+    }
+
+    /// Returns the coordinates of the linear index, `idx`, within these
+    /// dimensions. The inverse of `::linear_index`.
+    ///
+    /// Unused dimensions are zero.
+    ///
+    /// ## Panics
+    ///
+    /// These dimensions must be specified and `idx` must be less than
+    /// `::to_len`.
+    ///
+    pub fn coords(&self, idx: usize) -> [usize; 3] {
+        let lens = self.to_lens().expect("ocl::SpatialDims::coords()");
+        assert!(idx < self.to_len(), "ocl::SpatialDims::coords(): Index: {}, out of range for \
+            {:?}.", idx, self);
+
+        [idx % lens[0], (idx / lens[0]) % lens[1], idx / (lens[0] * lens[1])]
+    }
+
+    /// Returns an iterator over the coordinates of every element within
+    /// these dimensions, in linear index order (see `::linear_index`).
+    ///
+    /// ## Example
+    ///
+    /// ```text
+    /// for (idx, [x, y, _]) in dims.iter_indices().enumerate() {
+    ///     assert_eq!(vec[idx], expected(x, y));
+    /// }
+    /// ```
+    ///
+    pub fn iter_indices(&self) -> Indices {
+        Indices { dims: *self, idx: 0, len: self.to_len() }
+    }
 }
 
+
+/// An iterator over the coordinates within a `SpatialDims`.
+///
+/// Created by `SpatialDims::iter_indices`.
+#[derive(Clone, Debug)]
+pub struct Indices {
+    dims: SpatialDims,
+    idx: usize,
+    len: usize,
+}
+
+impl Iterator for Indices {
+    type Item = [usize; 3];
+
+    fn next(&mut self) -> Option<[usize; 3]> {
+        if self.idx < self.len {
+            self.idx += 1;
+            Some(self.dims.coords(self.idx - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len - self.idx, Some(self.len - self.idx))
+    }
+}
+
+impl ExactSizeIterator for Indices {}
+
 impl MemLen for SpatialDims {
     fn to_len_padded(&self, incr: usize) -> usize {
         self.try_to_padded_len(incr).expect("ocl::SpatialDims::to_len_padded()")
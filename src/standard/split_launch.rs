@@ -0,0 +1,138 @@
+//! Splitting of long-running kernel launches.
+
+use std::cmp;
+use std::time::Duration;
+use core::error::{Error as OclError, Result as OclResult};
+use standard::{SpatialDims, Kernel};
+
+/// Splits a kernel launch with a very large global work size into a series
+/// of smaller, sequential launches, each offset to cover the next slab of
+/// the work space.
+///
+/// Desktop operating systems reset a GPU whose display driver does not
+/// respond within a few seconds (the Windows TDR and macOS GPU watchdogs).
+/// Each launch is sized so that its estimated execution time,
+/// `ns_per_item` multiplied by its number of work items, stays under
+/// `max_launch_time`.
+///
+/// The work space is split along its last dimension (rows of a 2D range),
+/// in multiples of the local work size if one is set. Kernels must use
+/// `get_global_id` rather than `get_group_id` based indexing to be
+/// unaffected by the split.
+///
+/// ## Example
+///
+/// ```text
+/// let split = SplitLaunch::new(Duration::from_millis(500), 20.0);
+/// let launches = split.enq(&kernel).unwrap();
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct SplitLaunch {
+    max_launch_time: Duration,
+    ns_per_item: f64,
+    finish_each: bool,
+}
+
+impl SplitLaunch {
+    /// Returns a new splitter keeping each launch under `max_launch_time`,
+    /// given an estimated cost of `ns_per_item` nanoseconds per work item.
+    pub fn new(max_launch_time: Duration, ns_per_item: f64) -> SplitLaunch {
+        SplitLaunch {
+            max_launch_time: max_launch_time,
+            ns_per_item: ns_per_item,
+            finish_each: false,
+        }
+    }
+
+    /// Blocks until each launch has completed before enqueuing the next
+    /// (default: false). Some drivers only service the display between
+    /// separately submitted batches of work.
+    pub fn finish_each(mut self, finish_each: bool) -> SplitLaunch {
+        self.finish_each = finish_each;
+        self
+    }
+
+    /// Returns the maximum number of work items per launch.
+    pub fn max_items(&self) -> usize {
+        let max_ns = self.max_launch_time.as_secs() as f64 * 1_000_000_000.0
+            + self.max_launch_time.subsec_nanos() as f64;
+
+        if self.ns_per_item <= 0.0 { return usize::max_value(); }
+        cmp::max((max_ns / self.ns_per_item) as usize, 1)
+    }
+
+    /// Returns the global work offset and size of each launch required to
+    /// cover the range described by `gwo`, `gws`, and `lws`.
+    pub fn chunks(&self, gwo: SpatialDims, gws: SpatialDims, lws: SpatialDims)
+            -> OclResult<Vec<(SpatialDims, SpatialDims)>>
+    {
+        let dim_count = gws.dim_count();
+        let gws_lens = try!(gws.to_lens());
+
+        let gwo_lens = match gwo {
+            SpatialDims::Unspecified => [0; 3],
+            _ if gwo.dim_count() == dim_count => try!(gwo.to_lens()),
+            _ => return OclError::err(format!("ocl::SplitLaunch::chunks: The global work offset \
+                ({:?}) and size ({:?}) must have the same number of dimensions.", gwo, gws)),
+        };
+
+        let lws_lens = match lws {
+            SpatialDims::Unspecified => [1; 3],
+            _ if lws.dim_count() == dim_count => try!(lws.to_lens()),
+            _ => return OclError::err(format!("ocl::SplitLaunch::chunks: The local work size \
+                ({:?}) and global work size ({:?}) must have the same number of dimensions.",
+                lws, gws)),
+        };
+
+        let split_dim = dim_count as usize - 1;
+        let slab_items: usize = gws_lens[..split_dim].iter().product();
+        let unit = lws_lens[split_dim];
+
+        if gws_lens[split_dim] % unit != 0 {
+            return OclError::err(format!("ocl::SplitLaunch::chunks: The global work size ({:?}) \
+                must be divisible by the local work size ({:?}).", gws, lws));
+        }
+
+        // Whole multiples of the local work size, at least one per launch:
+        let slabs_per_chunk = cmp::max((self.max_items() / cmp::max(slab_items, 1)) / unit, 1)
+            * unit;
+
+        let mut chunks = Vec::new();
+        let mut ofs = 0;
+
+        while ofs < gws_lens[split_dim] {
+            let len = cmp::min(slabs_per_chunk, gws_lens[split_dim] - ofs);
+            let mut chunk_gwo = gwo_lens;
+            let mut chunk_gws = gws_lens;
+            chunk_gwo[split_dim] += ofs;
+            chunk_gws[split_dim] = len;
+            chunks.push((to_dims(dim_count, chunk_gwo), to_dims(dim_count, chunk_gws)));
+            ofs += len;
+        }
+
+        Ok(chunks)
+    }
+
+    /// Enqueues `kernel` using its default queue and work sizes, split into
+    /// as many launches as necessary. Returns the number of launches.
+    pub fn enq(&self, kernel: &Kernel) -> OclResult<usize> {
+        let chunks = try!(self.chunks(kernel.get_gwo(), kernel.get_gws(), kernel.get_lws()));
+
+        for &(gwo, gws) in chunks.iter() {
+            try!(kernel.cmd().gwo(gwo).gws(gws).enq());
+            if self.finish_each { kernel.default_queue().finish(); }
+        }
+
+        Ok(chunks.len())
+    }
+}
+
+
+fn to_dims(dim_count: u32, lens: [usize; 3]) -> SpatialDims {
+    match dim_count {
+        1 => SpatialDims::One(lens[0]),
+        2 => SpatialDims::Two(lens[0], lens[1]),
+        _ => SpatialDims::Three(lens[0], lens[1], lens[2]),
+    }
+}
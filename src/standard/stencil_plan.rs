@@ -0,0 +1,221 @@
+//! Work size planning for stencil (convolution-style) kernels.
+
+use std::cmp;
+use core::error::{Error as OclError, Result as OclResult};
+use standard::{SpatialDims, KernelCmd, Kernel, BuildOpt};
+
+/// Which elements of the domain a stencil kernel computes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorderMode {
+    /// Every element is computed. Reads falling outside of the domain must
+    /// be handled (clamped, wrapped, zeroed, etc.) by the kernel.
+    Full,
+    /// Only elements with a complete halo are computed. The global work
+    /// offset is set to the halo width and the border is left untouched.
+    Interior,
+}
+
+
+/// Global, local, and offset work sizes for a stencil kernel which stages
+/// tiles, including a surrounding halo, in local memory.
+///
+/// The global work size is rounded up to a multiple of the tile size, so
+/// kernels must skip work items whose global id falls outside the computed
+/// range (see `::build_opts`).
+///
+/// ## Example
+///
+/// ```text
+/// let plan = StencilPlan::new([1920, 1080], 2, [16, 16], BorderMode::Full).unwrap();
+/// let program = plan.build_opts().into_iter()
+///     .fold(Program::builder().src(src), |bldr, opt| bldr.bo(opt))
+///     .build(&context).unwrap();
+/// plan.apply(kernel.cmd()).enq().unwrap();
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct StencilPlan {
+    dim_count: u32,
+    domain: [usize; 3],
+    halo: [usize; 3],
+    tile: [usize; 3],
+    mode: BorderMode,
+}
+
+impl StencilPlan {
+    /// Returns a new plan for a domain of size `domain` with a halo of
+    /// `halo` elements on each side of every used dimension, processed in
+    /// tiles of size `tile` (the local work size).
+    ///
+    /// `domain` and `tile` must have the same number of dimensions.
+    pub fn new<D, T>(domain: D, halo: usize, tile: T, mode: BorderMode) -> OclResult<StencilPlan>
+            where D: Into<SpatialDims>, T: Into<SpatialDims>
+    {
+        let domain: SpatialDims = domain.into();
+        let tile: SpatialDims = tile.into();
+
+        if domain.dim_count() != tile.dim_count() {
+            return OclError::err(format!("ocl::StencilPlan::new: The domain ({:?}) and tile \
+                ({:?}) must have the same number of dimensions.", domain, tile));
+        }
+
+        let dim_count = domain.dim_count();
+        let domain_lens = try!(domain.to_lens());
+        let tile_lens = try!(tile.to_lens());
+        let mut halo_lens = [0; 3];
+
+        for i in 0..(dim_count as usize) {
+            if tile_lens[i] == 0 {
+                return OclError::err("ocl::StencilPlan::new: Tile sizes must be non-zero.");
+            }
+            halo_lens[i] = halo;
+        }
+
+        Ok(StencilPlan {
+            dim_count: dim_count,
+            domain: domain_lens,
+            halo: halo_lens,
+            tile: tile_lens,
+            mode: mode,
+        })
+    }
+
+    /// Returns the range of elements computed in each dimension as
+    /// `(start, len)`. Interior ranges saturate at zero length.
+    pub fn computed_range(&self) -> [(usize, usize); 3] {
+        let mut range = [(0, 1); 3];
+
+        for i in 0..(self.dim_count as usize) {
+            range[i] = match self.mode {
+                BorderMode::Full => (0, self.domain[i]),
+                BorderMode::Interior => (self.halo[i],
+                    self.domain[i].saturating_sub(2 * self.halo[i])),
+            };
+        }
+
+        range
+    }
+
+    /// Returns the number of tiles (work groups) in each dimension.
+    pub fn tile_counts(&self) -> [usize; 3] {
+        let range = self.computed_range();
+        let mut counts = [1; 3];
+
+        for i in 0..(self.dim_count as usize) {
+            counts[i] = cmp::max((range[i].1 + self.tile[i] - 1) / self.tile[i], 1);
+        }
+
+        counts
+    }
+
+    /// Returns the global work offset.
+    pub fn gwo(&self) -> SpatialDims {
+        let range = self.computed_range();
+        self.to_dims([range[0].0, range[1].0, range[2].0])
+    }
+
+    /// Returns the global work size, rounded up to a multiple of the tile
+    /// size.
+    pub fn gws(&self) -> SpatialDims {
+        let counts = self.tile_counts();
+        self.to_dims([counts[0] * self.tile[0], counts[1] * self.tile[1],
+            counts[2] * self.tile[2]])
+    }
+
+    /// Returns the local work size (the tile size).
+    pub fn lws(&self) -> SpatialDims {
+        self.to_dims(self.tile)
+    }
+
+    /// Returns the dimensions of a tile including its halo, the size of the
+    /// local memory array required to stage it.
+    pub fn local_dims(&self) -> SpatialDims {
+        self.to_dims(self.local_lens())
+    }
+
+    /// Returns the global coordinates of the first element (the corner of
+    /// the halo) to be staged by the tile at `tile_idx`.
+    ///
+    /// Coordinates may be negative or beyond the domain at its borders.
+    pub fn tile_origin(&self, tile_idx: [usize; 3]) -> [isize; 3] {
+        let start = self.computed_range();
+        let mut origin = [0; 3];
+
+        for i in 0..(self.dim_count as usize) {
+            origin[i] = (start[i].0 + tile_idx[i] * self.tile[i]) as isize - self.halo[i] as isize;
+        }
+
+        origin
+    }
+
+    /// Returns the origin (see `::tile_origin`) of every tile, with
+    /// dimension 0 varying fastest.
+    pub fn tile_origins(&self) -> Vec<[isize; 3]> {
+        let counts = self.tile_counts();
+        let mut origins = Vec::with_capacity(counts[0] * counts[1] * counts[2]);
+
+        for z in 0..counts[2] {
+            for y in 0..counts[1] {
+                for x in 0..counts[0] {
+                    origins.push(self.tile_origin([x, y, z]));
+                }
+            }
+        }
+
+        origins
+    }
+
+    /// Returns compiler definitions describing the plan for use within
+    /// kernel source. For each used dimension, `{n}` (0, 1, or 2):
+    ///
+    /// * `STENCIL_HALO_{n}`: The halo width.
+    /// * `STENCIL_TILE_{n}`: The tile size (local work size).
+    /// * `STENCIL_LOCAL_{n}`: The tile size including halo.
+    /// * `STENCIL_DOMAIN_{n}`: The domain size.
+    /// * `STENCIL_END_{n}`: One past the last computed global id. Work items
+    ///   at or beyond this must return without writing.
+    ///
+    pub fn build_opts(&self) -> Vec<BuildOpt> {
+        let range = self.computed_range();
+        let local = self.local_lens();
+        let mut opts = Vec::with_capacity(5 * self.dim_count as usize);
+
+        for i in 0..(self.dim_count as usize) {
+            opts.push(BuildOpt::define(format!("STENCIL_HALO_{}", i), self.halo[i]));
+            opts.push(BuildOpt::define(format!("STENCIL_TILE_{}", i), self.tile[i]));
+            opts.push(BuildOpt::define(format!("STENCIL_LOCAL_{}", i), local[i]));
+            opts.push(BuildOpt::define(format!("STENCIL_DOMAIN_{}", i), self.domain[i]));
+            opts.push(BuildOpt::define(format!("STENCIL_END_{}", i), range[i].0 + range[i].1));
+        }
+
+        opts
+    }
+
+    /// Sets the global work offset, global work size, and local work size
+    /// of a kernel command.
+    pub fn apply<'k>(&self, cmd: KernelCmd<'k>) -> KernelCmd<'k> {
+        cmd.gwo(self.gwo()).gws(self.gws()).lws(self.lws())
+    }
+
+    /// Sets the default global work offset, global work size, and local
+    /// work size of a kernel (builder-style).
+    pub fn apply_to_kernel(&self, kernel: Kernel) -> Kernel {
+        kernel.gwo(self.gwo()).gws(self.gws()).lws(self.lws())
+    }
+
+    /// Returns the size of a tile including its halo.
+    fn local_lens(&self) -> [usize; 3] {
+        [self.tile[0] + 2 * self.halo[0], self.tile[1] + 2 * self.halo[1],
+            self.tile[2] + 2 * self.halo[2]]
+    }
+
+    /// Converts a 3D size into a `SpatialDims` with this plan's dimension
+    /// count.
+    fn to_dims(&self, lens: [usize; 3]) -> SpatialDims {
+        match self.dim_count {
+            1 => SpatialDims::One(lens[0]),
+            2 => SpatialDims::Two(lens[0], lens[1]),
+            _ => SpatialDims::Three(lens[0], lens[1], lens[2]),
+        }
+    }
+}
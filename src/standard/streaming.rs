@@ -0,0 +1,180 @@
+//! Double-buffered streaming of host data through device buffers.
+
+use std::ptr;
+use log::{log, error};
+use core::error::{Error as OclError, Result as OclResult};
+use core::{OclPrm, MemFlags, ClWaitList};
+use standard::{Queue, Buffer, Event, EventList};
+use standard::event::{copy_to_new, flush_event_queues};
+
+
+/// One of the two device buffers along with the host copy of the chunk
+/// being uploaded to it and the events of the last commands using it.
+#[derive(Debug)]
+struct Slot<T: OclPrm> {
+    buffer: Buffer<T>,
+    staging: Vec<T>,
+    upload: Event,
+    process: Event,
+}
+
+
+/// Streams a dataset larger than device memory through a pair of device
+/// buffers, uploading the next chunk into one while the previous chunk,
+/// in the other, is being processed.
+///
+/// Uploads are enqueued without blocking on the queue passed to `::new`.
+/// Use a different queue for processing to allow the two to overlap. The
+/// event dependencies are managed automatically:
+///
+/// - Processing a chunk waits for its upload.
+/// - Uploading into a buffer waits for the processing of the chunk last
+///   uploaded into it.
+///
+/// ```text
+/// let mut stream = StreamingBuffer::<f32>::new(upload_queue, None, 1 << 20)?;
+///
+/// for chunk in dataset.chunks(stream.chunk_len()) {
+///     stream.stream(chunk, |buffer, len, ewait, enew| {
+///         kernel.set_arg_buf_named("input", Some(buffer))?;
+///         kernel.cmd().gws(len).ewait(ewait).enew(enew).enq()
+///     })?;
+/// }
+/// stream.wait()?;
+/// ```
+///
+/// Each chunk is copied into host memory owned by the stream before being
+/// uploaded so that the caller's data need not outlive the upload.
+///
+#[derive(Debug)]
+pub struct StreamingBuffer<T: OclPrm> {
+    slots: [Slot<T>; 2],
+    chunk_len: usize,
+    current: usize,
+}
+
+impl<T: OclPrm> StreamingBuffer<T> {
+    /// Creates two device buffers of `chunk_len` elements each, uploaded to
+    /// using `upload_queue`.
+    pub fn new(upload_queue: Queue, flags: Option<MemFlags>, chunk_len: usize)
+            -> OclResult<StreamingBuffer<T>>
+    {
+        if chunk_len == 0 {
+            return OclError::err("ocl::StreamingBuffer::new(): The chunk length must be \
+                greater than zero.");
+        }
+
+        let slot = |queue: Queue| -> OclResult<Slot<T>> {
+            Ok(Slot {
+                buffer: try!(Buffer::new(queue, flags, [chunk_len], None)),
+                staging: Vec::with_capacity(chunk_len),
+                upload: Event::empty(),
+                process: Event::empty(),
+            })
+        };
+
+        Ok(StreamingBuffer {
+            slots: [try!(slot(upload_queue.clone())), try!(slot(upload_queue))],
+            chunk_len: chunk_len,
+            current: 0,
+        })
+    }
+
+    /// Uploads `chunk` into the next buffer then calls `process` to enqueue
+    /// the commands which use it.
+    ///
+    /// `process` is passed the buffer, the length of the chunk, the wait
+    /// list it must wait on (using `::ewait`), and the event its last
+    /// command must set (using `::enew`). Returns that event.
+    ///
+    /// Blocks only if the host copy of the chunk previously uploaded into
+    /// the same buffer has not yet been transferred.
+    ///
+    /// ## Errors
+    ///
+    /// `chunk` may not be empty or longer than the chunk length. `process`
+    /// must set the event passed to it.
+    ///
+    pub fn stream<F>(&mut self, chunk: &[T], process: F) -> OclResult<Event>
+            where F: FnOnce(&Buffer<T>, usize, &EventList, &mut Event) -> OclResult<()>
+    {
+        if chunk.is_empty() || chunk.len() > self.chunk_len {
+            return OclError::err(format!("ocl::StreamingBuffer::stream(): Chunks must contain \
+                between 1 and {} elements (len: {}).", self.chunk_len, chunk.len()));
+        }
+
+        let event = {
+            let slot = &mut self.slots[self.current];
+
+            if !slot.upload.is_empty() { try!(slot.upload.wait()); }
+            slot.staging.clear();
+            slot.staging.extend_from_slice(chunk);
+
+            let mut upload = Event::empty();
+            {
+                let ewait = if slot.process.is_empty() { None }
+                    else { Some(&slot.process as &ClWaitList) };
+                // The staging vector is not modified again until `upload` completes:
+                try!(unsafe { slot.buffer.write_async(&slot.staging).ewait_opt(ewait)
+                    .enew(&mut upload).enq() });
+            }
+            // Stored before anything else can fail so that the staging vector
+            // is never reused or freed while the upload may be reading it:
+            slot.upload = upload.clone();
+
+            // Submit the upload so that commands on other queues may wait on it:
+            try!(flush_event_queues(&upload, ptr::null_mut(), "ocl::StreamingBuffer::stream()"));
+
+            let mut ewait = EventList::new();
+            try!(copy_to_new(&upload, &mut ewait));
+            let mut event = Event::empty();
+            try!(process(&slot.buffer, chunk.len(), &ewait, &mut event));
+
+            if event.is_empty() {
+                return OclError::err("ocl::StreamingBuffer::stream(): The processing closure \
+                    must set the event passed to it.");
+            }
+
+            slot.process = event.clone();
+            event
+        };
+
+        self.current ^= 1;
+        Ok(event)
+    }
+
+    /// Blocks until all uploads and processing have completed.
+    pub fn wait(&self) -> OclResult<()> {
+        for slot in self.slots.iter() {
+            if !slot.upload.is_empty() { try!(slot.upload.wait()); }
+            if !slot.process.is_empty() { try!(slot.process.wait()); }
+        }
+        Ok(())
+    }
+
+    /// Returns the maximum number of elements per chunk.
+    pub fn chunk_len(&self) -> usize {
+        self.chunk_len
+    }
+
+    /// Returns the two device buffers.
+    pub fn buffers(&self) -> (&Buffer<T>, &Buffer<T>) {
+        (&self.slots[0].buffer, &self.slots[1].buffer)
+    }
+}
+
+impl<T: OclPrm> Drop for StreamingBuffer<T> {
+    /// Blocks until all uploads and processing have completed, as the
+    /// staging vectors may still be in use.
+    fn drop(&mut self) {
+        for slot in self.slots.iter() {
+            for event in [&slot.upload, &slot.process].iter() {
+                if event.is_empty() { continue; }
+
+                if let Err(err) = event.wait() {
+                    error!("ocl::StreamingBuffer::drop(): {}", err);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Automatic event dependencies between commands.
+
+use std::collections::HashMap;
+use core::Mem as MemCore;
+use core::error::Result as OclResult;
+use standard::{Event, EventList, Kernel};
+use standard::event::copy_to_new;
+use standard::write_tracker::is_complete;
+
+
+/// The outstanding commands accessing one memory object.
+#[derive(Debug, Default)]
+struct MemDeps {
+    write: Option<Event>,
+    reads: Vec<Event>,
+}
+
+
+/// Derives the wait lists of commands from the memory objects each one
+/// reads and writes.
+///
+/// Each command is registered as a task declaring the buffers and images it
+/// reads and writes. When a task is enqueued it waits on:
+///
+/// - the last write to every memory object it reads or writes, and
+/// - every read, since that write, of each memory object it writes.
+///
+/// Independent tasks, even on different queues, are not ordered relative
+/// to each other.
+///
+/// ```text
+/// let mut graph = TaskGraph::new();
+///
+/// graph.task().writes(&input).enq(|ewait, enew| {
+///     input.write(&data).ewait(ewait).enew(enew).enq()
+/// })?;
+/// graph.task().reads(&input).writes(&output).enq_kernel(&kernel)?;
+/// graph.task().reads(&output).enq(|ewait, enew| {
+///     output.read(&mut result).ewait(ewait).enew(enew).enq()
+/// })?;
+/// ```
+///
+/// Commands enqueued outside of the graph are not accounted for.
+///
+#[derive(Debug, Default)]
+pub struct TaskGraph {
+    mems: HashMap<usize, MemDeps>,
+}
+
+impl TaskGraph {
+    /// Returns a new, empty, graph.
+    pub fn new() -> TaskGraph {
+        TaskGraph { mems: HashMap::new() }
+    }
+
+    /// Returns a new task builder. Declare the memory objects the task
+    /// accesses then enqueue it with `Task::enq` or `Task::enq_kernel`.
+    pub fn task(&mut self) -> Task {
+        Task { graph: self, reads: Vec::new(), writes: Vec::new() }
+    }
+
+    /// Returns the events of every outstanding task.
+    pub fn outstanding(&self) -> OclResult<EventList> {
+        let mut list = EventList::new();
+
+        for deps in self.mems.values() {
+            for event in deps.write.iter().chain(deps.reads.iter()) {
+                if !is_complete(event) { try!(copy_to_new(event, &mut list)); }
+            }
+        }
+        Ok(list)
+    }
+
+    /// Blocks until every outstanding task has completed.
+    pub fn wait(&self) -> OclResult<()> {
+        try!(self.outstanding()).wait()
+    }
+
+    /// Forgets all dependencies. Tasks enqueued afterwards do not wait on
+    /// those enqueued before.
+    pub fn clear(&mut self) {
+        self.mems.clear();
+    }
+
+    /// Returns the wait list for a task accessing `reads` and `writes`.
+    fn wait_list(&self, reads: &[usize], writes: &[usize]) -> OclResult<EventList> {
+        let mut list = EventList::new();
+
+        for id in reads.iter().chain(writes.iter()) {
+            if let Some(&MemDeps { write: Some(ref event), .. }) = self.mems.get(id) {
+                try!(copy_to_new(event, &mut list));
+            }
+        }
+
+        for id in writes {
+            if let Some(deps) = self.mems.get(id) {
+                for event in deps.reads.iter() {
+                    try!(copy_to_new(event, &mut list));
+                }
+            }
+        }
+
+        Ok(list)
+    }
+
+    /// Records `event` as the latest access to `reads` and `writes`.
+    fn record(&mut self, reads: &[usize], writes: &[usize], event: &Event) {
+        for &id in writes {
+            let deps = self.mems.entry(id).or_insert_with(MemDeps::default);
+            deps.write = Some(event.clone());
+            deps.reads.clear();
+        }
+
+        for &id in reads {
+            if writes.contains(&id) { continue; }
+            let deps = self.mems.entry(id).or_insert_with(MemDeps::default);
+            deps.reads.retain(|e| !is_complete(e));
+            deps.reads.push(event.clone());
+        }
+    }
+}
+
+
+/// A command being registered with a `TaskGraph`.
+///
+/// Created by `TaskGraph::task`.
+///
+#[must_use = "tasks do nothing unless enqueued"]
+pub struct Task<'g> {
+    graph: &'g mut TaskGraph,
+    reads: Vec<usize>,
+    writes: Vec<usize>,
+}
+
+impl<'g> Task<'g> {
+    /// Declares that the task reads `mem` (a `Buffer` or `Image`).
+    pub fn reads(mut self, mem: &MemCore) -> Task<'g> {
+        self.reads.push(unsafe { mem.as_ptr() } as usize);
+        self
+    }
+
+    /// Declares that the task writes `mem` (a `Buffer` or `Image`). Tasks
+    /// which both read and write a memory object need only declare the
+    /// write.
+    pub fn writes(mut self, mem: &MemCore) -> Task<'g> {
+        self.writes.push(unsafe { mem.as_ptr() } as usize);
+        self
+    }
+
+    /// Enqueues the task by calling `enq` with the derived wait list and an
+    /// empty event, which `enq` must pass to the command it enqueues (using
+    /// `::ewait` and `::enew`). Returns the task's event.
+    pub fn enq<F>(self, enq: F) -> OclResult<Event>
+            where F: FnOnce(&EventList, &mut Event) -> OclResult<()>
+    {
+        let wait_list = try!(self.graph.wait_list(&self.reads, &self.writes));
+        let mut event = Event::empty();
+        try!(enq(&wait_list, &mut event));
+
+        // A command which did not set the event cannot be depended upon:
+        if !event.is_empty() { self.graph.record(&self.reads, &self.writes, &event); }
+        Ok(event)
+    }
+
+    /// Enqueues `kernel` on its default queue as the task.
+    pub fn enq_kernel(self, kernel: &Kernel) -> OclResult<Event> {
+        self.enq(|ewait, enew| kernel.cmd().ewait(ewait).enew(enew).enq())
+    }
+}
@@ -0,0 +1,164 @@
+//! Opt-in detection of unordered, overlapping writes to a memory object.
+
+use std;
+use std::sync::{Arc, Mutex};
+use log::{log, warn};
+use core::{EventInfo, EventInfoResult, CommandExecutionStatus, CommandQueueInfo,
+    CommandQueueInfoResult, ClWaitList, QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE};
+use core::error::{Error as OclError, Result as OclResult};
+use standard::{Queue, Event};
+
+
+/// What to do when a conflicting write is detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteConflict {
+    /// Log a warning, using the `log` crate, and enqueue the write anyway.
+    Warn,
+    /// Return an error instead of enqueuing the write.
+    Error,
+}
+
+
+/// The most recent write to a tracked memory object.
+#[derive(Debug)]
+struct LastWrite {
+    label: String,
+    queue_ptr: usize,
+    in_order: bool,
+    event: Event,
+}
+
+#[derive(Debug)]
+struct Inner {
+    action: WriteConflict,
+    last: Option<LastWrite>,
+    conflicts: usize,
+}
+
+
+/// Tracks the outstanding write to a buffer and catches a second write
+/// enqueued without a dependency on the first.
+///
+/// Attach a tracker to a buffer with `Buffer::set_write_tracker`. Buffer
+/// writes, fills, and copies into the buffer are then checked before being
+/// enqueued, as are kernels which use the buffer as a non-`const` argument
+/// (set after the tracker was attached).
+///
+/// A write depends on the previous one if the previous one has completed,
+/// is contained in the new write's wait list, or was enqueued on the same
+/// in-order queue.
+///
+/// Tracking requires an event for every tracked command and adds a query of
+/// the previous write's status to each enqueue. It is meant for debugging.
+///
+#[derive(Debug, Clone)]
+pub struct WriteTracker(Arc<Mutex<Inner>>);
+
+impl WriteTracker {
+    /// Returns a new tracker which takes `action` upon a conflict.
+    pub fn new(action: WriteConflict) -> WriteTracker {
+        WriteTracker(Arc::new(Mutex::new(Inner {
+            action: action,
+            last: None,
+            conflicts: 0,
+        })))
+    }
+
+    /// Returns the action taken upon a conflict.
+    pub fn action(&self) -> WriteConflict {
+        self.0.lock().expect("ocl::WriteTracker::action()").action
+    }
+
+    /// Returns the number of conflicts detected so far.
+    pub fn conflict_count(&self) -> usize {
+        self.0.lock().expect("ocl::WriteTracker::conflict_count()").conflicts
+    }
+
+    /// Checks a write, described by `label`, about to be enqueued on `queue`
+    /// and waiting on `ewait` against the previous write.
+    ///
+    /// Returns an error upon a conflict if the action is
+    /// `WriteConflict::Error`.
+    pub fn check(&self, label: &str, queue: &Queue, ewait: Option<&ClWaitList>) -> OclResult<()> {
+        let mut inner = self.0.lock().expect("ocl::WriteTracker::check()");
+
+        let conflict = match inner.last {
+            Some(ref last) => {
+                if last.in_order && last.queue_ptr == queue_ptr(queue) {
+                    None
+                } else if is_complete(&last.event) || waits_on(ewait, &last.event) {
+                    None
+                } else {
+                    Some(format!("ocl::WriteTracker: '{}' was enqueued without waiting on \
+                        the still outstanding write, '{}', to the same buffer. Add the \
+                        event of the first write to the wait list of the second.",
+                        label, last.label))
+                }
+            },
+            None => None,
+        };
+
+        match conflict {
+            Some(msg) => {
+                inner.conflicts += 1;
+
+                match inner.action {
+                    WriteConflict::Warn => {
+                        warn!("{}", msg);
+                        Ok(())
+                    },
+                    WriteConflict::Error => OclError::err(msg),
+                }
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Records `event` as belonging to the most recent write, described by
+    /// `label`, enqueued on `queue`.
+    pub fn record<S: Into<String>>(&self, label: S, queue: &Queue, event: Event) {
+        let in_order = match queue.info(CommandQueueInfo::Properties) {
+            CommandQueueInfoResult::Properties(props) =>
+                !props.contains(QUEUE_OUT_OF_ORDER_EXEC_MODE_ENABLE),
+            _ => false,
+        };
+
+        self.0.lock().expect("ocl::WriteTracker::record()").last = Some(LastWrite {
+            label: label.into(),
+            queue_ptr: queue_ptr(queue),
+            in_order: in_order,
+            event: event,
+        });
+    }
+}
+
+
+fn queue_ptr(queue: &Queue) -> usize {
+    unsafe { queue.core_as_ref().as_ptr() as usize }
+}
+
+/// Returns true if `event` is empty or its command has completed.
+pub fn is_complete(event: &Event) -> bool {
+    if event.is_empty() { return true; }
+
+    match event.info(EventInfo::CommandExecutionStatus) {
+        EventInfoResult::CommandExecutionStatus(CommandExecutionStatus::Complete) => true,
+        _ => false,
+    }
+}
+
+/// Returns true if `event` is contained in `ewait`.
+fn waits_on(ewait: Option<&ClWaitList>, event: &Event) -> bool {
+    let (ewait, event_core) = match (ewait, event.core_as_ref()) {
+        (Some(ewait), Some(event_core)) => (ewait, event_core),
+        _ => return false,
+    };
+
+    if ewait.count() == 0 { return false; }
+
+    unsafe {
+        let event_ptr = *event_core.as_ptr_ptr();
+        let ptrs = std::slice::from_raw_parts(ewait.as_ptr_ptr(), ewait.count() as usize);
+        ptrs.iter().any(|&ptr| ptr == event_ptr)
+    }
+}
@@ -0,0 +1,40 @@
+use standard::{Context, Queue, Program, Kernel};
+use enums::DeviceInfo;
+
+#[test]
+fn arm_printf() {
+    let src = r#"
+        __kernel void hello() {
+            printf("hello %u\n", get_global_id(0));
+        }
+    "#;
+
+    let default_context = Context::builder().build().unwrap();
+    assert!(default_context.printf_capture().is_none());
+
+    let device = default_context.get_device_by_wrapping_index(0);
+    assert!(device.printf_buffer_size().unwrap() > 0);
+
+    let extensions: String = device.info(DeviceInfo::Extensions).into();
+    let supported = extensions.split_whitespace().any(|ext| ext == "cl_arm_printf");
+
+    let context = Context::builder().devices(device).arm_printf(1 << 16).build();
+
+    if !supported {
+        assert!(context.is_err());
+        return;
+    }
+
+    let context = context.unwrap();
+    let program = Program::builder().src(src).devices(device).build(&context).unwrap();
+    let queue = Queue::new(&context, device).unwrap();
+    Kernel::new("hello", &program, &queue).unwrap().gws([4]).enq().unwrap();
+    queue.finish();
+
+    let capture = context.printf_capture().unwrap();
+    let output = capture.take_output();
+    assert_eq!(output.lines().count(), 4);
+    assert!(output.contains("hello 3"));
+    assert!(!capture.is_truncated());
+    assert!(capture.take_output().is_empty());
+}
@@ -0,0 +1,32 @@
+use standard::{ProQue, Buffer};
+use core::{MemInfo, MemInfoResult};
+
+#[test]
+fn buffer_alignment() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([1000])
+        .build().unwrap();
+
+    let data: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+    let buffer = Buffer::with_alignment(pro_que.queue().clone(), None, [1000], Some(&data[..]),
+        4096).unwrap();
+
+    assert_eq!(buffer.len(), 1000);
+    assert_eq!(buffer.padded_len(), 1024);
+
+    match buffer.mem_info(MemInfo::Size) {
+        MemInfoResult::Size(size) => assert_eq!(size, 4096),
+        other => panic!("Unexpected mem info result: {:?}", other),
+    }
+
+    let mut vec = vec![0.0f32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert_eq!(vec, data);
+
+    // Reads past the logical length are rejected:
+    assert!(buffer.read(&mut vec[..100]).offset(950).enq().is_err());
+
+    assert!(Buffer::<f32>::with_alignment(pro_que.queue().clone(), None, [1000], None, 3000)
+        .is_err());
+}
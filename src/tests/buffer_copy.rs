@@ -116,3 +116,36 @@ fn buffer_copy_standard() {
         }
     }
 }
+
+#[test]
+fn buffer_copy_offsets() {
+    use standard::{ProQue, Buffer, Event};
+    use flags;
+
+    let pro_que = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([1024])
+        .build().unwrap();
+
+    let src_buffer = pro_que.create_buffer::<u32>().unwrap();
+    let dst_buffer = Buffer::<u32>::new(pro_que.queue().clone(), Some(flags::MEM_READ_WRITE),
+        [256], None).unwrap();
+    src_buffer.cmd().fill(7u32, None).enq().unwrap();
+    dst_buffer.cmd().fill(0u32, None).enq().unwrap();
+
+    // Source offset 500, destination offset 100, 50 elements:
+    let mut event = Event::empty();
+    src_buffer.cmd().copy(&dst_buffer, 100, 50).offset(500).enew(&mut event).enq().unwrap();
+    event.wait().unwrap();
+
+    let mut vec = vec![0u32; dst_buffer.len()];
+    dst_buffer.read(&mut vec).enq().unwrap();
+
+    for (idx, &ele) in vec.iter().enumerate() {
+        assert_eq!(ele, if idx >= 100 && idx < 150 { 7 } else { 0 });
+    }
+
+    // The destination range must fit into the destination buffer:
+    assert!(src_buffer.cmd().copy(&dst_buffer, 200, 57).enq().is_err());
+    assert!(src_buffer.cmd().copy(&dst_buffer, 200, 56).enq().is_ok());
+}
@@ -85,3 +85,30 @@ fn fill_with_float4() {
         assert_eq!(ele, final_val);
     }
 }
+
+#[test]
+fn fill_from_offset() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<u32>().unwrap();
+    buffer.cmd().fill(1u32, None).enq().unwrap();
+
+    // Without a length, the fill runs from the offset to the end:
+    buffer.cmd().fill(2u32, None).offset(1000).enq().unwrap();
+    buffer.cmd().fill(3u32, Some(10)).offset(100).enq().unwrap();
+
+    let mut vec = vec![0u32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+
+    for (idx, &ele) in vec.iter().enumerate() {
+        let expected = match idx {
+            100...109 => 3,
+            1000...1023 => 2,
+            _ => 1,
+        };
+        assert_eq!(ele, expected);
+    }
+}
@@ -0,0 +1,61 @@
+use standard::{ProQue, Buffer};
+use flags;
+
+static SRC: &'static str = r#"
+    __kernel void add(__global float* buffer, float addend) {
+        buffer[get_global_id(0)] += addend;
+    }
+"#;
+
+#[test]
+fn from_host_vec() {
+    let pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = Buffer::from_host_vec(pro_que.queue().clone(), None, [1024],
+        vec![1.0f32; 1024]).unwrap();
+    let clone = buffer.clone();
+    drop(buffer);
+
+    pro_que.create_kernel("add").unwrap()
+        .arg_buf(&clone)
+        .arg_scl(1.0f32)
+        .enq().unwrap();
+
+    let mut vec = vec![0.0f32; 1024];
+    clone.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 2.0));
+
+    assert!(Buffer::from_host_vec(pro_que.queue().clone(), None, [1024],
+        vec![1.0f32; 512]).is_err());
+}
+
+#[test]
+fn use_host_slice() {
+    let pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([1024])
+        .build().unwrap();
+
+    let mut host = vec![1.0f32; 1024];
+
+    {
+        let buffer = unsafe { Buffer::use_host_slice(pro_que.queue().clone(), None, [1024],
+            &mut host).unwrap() };
+
+        pro_que.create_kernel("add").unwrap()
+            .arg_buf(&buffer)
+            .arg_scl(1.0f32)
+            .enq().unwrap();
+
+        let mut vec = vec![0.0f32; 1024];
+        buffer.read(&mut vec).enq().unwrap();
+        assert!(vec.iter().all(|&v| v == 2.0));
+    }
+
+    // The borrowing constructors may not use host memory:
+    assert!(Buffer::new(pro_que.queue().clone(), Some(flags::MEM_READ_WRITE |
+        flags::MEM_USE_HOST_PTR), [1024], Some(&host)).is_err());
+}
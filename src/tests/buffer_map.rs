@@ -0,0 +1,40 @@
+use standard::{ProQue, Event};
+use flags;
+
+#[test]
+fn buffer_map() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let mut fill_event = Event::empty();
+    buffer.cmd().fill(1.0, None).enew(&mut fill_event).enq().unwrap();
+
+    {
+        let mut map = buffer.cmd().ewait(&fill_event).offset(512).map(flags::MAP_READ |
+            flags::MAP_WRITE, None).unwrap();
+        assert_eq!(map.len(), 512);
+        assert!(map.iter().all(|&v| v == 1.0));
+
+        for val in map.iter_mut() {
+            *val = 2.0;
+        }
+    }
+
+    let mut vec = vec![0.0f32; 1024];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec[..512].iter().all(|&v| v == 1.0));
+    assert!(vec[512..].iter().all(|&v| v == 2.0));
+
+    let mut map_event = Event::empty();
+    let map = buffer.cmd().offset(1000).enew(&mut map_event).map(flags::MAP_READ, Some(24))
+        .unwrap();
+    assert_eq!(map.len(), 24);
+    assert!(!map_event.is_empty());
+    map.unmap().unwrap();
+
+    // Out of range:
+    assert!(buffer.cmd().offset(1000).map(flags::MAP_READ, Some(25)).is_err());
+}
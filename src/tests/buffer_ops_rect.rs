@@ -42,7 +42,7 @@ fn buffer_ops_rect() {
     //     flags::MEM_READ_WRITE | flags::MEM_COPY_HOST_PTR,
     //     proque.dims().to_len().unwrap(), Some(&vec), proque.queue()) };
     let buf = Buffer::new(proque.queue().clone(), Some(core::MEM_READ_WRITE |
-        core::MEM_COPY_HOST_PTR), proque.dims().clone(), Some(&vec)).unwrap();
+        core::MEM_COPY_HOST_PTR), proque.dims(), Some(&vec)).unwrap();
 
     let kernel_add = proque.create_kernel("add").unwrap()
         .arg_buf(&buf)
@@ -227,7 +227,7 @@ fn buffer_ops_rect() {
     //     flags::MEM_READ_ONLY | flags::MEM_HOST_WRITE_ONLY | flags::MEM_COPY_HOST_PTR,
     //     proque.dims().to_len().unwrap(), Some(&vec_src), proque.queue()) };
     let buf_src = Buffer::new(proque.queue().clone(), Some(core::MEM_READ_WRITE |
-        core::MEM_COPY_HOST_PTR), proque.dims().clone(), Some(&vec_src)).unwrap();
+        core::MEM_COPY_HOST_PTR), proque.dims(), Some(&vec_src)).unwrap();
 
     // Destination Buffer:
     let mut vec_dst = vec![0.0f32; proque.dims().to_len()];
@@ -235,7 +235,7 @@ fn buffer_ops_rect() {
     //     flags::MEM_WRITE_ONLY | flags::MEM_HOST_READ_ONLY | flags::MEM_COPY_HOST_PTR,
     //     proque.dims().to_len().unwrap(), Some(&vec_dst), proque.queue()) };
     let buf_dst = Buffer::new(proque.queue().clone(), Some(core::MEM_READ_WRITE |
-        core::MEM_COPY_HOST_PTR), proque.dims().clone(), Some(&vec_dst)).unwrap();
+        core::MEM_COPY_HOST_PTR), proque.dims(), Some(&vec_dst)).unwrap();
 
     // Source origin doesn't matter for this:
     let src_origin = [0, 0, 0];
@@ -320,3 +320,47 @@ fn buffer_ops_rect() {
 
     println!("{} total test runs complete.\n", ttl_runs);
 }
+
+#[test]
+fn buffer_ops_rect_bounds() {
+    let proque = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([64, 64])
+        .build().unwrap();
+
+    let buf = proque.create_buffer::<u32>().unwrap();
+    let vals: Vec<u32> = (0..buf.len() as u32).collect();
+    buf.write(&vals).enq().unwrap();
+
+    // Read a tightly packed 8x4 tile at (16, 32) out of the flat 64x64 buffer:
+    let mut tile = vec![0u32; 8 * 4];
+    buf.cmd().read(&mut tile).rect([16, 32, 0], [0, 0, 0], [8, 4, 1], 64, 0, 0, 0)
+        .enq().unwrap();
+
+    for y in 0..4 {
+        for x in 0..8 {
+            assert_eq!(tile[y * 8 + x], ((32 + y) * 64 + 16 + x) as u32);
+        }
+    }
+
+    // Regions extending past the buffer or the host slice are rejected:
+    assert!(buf.cmd().read(&mut tile).rect([60, 32, 0], [0, 0, 0], [8, 4, 1], 64, 0, 0, 0)
+        .enq().is_ok());
+    assert!(buf.cmd().read(&mut tile).rect([60, 63, 0], [0, 0, 0], [8, 4, 1], 64, 0, 0, 0)
+        .enq().is_err());
+    assert!(buf.cmd().read(&mut tile).rect([0, 0, 0], [1, 0, 0], [8, 4, 1], 64, 0, 0, 0)
+        .enq().is_err());
+    assert!(buf.cmd().write(&tile).rect([0, 0, 0], [0, 0, 0], [8, 4, 1], 4, 0, 0, 0)
+        .enq().is_err());
+
+    // Copy the tile to the top left corner of another buffer:
+    let dst = proque.create_buffer::<u32>().unwrap();
+    buf.cmd().copy(&dst, 0, 0).rect([16, 32, 0], [0, 0, 0], [8, 4, 1], 64, 0, 64, 0)
+        .enq().unwrap();
+    assert!(buf.cmd().copy(&dst, 0, 0).rect([16, 32, 0], [60, 63, 0], [8, 4, 1], 64, 0, 64, 0)
+        .enq().is_err());
+
+    let mut copied = vec![0u32; dst.len()];
+    dst.read(&mut copied).enq().unwrap();
+    assert_eq!(copied[64 + 2], ((32 + 1) * 64 + 16 + 2) as u32);
+}
@@ -0,0 +1,68 @@
+use standard::{ProQue, Event};
+
+struct Frame {
+    header: [f32; 4],
+    samples: [f32; 60],
+}
+
+#[test]
+fn read_write_slices() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let mut frame = Frame { header: [1.0; 4], samples: [2.0; 60] };
+
+    // Write from and read into fields of a caller-owned struct:
+    buffer.write(&frame.header[..]).enq().unwrap();
+    buffer.write(&frame.samples[..]).offset(4).enq().unwrap();
+
+    frame.header = [0.0; 4];
+    frame.samples = [0.0; 60];
+
+    buffer.read(&mut frame.header[..]).enq().unwrap();
+    assert!(frame.header.iter().all(|&v| v == 1.0));
+
+    let mut event = Event::empty();
+    unsafe {
+        buffer.read_async(&mut frame.samples[..]).offset(4).enew(&mut event).enq().unwrap();
+    }
+    event.wait().unwrap();
+    assert!(frame.samples.iter().all(|&v| v == 2.0));
+
+    // Out of range:
+    assert!(buffer.read(&mut frame.samples[..]).offset(5).enq().is_err());
+}
+
+#[test]
+fn read_write_ranges() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<u32>().unwrap();
+    let mut frame: Vec<u32> = (0..64).collect();
+    buffer.write(&frame[..]).enq().unwrap();
+
+    // Update only a changed region:
+    for v in &mut frame[20..30] { *v += 100; }
+    buffer.cmd().offset(20).len(10).write(&frame[20..]).enq().unwrap();
+
+    let mut vec = vec![0u32; 64];
+    buffer.read(&mut vec).enq().unwrap();
+    assert_eq!(vec, frame);
+
+    // Read part of a buffer into the start of a larger slice:
+    let mut part = vec![0u32; 16];
+    buffer.cmd().offset(24).len(4).read(&mut part).enq().unwrap();
+    assert_eq!(&part[..4], &frame[24..28]);
+    assert!(part[4..].iter().all(|&v| v == 0));
+
+    // Bounds are checked against both the buffer and the host data:
+    assert!(buffer.cmd().offset(60).len(8).write(&frame[..]).enq().is_err());
+    assert!(buffer.cmd().len(8).write(&frame[..4]).enq().is_err());
+    assert!(buffer.cmd().offset(64).len(0).write(&frame[..]).enq().is_err());
+}
@@ -0,0 +1,41 @@
+use standard::ProQue;
+
+#[test]
+fn buffer_unmapped() {
+    let src = r#"
+        __kernel void set(__global float* buffer, float value) {
+            buffer[get_global_id(0)] = value;
+        }
+
+        __kernel void add(__global float* src, __global float* dst, float addend) {
+            dst[get_global_id(0)] = src[get_global_id(0)] + addend;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([1024])
+        .build().unwrap();
+
+    let intermediate = pro_que.create_buffer_unmapped::<f32>().unwrap();
+    let result = pro_que.create_buffer::<f32>().unwrap();
+    assert_eq!(intermediate.len(), 1024);
+
+    pro_que.create_kernel("set").unwrap()
+        .arg_buf(&intermediate)
+        .arg_scl(5.0f32)
+        .enq().unwrap();
+
+    pro_que.create_kernel("add").unwrap()
+        .arg_buf(&intermediate)
+        .arg_buf(&result)
+        .arg_scl(1.0f32)
+        .enq().unwrap();
+
+    let mut vec = vec![0.0f32; 1024];
+    result.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 6.0));
+
+    // Device-only buffers cannot be read from the host:
+    assert!(intermediate.read(&mut vec).enq().is_err());
+}
@@ -1,4 +1,5 @@
 use super::super::ProQue;
+use enums::Status;
 
 #[test]
 #[should_panic]
@@ -15,3 +16,49 @@ fn bad_kernel_variable_names() {
 
     let ocl_pq = ProQue::builder().src(kernel).build().unwrap();
 }
+
+#[test]
+fn build_error_excerpt() {
+    let kernel = r#"
+        kernel void multiply(global float* buffer, float coeff) {
+            buffer[get_global_id(0)] *= not_a_variable;
+        }
+    "#;
+
+    let err = ProQue::builder().src(kernel).build().unwrap_err();
+    let msg = err.to_string();
+
+    assert_eq!(err.status(), Some(Status::CL_BUILD_PROGRAM_FAILURE));
+    // Clang based compilers report `line:col` references, which get an excerpt:
+    if msg.contains("undeclared identifier") {
+        assert!(msg.contains(" | ") && msg.contains("^"));
+        assert!(msg.contains("*= not_a_variable;"));
+    }
+}
+
+#[test]
+fn build_error_fragments() {
+    use std::fs::File;
+    use std::io::Write;
+    use std::env;
+    use standard::Program;
+
+    let path = env::temp_dir().join("ocl_build_error_fragments.cl");
+    File::create(&path).unwrap().write_all(b"float scale(float x) {\n    return x * not_a_scale;\n}\n")
+        .unwrap();
+
+    let kernels = ["kernel void first(global float* buffer) {\n    buffer[0] = 1.0f;\n}\n",
+        "kernel void second(global float* buffer) {\n    buffer[0] = scale(1.0f);\n    \
+            buffer[1] = not_an_offset;\n}\n"];
+
+    let err = ProQue::builder()
+        .prog_bldr(Program::builder().src(kernels[0]).src(kernels[1]).src_file(&path))
+        .build().unwrap_err();
+    let msg = err.to_string();
+
+    assert_eq!(err.status(), Some(Status::CL_BUILD_PROGRAM_FAILURE));
+    if msg.contains("undeclared identifier") {
+        assert!(msg.contains(&format!("--> {}:2:", path.display())));
+        assert!(msg.contains("--> [source string 2]:3:"));
+    }
+}
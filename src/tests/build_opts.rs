@@ -0,0 +1,102 @@
+use core::ProgramBuildInfo;
+use standard::{Program, BuildOpt, ClStd, OptFlag, BuildProfile, ProQue};
+
+#[test]
+fn build_opts_escaping() {
+    let opts = Program::builder()
+        .cmplr_def("MAX_ITERS", 500)
+        .define("GREETING", "hello \"world\"")
+        .incl_dir("/path with spaces/include")
+        .cl_std(ClStd::Cl1_2)
+        .opt(OptFlag::FastRelaxedMath)
+        .get_compiler_options().unwrap();
+
+    assert_eq!(opts.to_str().unwrap(), "-D MAX_ITERS=500 -D GREETING=\"hello \\\"world\\\"\" \
+        -I \"/path with spaces/include\" -cl-std=CL1.2 -cl-fast-relaxed-math");
+
+    assert!(Program::builder().bo(BuildOpt::define("NOT VALID", 1))
+        .get_compiler_options().is_err());
+
+    // Function-like macros:
+    let opts = Program::builder()
+        .define("SQUARE(x)", "((x) * (x))")
+        .define("MAX(a,b)", "((a) > (b) ? (a) : (b))")
+        .get_compiler_options().unwrap();
+    assert_eq!(opts.to_str().unwrap(), "-D SQUARE(x)=\"((x) * (x))\" \
+        -D MAX(a,b)=\"((a) > (b) ? (a) : (b))\"");
+
+    assert!(Program::builder().bo(BuildOpt::define("MAX(a, b)", 1))
+        .get_compiler_options().is_err());
+    assert!(Program::builder().bo(BuildOpt::define("F(x", 1))
+        .get_compiler_options().is_err());
+}
+
+#[test]
+fn build_profiles() {
+    let src = r#"
+        __kernel void scale(__global float* buffer, float factor) {
+            buffer[get_global_id(0)] *= factor;
+        }
+    "#;
+
+    // Profiles not applying to the device are skipped:
+    let pro_que = ProQue::builder()
+        .prog_bldr(Program::builder()
+            .src(src)
+            .profile(BuildProfile::FastMath)
+            .profile(BuildProfile::NvidiaVerbose)
+            .profile(BuildProfile::AmdDumpIsa))
+        .dims([64])
+        .build().unwrap();
+
+    let opts: String = pro_que.program().build_info(*pro_que.queue().device(),
+        ProgramBuildInfo::BuildOptions).into();
+    assert!(opts.contains("-cl-fast-relaxed-math"));
+    assert!(opts.contains("-cl-mad-enable"));
+
+    pro_que.create_kernel("scale").unwrap()
+        .arg_buf(&pro_que.create_buffer::<f32>().unwrap())
+        .arg_scl(2.0f32)
+        .enq().unwrap();
+}
+
+#[test]
+fn program_intermediate_code() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void add(__global float* buffer) { buffer[get_global_id(0)] += 1.0f; }")
+        .dims([64])
+        .build().unwrap();
+
+    let device = *pro_que.queue().device();
+
+    // Availability depends on the vendor: text when returned, an explanatory
+    // error otherwise:
+    match pro_que.program().intermediate_code(device) {
+        Ok(code) => assert!(!code.is_empty()),
+        Err(err) => assert!(format!("{}", err).contains("is not text")),
+    }
+}
+
+#[test]
+fn pro_que_build_opts() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void fill(__global int* buffer) { buffer[get_global_id(0)] = VALUE; }")
+        .define("VALUE", 7)
+        .cmplr_opt("-cl-kernel-arg-info")
+        .src("__kernel void add(__global int* buffer) { buffer[get_global_id(0)] += OFFSET; }")
+        .prog_bldr_with(|pb| pb.cl_std(ClStd::Cl1_2).define("OFFSET", 2))
+        .dims([32])
+        .build().unwrap();
+
+    let opts: String = pro_que.program().build_info(*pro_que.queue().device(),
+        ProgramBuildInfo::BuildOptions).into();
+    assert!(opts.contains("-D VALUE=7") && opts.contains("-cl-std=CL1.2"));
+
+    let buffer = pro_que.create_buffer::<i32>().unwrap();
+    pro_que.create_kernel("fill").unwrap().arg_buf(&buffer).enq().unwrap();
+    pro_que.create_kernel("add").unwrap().arg_buf(&buffer).enq().unwrap();
+
+    let mut vec = vec![0; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 9));
+}
@@ -0,0 +1,44 @@
+use std::thread;
+use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use standard::{ProQue, Event, CallbackTracker};
+use core::CommandExecutionStatus;
+
+#[test]
+fn callback_tracker_wait() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let tracker = CallbackTracker::new();
+    let finished = Arc::new(Mutex::new(0usize));
+
+    for i in 0..4 {
+        let mut event = Event::empty();
+        buffer.cmd().fill(i as f32, None).enew(&mut event).enq().unwrap();
+
+        let finished = finished.clone();
+        tracker.set_callback(&event, CommandExecutionStatus::Complete, move |_, _| {
+            // Outlast event completion:
+            thread::sleep(Duration::from_millis(20));
+            *finished.lock().unwrap() += 1;
+        }).unwrap();
+    }
+
+    tracker.wait();
+    assert_eq!(tracker.pending(), 0);
+    assert_eq!(*finished.lock().unwrap(), 4);
+    assert!(tracker.wait_timeout(Duration::from_millis(0)));
+}
+
+#[test]
+fn callback_tracker_failed_registration() {
+    let tracker = CallbackTracker::new();
+
+    assert!(tracker.set_callback(&Event::empty(), CommandExecutionStatus::Complete,
+        |_, _| {}).is_err());
+    assert_eq!(tracker.pending(), 0);
+    tracker.wait();
+}
@@ -32,3 +32,36 @@ fn clear_completed() {
     }
 }
 
+
+#[test]
+fn pruning() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([2 << 10])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(10.0f32);
+
+    let mut event_list = EventList::with_pruning(16);
+    assert_eq!(event_list.prune_len(), Some(16));
+
+    for _ in 0..2048 {
+        kernel.cmd().enew(&mut event_list).enq().unwrap();
+        pro_que.queue().finish();
+    }
+
+    // Every event but the most recent has completed by the time the next
+    // is added:
+    assert!(event_list.len() <= 16);
+    event_list.wait().unwrap();
+}
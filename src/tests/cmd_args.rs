@@ -0,0 +1,70 @@
+use standard::{ProQue, Program, Image};
+use enums::{ImageChannelOrder, ImageChannelDataType, MemObjectType};
+use aliases::ClUint4;
+
+#[test]
+fn per_enqueue_args() {
+    let pro_que = ProQue::builder()
+        .src(r#"
+            __kernel void add(__global float* buffer, float value) {
+                buffer[get_global_id(0)] += value;
+            }
+        "#)
+        .dims([128])
+        .build().unwrap();
+
+    let buffers: Vec<_> = (0..3).map(|_| pro_que.create_buffer::<f32>().unwrap()).collect();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffers[0])
+        .arg_scl(1.0f32);
+
+    for (i, buffer) in buffers.iter().enumerate() {
+        kernel.cmd().arg_buf(0, buffer).arg_scl(1, i as f32 + 2.0).enq().unwrap();
+    }
+
+    // The kernel's own arguments are unchanged:
+    kernel.enq().unwrap();
+
+    let mut vec = vec![0.0f32; buffers[0].len()];
+    for (i, buffer) in buffers.iter().enumerate() {
+        buffer.read(&mut vec).enq().unwrap();
+        let expected = i as f32 + 2.0 + if i == 0 { 1.0 } else { 0.0 };
+        assert!(vec.iter().all(|&v| v == expected));
+    }
+
+    // Errors are returned when enqueuing:
+    assert!(kernel.cmd().arg_scl(2, 1.0f32).enq().is_err());
+    assert!(kernel.cmd().arg_scl(1, 1i32).enq().is_err());
+    assert!(kernel.cmd().arg_buf(0, &pro_que.create_buffer::<u32>().unwrap()).enq().is_err());
+}
+
+#[test]
+fn per_enqueue_image_args() {
+    let pro_que = ProQue::builder()
+        .prog_bldr(Program::builder().cmplr_opt("-cl-kernel-arg-info").src(r#"
+            __kernel void copy(read_only image2d_t src, __global uint4* dst) {
+                int2 coord = (int2)(get_global_id(0), get_global_id(1));
+                dst[coord.y * get_global_size(0) + coord.x] = read_imageui(src, coord);
+            }
+        "#))
+        .dims([16, 16])
+        .build().unwrap();
+
+    let image = Image::<u8>::builder()
+        .channel_order(ImageChannelOrder::Rgba)
+        .channel_data_type(ImageChannelDataType::UnsignedInt8)
+        .image_type(MemObjectType::Image2d)
+        .dims(pro_que.dims())
+        .build(pro_que.queue()).unwrap();
+    let buffer = pro_que.create_buffer::<ClUint4>().unwrap();
+
+    let kernel = pro_que.create_kernel("copy").unwrap()
+        .arg_img(&image)
+        .arg_buf(&buffer);
+
+    kernel.cmd().arg_img(0, &image).enq().unwrap();
+
+    // Images may only be bound to image arguments:
+    assert!(kernel.cmd().arg_img(1, &image).enq().is_err());
+    assert!(kernel.cmd().arg_img(2, &image).enq().is_err());
+}
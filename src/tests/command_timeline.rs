@@ -0,0 +1,54 @@
+use core::Status;
+use core::error::Error as OclError;
+use standard::ProQue;
+
+#[test]
+fn command_timeline() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([1 << 10])
+        .build().unwrap();
+
+    pro_que.queue().set_timeline_len(4);
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(10.0f32);
+
+    let mut vec = vec![0.0f32; buffer.len()];
+
+    for _ in 0..8 {
+        kernel.enq().unwrap();
+        buffer.read(&mut vec).enq().unwrap();
+    }
+
+    let records = pro_que.queue().timeline().records();
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[3].label(), "Buffer::read");
+    assert_eq!(records[3].size(), buffer.len() * 4);
+    assert_eq!(records[2].label(), "Kernel::add");
+
+    // An out of range read fails and the error carries the timeline:
+    let err = buffer.read(&mut vec).offset(buffer.len()).enq().unwrap_err();
+    assert!(err.to_string().contains("Kernel::add"));
+    assert!(pro_que.queue().timeline().records()[3].error().is_some());
+
+    // Status errors keep their status:
+    let err = OclError::Status {
+        status: Status::CL_INVALID_VALUE,
+        status_string: "CL_INVALID_VALUE".to_owned(),
+        fn_name: "clEnqueueReadBuffer",
+        fn_info: String::new(),
+        desc: "Unable to read.".to_owned(),
+    };
+    let err = pro_que.queue().timeline().annotate(err);
+    assert_eq!(err.status(), Some(Status::CL_INVALID_VALUE));
+    assert!(err.to_string().contains("Kernel::add"));
+}
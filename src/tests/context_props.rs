@@ -1,7 +1,7 @@
 //! Test adding multiple context properties.
 
 use standard::{Platform, Device, Context};
-use core::{ContextPropertyValue};
+use core::{self, ContextPropertyValue};
 
 // static SRC: &'static str = r#"
 //     __kernel void multiply(__global float* buffer, float coeff) {
@@ -67,4 +67,16 @@ fn test_context_props() {
         //     event.wait().unwrap();
         // }
     }
+}
+
+#[test]
+fn context_from_device_type() {
+    let platform = Platform::default();
+
+    let context = Context::builder()
+        .platform(platform)
+        .device_type(core::DEVICE_TYPE_ALL)
+        .build().unwrap();
+
+    assert_eq!(context.devices().len(), Device::list_all(&platform).unwrap().len());
 }
\ No newline at end of file
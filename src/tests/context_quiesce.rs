@@ -0,0 +1,52 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use standard::{Context, Queue, Buffer};
+
+#[test]
+fn context_quiesce() {
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    assert_eq!(context.queue_count(), 0);
+
+    let queue_a = Queue::new(&context, device).unwrap();
+    let queue_b = Queue::new(&context, device).unwrap();
+    let queue_b_clone = queue_b.clone();
+    assert_eq!(context.queue_count(), 2);
+
+    // Queues are only tracked while in use:
+    drop(queue_b);
+    assert_eq!(context.queue_count(), 2);
+    drop(queue_b_clone);
+    assert_eq!(context.queue_count(), 1);
+
+    let buffer = Buffer::<u32>::new(queue_a.clone(), None, [1 << 16], None).unwrap();
+    buffer.cmd().fill(1, None).enq().unwrap();
+    context.finish_all().unwrap();
+
+    // A hook flushing a write batched elsewhere:
+    let pending = Arc::new(Mutex::new(Some(7u32)));
+    let hook_calls = Arc::new(AtomicUsize::new(0));
+    {
+        let pending = pending.clone();
+        let hook_calls = hook_calls.clone();
+        let buffer = buffer.clone();
+
+        context.clone().add_flush_hook(move || {
+            hook_calls.fetch_add(1, Ordering::SeqCst);
+            match pending.lock().unwrap().take() {
+                Some(val) => buffer.cmd().fill(val, None).enq(),
+                None => Ok(()),
+            }
+        });
+    }
+
+    context.quiesce().unwrap();
+    assert_eq!(hook_calls.load(Ordering::SeqCst), 1);
+
+    let mut vec = vec![0u32; 1 << 16];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 7));
+
+    context.quiesce().unwrap();
+    assert_eq!(hook_calls.load(Ordering::SeqCst), 2);
+}
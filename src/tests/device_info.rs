@@ -0,0 +1,40 @@
+use core::{DeviceInfo, DeviceInfoResult};
+use standard::{Platform, Device};
+
+#[test]
+fn typed_getters() {
+    let device = Device::first(Platform::default());
+
+    assert!(device.max_compute_units().unwrap() > 0);
+    assert!(device.global_mem_size().unwrap() > 0);
+    assert!(device.local_mem_size().unwrap() > 0);
+    assert!(device.max_mem_alloc_size().unwrap() <= device.global_mem_size().unwrap());
+    assert!(device.is_available().unwrap());
+    assert_eq!(device.max_wg_size().unwrap(), device.max_work_group_size().unwrap());
+
+    let item_sizes = device.max_work_item_sizes().unwrap();
+    assert!(item_sizes.len() >= 3);
+    assert!(item_sizes.iter().all(|&s| s > 0));
+
+    match device.info(DeviceInfo::ImageSupport) {
+        DeviceInfoResult::ImageSupport(s) => assert_eq!(s, device.image_support().unwrap()),
+        r => panic!("Unexpected result: {:?}", r),
+    }
+}
+
+#[test]
+fn extensions() {
+    let device = Device::first(Platform::default());
+    let raw: String = device.info(DeviceInfo::Extensions).into();
+    let extensions = device.extensions().unwrap();
+
+    assert_eq!(extensions.len(), raw.split_whitespace().count());
+    assert!(raw.split_whitespace().all(|ext| extensions.contains(ext)));
+    assert!(!extensions.contains("cl_not_a_real_extension"));
+
+    // Cached:
+    assert_eq!(device.extensions().unwrap().len(), extensions.len());
+
+    assert_eq!(device.supports_images().unwrap(), device.image_support().unwrap());
+    device.supports_svm_coarse().unwrap();
+}
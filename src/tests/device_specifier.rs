@@ -0,0 +1,85 @@
+use standard::{Platform, Device, DeviceSpecifier, Context, ProQue};
+
+#[test]
+fn device_specifier_filters() {
+    let platform = Platform::default();
+    let first = Device::first(platform);
+
+    let vendor = first.vendor().to_uppercase();
+    let by_vendor = DeviceSpecifier::VendorContains(vendor.clone())
+        .to_device_list(Some(&platform)).unwrap();
+    assert!(by_vendor.iter().any(|d| unsafe { d.as_ptr() == first.as_ptr() }));
+
+    let by_name = DeviceSpecifier::NameContains(first.name())
+        .to_device_list(Some(&platform)).unwrap();
+    assert!(by_name.iter().any(|d| unsafe { d.as_ptr() == first.as_ptr() }));
+
+    assert!(DeviceSpecifier::NameContains("no such device, surely".to_owned())
+        .to_device_list(Some(&platform)).is_err());
+
+    // A filter matching several devices selects the first for a `ProQue`:
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .platform(platform)
+        .device(DeviceSpecifier::VendorContains(vendor))
+        .build().unwrap();
+    assert!(unsafe { pro_que.queue().device().as_ptr() == by_vendor[0].as_ptr() });
+}
+
+#[test]
+fn device_default_for() {
+    let platform = Platform::default();
+
+    // Only compare if the environment does not override the default:
+    if ::std::env::var("OCL_DEFAULT_DEVICE_IDX").is_err() {
+        assert!(unsafe {
+            Device::default_for(platform).unwrap().as_ptr() == Device::first(platform).as_ptr()
+        });
+    }
+}
+
+#[test]
+fn try_constructors() {
+    assert!(!Platform::try_list().unwrap().is_empty());
+
+    let platform = Platform::try_default().unwrap();
+    let device = Device::try_first(platform).unwrap();
+    assert!(unsafe { device.as_ptr() == Device::first(platform).as_ptr() });
+
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .device(device)
+        .build().unwrap();
+    pro_que.queue().try_finish().unwrap();
+}
+
+#[test]
+fn pro_que_context() {
+    let platform = Platform::default();
+    let devices = Device::list_all(&platform).unwrap();
+    let last = devices[devices.len() - 1];
+    let context = Context::builder().platform(platform).devices(last).build().unwrap();
+
+    // The first device of the context is used, not the first of the platform:
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .context(context.clone())
+        .build().unwrap();
+    assert!(unsafe { pro_que.queue().device().as_ptr() == last.as_ptr() });
+
+    // Platform and context are mutually exclusive:
+    assert!(ProQue::builder()
+        .src("__kernel void unused() {}")
+        .platform(platform)
+        .context(context.clone())
+        .build().is_err());
+
+    // Devices outside the context are rejected:
+    if devices.len() > 1 {
+        assert!(ProQue::builder()
+            .src("__kernel void unused() {}")
+            .context(context)
+            .device(devices[0])
+            .build().is_err());
+    }
+}
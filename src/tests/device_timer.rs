@@ -0,0 +1,27 @@
+use std::time::{Duration, Instant};
+use core::OpenclVersion;
+use standard::{Platform, Device};
+
+#[test]
+fn timer_sync() {
+    let device = Device::first(Platform::default());
+
+    if device.version().unwrap() < OpenclVersion::new(2, 1) {
+        assert!(device.device_and_host_timer().is_err());
+        assert!(device.host_timer().is_err());
+        return;
+    }
+
+    let (_, host_ns_0) = device.device_and_host_timer().unwrap();
+    let host_ns_1 = device.host_timer().unwrap();
+    assert!(host_ns_1 >= host_ns_0);
+
+    let sync = device.timer_sync().unwrap();
+    assert!(sync.instant() <= Instant::now());
+    assert_eq!(sync.to_instant(sync.device_ns()), Some(sync.instant()));
+    assert_eq!(sync.to_instant(sync.device_ns() + 1_000_000),
+        Some(sync.instant() + Duration::new(0, 1_000_000)));
+    // Timestamps from long before the sync must not panic:
+    let _ = sync.to_instant(0);
+    assert_eq!(sync.to_host_ns(sync.device_ns() + 5), sync.host_ns() + 5);
+}
@@ -0,0 +1,29 @@
+use standard::{ProQue, Kernel, ErrorHints, status_hints};
+use enums::Status;
+use core::error::Error as OclError;
+
+#[test]
+fn error_hints() {
+    assert!(!status_hints(Status::CL_INVALID_COMMAND_QUEUE).is_empty());
+    assert!(status_hints(Status::CL_SUCCESS).is_empty());
+
+    // Errors without a status code display unchanged:
+    let err = OclError::new("plain error");
+    assert!(ErrorHints(&err).hints().is_empty());
+    assert_eq!(format!("{:#}", ErrorHints(&err)), "plain error");
+
+    let pro_que = ProQue::builder()
+        .src("__kernel void add(__global float* buffer) {}")
+        .dims([64])
+        .build().unwrap();
+
+    let err = Kernel::new("sub", pro_que.program(), pro_que.queue()).unwrap_err();
+    assert_eq!(err.status(), Some(Status::CL_INVALID_KERNEL_NAME));
+
+    let plain = format!("{}", ErrorHints(&err));
+    let alternate = format!("{:#}", ErrorHints(&err));
+    assert_eq!(plain, err.to_string());
+    assert!(alternate.starts_with(&plain));
+    assert!(alternate.contains("Common causes:"));
+    assert!(alternate.contains("`__kernel` function with that name"));
+}
@@ -0,0 +1,30 @@
+use std::sync::mpsc;
+use standard::{ProQue, Event};
+use core::CommandExecutionStatus;
+
+#[test]
+fn event_callback_closure() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    buffer.cmd().fill(7.0, None).enq().unwrap();
+
+    let mut vec = vec![0.0f32; buffer.len()];
+    let mut event = Event::empty();
+    // Explicitly non-blocking so that the callback is set before completion
+    // where possible:
+    unsafe { buffer.cmd().read_async(&mut vec).block(false).enew(&mut event).enq().unwrap(); }
+
+    let (tx, rx) = mpsc::channel();
+
+    event.set_callback(CommandExecutionStatus::Complete, move |_, status| {
+        tx.send(status).unwrap();
+    }).unwrap();
+
+    event.wait().unwrap();
+    assert_eq!(rx.recv().unwrap(), 0);
+    assert!(vec.iter().all(|&v| v == 7.0));
+}
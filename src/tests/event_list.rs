@@ -0,0 +1,49 @@
+use standard::{ProQue, EventList, Event};
+
+#[test]
+fn event_list_access() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+
+    let mut event_list = EventList::new();
+    assert!(event_list.first().is_none());
+    assert_eq!(event_list.iter().count(), 0);
+
+    for _ in 0..4 {
+        kernel.cmd().enew(&mut event_list).enq().unwrap();
+    }
+    event_list.wait().unwrap();
+
+    assert!(event_list.first().is_some());
+    assert!(event_list.get(3).is_some());
+    assert!(event_list.get(4).is_none());
+
+    let events: Vec<Event> = event_list.iter().collect();
+    assert_eq!(events.len(), 4);
+    let ptr = |event: &Event| unsafe { *event.core_as_ref().unwrap().as_ptr_ref() };
+    assert_eq!(ptr(&events[0]), ptr(&event_list.first().unwrap()));
+    assert_eq!(ptr(&events[3]), ptr(&event_list.last_clone().unwrap()));
+
+    for event in &event_list {
+        event.wait().unwrap();
+    }
+
+    let display = format!("{}", event_list);
+    assert!(display.starts_with("EventList (4 events):"));
+    assert!(display.contains("[3]: "));
+
+    assert_eq!(event_list.into_iter().count(), 4);
+}
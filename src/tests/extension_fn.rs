@@ -0,0 +1,18 @@
+use standard::Platform;
+
+#[test]
+fn extension_fn() {
+    let platform = Platform::default();
+
+    unsafe {
+        assert!(platform.extension_fn::<unsafe extern "system" fn()>("clNotARealFunctionKHR")
+            .is_err());
+        // Not pointer-sized:
+        assert!(platform.extension_fn::<[usize; 2]>("clTerminateContextKHR").is_err());
+    }
+
+    // Available or not, depending on the platform:
+    let supported = platform.extensions().contains("cl_khr_terminate_context");
+    let loaded = platform.terminate_context_fn();
+    if supported { assert!(loaded.is_ok()); }
+}
@@ -0,0 +1,26 @@
+use standard::{Context, Queue, Program, Kernel, Buffer};
+
+#[test]
+fn fp64_gating() {
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let queue = Queue::new(&context, device).unwrap();
+    let supported = device.supports_fp64().unwrap();
+
+    let buffer = Buffer::<f64>::new(queue.clone(), None, [64], None);
+    assert_eq!(buffer.is_ok(), supported);
+
+    if !supported {
+        let err = format!("{}", buffer.unwrap_err());
+        assert!(err.contains("cl_khr_fp64"));
+
+        // Scalars are checked as well:
+        let program = Program::builder().src("__kernel void scale(float factor) {}")
+            .devices(device).build(&context).unwrap();
+        let mut kernel = Kernel::new("scale", &program, &queue).unwrap().arg_scl(1.0f32);
+        assert!(kernel.set_arg(0, 2.0f64).is_err());
+    }
+
+    // Other types are unaffected:
+    Buffer::<f32>::new(queue, None, [64], None).unwrap();
+}
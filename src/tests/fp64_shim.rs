@@ -0,0 +1,50 @@
+use standard::{Context, Queue, Program, Kernel, Buffer};
+use enums::DeviceInfo;
+
+#[test]
+fn fp64_shim() {
+    let src = r#"
+        #ifndef OCL_FP64_SHIM
+            #pragma OPENCL EXTENSION cl_khr_fp64 : enable
+        #endif
+
+        __kernel void halve(__global float* buffer, __global int* shimmed) {
+            double val = convert_double(buffer[get_global_id(0)]);
+            buffer[get_global_id(0)] = (float)(val * 0.5 * (M_PI / M_PI));
+
+            #ifdef OCL_FP64_SHIM
+                shimmed[0] = 1;
+            #endif
+        }
+    "#;
+
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let extensions: String = device.info(DeviceInfo::Extensions).into();
+    let has_fp64 = extensions.split_whitespace()
+        .any(|ext| ext == "cl_khr_fp64" || ext == "cl_amd_fp64");
+
+    let program = Program::builder().src(src).devices(device).fp64_shim(true)
+        .build(&context).unwrap();
+    let queue = Queue::new(&context, device).unwrap();
+
+    let buffer = Buffer::<f32>::new(queue.clone(), None, [256], None).unwrap();
+    buffer.cmd().fill(3.0, None).enq().unwrap();
+    let shimmed = Buffer::<i32>::new(queue.clone(), None, [1], None).unwrap();
+    shimmed.cmd().fill(0, None).enq().unwrap();
+
+    Kernel::new("halve", &program, &queue).unwrap()
+        .gws([256])
+        .arg_buf(&buffer)
+        .arg_buf(&shimmed)
+        .enq().unwrap();
+
+    let mut vec = vec![0.0f32; 256];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| (v - 1.5).abs() < 1e-5));
+
+    // The shim is only applied to devices lacking double precision:
+    let mut flag = vec![0i32];
+    shimmed.read(&mut flag).enq().unwrap();
+    assert_eq!(flag[0] == 1, !has_fp64);
+}
@@ -0,0 +1,36 @@
+use futures::Future;
+use standard::{ProQue, FutureEvent, Event};
+
+static SRC: &'static str = r#"
+    __kernel void add(__global float* buffer, float addend) {
+        buffer[get_global_id(0)] += addend;
+    }
+"#;
+
+#[test]
+fn futures() {
+    let pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+
+    let fill = buffer.cmd().fill(2.0, None).enq_future().unwrap();
+    let kern = kernel.cmd().ewait(fill.event()).enq_future().unwrap();
+    let read = buffer.cmd().offset(512).ewait(kern.event())
+        .read_future(vec![0.0f32; 512]).unwrap();
+
+    fill.join(kern).wait().unwrap();
+    let vec = read.wait().unwrap();
+    assert_eq!(vec.len(), 512);
+    assert!(vec.iter().all(|&v| v == 3.0));
+
+    // Empty events and new event destinations are rejected:
+    assert!(FutureEvent::new(Event::empty()).is_err());
+    let mut event = Event::empty();
+    assert!(kernel.cmd().enew(&mut event).enq_future().is_err());
+}
@@ -0,0 +1,39 @@
+use standard::{ProQue, Program, HeatMap};
+
+#[test]
+fn heat_map() {
+    let src = r#"
+        __kernel void uneven(__global float* buffer OCL_HEAT_MAP_ARG) {
+            uint iters = get_group_id(0) + 1;
+            float val = 0.0f;
+
+            for (uint i = 0; i < iters; i++) {
+                val += 1.0f;
+            }
+
+            buffer[get_global_id(0)] = val;
+            OCL_HEAT_MAP_COUNT(iters);
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .prog_bldr(Program::builder().src(src).heat_map(true))
+        .dims([256])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("uneven").unwrap()
+        .lws([32])
+        .arg_buf(&buffer);
+
+    let heat_map = HeatMap::for_kernel(&kernel).unwrap();
+    let kernel = kernel.arg_buf(heat_map.buffer());
+    let report = heat_map.run(&kernel).unwrap();
+
+    // Each of the 32 work items in group `g` counts `g + 1` iterations:
+    assert_eq!(report.counts().len(), 8);
+    assert_eq!(report.min(), 32);
+    assert_eq!(report.max(), 8 * 32);
+    assert_eq!(report.hottest(1), vec![7]);
+    assert!(report.imbalance() > 1.0);
+}
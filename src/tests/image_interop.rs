@@ -0,0 +1,57 @@
+use image::{ImageBuffer, DynamicImage, Luma, Rgba, Rgb};
+use core::{ImageChannelOrder, ImageChannelDataType};
+use standard::{ProQue, Image, ClPixel};
+use flags;
+
+#[test]
+fn pixel_formats() {
+    let format = Rgba::<u8>::image_format();
+    assert_eq!(format.channel_order, ImageChannelOrder::Rgba);
+    assert_eq!(format.channel_data_type, ImageChannelDataType::UnormInt8);
+
+    let format = Luma::<u16>::image_format();
+    assert_eq!(format.channel_order, ImageChannelOrder::R);
+    assert_eq!(format.channel_data_type, ImageChannelDataType::UnormInt16);
+}
+
+#[test]
+fn image_buffer_round_trip() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([1])
+        .build().unwrap();
+
+    let (width, height) = (13, 7);
+    let src = ImageBuffer::from_fn(width, height, |x, y| {
+        Rgba([x as u8, y as u8, (x * y) as u8, 255])
+    });
+
+    let image = Image::from_image_buffer(pro_que.queue(), flags::MEM_READ_WRITE, &src).unwrap();
+    assert_eq!(image.dims().to_lens().unwrap(), [width as usize, height as usize, 1]);
+
+    let dst: ImageBuffer<Rgba<u8>, _> = image.read_image_buffer().unwrap();
+    assert_eq!(dst.into_raw(), src.into_raw());
+
+    // Mismatched channel count:
+    assert!(image.read_image_buffer::<Luma<u8>>().is_err());
+}
+
+#[test]
+fn dynamic_image_round_trip() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([1])
+        .build().unwrap();
+
+    // RGB images are stored as RGBA:
+    let rgb = ImageBuffer::from_fn(5, 3, |x, y| Rgb([x as u8, y as u8, 9]));
+    let src = DynamicImage::ImageRgb8(rgb);
+
+    let image = Image::from_dynamic_image(pro_que.queue(), flags::MEM_READ_WRITE, &src).unwrap();
+    assert_eq!(image.pixel_element_len(), 4);
+
+    match image.read_dynamic_image().unwrap() {
+        DynamicImage::ImageRgba8(dst) => assert_eq!(dst.into_raw(), src.to_rgba().into_raw()),
+        _ => panic!("expected an RGBA image"),
+    }
+}
@@ -1,13 +1,14 @@
 //! Tests various image operations.
 //!
-//! TODO: Test fill and copy to buffer.
+//! TODO: Test copy to buffer.
 //!
 //! Runs both the core function and the 'standard' method call for each.
 
 use core;
 use flags;
 use standard::{ProQue, Image, Sampler};
-use enums::{AddressingMode, FilterMode, ImageChannelOrder, ImageChannelDataType, MemObjectType};
+use enums::{AddressingMode, FilterMode, ImageChannelOrder, ImageChannelDataType, MemObjectType,
+    SamplerInfo, SamplerInfoResult};
 use aliases::{ClInt4};
 use tests;
 
@@ -209,3 +210,288 @@ fn image_ops() {
 
     println!("{} total test runs complete.\n", ttl_runs);
 }
+
+#[test]
+fn image_ops_regions() {
+    let proque = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([16, 16])
+        .build().unwrap();
+
+    let builder = || {
+        let mut builder = Image::<u8>::builder();
+        builder.channel_order(ImageChannelOrder::Rgba)
+            .channel_data_type(ImageChannelDataType::UnsignedInt8)
+            .image_type(MemObjectType::Image2d)
+            .dims([16, 16]);
+        builder
+    };
+
+    let img_src = builder().build(proque.queue()).unwrap();
+    let img_dst = builder().build(proque.queue()).unwrap();
+
+    // Pixel (x, y) holds [x, y, 0, 255]:
+    let pixels: Vec<u8> = (0..16 * 16).flat_map(|i| vec![(i % 16) as u8, (i / 16) as u8, 0, 255])
+        .collect();
+    img_src.write(&pixels).enq().unwrap();
+
+    // Read a 4x2 region into a host buffer 6 pixels wide (24 byte rows):
+    let mut host = vec![0u8; 6 * 2 * 4];
+    img_src.read(&mut host).origin([8, 3, 0]).region([4, 2, 1]).pitch(24, 0).enq().unwrap();
+    assert_eq!(&host[0..4], &[8, 3, 0, 255]);
+    assert_eq!(&host[24 + 12..24 + 16], &[11, 4, 0, 255]);
+    assert_eq!(&host[16..24], &[0; 8]);
+
+    // Copy the region to the bottom right corner of the destination image:
+    img_src.cmd().copy(&img_dst, [12, 14, 0]).origin([8, 3, 0]).region([4, 2, 1])
+        .enq().unwrap();
+    let mut copied = vec![0u8; pixels.len()];
+    img_dst.read(&mut copied).enq().unwrap();
+    let idx = ((15 * 16) + 15) * 4;
+    assert_eq!(&copied[idx..idx + 4], &[11, 4, 0, 255]);
+
+    // Out of range regions and undersized host data are rejected:
+    assert!(img_src.read(&mut host).origin([14, 0, 0]).region([4, 2, 1]).pitch(24, 0)
+        .enq().is_err());
+    assert!(img_src.read(&mut host).region([4, 2, 1]).pitch(12, 0).enq().is_err());
+    assert!(img_src.read(&mut host).region([4, 3, 1]).pitch(24, 0).enq().is_err());
+    assert!(img_src.cmd().copy(&img_dst, [13, 14, 0]).region([4, 2, 1]).enq().is_err());
+}
+
+#[test]
+fn image_fill() {
+    let proque = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([16, 16])
+        .build().unwrap();
+
+    let image = Image::<u8>::builder()
+        .channel_order(ImageChannelOrder::Rgba)
+        .channel_data_type(ImageChannelDataType::UnsignedInt8)
+        .image_type(MemObjectType::Image2d)
+        .dims([16, 16])
+        .build(proque.queue()).unwrap();
+
+    // Clear the whole image then fill a 4x2 region:
+    image.cmd().fill([1u32, 2, 3, 4]).enq().unwrap();
+    image.cmd().fill([9u32, 9, 9, 255]).origin([2, 5, 0]).region([4, 2, 1]).enq().unwrap();
+
+    let mut pixels = vec![0u8; 16 * 16 * 4];
+    image.read(&mut pixels).enq().unwrap();
+
+    for (i, pixel) in pixels.chunks(4).enumerate() {
+        let (x, y) = (i % 16, i / 16);
+        if x >= 2 && x < 6 && y >= 5 && y < 7 {
+            assert_eq!(pixel, &[9, 9, 9, 255]);
+        } else {
+            assert_eq!(pixel, &[1, 2, 3, 4]);
+        }
+    }
+
+    // The color's channel type must suit the image's channel data type:
+    assert!(image.cmd().fill([0.0f32, 0.0, 0.0, 1.0]).enq().is_err());
+    assert!(image.cmd().fill([0i32; 4]).enq().is_err());
+    assert!(image.cmd().fill([0u32; 4]).origin([15, 0, 0]).region([2, 1, 1]).enq().is_err());
+}
+
+#[test]
+fn image_map() {
+    let proque = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([16, 16])
+        .build().unwrap();
+
+    let image = Image::<u8>::builder()
+        .channel_order(ImageChannelOrder::Rgba)
+        .channel_data_type(ImageChannelDataType::UnsignedInt8)
+        .image_type(MemObjectType::Image2d)
+        .dims([16, 16])
+        .build(proque.queue()).unwrap();
+
+    image.cmd().fill([0u32; 4]).enq().unwrap();
+
+    // Write a 4x3 region through a map:
+    {
+        let mut map = image.cmd().origin([4, 8, 0]).region([4, 3, 1])
+            .map(flags::MAP_WRITE).unwrap();
+        assert_eq!(map.region(), [4, 3, 1]);
+        assert!(map.row_pitch() >= 4 * 4);
+        assert_eq!(map.slc_pitch(), 0);
+
+        for y in 0..3 {
+            for (x, pixel) in map.row_mut(y, 0).chunks_mut(4).enumerate() {
+                pixel.copy_from_slice(&[x as u8, y as u8, 7, 255]);
+            }
+        }
+    }
+
+    let mut pixels = vec![0u8; 16 * 16 * 4];
+    image.read(&mut pixels).enq().unwrap();
+    let idx = ((9 * 16) + 6) * 4;
+    assert_eq!(&pixels[idx..idx + 4], &[2, 1, 7, 255]);
+    assert_eq!(&pixels[0..4], &[0; 4]);
+
+    // Read back through a map of the whole image:
+    let map = image.cmd().map(flags::MAP_READ).unwrap();
+    assert_eq!(&map.row(9, 0)[6 * 4..7 * 4], &[2, 1, 7, 255]);
+    map.unmap().unwrap();
+
+    assert!(image.cmd().origin([14, 0, 0]).region([4, 1, 1]).map(flags::MAP_READ).is_err());
+
+    // The pitches of a 3D map span whole rows and slices of the region:
+    let volume = Image::<u8>::builder()
+        .channel_order(ImageChannelOrder::Rgba)
+        .channel_data_type(ImageChannelDataType::UnsignedInt8)
+        .image_type(MemObjectType::Image3d)
+        .dims([8, 4, 2])
+        .build(proque.queue()).unwrap();
+
+    volume.cmd().fill([0u32, 0, 9, 0]).enq().unwrap();
+
+    let map = volume.cmd().map(flags::MAP_READ).unwrap();
+    assert!(map.row_pitch() >= 8 * 4);
+    assert!(map.slc_pitch() >= map.row_pitch() * 4);
+    assert_eq!(map.len(), map.slc_pitch() + (map.row_pitch() * 3) + (8 * 4));
+    assert_eq!(&map.row(3, 1)[7 * 4..8 * 4], &[0, 0, 9, 0]);
+}
+
+#[test]
+fn image_arrays_3d() {
+    let proque = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([8, 8, 3])
+        .build().unwrap();
+
+    let builder = |image_type, dims: [usize; 3]| {
+        let mut builder = Image::<u32>::builder();
+        builder.channel_order(ImageChannelOrder::R)
+            .channel_data_type(ImageChannelDataType::UnsignedInt32)
+            .image_type(image_type)
+            .dims(dims);
+        builder
+    };
+
+    // 2D image array, three layers taken from the depth:
+    let layered: Vec<u32> = (0..8 * 8 * 3).map(|i| (i / 64) as u32).collect();
+    let array_2d = builder(MemObjectType::Image2dArray, [8, 8, 3])
+        .build(proque.queue()).unwrap();
+    assert_eq!(array_2d.dims().to_lens().unwrap(), [8, 8, 3]);
+    array_2d.write(&layered).enq().unwrap();
+
+    let mut layer = vec![0u32; 8 * 8];
+    array_2d.read(&mut layer).origin([0, 0, 1]).region([8, 8, 1]).enq().unwrap();
+    assert!(layer.iter().all(|&v| v == 1));
+
+    // 1D image array, four layers taken from the height:
+    let array_1d = builder(MemObjectType::Image1dArray, [16, 4, 1])
+        .build(proque.queue()).unwrap();
+    assert_eq!(array_1d.dims().to_lens().unwrap(), [16, 4, 1]);
+    array_1d.cmd().fill([5u32, 0, 0, 0]).origin([0, 2, 0]).region([16, 1, 1]).enq().unwrap();
+    let mut rows = vec![0u32; 16];
+    array_1d.read(&mut rows).origin([0, 2, 0]).region([16, 1, 1]).enq().unwrap();
+    assert!(rows.iter().all(|&v| v == 5));
+
+    // 3D image:
+    let volume = builder(MemObjectType::Image3d, [4, 4, 4]).build(proque.queue()).unwrap();
+    volume.cmd().fill([9u32, 0, 0, 0]).enq().unwrap();
+    let mut voxels = vec![0u32; 4 * 4 * 4];
+    volume.read(&mut voxels).enq().unwrap();
+    assert!(voxels.iter().all(|&v| v == 9));
+
+    // Descriptors inconsistent with the image type are rejected:
+    assert!(builder(MemObjectType::Image2d, [8, 8, 3]).build(proque.queue()).is_err());
+    assert!(builder(MemObjectType::Image2dArray, [8, 8, 3]).array_size(4)
+        .build(proque.queue()).is_err());
+    assert!(builder(MemObjectType::Image3d, [4, 4, 4]).array_size(2)
+        .build(proque.queue()).is_err());
+    assert!(builder(MemObjectType::Image1d, [16, 4, 1]).build(proque.queue()).is_err());
+    assert!(builder(MemObjectType::Image2d, [8, 8, 1]).slc_pitch_bytes(256)
+        .build_with_data(proque.queue(), &layered).is_err());
+    assert!(builder(MemObjectType::Image2dArray, [8, 8, 1]).row_pitch_bytes(32)
+        .build(proque.queue()).is_err());
+}
+
+#[test]
+fn image_formats() {
+    let proque = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([8, 8])
+        .build().unwrap();
+
+    let formats = Image::<u8>::supported_formats(proque.context(), flags::MEM_READ_WRITE,
+        MemObjectType::Image2d).unwrap();
+
+    // Required of every device supporting images:
+    assert!(formats.iter().any(|fmt| fmt.channel_order == ImageChannelOrder::Rgba &&
+        fmt.channel_data_type == ImageChannelDataType::UnormInt8));
+
+    // 'Rgb' is only valid with packed data types:
+    let err = Image::<f32>::builder()
+        .channel_order(ImageChannelOrder::Rgb)
+        .channel_data_type(ImageChannelDataType::Float)
+        .image_type(MemObjectType::Image2d)
+        .dims([8, 8])
+        .build(proque.queue()).unwrap_err();
+    assert!(format!("{}", err).contains("(Rgba, UnormInt8)"));
+}
+
+#[test]
+fn sampler_builder() {
+    let proque = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([8, 8])
+        .build().unwrap();
+
+    let sampler = Sampler::builder()
+        .normalized_coords(true)
+        .addressing_mode(AddressingMode::ClampToEdge)
+        .filter_mode(FilterMode::Linear)
+        .build(proque.context()).unwrap();
+
+    match sampler.info(SamplerInfo::NormalizedCoords) {
+        SamplerInfoResult::NormalizedCoords(normalized) => assert!(normalized),
+        res => panic!("Unexpected 'SamplerInfoResult': {:?}", res),
+    }
+    match sampler.info(SamplerInfo::AddressingMode) {
+        SamplerInfoResult::AddressingMode(mode) => assert_eq!(mode, AddressingMode::ClampToEdge),
+        res => panic!("Unexpected 'SamplerInfoResult': {:?}", res),
+    }
+
+    assert!(Sampler::builder().lod_range(2.0, 1.0).build(proque.context()).is_err());
+}
+
+#[test]
+fn image_tiles() {
+    let proque = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .dims([10, 7])
+        .build().unwrap();
+
+    let image = Image::<u32>::builder()
+        .channel_order(ImageChannelOrder::R)
+        .channel_data_type(ImageChannelDataType::UnsignedInt32)
+        .image_type(MemObjectType::Image2d)
+        .dims([10, 7])
+        .build(proque.queue()).unwrap();
+
+    let tiles = image.tiles([4, 4]).unwrap();
+    assert_eq!(tiles.counts(), [3, 2, 1]);
+
+    let tiles: Vec<_> = tiles.collect();
+    assert_eq!(tiles.len(), 6);
+    assert_eq!(tiles[0], ([0, 0, 0], [4, 4, 1]));
+    assert_eq!(tiles[2], ([8, 0, 0], [2, 4, 1]));
+    assert_eq!(tiles[5], ([8, 4, 0], [2, 3, 1]));
+    assert_eq!(tiles.iter().map(|&(_, r)| r[0] * r[1]).sum::<usize>(), 10 * 7);
+
+    // Fill each tile with its index and read the image back whole:
+    for (idx, &(origin, region)) in tiles.iter().enumerate() {
+        image.cmd().fill([idx as u32, 0, 0, 0]).origin(origin).region(region).enq().unwrap();
+    }
+    let mut pixels = vec![0u32; 10 * 7];
+    image.read(&mut pixels).enq().unwrap();
+    assert_eq!(pixels[9], 2);
+    assert_eq!(pixels[6 * 10 + 9], 5);
+
+    assert!(image.tiles([0, 4]).is_err());
+}
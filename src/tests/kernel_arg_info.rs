@@ -0,0 +1,333 @@
+use standard::{ProQue, Program, Buffer};
+use enums::KernelArgAddressQualifier;
+
+#[test]
+fn kernel_arg_info() {
+    let src = r#"
+        __kernel void add(__global float const* const src, __global float* dst, int addend) {
+            dst[get_global_id(0)] = src[get_global_id(0)] + addend;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .prog_bldr(Program::builder().src(src).cmplr_opt("-cl-kernel-arg-info"))
+        .dims([64])
+        .build().unwrap();
+
+    let kernel = pro_que.create_kernel("add").unwrap();
+    let args: Vec<_> = kernel.args().unwrap().map(|a| a.unwrap()).collect();
+
+    assert_eq!(args.len(), 3);
+    assert_eq!(args[0].name(), "src");
+    assert_eq!(args[1].name(), "dst");
+    assert_eq!(args[1].type_name(), "float*");
+    assert_eq!(args[1].address(), KernelArgAddressQualifier::Global);
+    assert_eq!(args[2].type_name(), "int");
+    assert_eq!(args[2].address(), KernelArgAddressQualifier::Private);
+}
+
+#[test]
+fn kernel_arg_type_check() {
+    let src = r#"
+        __kernel void add(__global int* dst, int addend) {
+            dst[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .prog_bldr(Program::builder().src(src).cmplr_opt("-cl-kernel-arg-info"))
+        .dims([64])
+        .build().unwrap();
+
+    let good = pro_que.create_buffer::<i32>().unwrap();
+    let bad = pro_que.create_buffer::<f32>().unwrap();
+
+    let mut kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf_named("dst", Some(&good))
+        .arg_scl(5i32);
+
+    assert!(kernel.set_arg_buf_named("dst", Some(&good)).is_ok());
+
+    let err = kernel.set_arg_buf_named("dst", Some(&bad)).unwrap_err();
+    assert!(err.to_string().contains("int*"));
+}
+
+#[test]
+fn kernel_arg_type_check_builder() {
+    let src = r#"
+        __kernel void add(__global int* dst, int addend) {
+            dst[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .prog_bldr(Program::builder().src(src).cmplr_opt("-cl-kernel-arg-info"))
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<i32>().unwrap();
+
+    let mut kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(5.0f32);
+
+    assert!(kernel.verify_args().unwrap_err().to_string().contains("mismatched argument"));
+    assert!(kernel.enq().is_err());
+
+    // Setting the argument again correctly clears the mismatch:
+    kernel.set_arg(1, 5i32).unwrap();
+    kernel.enq().unwrap();
+}
+
+#[test]
+fn kernel_arg_type_reinterpret() {
+    let src = r#"
+        typedef float real;
+
+        __kernel void scale(__global float4* data, real factor) {
+            data[get_global_id(0)] *= factor;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .prog_bldr(Program::builder().src(src).cmplr_opt("-cl-kernel-arg-info"))
+        .dims([16])
+        .build().unwrap();
+
+    // A buffer of scalars viewed as vectors, and a typedef'd scalar:
+    let buffer = Buffer::<f32>::new(pro_que.queue().clone(), None, [64], None).unwrap();
+    let kernel = pro_que.create_kernel("scale").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(2.0f32);
+
+    assert!(kernel.verify_args().is_ok());
+    kernel.enq().unwrap();
+
+    // The component type must still match:
+    let ints = Buffer::<i32>::new(pro_que.queue().clone(), None, [64], None).unwrap();
+    let kernel = pro_que.create_kernel("scale").unwrap()
+        .arg_buf(&ints)
+        .arg_scl(2.0f32);
+    assert!(kernel.verify_args().is_err());
+}
+
+#[test]
+fn kernel_wg_info() {
+    let src = r#"
+        __kernel void add(__global float* buffer, __local float* scratch) {
+            scratch[get_local_id(0)] = buffer[get_global_id(0)];
+            barrier(CLK_LOCAL_MEM_FENCE);
+            buffer[get_global_id(0)] = scratch[get_local_id(0)] + 1.0f;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_loc::<f32>(16);
+
+    let device = pro_que.queue().device();
+
+    assert!(kernel.wg_size(device).unwrap() >= 1);
+    assert!(kernel.preferred_wg_size_multiple(device).unwrap() >= 1);
+    assert!(kernel.local_mem_size(device).unwrap() >= 16 * 4);
+    kernel.private_mem_size(device).unwrap();
+    assert_eq!(kernel.compile_wg_size(device).unwrap(), [0, 0, 0]);
+}
+
+#[test]
+fn kernel_local_mem_budget() {
+    let src = r#"
+        __kernel void add(__global float* buffer, __local float* scratch) {
+            scratch[get_local_id(0)] = buffer[get_global_id(0)];
+            barrier(CLK_LOCAL_MEM_FENCE);
+            buffer[get_global_id(0)] = scratch[get_local_id(0)] + 1.0f;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_loc::<f32>(64);
+
+    let budget = kernel.local_mem_budget(pro_que.queue().device()).unwrap();
+    assert_eq!(budget.args(), &[(1, 256)]);
+    assert!(!budget.is_exceeded());
+    assert_eq!(kernel.remaining_local_mem([64]).unwrap(),
+        (budget.device_total() - budget.kernel_total()) / 64);
+    assert_eq!(kernel.remaining_local_mem([1]).unwrap(),
+        budget.device_total() - budget.kernel_total());
+    assert!(kernel.remaining_local_mem([0]).is_err());
+}
+
+#[test]
+fn kernel_local_mem_exceeded() {
+    let src = r#"
+        __kernel void add(__global float* buffer, __local float* scratch) {
+            scratch[get_local_id(0)] = buffer[get_global_id(0)];
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_loc::<f32>(1 << 30);
+
+    let err = kernel.verify_args().unwrap_err().to_string();
+    assert!(err.contains("argument 1 exceeds the device's local memory"));
+    assert!(kernel.enq().is_err());
+}
+
+#[test]
+fn kernel_arg_loc_named() {
+    let src = r#"
+        __kernel void add(__global float* buffer, __local float* scratch) {
+            scratch[get_local_id(0)] = buffer[get_global_id(0)];
+            barrier(CLK_LOCAL_MEM_FENCE);
+            buffer[get_global_id(0)] = scratch[get_local_id(0)] + 1.0f;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let mut kernel = pro_que.create_kernel("add").unwrap()
+        .lws([16])
+        .arg_buf(&buffer)
+        .arg_loc_named::<f32>("scratch", 16);
+
+    kernel.enq().unwrap();
+
+    kernel.set_arg_loc_named::<f32>("scratch", 32).unwrap();
+    let device = pro_que.queue().device();
+    assert_eq!(kernel.local_mem_budget(device).unwrap().args(), &[(1, 128)]);
+    kernel.enq().unwrap();
+
+    assert!(kernel.set_arg_loc_named::<f32>("scratch", 1 << 30).is_err());
+    assert_eq!(kernel.local_mem_budget(device).unwrap().args(), &[(1, 128)]);
+
+    let mut vec = vec![0.0f32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 2.0));
+}
+
+#[test]
+fn set_arg_by_index() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    // Without argument info, types are checked against the first set:
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([64])
+        .build().unwrap();
+
+    let buffer_a = pro_que.create_buffer::<f32>().unwrap();
+    let buffer_b = pro_que.create_buffer::<f32>().unwrap();
+    let int_buffer = pro_que.create_buffer::<i32>().unwrap();
+
+    let mut kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer_a)
+        .arg_scl(1.0f32);
+
+    kernel.set_arg_buf(0, &buffer_b).unwrap()
+        .set_arg(1, 2.0f32).unwrap();
+    kernel.enq().unwrap();
+
+    let mut vec = vec![0.0f32; 64];
+    buffer_b.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 2.0));
+
+    assert!(kernel.set_arg(1, 2i32).is_err());
+    assert!(kernel.set_arg_buf(0, &int_buffer).is_err());
+    assert!(kernel.set_arg_buf(1, &buffer_a).is_err());
+    assert!(kernel.set_arg(2, 2.0f32).is_err());
+}
+
+#[test]
+fn kernel_declared_args() {
+    let src = r#"
+        __kernel void add(__global int* dst, int addend, int scale) {
+            dst[get_global_id(0)] = (dst[get_global_id(0)] + addend) * scale;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .prog_bldr(Program::builder().src(src).cmplr_opt("-cl-kernel-arg-info"))
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<i32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf_named("dst", Some(&buffer));
+
+    let err = kernel.verify_args().unwrap_err().to_string();
+    assert!(err.contains("1 ('int addend')") && err.contains("2 ('int scale')"));
+    assert!(kernel.enq().unwrap_err().to_string().contains("missing arguments"));
+
+    let kernel = kernel.arg_scl(1i32).arg_scl(2i32);
+    assert!(kernel.verify_args().is_ok());
+    kernel.enq().unwrap();
+}
+
+#[test]
+fn kernel_declared_args_name() {
+    let src = r#"
+        __kernel void add(__global int* dst, int addend) {
+            dst[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .prog_bldr(Program::builder().src(src).cmplr_opt("-cl-kernel-arg-info"))
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<i32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf_named("buffer", Some(&buffer))
+        .arg_scl(1i32);
+
+    let err = kernel.verify_args().unwrap_err().to_string();
+    assert!(err.contains("argument 0 is declared as 'dst' but was added as 'buffer'"));
+    assert!(kernel.enq().unwrap_err().to_string().contains("declared as 'dst'"));
+}
+
+#[test]
+fn kernel_declared_args_extra() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void add(__global int* dst) { dst[get_global_id(0)] += 1; }")
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<i32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1i32)
+        .arg_scl(2i32);
+
+    let err = kernel.verify_args().unwrap_err().to_string();
+    assert!(err.contains("extra arguments: 1, 2"));
+    assert!(kernel.enq().unwrap_err().to_string().contains("extra arguments"));
+}
@@ -0,0 +1,23 @@
+use standard::{ProQue, Buffer, MemFlagsBuilder};
+use flags;
+
+#[test]
+fn mem_flags_builder() {
+    assert_eq!(MemFlagsBuilder::new().build().unwrap(), flags::MEM_READ_WRITE);
+    assert_eq!(MemFlagsBuilder::new().read_only().alloc_host_ptr().build().unwrap(),
+        flags::MEM_READ_ONLY | flags::MEM_ALLOC_HOST_PTR);
+    assert_eq!(MemFlagsBuilder::new().write_only().host_read_only().host_no_access().build()
+        .unwrap(), flags::MEM_WRITE_ONLY | flags::MEM_HOST_NO_ACCESS);
+    assert!(MemFlagsBuilder::new().use_host_ptr().copy_host_ptr().build().is_err());
+
+    let pro_que = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .build().unwrap();
+
+    let flags = MemFlagsBuilder::new().read_only().build().unwrap();
+    Buffer::<f32>::new(pro_que.queue().clone(), Some(flags), [64], None).unwrap();
+
+    // Raw flags are checked as well:
+    assert!(Buffer::<f32>::new(pro_que.queue().clone(),
+        Some(flags::MEM_READ_ONLY | flags::MEM_WRITE_ONLY), [64], None).is_err());
+}
@@ -12,11 +12,59 @@ pub mod buffer_copy;
 pub mod buffer_ops_rect;
 pub mod image_ops;
 pub mod buffer_fill;
+pub mod buffer_alignment;
 pub mod clear_completed;
 pub mod concurrent;
 pub mod kernel_arg_ptr;
 pub mod vector_types;
 pub mod context_props;
+pub mod command_timeline;
+pub mod ops;
+pub mod program_cache;
+pub mod program_link;
+pub mod build_opts;
+pub mod kernel_arg_info;
+pub mod heat_map;
+pub mod event_callback;
+pub mod spatial_dims;
+pub mod stencil_plan;
+pub mod profiling;
+pub mod split_launch;
+pub mod arm_printf;
+pub mod error_hints;
+pub mod slow_call;
+pub mod read_then;
+pub mod write_tracker;
+pub mod raw_handle;
+pub mod fp64_shim;
+pub mod context_quiesce;
+pub mod callback_tracker;
+pub mod pro_que_notify;
+pub mod ocl_struct;
+pub mod work_offset;
+pub mod unchecked;
+pub mod device_specifier;
+pub mod sync_point;
+pub mod event_list;
+pub mod buffer_unmapped;
+pub mod buffer_slices;
+pub mod buffer_host_ptr;
+pub mod pinned;
+pub mod mem_flags;
+pub mod buffer_map;
+pub mod sub_devices;
+pub mod device_info;
+pub mod thread_safety;
+pub mod task_graph;
+pub mod streaming;
+pub mod extension_fn;
+pub mod fp64;
+pub mod cmd_args;
+pub mod native_kernel;
+#[cfg(feature = "ndarray")] pub mod ndarray_interop;
+#[cfg(feature = "opencl_version_2_1")] pub mod device_timer;
+#[cfg(feature = "futures")] pub mod future;
+#[cfg(feature = "image")] pub mod image_interop;
 
 use rand::{self, Rng};
 use core::error::{Error as OclError, Result as OclResult};
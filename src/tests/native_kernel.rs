@@ -0,0 +1,51 @@
+use std::slice;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use standard::{ProQue, Event, EventList};
+
+#[test]
+fn native_kernel() {
+    let pro_que = ProQue::builder()
+        .src(r#"
+            __kernel void double_it(__global int* buffer) {
+                buffer[get_global_id(0)] *= 2;
+            }
+        "#)
+        .dims([256])
+        .build().unwrap();
+
+    let queue = pro_que.queue();
+    let buffer = pro_que.create_buffer::<i32>().unwrap();
+    let vec: Vec<i32> = (0..256).collect();
+    buffer.write(&vec[..]).enq().unwrap();
+
+    if !queue.device().supports_native_kernels().unwrap() {
+        assert!(queue.enqueue_native_kernel(&[], &EventList::new(), |_| ()).is_err());
+        return;
+    }
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let calls_nk = calls.clone();
+    let len = buffer.len();
+
+    // Host step between two device steps:
+    let mut kernel_event = Event::empty();
+    pro_que.create_kernel("double_it").unwrap().arg_buf(&buffer)
+        .cmd().enew(&mut kernel_event).enq().unwrap();
+
+    let native_event = queue.enqueue_native_kernel(&[buffer.core_as_ref()], &kernel_event,
+            move |ptrs| {
+        let data = unsafe { slice::from_raw_parts_mut(ptrs[0] as *mut i32, len) };
+        for x in data.iter_mut() { *x += 1; }
+        calls_nk.fetch_add(1, Ordering::SeqCst);
+    }).unwrap();
+
+    pro_que.create_kernel("double_it").unwrap().arg_buf(&buffer)
+        .cmd().ewait(&native_event).enq().unwrap();
+
+    let mut result = vec![0; len];
+    buffer.read(&mut result).enq().unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(result.iter().zip(vec.iter()).all(|(&r, &v)| r == (v * 2 + 1) * 2));
+}
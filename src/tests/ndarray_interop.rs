@@ -0,0 +1,47 @@
+use ndarray::{Array, Axis};
+use standard::{ProQue, Buffer, SpatialDims};
+
+#[test]
+fn from_shape() {
+    assert_eq!(SpatialDims::from_shape(&[4]).unwrap().to_lens().unwrap(), [4, 1, 1]);
+    assert_eq!(SpatialDims::from_shape(&[2, 3]).unwrap().to_lens().unwrap(), [3, 2, 1]);
+    assert_eq!(SpatialDims::from_shape(&[2, 3, 4]).unwrap().to_lens().unwrap(), [4, 3, 2]);
+    assert!(SpatialDims::from_shape(&[1, 2, 3, 4]).is_err());
+}
+
+#[test]
+fn read_write_arrays() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void nop() {}")
+        .build().unwrap();
+
+    let array = Array::from_shape_fn((8, 16), |(r, c)| (r * 16 + c) as f32);
+
+    // Standard layout:
+    let buffer = Buffer::from_array(pro_que.queue().clone(), None, &array).unwrap();
+    assert_eq!(buffer.len(), 8 * 16);
+    let mut copy = Array::zeros((8, 16));
+    buffer.read_array(&mut copy).unwrap();
+    assert_eq!(copy, array);
+
+    // Strided, with a contiguous last axis (rectangular transfers):
+    let sub = array.slice(s![2..6, 4..12]);
+    let sub_buffer = Buffer::from_array(pro_que.queue().clone(), None, &sub).unwrap();
+    let mut big = Array::zeros((8, 16));
+    sub_buffer.read_array(&mut big.slice_mut(s![2..6, 4..12])).unwrap();
+    assert_eq!(big.slice(s![2..6, 4..12]), sub);
+    assert_eq!(big[[0, 0]], 0.0);
+
+    // Transposed (copied through a temporary):
+    let transposed = array.view().reversed_axes();
+    buffer.write_array(&transposed).unwrap();
+    let mut vec = vec![0.0f32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert_eq!(vec[1], array[[1, 0]]);
+    let mut transposed_copy = Array::zeros((16, 8));
+    buffer.read_array(&mut transposed_copy.view_mut().reversed_axes()).unwrap();
+    assert_eq!(transposed_copy.view().reversed_axes(), array.view());
+
+    // Length mismatch:
+    assert!(buffer.write_array(&array.subview(Axis(0), 0)).is_err());
+}
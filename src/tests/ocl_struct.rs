@@ -0,0 +1,83 @@
+use standard::{ProQue, Buffer};
+use traits::{OclStruct, ClTypeName};
+use aliases::{ClFloat4, ClUint2};
+
+ocl_struct! {
+    pub struct Particle {
+        pub pos: ClFloat4,
+        pub vel: ClFloat4,
+        pub mass: f32,
+        pub id: u32,
+        pub _pad: ClUint2,
+    }
+}
+
+ocl_struct! {
+    pub struct Misaligned {
+        pub mass: f32,
+        pub pos: ClFloat4,
+    }
+}
+
+// Nested structs are aligned to their largest field in OpenCL C:
+ocl_struct! {
+    pub struct Emitter {
+        pub rate: f32,
+        pub particle: Particle,
+    }
+}
+
+ocl_struct! {
+    pub struct PaddedEmitter {
+        pub rate: f32,
+        pub _pad0: u32,
+        pub _pad1: ClUint2,
+        pub particle: Particle,
+    }
+}
+
+#[test]
+fn ocl_struct_particles() {
+    Particle::check_layout().unwrap();
+    assert!(Misaligned::check_layout().is_err());
+
+    assert_eq!(Particle::cl_align(), 16);
+    assert!(Emitter::check_layout().is_err());
+    PaddedEmitter::check_layout().unwrap();
+
+    let src = format!(r#"
+        {}
+
+        __kernel void step(__global Particle* particles, Particle gravity, float dt) {{
+            __global Particle* p = &particles[get_global_id(0)];
+            p->vel += gravity.vel * dt;
+            p->pos += p->vel * dt;
+            p->id = get_global_id(0);
+        }}
+    "#, Particle::cl_decl());
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([256])
+        .build().unwrap();
+
+    let particles = Buffer::<Particle>::new(pro_que.queue().clone(), None, [256], None).unwrap();
+
+    let mut gravity = Particle::default();
+    gravity.vel = ClFloat4(0.0, -10.0, 0.0, 0.0);
+
+    pro_que.create_kernel("step").unwrap()
+        .arg_buf(&particles)
+        .arg_scl(gravity)
+        .arg_scl(0.5f32)
+        .enq().unwrap();
+
+    let mut vec = vec![Particle::default(); particles.len()];
+    particles.read(&mut vec).enq().unwrap();
+
+    for (i, p) in vec.iter().enumerate() {
+        assert_eq!(p.vel, ClFloat4(0.0, -5.0, 0.0, 0.0));
+        assert_eq!(p.pos, ClFloat4(0.0, -2.5, 0.0, 0.0));
+        assert_eq!(p.id, i as u32);
+    }
+}
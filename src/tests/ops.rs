@@ -0,0 +1,364 @@
+use standard::{ProQue, Buffer};
+use ops;
+
+const DATASET_SIZE: usize = 2 << 14;
+
+/// Devices may contract or reorder floating point operations, so results are
+/// only compared to within a relative tolerance.
+fn approx_eq(a: f32, b: f32) -> bool {
+    (a - b).abs() <= b.abs().max(1.0) * 1e-5
+}
+
+#[test]
+fn ops_map_zip() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([DATASET_SIZE])
+        .build().unwrap();
+
+    let a = pro_que.create_buffer::<f32>().unwrap();
+    let b = pro_que.create_buffer::<f32>().unwrap();
+    let mut dst = pro_que.create_buffer::<f32>().unwrap();
+
+    let a_vec: Vec<f32> = (0..DATASET_SIZE).map(|i| i as f32).collect();
+    a.write(&a_vec).enq().unwrap();
+    b.cmd().fill(3.0, None).enq().unwrap();
+
+    // Run twice to exercise the program cache:
+    for _ in 0..2 {
+        ops::map(&a, &mut dst, "x * x + 1.0f").unwrap();
+    }
+
+    let mut vec = vec![0.0f32; dst.len()];
+    dst.read(&mut vec).enq().unwrap();
+
+    for (i, &ele) in vec.iter().enumerate() {
+        let expected = (i as f32) * (i as f32) + 1.0;
+        assert!(approx_eq(ele, expected), "dst[{}]: {} != {}", i, ele, expected);
+    }
+
+    ops::zip(&a, &b, &mut dst, "a + b").unwrap();
+    dst.read(&mut vec).enq().unwrap();
+
+    for (i, &ele) in vec.iter().enumerate() {
+        assert!(approx_eq(ele, i as f32 + 3.0), "dst[{}]: {} != {}", i, ele, i as f32 + 3.0);
+    }
+}
+
+#[test]
+fn ops_compact() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([DATASET_SIZE])
+        .build().unwrap();
+
+    let src = pro_que.create_buffer::<i32>().unwrap();
+    let flags = pro_que.create_buffer::<u8>().unwrap();
+    let mut dst = pro_que.create_buffer::<i32>().unwrap();
+
+    let src_vec: Vec<i32> = (0..DATASET_SIZE as i32).collect();
+    let flags_vec: Vec<u8> = (0..DATASET_SIZE).map(|i| (i % 3 == 0) as u8).collect();
+    src.write(&src_vec).enq().unwrap();
+    flags.write(&flags_vec).enq().unwrap();
+
+    let count = ops::compact(&src, &flags, &mut dst).unwrap();
+    let expected: Vec<i32> = src_vec.iter().cloned().filter(|&v| v % 3 == 0).collect();
+    assert_eq!(count, expected.len());
+
+    let mut vec = vec![0i32; dst.len()];
+    dst.read(&mut vec).enq().unwrap();
+    assert_eq!(&vec[..count], &expected[..]);
+}
+
+#[test]
+fn ops_transpose_copy_pitched() {
+    let (rows, cols) = (67, 45);
+
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([rows * cols])
+        .build().unwrap();
+
+    let src = pro_que.create_buffer::<f32>().unwrap();
+    let mut dst = pro_que.create_buffer::<f32>().unwrap();
+
+    let src_vec: Vec<f32> = (0..rows * cols).map(|i| i as f32).collect();
+    src.write(&src_vec).enq().unwrap();
+
+    ops::transpose(&src, &mut dst, rows, cols).unwrap();
+
+    let mut vec = vec![0.0f32; dst.len()];
+    dst.read(&mut vec).enq().unwrap();
+
+    for r in 0..rows {
+        for c in 0..cols {
+            assert_eq!(vec[c * rows + r], src_vec[r * cols + c]);
+        }
+    }
+
+    // Copy the top-left 10 x 20 block into a densely packed buffer:
+    let mut packed = pro_que.create_buffer::<f32>().unwrap();
+    ops::copy_pitched(&src, cols, &mut packed, 20, 10, 20).unwrap();
+    packed.read(&mut vec).enq().unwrap();
+
+    for r in 0..10 {
+        for c in 0..20 {
+            assert_eq!(vec[r * 20 + c], src_vec[r * cols + c]);
+        }
+    }
+}
+
+#[test]
+fn ops_convert() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([256])
+        .build().unwrap();
+
+    let bytes = pro_que.create_buffer::<u8>().unwrap();
+    let mut floats = pro_que.create_buffer::<f32>().unwrap();
+    let mut halves = pro_que.create_buffer::<u16>().unwrap();
+    let mut ints = pro_que.create_buffer::<i32>().unwrap();
+
+    let bytes_vec: Vec<u8> = (0..256).map(|i| i as u8).collect();
+    bytes.write(&bytes_vec).enq().unwrap();
+
+    ops::unorm8_to_float(&bytes, &mut floats).unwrap();
+    ops::float_to_half(&floats, &mut halves).unwrap();
+    ops::half_to_float(&halves, &mut floats).unwrap();
+
+    let mut bytes_out = pro_que.create_buffer::<u8>().unwrap();
+    ops::float_to_unorm8(&floats, &mut bytes_out).unwrap();
+
+    let mut vec = vec![0u8; 256];
+    bytes_out.read(&mut vec).enq().unwrap();
+    assert_eq!(vec, bytes_vec);
+
+    ops::convert::<u8, i32>(&bytes, &mut ints).unwrap();
+    let mut ints_vec = vec![0i32; 256];
+    ints.read(&mut ints_vec).enq().unwrap();
+    assert!(ints_vec.iter().enumerate().all(|(i, &v)| v == i as i32));
+}
+
+#[test]
+fn ops_accumulator() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([64])
+        .build().unwrap();
+
+    let partials = pro_que.create_buffer::<u32>().unwrap();
+    let counts = ops::Accumulator::<u32>::new(pro_que.queue(), 8).unwrap();
+    let sums = ops::Accumulator::<f32>::new(pro_que.queue(), 4).unwrap();
+    let partials_f32 = pro_que.create_buffer::<f32>().unwrap();
+
+    for i in 1..4 {
+        partials.cmd().fill(i, None).enq().unwrap();
+        counts.merge(&partials).unwrap();
+        partials_f32.cmd().fill(0.5, None).enq().unwrap();
+        sums.merge(&partials_f32).unwrap();
+    }
+
+    // Each of the 8 totals receives 64 / 8 partials per launch:
+    assert_eq!(counts.read_and_reset().unwrap(), vec![(1 + 2 + 3) * 8; 8]);
+    assert_eq!(counts.read_and_reset().unwrap(), vec![0; 8]);
+    assert_eq!(sums.read_and_reset().unwrap(), vec![0.5 * 16.0 * 3.0; 4]);
+
+    let odd = pro_que.create_buffer::<u32>().unwrap();
+    assert!(ops::Accumulator::<u32>::new(pro_que.queue(), 7).unwrap().merge(&odd).is_err());
+    assert!(ops::Accumulator::<u8>::new(pro_que.queue(), 8).is_err());
+}
+
+#[test]
+fn ops_fill_random() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([DATASET_SIZE])
+        .build().unwrap();
+
+    let mut floats = pro_que.create_buffer::<f32>().unwrap();
+    let mut ints = pro_que.create_buffer::<i32>().unwrap();
+
+    ops::fill_random(&mut floats, (-5.0f32, 20.0), 7).unwrap();
+    ops::fill_random(&mut ints, (-3i32, 4), 7).unwrap();
+
+    let mut float_vec = vec![0.0f32; DATASET_SIZE];
+    floats.read(&mut float_vec).enq().unwrap();
+    assert!(float_vec.iter().all(|&v| v >= -5.0 && v < 20.0));
+    assert!(float_vec.iter().any(|&v| v != float_vec[0]));
+
+    let mut int_vec = vec![0i32; DATASET_SIZE];
+    ints.read(&mut int_vec).enq().unwrap();
+    assert!(int_vec.iter().all(|&v| v >= -3 && v < 4));
+    for val in -3..4 { assert!(int_vec.contains(&val)); }
+
+    // The same seed produces the same values:
+    let mut again = vec![0.0f32; DATASET_SIZE];
+    ops::fill_random(&mut floats, (-5.0f32, 20.0), 7).unwrap();
+    floats.read(&mut again).enq().unwrap();
+    assert_eq!(float_vec, again);
+
+    assert!(ops::fill_random(&mut ints, (1, 1), 0).is_err());
+    assert!(ops::fill_random(&mut ints, (4, -3), 0).is_err());
+    assert!(ops::fill_random(&mut floats, (20.0, -5.0), 0).is_err());
+}
+
+#[test]
+fn ops_reduce() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([DATASET_SIZE])
+        .build().unwrap();
+
+    let ints = pro_que.create_buffer::<i32>().unwrap();
+    let int_vec: Vec<i32> = (0..DATASET_SIZE as i32).map(|i| (i % 101) - 50).collect();
+    ints.write(&int_vec).enq().unwrap();
+
+    assert_eq!(ops::sum(&ints).unwrap(), int_vec.iter().fold(0, |acc, &v| acc + v));
+    assert_eq!(ops::min(&ints).unwrap(), -50);
+    assert_eq!(ops::max(&ints).unwrap(), 50);
+
+    let a = pro_que.create_buffer::<f32>().unwrap();
+    let b = pro_que.create_buffer::<f32>().unwrap();
+    let a_vec: Vec<f32> = (0..DATASET_SIZE).map(|i| (i % 8) as f32).collect();
+    a.write(&a_vec).enq().unwrap();
+    b.cmd().fill(0.5, None).enq().unwrap();
+
+    let expected = a_vec.iter().fold(0.0, |acc, &v| acc + v * 0.5);
+    assert_eq!(ops::dot(&a, &b).unwrap(), expected);
+    assert_eq!(ops::max(&a).unwrap(), 7.0);
+
+    // A single element, reduced by one work group:
+    let one = Buffer::<f32>::new(pro_que.queue().clone(), None, [1], Some(&[3.0f32])).unwrap();
+    assert_eq!(ops::sum(&one).unwrap(), 3.0);
+    assert_eq!(ops::min(&one).unwrap(), 3.0);
+}
+
+#[test]
+fn ops_scan() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([DATASET_SIZE])
+        .build().unwrap();
+
+    // Long enough to need more than one level of block totals on most
+    // devices, and not a multiple of any block length:
+    for &len in &[1, 1000, 300_001] {
+        let src_vec: Vec<u32> = (0..len).map(|i| (i % 3) as u32).collect();
+        let src = Buffer::new(pro_que.queue().clone(), None, [len], Some(&src_vec[..]))
+            .unwrap();
+        let mut dst = Buffer::<u32>::new(pro_que.queue().clone(), None, [len], None).unwrap();
+        let mut vec = vec![0u32; len];
+
+        ops::scan_inclusive(&src, &mut dst).unwrap();
+        dst.read(&mut vec).enq().unwrap();
+        let mut total = 0;
+        for (i, &ele) in vec.iter().enumerate() {
+            total += src_vec[i];
+            assert_eq!(ele, total);
+        }
+
+        ops::scan_exclusive(&src, &mut dst).unwrap();
+        dst.read(&mut vec).enq().unwrap();
+        let mut total = 0;
+        for (i, &ele) in vec.iter().enumerate() {
+            assert_eq!(ele, total);
+            total += src_vec[i];
+        }
+    }
+}
+
+#[test]
+fn ops_sort() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([DATASET_SIZE])
+        .build().unwrap();
+    let queue = pro_que.queue().clone();
+
+    // Not a power of two, with duplicates and negative keys:
+    let len = 10_007;
+    let keys_vec: Vec<i32> = (0..len as i32).map(|i| (i * 7919) % 2003 - 1000).collect();
+    let vals_vec: Vec<u32> = (0..len as u32).collect();
+
+    let mut keys = Buffer::new(queue.clone(), None, [len], Some(&keys_vec[..])).unwrap();
+    ops::sort(&mut keys).unwrap();
+    let mut sorted = vec![0i32; len];
+    keys.read(&mut sorted).enq().unwrap();
+    let mut expected = keys_vec.clone();
+    expected.sort();
+    assert_eq!(sorted, expected);
+
+    // The radix sort is stable:
+    let mut keys = Buffer::new(queue.clone(), None, [len], Some(&keys_vec[..])).unwrap();
+    let mut vals = Buffer::new(queue.clone(), None, [len], Some(&vals_vec[..])).unwrap();
+    ops::sort_by_key(&mut keys, &mut vals).unwrap();
+    let mut sorted_vals = vec![0u32; len];
+    vals.read(&mut sorted_vals).enq().unwrap();
+    let mut pairs: Vec<(i32, u32)> = keys_vec.iter().cloned().zip(vals_vec.iter().cloned())
+        .collect();
+    pairs.sort();
+    assert_eq!(sorted_vals, pairs.iter().map(|&(_, v)| v).collect::<Vec<_>>());
+
+    // Narrow keys take only two passes:
+    let byte_vec: Vec<i8> = keys_vec.iter().map(|&k| k as i8).collect();
+    let mut keys = Buffer::new(queue.clone(), None, [len], Some(&byte_vec[..])).unwrap();
+    ops::sort(&mut keys).unwrap();
+    let mut sorted_bytes = vec![0i8; len];
+    keys.read(&mut sorted_bytes).enq().unwrap();
+    let mut expected = byte_vec.clone();
+    expected.sort();
+    assert_eq!(sorted_bytes, expected);
+
+    // Floating point keys use the bitonic sort:
+    let float_vec: Vec<f32> = keys_vec.iter().map(|&k| k as f32 * 0.5).collect();
+    let mut keys = Buffer::new(queue.clone(), None, [len], Some(&float_vec[..])).unwrap();
+    let mut vals = Buffer::new(queue.clone(), None, [len], Some(&keys_vec[..])).unwrap();
+    ops::sort_by_key(&mut keys, &mut vals).unwrap();
+    let mut sorted_floats = vec![0.0f32; len];
+    keys.read(&mut sorted_floats).enq().unwrap();
+    vals.read(&mut sorted).enq().unwrap();
+    assert!(sorted_floats.windows(2).all(|w| w[0] <= w[1]));
+    assert!(sorted_floats.iter().zip(sorted.iter()).all(|(&f, &k)| f == k as f32 * 0.5));
+}
+
+#[test]
+fn ops_elementwise() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([DATASET_SIZE])
+        .build().unwrap();
+
+    let x = pro_que.create_buffer::<f32>().unwrap();
+    let y = pro_que.create_buffer::<f32>().unwrap();
+    x.cmd().fill(2.0, None).enq().unwrap();
+    y.cmd().fill(1.0, None).enq().unwrap();
+
+    let axpy = ops::ElementwiseKernel::new(pro_que.queue(), "axpy",
+        "float a, __global float const* x, float* y", "y[i] = a * x[i] + y[i]").unwrap();
+
+    axpy.call().scl(3.0f32).buf(&x).buf(&y).enq().unwrap();
+
+    let mut vec = vec![0.0f32; DATASET_SIZE];
+    y.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 7.0));
+
+    // Only the first ten elements:
+    axpy.call().scl(1.0f32).buf(&x).buf(&y).len(10).enq().unwrap();
+    y.read(&mut vec).enq().unwrap();
+    assert!(vec[..10].iter().all(|&v| v == 9.0));
+    assert!(vec[10..].iter().all(|&v| v == 7.0));
+
+    // Mismatched and missing arguments:
+    assert!(axpy.call().scl(3i32).buf(&x).buf(&y).enq().is_err());
+    assert!(axpy.call().buf(&x).scl(3.0f32).buf(&y).enq().is_err());
+    assert!(axpy.call().scl(3.0f32).buf(&x).enq().is_err());
+
+    // Lengths beyond the shortest buffer:
+    let short = Buffer::<f32>::new(pro_que.queue().clone(), None, [10], None).unwrap();
+    assert!(axpy.call().scl(1.0f32).buf(&x).buf(&short).len(11).enq().is_err());
+    axpy.call().scl(1.0f32).buf(&x).buf(&short).enq().unwrap();
+    let mut vec = vec![0.0f32; 10];
+    short.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 2.0));
+}
@@ -0,0 +1,40 @@
+use standard::{ProQue, PinnedVec};
+
+#[test]
+fn pinned_vec() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([4096])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+
+    let mut pinned = PinnedVec::<f32>::new(pro_que.queue().clone(), 4096).unwrap();
+    assert_eq!(pinned.len(), 4096);
+    assert!(pinned.iter().all(|&v| v == 0.0));
+
+    for i in 0..4 {
+        for (idx, val) in pinned.iter_mut().enumerate() {
+            *val = (idx + i) as f32;
+        }
+
+        pinned.write_to(&buffer).enq().unwrap();
+        kernel.enq().unwrap();
+        pinned.read_from(&buffer).enq().unwrap();
+
+        assert!(pinned.iter().enumerate().all(|(idx, &v)| v == (idx + i + 1) as f32));
+    }
+
+    // Equivalent to the above, using the slice directly:
+    buffer.write(&pinned).enq().unwrap();
+    buffer.read(&mut pinned).enq().unwrap();
+}
@@ -0,0 +1,128 @@
+use standard::{ProQue, Program};
+
+static SRC: &'static str = r#"
+    __kernel void add(__global float* buffer, float addend) {
+        buffer[get_global_id(0)] += addend;
+    }
+"#;
+
+#[test]
+fn pro_que_set_dims_follows() {
+    let mut pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+
+    // Only the first half of the buffer is processed after shrinking:
+    let clone = pro_que.clone();
+    pro_que.set_dims([32]);
+    assert_eq!(clone.dims().to_lens().unwrap(), [32, 1, 1]);
+    assert_eq!(kernel.get_gws().to_lens().unwrap(), [32, 1, 1]);
+    kernel.enq().unwrap();
+
+    let mut vec = vec![0.0f32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec[..32].iter().all(|&v| v == 1.0));
+    assert!(vec[32..].iter().all(|&v| v == 0.0));
+
+    // An explicitly set work size no longer follows:
+    let kernel = kernel.gws([64]);
+    pro_que.set_dims([16]);
+    assert_eq!(kernel.get_gws().to_lens().unwrap(), [64, 1, 1]);
+}
+
+#[test]
+fn pro_que_set_program_stale() {
+    let mut pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+    assert!(!kernel.is_stale());
+
+    let program = Program::builder()
+        .src(SRC)
+        .devices(pro_que.queue().device())
+        .build(pro_que.context()).unwrap();
+    pro_que.set_program(program);
+
+    assert!(kernel.is_stale());
+    let err = kernel.enq().unwrap_err();
+    assert!(format!("{}", err).contains("ProQue::set_program"));
+
+    pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32)
+        .enq().unwrap();
+}
+
+#[test]
+fn pro_que_default_dims() {
+    let pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([256])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer_with_data(None, &vec![1.0f32; 256]).unwrap();
+    assert_eq!(buffer.len(), 256);
+    assert!(pro_que.create_buffer_with_data(None, &vec![1.0f32; 255]).is_err());
+
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+    assert_eq!(kernel.get_gws().to_len(), 256);
+    kernel.enq().unwrap();
+
+    let mut vec = vec![0.0f32; 256];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 2.0));
+}
+
+#[test]
+fn pro_que_additional_programs() {
+    let mut pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([64])
+        .build().unwrap();
+
+    let handle = pro_que.build_additional(r#"
+        __kernel void mul(__global float* buffer, float coeff) {
+            buffer[get_global_id(0)] *= coeff;
+        }
+    "#).unwrap();
+
+    // Kernels are looked up in the main program unless a handle is given:
+    assert!(pro_que.create_kernel("mul").is_err());
+    assert!(pro_que.create_kernel_from(handle, "add").is_err());
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    pro_que.create_kernel("add").unwrap().arg_buf(&buffer).arg_scl(2.0f32).enq().unwrap();
+    let mul = pro_que.clone().create_kernel_from(handle, "mul").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(3.0f32);
+
+    // Replacing the main program does not affect additional programs:
+    let program = Program::builder()
+        .src(SRC)
+        .devices(pro_que.queue().device())
+        .build(pro_que.context()).unwrap();
+    pro_que.set_program(program);
+    assert!(!mul.is_stale());
+    mul.enq().unwrap();
+
+    let mut vec = vec![0.0f32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 6.0));
+
+    let other = ProQue::builder().src(SRC).build().unwrap();
+    assert!(other.create_kernel_from(handle, "mul").is_err());
+}
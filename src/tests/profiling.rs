@@ -0,0 +1,240 @@
+use standard::{Context, Queue, QueueBuilder, Program, Kernel, Buffer, Event};
+use flags::{self, QUEUE_PROFILING_ENABLE};
+use profiling::{Profiler, Sweep};
+
+#[test]
+fn profiler() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let program = Program::builder().src(src).devices(device).build(&context).unwrap();
+
+    // Profilers can only be attached to queues created with profiling enabled:
+    let plain_queue = Queue::new(&context, device).unwrap();
+    assert!(!plain_queue.is_profiling_enabled());
+    assert!(plain_queue.set_profiler(Some(Profiler::new())).is_err());
+
+    let queue = Queue::with_properties(&context, device, QUEUE_PROFILING_ENABLE).unwrap();
+    assert!(queue.is_profiling_enabled());
+
+    let profiler = Profiler::new();
+    queue.set_profiler(Some(profiler.clone())).unwrap();
+
+    let buffer = Buffer::<f32>::new(queue.clone(), Some(flags::MEM_READ_WRITE), [1 << 12], None)
+        .unwrap();
+    let kernel = Kernel::new("add", &program, &queue).unwrap()
+        .gws([1 << 12])
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+
+    let mut vec = vec![0.0f32; buffer.len()];
+
+    for _ in 0..6 {
+        kernel.enq().unwrap();
+    }
+    buffer.read(&mut vec).enq().unwrap();
+
+    queue.finish();
+    profiler.collect().unwrap();
+    assert_eq!(profiler.pending_len(), 0);
+    assert!(vec.iter().all(|&v| v == 6.0));
+
+    let kernel_stats = profiler.get("Kernel::add").unwrap();
+    assert_eq!(kernel_stats.count(), 6);
+    assert!(kernel_stats.min() <= kernel_stats.mean());
+    assert!(kernel_stats.mean() <= kernel_stats.max());
+    assert!(kernel_stats.max() <= kernel_stats.total());
+    assert_eq!(profiler.get("Buffer::read").unwrap().count(), 1);
+    assert_eq!(profiler.stats().len(), 2);
+
+    // Commands given their own event are recorded with a copy of it:
+    let mut event = Event::empty();
+    kernel.cmd().enew(&mut event).enq().unwrap();
+    event.wait().unwrap();
+    assert!(event.duration().unwrap() <= event.queued_to_end().unwrap());
+    assert_eq!(profiler.pending_len(), 1);
+    profiler.collect().unwrap();
+    assert_eq!(profiler.get("Kernel::add").unwrap().count(), 7);
+
+    queue.set_profiler(None).unwrap();
+    kernel.enq().unwrap();
+    assert_eq!(profiler.pending_len(), 0);
+
+    profiler.reset();
+    assert!(profiler.stats().is_empty());
+}
+
+#[test]
+fn chrome_trace() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let program = Program::builder().src(src).devices(device).build(&context).unwrap();
+    let queue_a = Queue::with_properties(&context, device, QUEUE_PROFILING_ENABLE).unwrap();
+    let queue_b = Queue::with_properties(&context, device, QUEUE_PROFILING_ENABLE).unwrap();
+
+    let profiler = Profiler::new();
+    let mut out = Vec::new();
+    assert!(profiler.write_chrome_trace(&mut out).is_err());
+
+    profiler.set_tracing(true);
+    queue_a.set_profiler(Some(profiler.clone())).unwrap();
+    queue_b.set_profiler(Some(profiler.clone())).unwrap();
+
+    let buffer = Buffer::<f32>::new(queue_a.clone(), Some(flags::MEM_READ_WRITE), [256], None)
+        .unwrap();
+    let kernel = Kernel::new("add", &program, &queue_a).unwrap()
+        .gws([256])
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+
+    kernel.enq().unwrap();
+    queue_a.finish();
+    kernel.cmd().queue(&queue_b).enq().unwrap();
+    queue_b.finish();
+    profiler.collect().unwrap();
+
+    profiler.write_chrome_trace(&mut out).unwrap();
+    let json = String::from_utf8(out).unwrap();
+    assert!(json.starts_with("{\"traceEvents\":["));
+    assert!(json.contains("\"name\":\"Kernel::add\",\"cat\":\"kernel\",\"ph\":\"X\""));
+    assert!(json.contains("\"args\":{\"name\":\"Queue 1\"}"));
+
+    let trace = profiler.take_trace();
+    assert_eq!(trace.len(), 2);
+    assert_eq!(trace[0].queue_idx(), 0);
+    assert_eq!(trace[1].queue_idx(), 1);
+    assert!(trace.iter().all(|rec| rec.start() <= rec.end()));
+    assert!(profiler.take_trace().is_empty());
+}
+
+#[test]
+fn profiler_regions() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let program = Program::builder().src(src).devices(device).build(&context).unwrap();
+    let queue = Queue::with_properties(&context, device, QUEUE_PROFILING_ENABLE).unwrap();
+
+    let profiler = Profiler::new();
+    queue.set_profiler(Some(profiler.clone())).unwrap();
+
+    let buffer = Buffer::<f32>::new(queue.clone(), Some(flags::MEM_READ_WRITE), [1024], None)
+        .unwrap();
+    let kernel = Kernel::new("add", &program, &queue).unwrap()
+        .gws([1024])
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+    let mut vec = vec![0.0f32; buffer.len()];
+
+    // Outside of any region:
+    kernel.enq().unwrap();
+
+    {
+        let fft = profiler.region("fft");
+        assert_eq!(fft.path(), "fft");
+        kernel.enq().unwrap();
+
+        // Regions are not visible from other threads:
+        let other = profiler.clone();
+        let other_region = ::std::thread::spawn(move || {
+            let _ifft = other.region("ifft");
+            other.current_region()
+        }).join().unwrap();
+        assert_eq!(other_region, Some("ifft".to_owned()));
+        assert_eq!(profiler.current_region(), Some("fft".to_owned()));
+
+        {
+            let _twiddle = profiler.region("twiddle");
+            assert_eq!(profiler.current_region(), Some("fft/twiddle".to_owned()));
+            kernel.enq().unwrap();
+            kernel.enq().unwrap();
+        }
+
+        assert_eq!(profiler.current_region(), Some("fft".to_owned()));
+        buffer.read(&mut vec).enq().unwrap();
+    }
+
+    assert_eq!(profiler.current_region(), None);
+    queue.finish();
+    profiler.collect().unwrap();
+
+    assert_eq!(profiler.get("Kernel::add").unwrap().count(), 4);
+    assert_eq!(profiler.get_region("fft").unwrap().count(), 4);
+    assert_eq!(profiler.get_region("fft/twiddle").unwrap().count(), 2);
+    assert_eq!(profiler.region_stats().len(), 2);
+    assert!(profiler.get_region("fft").unwrap().total() >=
+        profiler.get_region("fft/twiddle").unwrap().total());
+    assert!(profiler.to_string().contains("[fft/twiddle]: count: 2"));
+}
+
+#[test]
+fn sweep() {
+    let src = r#"
+        __kernel void scale(__global float* buffer, float factor) {
+            size_t idx = get_global_id(0) * UNROLL;
+            for (int i = 0; i < UNROLL; i++) {
+                buffer[idx + i] *= factor;
+            }
+        }
+    "#;
+
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let queue = Queue::with_properties(&context, device, QUEUE_PROFILING_ENABLE).unwrap();
+    let buffer = Buffer::<f32>::new(queue.clone(), None, [1 << 12], None).unwrap();
+
+    let sweep = Sweep::new("scale", Program::builder().src(src), [1 << 10])
+        .lws(vec![[16], [32], [1 << 20]])
+        .define("UNROLL", vec![1, 4])
+        .iters(3);
+
+    assert_eq!(sweep.points().len(), 6);
+
+    let report = sweep.run(&context, &queue, |kernel, point| {
+        // Cover the whole buffer regardless of unrolling:
+        let unroll: usize = point.define("UNROLL").unwrap().parse().unwrap();
+        Ok(kernel.gws([(1 << 12) / unroll]).arg_buf(&buffer).arg_scl(1.0f32))
+    }).unwrap();
+
+    // The oversized local work size fails for each unroll factor:
+    let failed = report.results().iter().filter(|r| r.outcome().is_err()).count();
+    assert_eq!(failed, 2);
+    assert!(report.results()[..4].iter().all(|r| r.outcome().is_ok()));
+    assert!(report.best().is_some());
+
+    let plain_queue = Queue::new(&context, device).unwrap();
+    assert!(sweep.run(&context, &plain_queue, |kernel, _| Ok(kernel)).is_err());
+}
+
+#[test]
+fn queue_builder() {
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+
+    let queue = Queue::builder().build(&context, device).unwrap();
+    assert!(!queue.is_profiling_enabled());
+
+    let queue = Queue::builder().profiling(true).build(&context, device).unwrap();
+    assert!(queue.is_profiling_enabled());
+    queue.set_profiler(Some(Profiler::new())).unwrap();
+
+    let mut bldr = QueueBuilder::new();
+    bldr.properties(QUEUE_PROFILING_ENABLE).profiling(false);
+    assert!(!bldr.build(&context, device).unwrap().is_profiling_enabled());
+}
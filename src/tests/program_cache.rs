@@ -0,0 +1,36 @@
+use std::env;
+use std::fs;
+use standard::{ProQue, Program};
+
+#[test]
+fn program_binary_cache() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let cache_dir = env::temp_dir().join("ocl_program_binary_cache_test");
+    let _ = fs::remove_dir_all(&cache_dir);
+
+    // The first build populates the cache, the second loads from it:
+    for _ in 0..2 {
+        let pro_que = ProQue::builder()
+            .prog_bldr(Program::builder().src(src).cache_dir(cache_dir.clone()))
+            .dims([1 << 10])
+            .build().unwrap();
+
+        let buffer = pro_que.create_buffer::<f32>().unwrap();
+        pro_que.create_kernel("add").unwrap()
+            .arg_buf(&buffer)
+            .arg_scl(10.0f32)
+            .enq().unwrap();
+
+        let mut vec = vec![0.0f32; buffer.len()];
+        buffer.read(&mut vec).enq().unwrap();
+        assert!(vec.iter().all(|&v| v == 10.0));
+    }
+
+    assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 1);
+    let _ = fs::remove_dir_all(&cache_dir);
+}
@@ -0,0 +1,53 @@
+use standard::{Platform, Device, Context, Queue, Program, Buffer, Kernel};
+use enums::Status;
+
+#[test]
+fn compile_and_link() {
+    let header = "float scale(float x);";
+
+    let lib_src = r#"
+        #include "scale.h"
+        float scale(float x) { return x * 2.0f; }
+    "#;
+
+    let main_src = r#"
+        #include "scale.h"
+        __kernel void apply_scale(__global float* buffer) {
+            buffer[get_global_id(0)] = scale(buffer[get_global_id(0)]);
+        }
+    "#;
+
+    let platform = Platform::default();
+    let device = Device::first(platform);
+    let context = Context::builder().platform(platform).devices(device).build().unwrap();
+    let queue = Queue::new(&context, device).unwrap();
+
+    let lib = Program::builder().src(lib_src).header("scale.h", header)
+        .devices(device).compile_only().build(&context).unwrap();
+    let main = Program::builder().src(main_src).header("scale.h", header)
+        .devices(device).compile_only().build(&context).unwrap();
+
+    let program = Program::link(&[lib, main], "").unwrap();
+
+    let buffer = Buffer::new(queue.clone(), None, [64], Some(&vec![1.5f32; 64][..])).unwrap();
+    Kernel::new("apply_scale", &program, &queue).unwrap()
+        .gws([64])
+        .arg_buf(&buffer)
+        .enq().unwrap();
+
+    let mut vec = vec![0.0f32; 64];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 3.0));
+}
+
+#[test]
+fn compile_failure_status() {
+    let platform = Platform::default();
+    let device = Device::first(platform);
+    let context = Context::builder().platform(platform).devices(device).build().unwrap();
+
+    let err = Program::builder().src("__kernel void broken(__global float* buffer {}")
+        .devices(device).compile_only().build(&context).unwrap_err();
+
+    assert_eq!(err.status(), Some(Status::CL_COMPILE_PROGRAM_FAILURE));
+}
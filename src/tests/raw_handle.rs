@@ -0,0 +1,53 @@
+use std::thread;
+use core::{self, CommandQueueInfo, CommandQueueInfoResult, MemInfo, MemInfoResult};
+use standard::{ProQue, RawQueueHandle, RawMemHandle};
+
+fn queue_ref_count(handle: RawQueueHandle) -> u32 {
+    let queue = unsafe { handle.to_core().unwrap() };
+    match core::get_command_queue_info(&queue, CommandQueueInfo::ReferenceCount) {
+        // Less the reference held by `queue`:
+        CommandQueueInfoResult::ReferenceCount(count) => count - 1,
+        res => panic!("Unexpected result: {:?}", res),
+    }
+}
+
+#[test]
+fn raw_handles() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([64])
+        .build().unwrap();
+
+    let queue_handle = RawQueueHandle::from(pro_que.queue());
+    assert_eq!(queue_handle.as_ptr(), unsafe { pro_que.queue().core_as_ref().as_ptr() });
+
+    let count = queue_ref_count(queue_handle);
+    unsafe { queue_handle.retain().unwrap(); }
+    assert_eq!(queue_ref_count(queue_handle), count + 1);
+    unsafe { queue_handle.release().unwrap(); }
+    assert_eq!(queue_ref_count(queue_handle), count);
+
+    // A retained memory object outlives its buffer and may cross threads:
+    let buffer = pro_que.create_buffer::<u32>().unwrap();
+    buffer.cmd().fill(7, None).enq().unwrap();
+    let mem_handle = RawMemHandle::from(buffer.core_as_ref());
+    unsafe { mem_handle.retain().unwrap(); }
+    drop(buffer);
+
+    let queue = pro_que.queue().clone();
+
+    thread::spawn(move || {
+        let mem = unsafe { mem_handle.to_core().unwrap() };
+        match core::get_mem_object_info(&mem, MemInfo::Size) {
+            MemInfoResult::Size(size) => assert_eq!(size, 64 * 4),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+
+        let mut vec = vec![0u32; 64];
+        unsafe { core::enqueue_read_buffer(&queue, &mem, true, 0, &mut vec, None, None)
+            .unwrap(); }
+        assert!(vec.iter().all(|&v| v == 7));
+
+        unsafe { mem_handle.release().unwrap(); }
+    }).join().unwrap();
+}
@@ -0,0 +1,26 @@
+use std::sync::mpsc;
+use standard::ProQue;
+
+#[test]
+fn read_then() {
+    let pro_que = ProQue::builder()
+        .src("__kernel void unused() {}")
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    buffer.cmd().fill(3.0, None).enq().unwrap();
+
+    let (tx, rx) = mpsc::channel();
+
+    let event = buffer.cmd().offset(24).read_then(vec![0.0f32; 1000], move |result| {
+        let data = result.unwrap();
+        tx.send((data.len(), data.iter().sum::<f32>())).unwrap();
+    }).unwrap();
+
+    event.wait().unwrap();
+    assert_eq!(rx.recv().unwrap(), (1000, 3000.0));
+
+    // Out of range:
+    assert!(buffer.cmd().offset(25).read_then(vec![0.0f32; 1000], |_| ()).is_err());
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+use standard::ProQue;
+
+#[test]
+fn slow_call_warnings() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([1 << 20])
+        .build().unwrap();
+
+    let queue = pro_que.queue();
+    assert_eq!(queue.slow_call_threshold(), None);
+
+    queue.set_slow_call_threshold(Some(Duration::from_millis(250)));
+    assert_eq!(queue.slow_call_threshold(), Some(Duration::from_millis(250)));
+
+    // Shared between clones:
+    queue.clone().set_slow_call_threshold(Some(Duration::new(0, 0)));
+    assert_eq!(queue.slow_call_threshold(), Some(Duration::new(0, 0)));
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let mut kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl_named("addend", Some(1.0f32));
+
+    let count = queue.slow_call_count();
+    kernel.set_arg_scl_named("addend", 2.0f32).unwrap();
+    kernel.enq().unwrap();
+    let mut vec = vec![0.0f32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(queue.slow_call_count() > count);
+
+    queue.set_slow_call_threshold(None);
+    let count = queue.slow_call_count();
+    kernel.enq().unwrap();
+    buffer.read(&mut vec).enq().unwrap();
+    assert_eq!(queue.slow_call_count(), count);
+    assert!(vec.iter().all(|&v| v == 4.0));
+}
@@ -0,0 +1,50 @@
+use standard::{ProQue, SpatialDims};
+
+#[test]
+fn spatial_dims_indices() {
+    let src = r#"
+        __kernel void coords(__global uint4* coords) {
+            size_t idx = get_global_id(0) + get_global_size(0) *
+                (get_global_id(1) + get_global_size(1) * get_global_id(2));
+            coords[idx] = (uint4)(get_global_id(0), get_global_id(1), get_global_id(2), 0);
+        }
+    "#;
+
+    let dims = SpatialDims::from((5, 4, 3));
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims(dims)
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<::aliases::ClUint4>().unwrap();
+    pro_que.create_kernel("coords").unwrap().arg_buf(&buffer).enq().unwrap();
+
+    let mut vec = vec![Default::default(); buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+
+    assert_eq!(dims.iter_indices().len(), vec.len());
+
+    for (idx, coords) in dims.iter_indices().enumerate() {
+        assert_eq!(dims.linear_index(coords).unwrap(), idx);
+        assert_eq!([vec[idx].0 as usize, vec[idx].1 as usize, vec[idx].2 as usize], coords);
+    }
+}
+
+#[test]
+fn spatial_dims_indices_1d_2d() {
+    let dims = SpatialDims::from([7]);
+    for (idx, coords) in dims.iter_indices().enumerate() {
+        assert_eq!(coords, [idx, 0, 0]);
+        assert_eq!(dims.linear_index(coords).unwrap(), idx);
+    }
+    assert!(dims.linear_index([7, 0, 0]).is_err());
+    assert!(dims.linear_index([0, 1, 0]).is_err());
+
+    let dims = SpatialDims::from((5, 3));
+    for (idx, coords) in dims.iter_indices().enumerate() {
+        assert_eq!(dims.linear_index(coords).unwrap(), idx);
+        assert_eq!(dims.linear_index((coords[0], coords[1])).unwrap(), idx);
+    }
+    assert!(dims.linear_index([0, 0, 1]).is_err());
+}
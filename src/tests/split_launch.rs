@@ -0,0 +1,49 @@
+use std::time::Duration;
+use standard::{ProQue, SplitLaunch, SpatialDims};
+
+#[test]
+fn split_launch() {
+    // 1000 items per launch:
+    let split = SplitLaunch::new(Duration::new(0, 10_000), 10.0);
+    assert_eq!(split.max_items(), 1000);
+
+    // 1D ranges are split into runs of items, respecting the offset:
+    let chunks = split.chunks(SpatialDims::One(5), SpatialDims::One(2500),
+        SpatialDims::Unspecified).unwrap();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks[2].0.to_lens().unwrap(), [2005, 1, 1]);
+    assert_eq!(chunks[2].1.to_lens().unwrap(), [500, 1, 1]);
+
+    // 2D ranges are split into whole rows, in multiples of the local size:
+    let chunks = split.chunks(SpatialDims::Unspecified, SpatialDims::Two(300, 64),
+        SpatialDims::Two(10, 2)).unwrap();
+    assert_eq!(chunks.len(), 32);
+    assert_eq!(chunks[1].0.to_lens().unwrap(), [0, 2, 1]);
+    assert_eq!(chunks[1].1.to_lens().unwrap(), [300, 2, 1]);
+
+    assert!(split.chunks(SpatialDims::Unspecified, SpatialDims::Two(300, 63),
+        SpatialDims::Two(10, 2)).is_err());
+
+    // Every work item is run exactly once:
+    let src = r#"
+        __kernel void incr(__global uint* buffer) {
+            buffer[get_global_id(1) * get_global_size(0) + get_global_id(0)] += 1;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([64, 50])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<u32>().unwrap();
+    let kernel = pro_que.create_kernel("incr").unwrap()
+        .arg_buf(&buffer);
+
+    let split = SplitLaunch::new(Duration::new(0, 1_000), 1.0).finish_each(true);
+    assert_eq!(split.enq(&kernel).unwrap(), 4);
+
+    let mut vec = vec![0u32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 1));
+}
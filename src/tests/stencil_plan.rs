@@ -0,0 +1,46 @@
+use standard::{ProQue, Program, StencilPlan, BorderMode};
+
+#[test]
+fn stencil_plan() {
+    let (width, height) = (37, 21);
+    let plan = StencilPlan::new([width, height], 2, [8, 8], BorderMode::Interior).unwrap();
+
+    assert_eq!(plan.computed_range()[0], (2, 33));
+    assert_eq!(plan.tile_counts(), [5, 3, 1]);
+    assert_eq!(plan.gws().to_lens().unwrap(), [40, 24, 1]);
+    assert_eq!(plan.gwo().to_offset().unwrap(), [2, 2, 0]);
+    assert_eq!(plan.local_dims().to_lens().unwrap(), [12, 12, 1]);
+    assert_eq!(plan.tile_origin([1, 0, 0]), [8, 0, 0]);
+    assert_eq!(plan.tile_origins().len(), 15);
+
+    let src = r#"
+        __kernel void mark(__global int* buffer) {
+            size_t x = get_global_id(0);
+            size_t y = get_global_id(1);
+            if (x >= STENCIL_END_0 || y >= STENCIL_END_1) { return; }
+            buffer[y * STENCIL_DOMAIN_0 + x] = 1;
+        }
+    "#;
+
+    let prog_bldr = plan.build_opts().into_iter()
+        .fold(Program::builder().src(src), |bldr, opt| bldr.bo(opt));
+
+    let pro_que = ProQue::builder()
+        .prog_bldr(prog_bldr)
+        .dims([width, height])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<i32>().unwrap();
+    let kernel = pro_que.create_kernel("mark").unwrap().arg_buf(&buffer);
+    plan.apply(kernel.cmd()).enq().unwrap();
+
+    let mut vec = vec![0i32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+
+    for y in 0..height {
+        for x in 0..width {
+            let interior = x >= 2 && x < width - 2 && y >= 2 && y < height - 2;
+            assert_eq!(vec[y * width + x], interior as i32);
+        }
+    }
+}
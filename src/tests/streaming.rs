@@ -0,0 +1,46 @@
+use standard::{Context, Queue, Program, Kernel, Buffer, StreamingBuffer};
+use flags;
+
+static SRC: &'static str = r#"
+    __kernel void sum(__global float const* chunk, __global float* total) {
+        if (get_global_id(0) == 0) {
+            float acc = 0.0f;
+            for (uint i = 0; i < get_global_size(0); i++) { acc += chunk[i]; }
+            total[0] += acc;
+        }
+    }
+"#;
+
+#[test]
+fn streaming_buffer() {
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let program = Program::builder().src(SRC).devices(device).build(&context).unwrap();
+    let upload_queue = Queue::new(&context, device).unwrap();
+    let kernel_queue = Queue::new(&context, device).unwrap();
+
+    let total = Buffer::<f32>::new(kernel_queue.clone(), Some(flags::MEM_READ_WRITE), [1],
+        Some(&[0.0f32])).unwrap();
+    let mut stream = StreamingBuffer::<f32>::new(upload_queue, Some(flags::MEM_READ_ONLY), 100)
+        .unwrap();
+    let mut kernel = Kernel::new("sum", &program, &kernel_queue).unwrap()
+        .arg_buf_named::<f32>("chunk", None)
+        .arg_buf(&total);
+
+    // Ten and a half chunks, the last one partial:
+    let data: Vec<f32> = (0..1050).map(|i| (i % 7) as f32).collect();
+
+    for chunk in data.chunks(stream.chunk_len()) {
+        stream.stream(chunk, |buffer, len, ewait, enew| {
+            try!(kernel.set_arg_buf_named("chunk", Some(buffer)));
+            kernel.cmd().gws([len]).ewait(ewait).enew(enew).enq()
+        }).unwrap();
+    }
+    stream.wait().unwrap();
+
+    assert!(stream.stream(&data, |_, _, _, _| Ok(())).is_err());
+
+    let mut result = vec![0.0f32];
+    total.read(&mut result).enq().unwrap();
+    assert_eq!(result[0], data.iter().fold(0.0, |acc, &v| acc + v));
+}
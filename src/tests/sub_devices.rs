@@ -0,0 +1,25 @@
+use standard::{Platform, Device, Context, Queue, Buffer, PartitionProperty};
+
+#[test]
+fn create_sub_devices() {
+    let platform = Platform::default();
+    let device = Device::first(platform);
+
+    assert!(device.create_sub_devices(PartitionProperty::ByCounts(vec![])).is_err());
+
+    // Devices which cannot be partitioned (most GPUs) return an error:
+    let sub_devices = match device.create_sub_devices(PartitionProperty::Equally(1)) {
+        Ok(sub_devices) => sub_devices,
+        Err(_) => return,
+    };
+    assert!(!sub_devices.is_empty());
+
+    // Sub-devices can be used to create contexts and queues:
+    let context = Context::builder().platform(platform).devices(*sub_devices[0]).build().unwrap();
+    let queue = Queue::new(&context, sub_devices[0].device()).unwrap();
+    let buffer = Buffer::<u32>::new(queue, None, [64], None).unwrap();
+
+    let mut vec = vec![1u32; 64];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 0));
+}
@@ -0,0 +1,113 @@
+use standard::{Context, Queue, Program, Kernel, Buffer, Event, EventList};
+use flags;
+
+static SRC: &'static str = r#"
+    __kernel void add(__global float* buffer, float addend) {
+        buffer[get_global_id(0)] += addend;
+    }
+"#;
+
+#[test]
+fn marker_and_barrier() {
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let program = Program::builder().src(SRC).devices(device).build(&context).unwrap();
+    let queue = Queue::builder().out_of_order(true).build(&context, device)
+        .or_else(|_| Queue::new(&context, device)).unwrap();
+
+    let buffer = Buffer::<f32>::new(queue.clone(), Some(flags::MEM_READ_WRITE), [1024], None)
+        .unwrap();
+    let kernel = Kernel::new("add", &program, &queue).unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+
+    let mut adds = EventList::new();
+    kernel.cmd().enew(&mut adds).enq().unwrap();
+    let barrier = queue.enqueue_barrier(&adds).unwrap();
+
+    let mut more_adds = EventList::new();
+    kernel.cmd().ewait(&barrier).enew(&mut more_adds).enq().unwrap();
+    let marker = queue.enqueue_marker(&more_adds).unwrap();
+    marker.wait().unwrap();
+
+    let mut vec = vec![0.0f32; 1024];
+    buffer.read(&mut vec).ewait(&marker).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 2.0));
+
+    // An empty wait list waits on all previously enqueued commands:
+    queue.enqueue_marker(&EventList::new()).unwrap().wait().unwrap();
+}
+
+#[test]
+fn cross_queue_overlap() {
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let program = Program::builder().src(SRC).devices(device).build(&context).unwrap();
+    let transfer_queue = Queue::new(&context, device).unwrap();
+    let compute_queue = Queue::new(&context, device).unwrap();
+
+    let buffers: Vec<_> = (0..2).map(|_| Buffer::<f32>::new(transfer_queue.clone(),
+        Some(flags::MEM_READ_WRITE), [1024], None).unwrap()).collect();
+    let data = vec![vec![1.0f32; 1024], vec![2.0f32; 1024]];
+    let mut results = vec![vec![0.0f32; 1024]; 2];
+
+    // Upload each buffer on the transfer queue while the previous one is
+    // being processed on the compute queue:
+    let mut computed = EventList::new();
+
+    for (buffer, data) in buffers.iter().zip(data.iter()) {
+        let mut uploaded = Event::empty();
+        unsafe { buffer.write_async(data).enew(&mut uploaded).enq().unwrap(); }
+
+        compute_queue.enqueue_wait_for_events(&uploaded).unwrap();
+        Kernel::new("add", &program, &compute_queue).unwrap()
+            .gws([1024])
+            .arg_buf(buffer)
+            .arg_scl(10.0f32)
+            .cmd().enew(&mut computed).enq().unwrap();
+    }
+
+    transfer_queue.enqueue_wait_for_events(&computed).unwrap();
+    for (buffer, result) in buffers.iter().zip(results.iter_mut()) {
+        buffer.read(result).enq().unwrap();
+    }
+
+    assert!(results[0].iter().all(|&v| v == 11.0));
+    assert!(results[1].iter().all(|&v| v == 12.0));
+
+    // Empty wait lists are ignored:
+    compute_queue.enqueue_wait_for_events(&EventList::new()).unwrap();
+}
+
+#[test]
+fn migrate_mem_objects() {
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let program = Program::builder().src(SRC).devices(device).build(&context).unwrap();
+    let queue = Queue::new(&context, device).unwrap();
+
+    let buffer = Buffer::<f32>::new(queue.clone(), Some(flags::MEM_READ_WRITE), [1024], None)
+        .unwrap();
+    let scratch = Buffer::<f32>::new(queue.clone(), Some(flags::MEM_READ_WRITE), [1024], None)
+        .unwrap();
+    let kernel = Kernel::new("add", &program, &queue).unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+
+    let migrated = queue.enqueue_migrate_mem_objects(&[&buffer, &scratch],
+        flags::MemMigrationFlags::empty(), &EventList::new()).unwrap();
+    let mut added = Event::empty();
+    kernel.cmd().ewait(&migrated).enew(&mut added).enq().unwrap();
+
+    // Migrate back to the host once the kernel completes:
+    let on_host = queue.enqueue_migrate_mem_objects(&[&buffer], flags::MIGRATE_MEM_OBJECT_HOST,
+        &added).unwrap();
+    on_host.wait().unwrap();
+
+    let mut vec = vec![0.0f32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 1.0));
+
+    assert!(queue.enqueue_migrate_mem_objects(&[], flags::MIGRATE_MEM_OBJECT_HOST,
+        &EventList::new()).is_err());
+}
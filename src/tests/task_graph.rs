@@ -0,0 +1,59 @@
+use standard::{Context, Queue, Program, Kernel, Buffer, TaskGraph};
+use flags;
+
+static SRC: &'static str = r#"
+    __kernel void add(__global float* buffer, float addend) {
+        buffer[get_global_id(0)] += addend;
+    }
+
+    __kernel void copy(__global float const* src, __global float* dst) {
+        dst[get_global_id(0)] = src[get_global_id(0)];
+    }
+"#;
+
+#[test]
+fn task_graph() {
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let program = Program::builder().src(SRC).devices(device).build(&context).unwrap();
+    let queue_a = Queue::builder().out_of_order(true).build(&context, device)
+        .or_else(|_| Queue::new(&context, device)).unwrap();
+    let queue_b = Queue::new(&context, device).unwrap();
+
+    let src = Buffer::<f32>::new(queue_a.clone(), Some(flags::MEM_READ_WRITE), [1024], None)
+        .unwrap();
+    let dst = Buffer::<f32>::new(queue_b.clone(), Some(flags::MEM_READ_WRITE), [1024], None)
+        .unwrap();
+
+    let add = Kernel::new("add", &program, &queue_a).unwrap()
+        .arg_buf(&src)
+        .arg_scl(1.0f32);
+    let copy = Kernel::new("copy", &program, &queue_b).unwrap()
+        .arg_buf(&src)
+        .arg_buf(&dst);
+
+    let mut graph = TaskGraph::new();
+    let zeros = vec![0.0f32; 1024];
+
+    graph.task().writes(&src).enq(|ewait, enew| {
+        src.write(&zeros).ewait(ewait).enew(enew).enq()
+    }).unwrap();
+
+    for _ in 0..3 {
+        graph.task().writes(&src).enq_kernel(&add).unwrap();
+    }
+
+    // Runs on another queue but must still see all three additions:
+    graph.task().reads(&src).writes(&dst).enq_kernel(&copy).unwrap();
+
+    // Must not overwrite `src` before the copy has read it:
+    graph.task().writes(&src).enq_kernel(&add).unwrap();
+
+    graph.wait().unwrap();
+
+    let mut vec = vec![0.0f32; 1024];
+    dst.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 3.0));
+    src.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 4.0));
+}
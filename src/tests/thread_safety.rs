@@ -0,0 +1,98 @@
+use std::thread;
+use standard::{Platform, Device, Context, Program, Queue, Buffer, Image, Sampler, Event, ProQue,
+    Kernel, SyncKernel};
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn send_sync() {
+    assert_send::<Platform>(); assert_sync::<Platform>();
+    assert_send::<Device>(); assert_sync::<Device>();
+    assert_send::<Context>(); assert_sync::<Context>();
+    assert_send::<Program>(); assert_sync::<Program>();
+    assert_send::<Queue>(); assert_sync::<Queue>();
+    assert_send::<Buffer<f32>>(); assert_sync::<Buffer<f32>>();
+    assert_send::<Image<u8>>(); assert_sync::<Image<u8>>();
+    assert_send::<Sampler>(); assert_sync::<Sampler>();
+    assert_send::<Event>(); assert_sync::<Event>();
+    assert_send::<ProQue>(); assert_sync::<ProQue>();
+    assert_send::<Kernel>();
+    assert_send::<SyncKernel>(); assert_sync::<SyncKernel>();
+}
+
+#[test]
+fn sync_kernel_dispatch() {
+    let pro_que = ProQue::builder()
+        .src(r#"
+            __kernel void inc(__global int* buffer) {
+                atomic_inc(&buffer[get_global_id(0)]);
+            }
+        "#)
+        .dims([256])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<i32>().unwrap();
+    let kernel = pro_que.create_kernel("inc").unwrap().arg_buf(&buffer).into_sync();
+
+    let threads: Vec<_> = (0..4).map(|_| {
+        let kernel = kernel.clone();
+        let queue = Queue::new(pro_que.context(), *pro_que.queue().device()).unwrap();
+
+        thread::spawn(move || {
+            for _ in 0..8 {
+                kernel.lock().cmd().queue(&queue).enq().unwrap();
+            }
+            queue.finish();
+        })
+    }).collect();
+
+    for th in threads { th.join().unwrap(); }
+
+    let mut vec = vec![0i32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 32));
+}
+
+#[test]
+fn kernel_duplicate() {
+    let pro_que = ProQue::builder()
+        .src(r#"
+            __kernel void add(__global int* buffer, int value) {
+                buffer[get_global_id(0)] += value;
+            }
+        "#)
+        .dims([64])
+        .build().unwrap();
+
+    let buffer_a = pro_que.create_buffer::<i32>().unwrap();
+    let buffer_b = pro_que.create_buffer::<i32>().unwrap();
+
+    let mut kernel_a = pro_que.create_kernel("add").unwrap()
+        .arg_buf_named("buffer", Some(&buffer_a))
+        .arg_scl(3i32);
+
+    // Same arguments:
+    let mut kernel_b = kernel_a.duplicate().unwrap();
+    kernel_b.enq().unwrap();
+
+    // Independent arguments:
+    kernel_b.set_arg_buf_named("buffer", Some(&buffer_b)).unwrap();
+    kernel_b.set_arg(1, 5i32).unwrap();
+    kernel_a.set_arg(1, 7i32).unwrap();
+    kernel_a.enq().unwrap();
+    kernel_b.enq().unwrap();
+
+    let handles: Vec<_> = (0..2).map(|_| {
+        let kernel = kernel_a.duplicate().unwrap();
+        thread::spawn(move || kernel.enq().unwrap())
+    }).collect();
+    for handle in handles { handle.join().unwrap(); }
+    pro_que.queue().finish();
+
+    let mut vec = vec![0i32; buffer_a.len()];
+    buffer_a.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 3 + 7 + 7 + 7));
+    buffer_b.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 5));
+}
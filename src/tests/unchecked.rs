@@ -0,0 +1,114 @@
+use core::{self, KernelArg, CommandExecutionStatus};
+use standard::{ProQue, Event};
+
+static SRC: &'static str = r#"
+    __kernel void add(__global float* buffer, float addend) {
+        buffer[get_global_id(0)] += addend;
+    }
+"#;
+
+#[test]
+fn enq_unchecked() {
+    let pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let mut fill_event = Event::empty();
+    buffer.cmd().fill(2.0, None).enew(&mut fill_event).enq().unwrap();
+
+    let mut vec = vec![0.0f32; 1024];
+    let mut read_event = Event::empty();
+
+    unsafe {
+        buffer.cmd().read_async(&mut vec).ewait(&fill_event).enew(&mut read_event)
+            .enq_unchecked().unwrap();
+    }
+    read_event.wait().unwrap();
+    assert!(vec.iter().all(|&v| v == 2.0));
+
+    if cfg!(debug_assertions) {
+        // Non-blocking read with no event to await:
+        assert!(unsafe { buffer.cmd().read_async(&mut vec).enq_unchecked() }.is_err());
+        // Still range checked:
+        assert!(unsafe { buffer.cmd().offset(1).read_async(&mut vec).enew(&mut read_event)
+            .enq_unchecked() }.is_err());
+    }
+}
+
+#[test]
+fn read_async_does_not_block() {
+    let pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let context = pro_que.context();
+
+    // A blocking read would never return while the gate is unset:
+    let gate = unsafe { Event::from_core(core::create_user_event(context).unwrap()) };
+    let mut vec = vec![1.0f32; 1024];
+    let mut read_event = Event::empty();
+    unsafe { buffer.read_async(&mut vec).ewait(&gate).enew(&mut read_event).enq().unwrap(); }
+
+    core::set_user_event_status(gate.core_as_ref().unwrap(), CommandExecutionStatus::Complete)
+        .unwrap();
+    read_event.wait().unwrap();
+    assert!(vec.iter().all(|&v| v == 0.0));
+}
+
+#[test]
+fn write_async() {
+    let pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+
+    let vec = vec![3.0f32; 1024];
+    let mut write_event = Event::empty();
+    let mut kernel_event = Event::empty();
+
+    // The kernel waits on the upload without the host blocking in between:
+    unsafe { buffer.write_async(&vec).enew(&mut write_event).enq().unwrap(); }
+    kernel.cmd().ewait(&write_event).enew(&mut kernel_event).enq().unwrap();
+
+    let mut result = vec![0.0f32; 1024];
+    buffer.read(&mut result).ewait(&kernel_event).enq().unwrap();
+    assert!(result.iter().all(|&v| v == 4.0));
+
+    if cfg!(debug_assertions) {
+        // Non-blocking write with no event to await:
+        assert!(unsafe { buffer.cmd().write_async(&vec).enq_unchecked() }.is_err());
+    }
+}
+
+#[test]
+fn set_arg_unchecked() {
+    let pro_que = ProQue::builder()
+        .src(SRC)
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<f32>().unwrap();
+    let mut kernel = pro_que.create_kernel("add").unwrap()
+        .arg_buf(&buffer)
+        .arg_scl(0.0f32);
+
+    unsafe { kernel.set_arg_unchecked(1, KernelArg::Scalar(5.0f32)).unwrap(); }
+    kernel.enq().unwrap();
+
+    let mut vec = vec![0.0f32; 1024];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 5.0));
+
+    if cfg!(debug_assertions) {
+        assert!(unsafe { kernel.set_arg_unchecked(2, KernelArg::Scalar(5.0f32)) }.is_err());
+    }
+}
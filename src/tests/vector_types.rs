@@ -1,8 +1,9 @@
 //! [INCOMPLETE]: [TODO]: Test various types with an assortment of operations.
 //!
 
+use std::mem;
 use standard::ProQue;
-use aliases::ClFloat3;
+use aliases::{ClFloat3, Float3, Float4, Int2, Uchar16, Double3};
 
 const DATASET_SIZE: usize = 2 << 20;
 
@@ -50,3 +51,41 @@ fn test_vector_types() {
         i += 1;
     }
 }
+
+#[test]
+fn vector_type_layout() {
+    // Sizes match the `OpenCL` C types, three-component vectors occupying
+    // the space of four:
+    assert_eq!(mem::size_of::<Int2>(), 8);
+    assert_eq!(mem::size_of::<Float3>(), 16);
+    assert_eq!(mem::size_of::<Float4>(), 16);
+    assert_eq!(mem::size_of::<Uchar16>(), 16);
+    assert_eq!(mem::size_of::<Double3>(), 32);
+
+    let src = r#"
+        __kernel void scale(__global float4* buffer, float4 scale, uchar16 mask) {
+            buffer[get_global_id(0)] *= scale * (float)mask.sf;
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([64])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<Float4>().unwrap();
+    buffer.cmd().fill(Float4::new(1.0, 2.0, 3.0, 4.0), None).enq().unwrap();
+
+    let mut mask = Uchar16::default();
+    mask.15 = 2;
+
+    pro_que.create_kernel("scale").unwrap()
+        .arg_buf(&buffer)
+        .arg_vec(Float4::new(1.0, 0.5, 2.0, 0.0))
+        .arg_vec(mask)
+        .enq().unwrap();
+
+    let mut vec = vec![Float4::default(); buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == Float4::new(2.0, 2.0, 12.0, 0.0)));
+}
@@ -0,0 +1,65 @@
+use standard::{ProQue, SpatialDims};
+
+#[test]
+fn kernel_gwo_passes() {
+    let src = r#"
+        __kernel void set_idx(__global uint* buffer) {
+            buffer[get_global_id(0)] = get_global_id(0);
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<u32>().unwrap();
+    let kernel = pro_que.create_kernel("set_idx").unwrap().arg_buf(&buffer);
+
+    // Four passes of 256 work items each:
+    for pass in 0..4 {
+        kernel.cmd().gwo([pass * 256]).gws([256]).enq().unwrap();
+    }
+
+    let mut vec = vec![0u32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+
+    for (i, &ele) in vec.iter().enumerate() {
+        assert_eq!(ele, i as u32);
+    }
+
+    assert!(kernel.cmd().gwo([0, 0]).gws([256]).enq().is_err());
+}
+
+#[test]
+fn kernel_cmd_overrides() {
+    let src = r#"
+        __kernel void set_size(__global uint* buffer) {
+            buffer[get_global_id(0)] = get_global_size(0);
+        }
+    "#;
+
+    let pro_que = ProQue::builder()
+        .src(src)
+        .dims([1024])
+        .build().unwrap();
+
+    let buffer = pro_que.create_buffer::<u32>().unwrap();
+    let kernel = pro_que.create_kernel("set_size").unwrap()
+        .arg_buf(&buffer)
+        .lws([64]);
+
+    kernel.cmd().gws([512]).lws([128]).enq().unwrap();
+    kernel.cmd().gwo([512]).gws([512]).lws(SpatialDims::Unspecified).enq().unwrap();
+
+    let mut vec = vec![0u32; buffer.len()];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&ele| ele == 512));
+
+    // The kernel's defaults are unchanged:
+    kernel.enq().unwrap();
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&ele| ele == 1024));
+
+    assert!(kernel.cmd().gws([32, 32]).enq().is_err());
+}
@@ -0,0 +1,71 @@
+use core::{self, CommandExecutionStatus};
+use standard::{Context, Queue, Program, Kernel, Buffer, Event, WriteTracker, WriteConflict};
+
+#[test]
+fn write_tracker() {
+    let src = r#"
+        __kernel void add(__global float* buffer, float addend) {
+            buffer[get_global_id(0)] += addend;
+        }
+    "#;
+
+    let context = Context::builder().build().unwrap();
+    let device = context.get_device_by_wrapping_index(0);
+    let program = Program::builder().src(src).devices(device).build(&context).unwrap();
+    let queue_a = Queue::new(&context, device).unwrap();
+    let queue_b = Queue::new(&context, device).unwrap();
+
+    let mut buffer = Buffer::<f32>::new(queue_a.clone(), None, [1024], None).unwrap();
+    let tracker = WriteTracker::new(WriteConflict::Error);
+    buffer.set_write_tracker(Some(tracker.clone()));
+
+    let kernel = Kernel::new("add", &program, &queue_b).unwrap()
+        .gws([1024])
+        .arg_buf(&buffer)
+        .arg_scl(1.0f32);
+
+    // Holds the first write back until released:
+    let gate = unsafe { Event::from_core(core::create_user_event(&context).unwrap()) };
+    let data = vec![5.0f32; 1024];
+    let mut write_event = Event::empty();
+    buffer.write(&data).block(false).ewait(&gate).enew(&mut write_event).enq().unwrap();
+
+    // Writes on the same in-order queue are implicitly ordered:
+    buffer.cmd().fill(5.0, None).ewait(&gate).enq().unwrap();
+    let mut fill_event = Event::empty();
+    buffer.cmd().fill(5.0, None).ewait(&gate).enew(&mut fill_event).enq().unwrap();
+    assert_eq!(tracker.conflict_count(), 0);
+
+    // A kernel writing on another queue without waiting conflicts:
+    assert!(kernel.cmd().enq().is_err());
+    assert_eq!(tracker.conflict_count(), 1);
+
+    // Waiting on the previous write resolves it:
+    let mut kern_event = Event::empty();
+    kernel.cmd().ewait(&fill_event).enew(&mut kern_event).enq().unwrap();
+
+    core::set_user_event_status(gate.core_as_ref().unwrap(), CommandExecutionStatus::Complete)
+        .unwrap();
+    kern_event.wait().unwrap();
+
+    // Completed writes never conflict:
+    buffer.cmd().fill(0.0, None).enq().unwrap();
+    assert_eq!(tracker.conflict_count(), 1);
+
+    let mut vec = vec![1.0f32; 1024];
+    buffer.read(&mut vec).enq().unwrap();
+    assert!(vec.iter().all(|&v| v == 0.0));
+
+    // Warnings still enqueue the write:
+    let warn_tracker = WriteTracker::new(WriteConflict::Warn);
+    buffer.set_write_tracker(Some(warn_tracker.clone()));
+    let gate = unsafe { Event::from_core(core::create_user_event(&context).unwrap()) };
+    buffer.cmd().fill(1.0, None).ewait(&gate).enq().unwrap();
+    buffer.cmd().queue(&queue_b).fill(2.0, None).enq().unwrap();
+    assert_eq!(warn_tracker.conflict_count(), 1);
+
+    core::set_user_event_status(gate.core_as_ref().unwrap(), CommandExecutionStatus::Complete)
+        .unwrap();
+    queue_a.finish();
+    queue_b.finish();
+}